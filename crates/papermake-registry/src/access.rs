@@ -0,0 +1,109 @@
+//! Read access control for templates
+//!
+//! By default every reference resolves for everyone. A registry configured
+//! with [`Registry::with_access_policy`] asks its [`AccessPolicy`] before
+//! resolving a reference: [`Registry::resolve_as`], [`Registry::prepare_as`]
+//! and [`Registry::render_as`] check on behalf of a user, while the plain
+//! `resolve`, `prepare` and `render` check as an anonymous caller.
+//!
+//! [`Registry::with_access_policy`]: crate::Registry::with_access_policy
+//! [`Registry::resolve_as`]: crate::Registry::resolve_as
+//! [`Registry::prepare_as`]: crate::Registry::prepare_as
+//! [`Registry::render_as`]: crate::Registry::render_as
+
+use std::collections::{HashMap, HashSet};
+
+/// Decides who may read templates in which namespace
+pub trait AccessPolicy: Send + Sync {
+    /// Whether `user` may read templates in `namespace`
+    ///
+    /// `namespace` is `None` for templates without one, e.g. `invoice:latest`,
+    /// and `user` is `None` for anonymous callers.
+    fn can_read(&self, namespace: Option<&str>, user: Option<&str>) -> bool;
+}
+
+/// Policy allowing every read
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AllowAll;
+
+impl AccessPolicy for AllowAll {
+    fn can_read(&self, _namespace: Option<&str>, _user: Option<&str>) -> bool {
+        true
+    }
+}
+
+/// Policy making each namespace private to its owner
+///
+/// A namespace is owned by the user named like its first segment, so
+/// `alice/invoice` and `alice/drafts/letter` are readable by `alice` only.
+/// Other users can be granted access with [`NamespaceOwners::with_member`],
+/// and [`NamespaceOwners::with_public`] opens a namespace to everyone,
+/// including anonymous callers. Templates without a namespace are public.
+#[derive(Debug, Default, Clone)]
+pub struct NamespaceOwners {
+    public: HashSet<String>,
+    members: HashMap<String, HashSet<String>>,
+}
+
+impl NamespaceOwners {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let everyone read templates owned by `owner`
+    pub fn with_public(mut self, owner: impl Into<String>) -> Self {
+        self.public.insert(owner.into());
+        self
+    }
+
+    /// Let `user` read templates owned by `owner`, e.g. an organization
+    pub fn with_member(mut self, owner: impl Into<String>, user: impl Into<String>) -> Self {
+        self.members
+            .entry(owner.into())
+            .or_default()
+            .insert(user.into());
+        self
+    }
+}
+
+impl AccessPolicy for NamespaceOwners {
+    fn can_read(&self, namespace: Option<&str>, user: Option<&str>) -> bool {
+        let Some(namespace) = namespace else {
+            return true;
+        };
+        let owner = namespace.split('/').next().unwrap_or(namespace);
+        if self.public.contains(owner) {
+            return true;
+        }
+
+        user.is_some_and(|user| {
+            user == owner
+                || self
+                    .members
+                    .get(owner)
+                    .is_some_and(|members| members.contains(user))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_owners() {
+        let policy = NamespaceOwners::new()
+            .with_public("acme")
+            .with_member("globex", "alice");
+
+        assert!(policy.can_read(Some("alice"), Some("alice")));
+        assert!(policy.can_read(Some("alice/drafts"), Some("alice")));
+        assert!(!policy.can_read(Some("alice"), Some("bob")));
+        assert!(!policy.can_read(Some("alice"), None));
+
+        assert!(policy.can_read(Some("acme"), None));
+        assert!(policy.can_read(Some("globex"), Some("alice")));
+        assert!(!policy.can_read(Some("globex"), Some("bob")));
+        assert!(policy.can_read(None, None));
+    }
+}
@@ -1,8 +1,51 @@
 use sha2::{Digest, Sha256};
+use std::io::{self, Read, Seek, Write};
+use tempfile::NamedTempFile;
+
+/// Default size above which [`ContentAddress::hash_reader`] spills to disk (8 MiB)
+pub const DEFAULT_SPILL_THRESHOLD: usize = 8 * 1024 * 1024;
+
+/// Size of the chunks [`ContentAddress::hash_reader`] reads at a time
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
 
 /// Utilities for content-addressable storage using SHA-256 hashing
 pub struct ContentAddress;
 
+/// Content read by [`ContentAddress::hash_reader`], kept for the upload
+///
+/// The content address is only known once the whole input has been read,
+/// so the bytes must be kept somewhere until they can be stored under it.
+#[derive(Debug)]
+pub enum HashedContent {
+    /// Content no larger than the spill threshold, held in memory
+    Memory(Vec<u8>),
+    /// Larger content, spilled to a temporary file deleted on drop
+    File(NamedTempFile),
+}
+
+impl HashedContent {
+    /// Content size in bytes
+    pub fn len(&self) -> io::Result<u64> {
+        match self {
+            HashedContent::Memory(bytes) => Ok(bytes.len() as u64),
+            HashedContent::File(file) => Ok(file.as_file().metadata()?.len()),
+        }
+    }
+
+    /// Whether the content is empty
+    pub fn is_empty(&self) -> io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Load the content into memory, reading the temporary file if spilled
+    pub fn into_bytes(self) -> io::Result<Vec<u8>> {
+        match self {
+            HashedContent::Memory(bytes) => Ok(bytes),
+            HashedContent::File(file) => std::fs::read(file.path()),
+        }
+    }
+}
+
 impl ContentAddress {
     /// Generate SHA-256 hash of content, returns hash with "sha256:" prefix
     pub fn hash(content: &[u8]) -> String {
@@ -12,6 +55,57 @@ impl ContentAddress {
         format!("sha256:{:x}", result)
     }
 
+    /// Hash everything read from `reader` without requiring it in memory
+    ///
+    /// Returns the same hash as [`ContentAddress::hash`] together with the
+    /// content, so it can be stored under its address afterwards (see
+    /// [`BlobStorage::put_stream`](crate::BlobStorage::put_stream)). Up to
+    /// `spill_threshold` bytes are buffered in memory; once the input grows
+    /// beyond that, the buffer is moved to a temporary file and the rest is
+    /// streamed there. Small blobs thus avoid disk I/O, while large ones cost
+    /// a temporary file of their size instead of a second in-memory copy.
+    pub fn hash_reader<R: Read>(
+        mut reader: R,
+        spill_threshold: usize,
+    ) -> io::Result<(String, HashedContent)> {
+        let mut hasher = Sha256::new();
+        let mut buffer = Vec::new();
+        let mut spilled: Option<NamedTempFile> = None;
+        let mut chunk = vec![0; HASH_CHUNK_SIZE];
+
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            hasher.update(&chunk[..read]);
+
+            match &mut spilled {
+                Some(file) => file.write_all(&chunk[..read])?,
+                None if buffer.len() + read > spill_threshold => {
+                    let mut file = NamedTempFile::new()?;
+                    file.write_all(&buffer)?;
+                    file.write_all(&chunk[..read])?;
+                    buffer = Vec::new();
+                    spilled = Some(file);
+                }
+                None => buffer.extend_from_slice(&chunk[..read]),
+            }
+        }
+
+        let content = match spilled {
+            Some(mut file) => {
+                file.flush()?;
+                file.rewind()?;
+                HashedContent::File(file)
+            }
+            None => HashedContent::Memory(buffer),
+        };
+        Ok((format!("sha256:{:x}", hasher.finalize()), content))
+    }
+
     /// Generate storage key for blob content
     /// Example: "blobs/sha256/abc123def456..."
     pub fn blob_key(hash: &str) -> String {
@@ -46,6 +140,13 @@ impl ContentAddress {
         format!("pdfs/sha256/{}", hash_value)
     }
 
+    /// Generate storage key for a detached manifest signature
+    /// Example: "signatures/sha256/abc123def456..."
+    pub fn signature_key(hash: &str) -> String {
+        let hash_value = Self::extract_hash_value(hash);
+        format!("signatures/sha256/{}", hash_value)
+    }
+
     /// Extract hash value from full hash string (removes "sha256:" prefix)
     /// Example: "sha256:abc123..." -> "abc123..."
     pub fn extract_hash_value(hash: &str) -> &str {
@@ -88,6 +189,28 @@ mod tests {
         assert_eq!(hash, hash2);
     }
 
+    #[test]
+    fn test_hash_reader_matches_in_memory_hash() {
+        let content: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+
+        // Above the threshold the content is spilled to a temporary file
+        let (hash, hashed) = ContentAddress::hash_reader(&content[..], 1024 * 1024).unwrap();
+        assert_eq!(hash, ContentAddress::hash(&content));
+        assert!(matches!(hashed, HashedContent::File(_)));
+        assert_eq!(hashed.len().unwrap(), content.len() as u64);
+        assert_eq!(hashed.into_bytes().unwrap(), content);
+
+        // Below it, the content stays in memory
+        let (hash, hashed) =
+            ContentAddress::hash_reader(&content[..], DEFAULT_SPILL_THRESHOLD).unwrap();
+        assert_eq!(hash, ContentAddress::hash(&content));
+        assert!(matches!(hashed, HashedContent::Memory(_)));
+
+        let (hash, hashed) = ContentAddress::hash_reader(io::empty(), 0).unwrap();
+        assert_eq!(hash, ContentAddress::hash(b""));
+        assert!(hashed.is_empty().unwrap());
+    }
+
     #[test]
     fn test_different_content_different_hash() {
         let hash1 = ContentAddress::hash(b"hello");
@@ -99,7 +222,7 @@ mod tests {
     #[test]
     fn test_blob_key_generation() {
         let hash = "sha256:abc123def456789";
-        let key = ContentAddress::blob_key(&hash);
+        let key = ContentAddress::blob_key(hash);
 
         assert_eq!(key, "blobs/sha256/abc123def456789");
     }
@@ -107,7 +230,7 @@ mod tests {
     #[test]
     fn test_manifest_key_generation() {
         let hash = "sha256:abc123def456789";
-        let key = ContentAddress::manifest_key(&hash);
+        let key = ContentAddress::manifest_key(hash);
 
         assert_eq!(key, "manifests/sha256/abc123def456789");
     }
@@ -130,21 +253,28 @@ mod tests {
     #[test]
     fn test_data_key_generation() {
         let hash = "sha256:abc123def456789";
-        let key = ContentAddress::data_key(&hash);
+        let key = ContentAddress::data_key(hash);
         assert_eq!(key, "data/sha256/abc123def456789");
     }
 
     #[test]
     fn test_pdf_key_generation() {
         let hash = "sha256:abc123def456789";
-        let key = ContentAddress::pdf_key(&hash);
+        let key = ContentAddress::pdf_key(hash);
         assert_eq!(key, "pdfs/sha256/abc123def456789");
     }
 
+    #[test]
+    fn test_signature_key_generation() {
+        let hash = "sha256:abc123def456789";
+        let key = ContentAddress::signature_key(hash);
+        assert_eq!(key, "signatures/sha256/abc123def456789");
+    }
+
     #[test]
     fn test_extract_hash_value() {
         let hash = "sha256:abc123def456";
-        let value = ContentAddress::extract_hash_value(&hash);
+        let value = ContentAddress::extract_hash_value(hash);
         assert_eq!(value, "abc123def456");
 
         // Should work with hash without prefix too
@@ -1,13 +1,29 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+
+use crate::address::ContentAddress;
+
+/// Archive entry holding the template metadata as JSON
+pub const ARCHIVE_METADATA_PATH: &str = "papermake.json";
 
 /// Metadata for a template containing descriptive information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TemplateMetadata {
     /// Human-readable name of the template
     pub name: String,
     /// Author email or identifier
     pub author: String,
+    /// Reference of the manifest this template was forked from, as
+    /// `namespace/name@sha256:...`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+    /// Named entrypoints besides `main.typ`, mapping a name to a `.typ`
+    /// file at the bundle root, e.g. `receipt` -> `receipt.typ`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub entrypoints: BTreeMap<String, String>,
 }
 
 impl TemplateMetadata {
@@ -16,6 +32,21 @@ impl TemplateMetadata {
         Self {
             name: name.into(),
             author: author.into(),
+            forked_from: None,
+            entrypoints: BTreeMap::new(),
+        }
+    }
+
+    /// Fill fields left blank with the values from `defaults`
+    ///
+    /// A field counts as blank if it is empty or only whitespace; fields
+    /// with a value are kept, so explicit metadata always wins.
+    pub fn merge_defaults(&mut self, defaults: &TemplateMetadata) {
+        if self.name.trim().is_empty() {
+            self.name = defaults.name.clone();
+        }
+        if self.author.trim().is_empty() {
+            self.author = defaults.author.clone();
         }
     }
 
@@ -33,6 +64,27 @@ impl TemplateMetadata {
             ));
         }
 
+        for (name, path) in &self.entrypoints {
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+            if !valid_name {
+                return Err(TemplateValidationError::InvalidMetadata(format!(
+                    "Entrypoint name '{}' can only contain lowercase letters, digits, dashes, and underscores",
+                    name
+                )));
+            }
+            // Entrypoints are compiled in place of main.typ, so imports in
+            // them resolve from the bundle root
+            if !path.ends_with(".typ") || path.contains('/') {
+                return Err(TemplateValidationError::InvalidMetadata(format!(
+                    "Entrypoint '{}' must be a .typ file at the bundle root, got {}",
+                    name, path
+                )));
+            }
+        }
+
         Ok(())
     }
 }
@@ -64,6 +116,26 @@ impl TemplateBundle {
         self
     }
 
+    /// Add default values for template data
+    ///
+    /// Renders deep-merge the incoming data over these defaults, so fields
+    /// the payload omits fall back to them. Must be a JSON object.
+    pub fn with_defaults(mut self, defaults_json: Vec<u8>) -> Self {
+        self.files
+            .insert("defaults.json".to_string(), defaults_json);
+        self
+    }
+
+    /// Declare a named entrypoint compiled instead of `main.typ`
+    ///
+    /// `path` is a `.typ` file at the bundle root, added with
+    /// [`TemplateBundle::add_file`]. Rendering `reference#name` selects it,
+    /// see [`crate::Registry::render`].
+    pub fn with_entrypoint(mut self, name: impl Into<String>, path: impl Into<String>) -> Self {
+        self.metadata.entrypoints.insert(name.into(), path.into());
+        self
+    }
+
     /// Add an additional file (assets, components, etc.)
     pub fn add_file<P: AsRef<str>>(mut self, path: P, content: Vec<u8>) -> Self {
         self.files.insert(path.as_ref().to_string(), content);
@@ -110,6 +182,11 @@ impl TemplateBundle {
         self.files.get("schema.json")
     }
 
+    /// Get the data defaults content if it exists
+    pub fn defaults(&self) -> Option<&Vec<u8>> {
+        self.files.get("defaults.json")
+    }
+
     /// List all file paths in the bundle
     pub fn file_paths(&self) -> Vec<&String> {
         self.files.keys().collect()
@@ -120,6 +197,128 @@ impl TemplateBundle {
         self.main_typ.len() + self.files.values().map(|v| v.len()).sum::<usize>()
     }
 
+    /// Files whose content is identical, grouped by content hash
+    ///
+    /// Each entry pairs a `sha256:` hash with the sorted paths sharing that
+    /// content, including `main.typ`. Storage already keeps one blob per
+    /// hash, so duplicates cost nothing at rest; this is for tooling that
+    /// wants to warn about or dedup assets added under several paths.
+    pub fn duplicate_files(&self) -> Vec<(String, Vec<String>)> {
+        let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        by_hash
+            .entry(ContentAddress::hash(&self.main_typ))
+            .or_default()
+            .push("main.typ".to_string());
+        for (path, content) in &self.files {
+            by_hash
+                .entry(ContentAddress::hash(content))
+                .or_default()
+                .push(path.clone());
+        }
+
+        by_hash
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(hash, mut paths)| {
+                paths.sort();
+                (hash, paths)
+            })
+            .collect()
+    }
+
+    /// Write the bundle as a tar archive
+    ///
+    /// The archive holds `main.typ`, every additional file at its bundle
+    /// path (including `schema.json` and `defaults.json`), and the metadata
+    /// as `papermake.json`. Entries are sorted and carry no timestamps or
+    /// ownership, so the same bundle always produces the same archive.
+    pub fn to_tar<W: Write>(&self, writer: W) -> Result<(), BundleArchiveError> {
+        if self.files.contains_key(ARCHIVE_METADATA_PATH) {
+            return Err(BundleArchiveError::InvalidPath(format!(
+                "{} is reserved for the bundle metadata",
+                ARCHIVE_METADATA_PATH
+            )));
+        }
+
+        let metadata = serde_json::to_vec_pretty(&self.metadata)
+            .map_err(|e| BundleArchiveError::InvalidMetadata(e.to_string()))?;
+        let mut entries: BTreeMap<&str, &[u8]> = BTreeMap::new();
+        entries.insert(ARCHIVE_METADATA_PATH, &metadata);
+        entries.insert("main.typ", &self.main_typ);
+        for (path, content) in &self.files {
+            entries.insert(path, content);
+        }
+
+        let mut builder = tar::Builder::new(writer);
+        for (path, content) in entries {
+            let mut header = tar::Header::new_ustar();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            builder
+                .append_data(&mut header, path, content)
+                .map_err(|e| BundleArchiveError::InvalidPath(format!("{}: {}", path, e)))?;
+        }
+        builder.into_inner()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Read a bundle from a tar archive written by [`TemplateBundle::to_tar`]
+    ///
+    /// Directory entries are skipped. The archive must contain `main.typ`
+    /// and `papermake.json`; every other regular file becomes an additional
+    /// file at its archive path. Absolute paths and `..` components are
+    /// rejected. The bundle is not validated.
+    pub fn from_tar<R: Read>(reader: R) -> Result<Self, BundleArchiveError> {
+        let mut main_typ = None;
+        let mut metadata = None;
+        let mut files = HashMap::new();
+
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let entry_type = entry.header().entry_type();
+            if entry_type.is_dir() {
+                continue;
+            }
+            let path = archive_path(&entry.path()?)?;
+            if !entry_type.is_file() {
+                return Err(BundleArchiveError::InvalidPath(format!(
+                    "{} is not a regular file",
+                    path
+                )));
+            }
+
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            let previous = match path.as_str() {
+                "main.typ" => main_typ.replace(content),
+                ARCHIVE_METADATA_PATH => metadata.replace(content),
+                _ => files.insert(path.clone(), content),
+            };
+            if previous.is_some() {
+                return Err(BundleArchiveError::InvalidPath(format!(
+                    "{} appears more than once",
+                    path
+                )));
+            }
+        }
+
+        let main_typ =
+            main_typ.ok_or_else(|| BundleArchiveError::MissingFile("main.typ".into()))?;
+        let metadata = metadata
+            .ok_or_else(|| BundleArchiveError::MissingFile(ARCHIVE_METADATA_PATH.into()))?;
+        let metadata = serde_json::from_slice(&metadata)
+            .map_err(|e| BundleArchiveError::InvalidMetadata(e.to_string()))?;
+
+        Ok(Self {
+            main_typ,
+            files,
+            metadata,
+        })
+    }
+
     /// Validate that the bundle is well-formed
     pub fn validate(&self) -> Result<(), TemplateValidationError> {
         // Check that main.typ is valid UTF-8
@@ -140,6 +339,22 @@ impl TemplateBundle {
             ));
         }
 
+        // Check that named entrypoints are well-formed and part of the bundle
+        self.metadata.validate()?;
+        for path in self.metadata.entrypoints.values() {
+            let content = match self.files.get(path) {
+                Some(content) => content,
+                None if path == "main.typ" => &self.main_typ,
+                None => return Err(TemplateValidationError::MissingFile(path.clone())),
+            };
+            if std::str::from_utf8(content).is_err() {
+                return Err(TemplateValidationError::InvalidMainTemplate(format!(
+                    "{} is not valid UTF-8",
+                    path
+                )));
+            }
+        }
+
         // Validate schema.json if present
         if let Some(schema_content) = self.schema() {
             serde_json::from_slice::<serde_json::Value>(schema_content).map_err(|e| {
@@ -147,12 +362,107 @@ impl TemplateBundle {
             })?;
         }
 
+        // Validate defaults.json if present
+        if let Some(defaults_content) = self.defaults() {
+            let defaults = serde_json::from_slice::<serde_json::Value>(defaults_content)
+                .map_err(|e| TemplateValidationError::InvalidDefaults(e.to_string()))?;
+            if !defaults.is_object() {
+                return Err(TemplateValidationError::InvalidDefaults(
+                    "must be a JSON object".into(),
+                ));
+            }
+        }
+
+        // Check that imported and included files are part of the bundle
+        self.check_imports()?;
+
+        Ok(())
+    }
+
+    /// Fail with `MissingFile` for the first file import that does not resolve
+    ///
+    /// Scans `main.typ` and every other `.typ` file. Imports of packages and
+    /// of computed paths cannot be checked and are left to rendering.
+    fn check_imports(&self) -> Result<(), TemplateValidationError> {
+        let paths: BTreeMap<&str, &[u8]> = self
+            .files
+            .iter()
+            .map(|(path, content)| (path.trim_start_matches('/'), content.as_slice()))
+            .chain(std::iter::once(("main.typ", self.main_typ.as_slice())))
+            .collect();
+
+        for (path, content) in paths.iter().filter(|(path, _)| path.ends_with(".typ")) {
+            let Ok(source) = std::str::from_utf8(content) else {
+                continue;
+            };
+            for import in papermake::file_imports(source) {
+                match resolve_import(path, &import) {
+                    Some(target) if paths.contains_key(target.as_str()) => {}
+                    Some(target) => return Err(TemplateValidationError::MissingFile(target)),
+                    None => {
+                        return Err(TemplateValidationError::MissingFile(format!(
+                            "{} (imported by {} from outside the bundle)",
+                            import, path
+                        )));
+                    }
+                }
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Bundle path of `import` as written in the file at bundle path `from`
+///
+/// Returns `None` if the import leaves the bundle root through `..`.
+fn resolve_import(from: &str, import: &str) -> Option<String> {
+    // Relative to the importing file's directory, or to the bundle root
+    let mut segments: Vec<&str> = from.split('/').collect();
+    segments.pop();
+    if import.starts_with('/') {
+        segments.clear();
+    }
+
+    for segment in import.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// Bundle path of an archive entry, rejecting paths outside the bundle
+fn archive_path(path: &Path) -> Result<String, BundleArchiveError> {
+    let mut segments = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_str().ok_or_else(|| {
+                BundleArchiveError::InvalidPath(format!("{} is not valid UTF-8", path.display()))
+            })?),
+            Component::CurDir => {}
+            _ => {
+                return Err(BundleArchiveError::InvalidPath(format!(
+                    "{} escapes the bundle root",
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    if segments.is_empty() {
+        return Err(BundleArchiveError::InvalidPath("empty path".into()));
+    }
+    Ok(segments.join("/"))
+}
+
 /// Information about a template in the registry
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TemplateInfo {
     /// Template name
     pub name: String,
@@ -204,6 +514,29 @@ pub enum TemplateValidationError {
 
     #[error("Invalid schema: {0}")]
     InvalidSchema(String),
+
+    #[error("Invalid defaults.json: {0}")]
+    InvalidDefaults(String),
+
+    /// An `#import` or `#include` names a file the bundle does not contain
+    #[error("Missing file: {0}")]
+    MissingFile(String),
+}
+
+/// Errors that can occur reading or writing a bundle archive
+#[derive(Debug, thiserror::Error)]
+pub enum BundleArchiveError {
+    #[error("Archive I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Archive is missing {0}")]
+    MissingFile(String),
+
+    #[error("Invalid archive path: {0}")]
+    InvalidPath(String),
+
+    #[error("Invalid bundle metadata: {0}")]
+    InvalidMetadata(String),
 }
 
 #[cfg(test)]
@@ -240,6 +573,22 @@ mod tests {
         assert_eq!(metadata.author, "john@example.com");
     }
 
+    #[test]
+    fn test_template_metadata_merge_defaults() {
+        let defaults = TemplateMetadata::new("Untitled", "templates@acme.com");
+
+        let mut metadata = TemplateMetadata::new("Invoice", " ");
+        metadata.merge_defaults(&defaults);
+        assert_eq!(
+            metadata,
+            TemplateMetadata::new("Invoice", "templates@acme.com")
+        );
+
+        let mut metadata = sample_metadata();
+        metadata.merge_defaults(&defaults);
+        assert_eq!(metadata, sample_metadata());
+    }
+
     #[test]
     fn test_template_bundle_creation() {
         let metadata = sample_metadata();
@@ -308,6 +657,32 @@ mod tests {
         assert_eq!(bundle.total_size(), expected_size);
     }
 
+    #[test]
+    fn test_template_bundle_duplicate_files() {
+        let logo = b"fake_logo_data".to_vec();
+        let bundle = TemplateBundle::new(sample_template_content(), sample_metadata())
+            .add_file("assets/logo.png", logo.clone())
+            .add_file("images/company-logo.png", logo.clone())
+            .add_file("assets/other.png", b"other".to_vec());
+
+        assert_eq!(
+            bundle.duplicate_files(),
+            vec![(
+                ContentAddress::hash(&logo),
+                vec![
+                    "assets/logo.png".to_string(),
+                    "images/company-logo.png".to_string()
+                ]
+            )]
+        );
+        assert!(
+            TemplateBundle::new(sample_template_content(), sample_metadata())
+                .add_file("assets/logo.png", logo)
+                .duplicate_files()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_template_bundle_validation_success() {
         let metadata = sample_metadata();
@@ -363,4 +738,106 @@ mod tests {
             TemplateValidationError::InvalidSchema(_)
         ));
     }
+
+    #[test]
+    fn test_template_bundle_validation_missing_import() {
+        let bundle = TemplateBundle::new(
+            b"#import \"missing.typ\": footer\n= Invoice".to_vec(),
+            sample_metadata(),
+        );
+        assert!(matches!(
+            bundle.validate(),
+            Err(TemplateValidationError::MissingFile(path)) if path == "missing.typ"
+        ));
+
+        // Partials resolve relative to their own directory
+        let bundle = TemplateBundle::new(
+            b"#import \"/components/header.typ\": header\n#include \"body.typ\"".to_vec(),
+            sample_metadata(),
+        )
+        .add_file("body.typ", b"Body".to_vec())
+        .add_file(
+            "components/header.typ",
+            b"#import \"../lib/style.typ\": *\n#import \"@preview/cetz:0.3.2\"\n#let header = [Acme]"
+                .to_vec(),
+        );
+        assert!(matches!(
+            bundle.validate(),
+            Err(TemplateValidationError::MissingFile(path)) if path == "lib/style.typ"
+        ));
+
+        let bundle = bundle.add_file("lib/style.typ", b"#let accent = blue".to_vec());
+        assert!(bundle.validate().is_ok());
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        assert_eq!(resolve_import("main.typ", "a.typ").unwrap(), "a.typ");
+        assert_eq!(resolve_import("lib/a.typ", "./b.typ").unwrap(), "lib/b.typ");
+        assert_eq!(resolve_import("lib/a.typ", "/b.typ").unwrap(), "b.typ");
+        assert_eq!(
+            resolve_import("lib/x/a.typ", "../b.typ").unwrap(),
+            "lib/b.typ"
+        );
+        assert_eq!(resolve_import("main.typ", "../b.typ"), None);
+    }
+
+    #[test]
+    fn test_template_bundle_tar_round_trip() {
+        let bundle = TemplateBundle::new(sample_template_content(), sample_metadata())
+            .with_schema(br#"{"type": "object"}"#.to_vec())
+            .add_file("assets/logo.png", vec![0, 1, 2, 255]);
+
+        let mut archive = Vec::new();
+        bundle.to_tar(&mut archive).unwrap();
+        let restored = TemplateBundle::from_tar(archive.as_slice()).unwrap();
+
+        assert_eq!(restored.main_typ(), bundle.main_typ());
+        assert_eq!(restored.files(), bundle.files());
+        assert_eq!(restored.metadata(), bundle.metadata());
+
+        // Archives are reproducible
+        let mut again = Vec::new();
+        restored.to_tar(&mut again).unwrap();
+        assert_eq!(again, archive);
+    }
+
+    #[test]
+    fn test_template_bundle_from_tar_rejects_bad_archives() {
+        let archive = |entries: &[(&str, &[u8])]| {
+            let mut builder = tar::Builder::new(Vec::new());
+            for (path, content) in entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                // Written as raw bytes so paths tar::Builder would refuse get through
+                header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+                header.set_cksum();
+                builder.append(&header, *content).unwrap();
+            }
+            builder.into_inner().unwrap()
+        };
+        let metadata = serde_json::to_vec(&sample_metadata()).unwrap();
+
+        let missing_main = archive(&[(ARCHIVE_METADATA_PATH, &metadata)]);
+        assert!(matches!(
+            TemplateBundle::from_tar(missing_main.as_slice()),
+            Err(BundleArchiveError::MissingFile(file)) if file == "main.typ"
+        ));
+
+        let escaping = archive(&[
+            (ARCHIVE_METADATA_PATH, &metadata),
+            ("main.typ", b"= Invoice"),
+            ("../outside.typ", b"x"),
+        ]);
+        assert!(matches!(
+            TemplateBundle::from_tar(escaping.as_slice()),
+            Err(BundleArchiveError::InvalidPath(_))
+        ));
+
+        let bad_metadata = archive(&[(ARCHIVE_METADATA_PATH, b"{}"), ("main.typ", b"= Invoice")]);
+        assert!(matches!(
+            TemplateBundle::from_tar(bad_metadata.as_slice()),
+            Err(BundleArchiveError::InvalidMetadata(_))
+        ));
+    }
 }
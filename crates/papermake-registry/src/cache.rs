@@ -0,0 +1,101 @@
+//! Least-recently-used cache for prepared templates
+//!
+//! Manifests are content-addressed and immutable, so a template prepared
+//! for one manifest hash stays valid forever and entries never need to be
+//! invalidated, only evicted when the cache is full.
+
+use std::collections::HashMap;
+
+/// Fixed-capacity map evicting the least recently used entry
+///
+/// Lookups scan for the oldest entry only on eviction, which is cheap for
+/// the small capacities templates are cached with.
+#[derive(Debug)]
+pub struct LruCache<V> {
+    capacity: usize,
+    entries: HashMap<String, (u64, V)>,
+    tick: u64,
+}
+
+impl<V: Clone> LruCache<V> {
+    /// Create an empty cache holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Clone the value for `key`, marking it as most recently used
+    pub fn get(&mut self, key: &str) -> Option<V> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(last_used, value)| {
+            *last_used = tick;
+            value.clone()
+        })
+    }
+
+    /// Insert `value`, evicting the least recently used entry if full
+    pub fn insert(&mut self, key: impl Into<String>, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let key = key.into();
+        if !self.entries.contains_key(&key)
+            && self.entries.len() >= self.capacity
+            && let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(key, _)| key.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+
+        self.tick += 1;
+        self.entries.insert(key, (self.tick, value));
+    }
+
+    /// Number of cached entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+
+        // Touch "a" so "b" becomes the eviction candidate
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c", 3);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(3));
+    }
+
+    #[test]
+    fn test_zero_capacity_caches_nothing() {
+        let mut cache = LruCache::new(0);
+        cache.insert("a", 1);
+
+        assert!(cache.is_empty());
+        assert_eq!(cache.get("a"), None);
+    }
+}
@@ -0,0 +1,102 @@
+//! Configuration values from environment variables or mounted files
+//!
+//! Every setting read with [`env_var`] can also be supplied through a file
+//! named by the same variable with a `_FILE` suffix, e.g.
+//! `S3_SECRET_ACCESS_KEY_FILE=/run/secrets/s3-secret`. This is how
+//! Kubernetes and Docker secrets are usually mounted. When both are set, the
+//! file takes precedence.
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// A `_FILE` variable pointed at a file that could not be read
+#[derive(Error, Debug)]
+#[error("Failed to read {var} from {}: {source}", path.display())]
+pub struct EnvFileError {
+    /// Name of the `_FILE` variable
+    pub var: String,
+    /// Path it pointed to
+    pub path: PathBuf,
+    #[source]
+    pub source: std::io::Error,
+}
+
+/// Read setting `name` from `<name>_FILE` or, failing that, `name`
+///
+/// File contents have trailing whitespace (including the final newline most
+/// editors and `kubectl create secret` add) removed. Returns `Ok(None)` if
+/// neither variable is set.
+pub fn env_var(name: &str) -> Result<Option<String>, EnvFileError> {
+    lookup(name, |var| std::env::var(var).ok())
+}
+
+fn lookup(
+    name: &str,
+    get_env: impl Fn(&str) -> Option<String>,
+) -> Result<Option<String>, EnvFileError> {
+    let file_var = format!("{}_FILE", name);
+    match get_env(&file_var) {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            let contents = std::fs::read_to_string(&path).map_err(|source| EnvFileError {
+                var: file_var,
+                path,
+                source,
+            })?;
+            Ok(Some(contents.trim_end().to_string()))
+        }
+        None => Ok(get_env(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> + use<> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_file_variant_takes_precedence_and_is_trimmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secret");
+        std::fs::write(&path, "s3cr3t \n\n").unwrap();
+
+        let get_env = env(&[
+            ("S3_SECRET_ACCESS_KEY", "from-env"),
+            ("S3_SECRET_ACCESS_KEY_FILE", path.to_str().unwrap()),
+        ]);
+
+        assert_eq!(
+            lookup("S3_SECRET_ACCESS_KEY", get_env).unwrap(),
+            Some("s3cr3t".to_string())
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_plain_variable() {
+        let get_env = env(&[("CLICKHOUSE_PASSWORD", "from-env")]);
+
+        assert_eq!(
+            lookup("CLICKHOUSE_PASSWORD", &get_env).unwrap(),
+            Some("from-env".to_string())
+        );
+        assert_eq!(lookup("CLICKHOUSE_USER", &get_env).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unreadable_file_is_an_error() {
+        let get_env = env(&[("S3_BUCKET_FILE", "/nonexistent/papermake/bucket")]);
+
+        let error = lookup("S3_BUCKET", get_env).unwrap_err();
+        assert_eq!(error.var, "S3_BUCKET_FILE");
+        assert!(error.to_string().contains("/nonexistent/papermake/bucket"));
+    }
+}
@@ -35,6 +35,10 @@ pub enum RegistryError {
     #[error("Render storage error: {0}")]
     RenderStorage(#[from] crate::render_storage::RenderStorageError),
 
+    /// Manifest signature verification errors
+    #[error("Signature error: {0}")]
+    Signature(#[from] SignatureError),
+
     /// Authorization and permission errors
     #[error("Access denied: {0}")]
     AccessDenied(String),
@@ -168,6 +172,21 @@ pub enum ContentAddressingError {
     CircularDependency { path: String },
 }
 
+/// Manifest signing and verification errors
+#[derive(Error, Debug)]
+pub enum SignatureError {
+    /// No signature stored for the manifest
+    #[error("Missing signature for manifest {manifest_hash}")]
+    Missing { manifest_hash: String },
+
+    /// Signature present but does not verify against the configured key
+    #[error("Invalid signature for manifest {manifest_hash}: {reason}")]
+    Invalid {
+        manifest_hash: String,
+        reason: String,
+    },
+}
+
 /// Cache operation errors
 #[derive(Error, Debug)]
 pub enum CacheError {
@@ -253,6 +272,15 @@ impl StorageError {
             message: message.into(),
         }
     }
+
+    /// Prefix `context` to the message of a backend error, leaving other
+    /// kinds untouched
+    pub fn with_context(self, context: impl std::fmt::Display) -> Self {
+        match self {
+            Self::Backend { message } => Self::backend(format!("{}: {}", context, message)),
+            other => other,
+        }
+    }
 }
 
 impl TemplateError {
@@ -398,6 +426,23 @@ impl ContentAddressingError {
     }
 }
 
+impl SignatureError {
+    /// Create a missing signature error
+    pub fn missing(manifest_hash: impl Into<String>) -> Self {
+        Self::Missing {
+            manifest_hash: manifest_hash.into(),
+        }
+    }
+
+    /// Create an invalid signature error
+    pub fn invalid(manifest_hash: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Invalid {
+            manifest_hash: manifest_hash.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
 impl CacheError {
     /// Create an initialization failed error
     pub fn initialization_failed(message: impl Into<String>) -> Self {
@@ -447,6 +492,21 @@ impl From<std::string::FromUtf8Error> for TemplateError {
     }
 }
 
+// Conversion from blob storage errors, keeping missing and forbidden keys apart
+// from backend failures
+impl From<crate::storage::blob_storage::StorageError> for StorageError {
+    fn from(err: crate::storage::blob_storage::StorageError) -> Self {
+        use crate::storage::blob_storage::StorageError as BlobError;
+        match err {
+            BlobError::NotFound(key) => StorageError::not_found(key),
+            BlobError::AccessDenied(key) => StorageError::access_denied(key),
+            BlobError::Backend(_) | BlobError::InvalidKey(_) => {
+                StorageError::backend(err.to_string())
+            }
+        }
+    }
+}
+
 // Conversion from std::io::Error to StorageError
 impl From<std::io::Error> for StorageError {
     fn from(err: std::io::Error) -> Self {
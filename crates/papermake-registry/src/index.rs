@@ -0,0 +1,114 @@
+//! Reference index for fast template listing
+//!
+//! Listing templates by scanning `refs/` costs one list call plus one GET per
+//! template, growing with the number of refs. When enabled, the registry also
+//! maintains a single JSON blob at [`REFS_INDEX_KEY`] mapping every template to
+//! its tags and manifest hashes, so listing needs a single GET.
+//!
+//! ## Consistency
+//!
+//! The `refs/` keys remain the source of truth; `resolve` never reads the
+//! index. On publish the ref is written first and the index second, so a
+//! crash in between leaves a ref that is missing from the index, never an
+//! index entry without a ref. Index updates are serialized within one
+//! registry instance; concurrent writers in different processes may lose
+//! updates. In both cases [`Registry::rebuild_index`](crate::Registry::rebuild_index)
+//! reconstructs the index from a full scan, and should be run after enabling
+//! the index on an existing registry or periodically as reconciliation.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Storage key of the reference index blob
+pub const REFS_INDEX_KEY: &str = "index/refs.json";
+
+/// Every reference in the registry: template path → tag → manifest hash
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefsIndex {
+    /// Keyed by namespace path, e.g. `"john/invoice"` or `"invoice"`
+    pub templates: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl RefsIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse an index from its stored JSON form
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// Serialize the index to JSON for storage
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Point `namespace_path:tag` at `manifest_hash`
+    pub fn set(&mut self, namespace_path: &str, tag: &str, manifest_hash: &str) {
+        self.templates
+            .entry(namespace_path.to_string())
+            .or_default()
+            .insert(tag.to_string(), manifest_hash.to_string());
+    }
+
+    /// Remove `namespace_path:tag`, dropping the template once it has no tags
+    pub fn remove(&mut self, namespace_path: &str, tag: &str) {
+        if let Some(tags) = self.templates.get_mut(namespace_path) {
+            tags.remove(tag);
+            if tags.is_empty() {
+                self.templates.remove(namespace_path);
+            }
+        }
+    }
+
+    /// Manifest hash for `namespace_path:tag`, if indexed
+    pub fn get(&self, namespace_path: &str, tag: &str) -> Option<&str> {
+        self.templates
+            .get(namespace_path)
+            .and_then(|tags| tags.get(tag))
+            .map(String::as_str)
+    }
+
+    /// Total number of indexed refs
+    pub fn len(&self) -> usize {
+        self.templates.values().map(BTreeMap::len).sum()
+    }
+
+    /// Whether the index has no refs
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_remove() {
+        let mut index = RefsIndex::new();
+        index.set("john/invoice", "latest", "sha256:aaa");
+        index.set("john/invoice", "v1", "sha256:bbb");
+        index.set("invoice", "latest", "sha256:ccc");
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get("john/invoice", "v1"), Some("sha256:bbb"));
+
+        index.remove("john/invoice", "v1");
+        index.remove("invoice", "latest");
+        assert_eq!(index.len(), 1);
+        assert!(!index.templates.contains_key("invoice"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut index = RefsIndex::new();
+        index.set("john/invoice", "latest", "sha256:aaa");
+
+        let bytes = index.to_bytes().unwrap();
+        assert_eq!(RefsIndex::from_bytes(&bytes).unwrap(), index);
+    }
+}
@@ -24,7 +24,7 @@
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! // Create a registry with memory storage
 //! let storage = MemoryStorage::new();
-//! let registry = Registry::new(storage);
+//! let registry = Registry::new_storage_only(storage);
 //!
 //! // Create a template bundle
 //! let metadata = TemplateMetadata::new("Invoice Template", "alice@company.com");
@@ -47,23 +47,44 @@
 //! # }
 //! ```
 
+pub mod access;
 pub mod address;
 pub mod bundle;
+pub mod cache;
+pub mod config;
 pub mod error;
+pub mod index;
 pub mod manifest;
 pub mod reference;
 pub mod registry;
 pub mod render_storage;
 pub mod storage;
 
+#[cfg(test)]
+mod test_harness;
+
+pub use access::{AccessPolicy, AllowAll, NamespaceOwners};
 pub use bundle::TemplateInfo;
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
 pub use error::RegistryError;
-pub use registry::Registry;
-pub use render_storage::{RenderStorage, RenderRecord, AnalyticsQuery, AnalyticsResult};
+pub use registry::{GcReport, PreparedTemplate, PublishReport, Registry, RegistryLimits};
+pub use render_storage::{RenderStorage, RenderRecord, AnalyticsQuery, AnalyticsResult, Granularity, Page};
 pub use storage::{BlobStorage, TypstFileSystem};
 
+#[cfg(feature = "zstd")]
+pub use storage::compressed::CompressedStorage;
+
+#[cfg(feature = "filesystem")]
+pub use storage::local_storage::FilesystemBlobStorage;
+
 #[cfg(feature = "s3")]
 pub use storage::s3_storage::S3Storage;
 
+#[cfg(feature = "s3")]
+pub use storage::retry::RetryPolicy;
+
 #[cfg(feature = "clickhouse")]
 pub use render_storage::clickhouse::ClickHouseStorage;
+
+#[cfg(feature = "redis")]
+pub use render_storage::redis::RedisRenderStorage;
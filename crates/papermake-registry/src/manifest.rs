@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use thiserror::Error;
 
+use crate::address::ContentAddress;
 use crate::bundle::TemplateMetadata;
 
 /// Template manifest containing file hashes and metadata
@@ -34,6 +35,7 @@ impl Manifest {
         metadata
             .validate()
             .map_err(|err| ManifestError::InvalidMetadata(err.to_string()))?;
+        Self::validate_entrypoints(&files, &metadata)?;
 
         // Validate file hashes
         for (path, hash) in &files {
@@ -53,6 +55,11 @@ impl Manifest {
         self.files.get(&self.entrypoint)
     }
 
+    /// Get the path of a named entrypoint declared in the metadata
+    pub fn named_entrypoint(&self, name: &str) -> Option<&String> {
+        self.metadata.entrypoints.get(name)
+    }
+
     /// Get all file paths in the manifest
     pub fn file_paths(&self) -> Vec<&String> {
         self.files.keys().collect()
@@ -90,6 +97,11 @@ impl Manifest {
         serde_json::to_vec_pretty(self).map_err(ManifestError::Serialization)
     }
 
+    /// Content hash of the serialized manifest, as used to address it in storage
+    pub fn digest(&self) -> Result<String, ManifestError> {
+        Ok(ContentAddress::hash(&self.to_bytes()?))
+    }
+
     /// Deserialize manifest from JSON bytes
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ManifestError> {
         let manifest: Manifest =
@@ -112,6 +124,7 @@ impl Manifest {
         self.metadata
             .validate()
             .map_err(|err| ManifestError::InvalidMetadata(err.to_string()))?;
+        Self::validate_entrypoints(&self.files, &self.metadata)?;
 
         // Validate all files
         for (path, hash) in &self.files {
@@ -122,6 +135,22 @@ impl Manifest {
         Ok(())
     }
 
+    /// Check that named entrypoints point to files in the manifest
+    fn validate_entrypoints(
+        files: &BTreeMap<String, String>,
+        metadata: &TemplateMetadata,
+    ) -> Result<(), ManifestError> {
+        for (name, path) in &metadata.entrypoints {
+            if !files.contains_key(path) {
+                return Err(ManifestError::InvalidMetadata(format!(
+                    "Entrypoint '{}' points to missing file {}",
+                    name, path
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Validate file path format
     fn validate_file_path(path: &str) -> Result<(), ManifestError> {
         if path.trim().is_empty() {
@@ -172,6 +201,50 @@ impl Manifest {
     }
 }
 
+/// Differences between the files of two manifests
+///
+/// Files are compared by content hash, so a path is changed exactly when it
+/// maps to a different hash in the two manifests. All path lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestDiff {
+    /// Paths only present in the newer manifest
+    pub added: Vec<String>,
+    /// Paths only present in the older manifest
+    pub removed: Vec<String>,
+    /// Paths present in both with different content
+    pub changed: Vec<String>,
+    /// Unified line diffs for changed files that are valid UTF-8 in both versions
+    pub text_diffs: BTreeMap<String, String>,
+}
+
+impl ManifestDiff {
+    /// Classify the files of `old` and `new` without looking at their content
+    pub fn between(old: &Manifest, new: &Manifest) -> Self {
+        let mut diff = Self::default();
+
+        for (path, hash) in &new.files {
+            match old.files.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(old_hash) if old_hash != hash => diff.changed.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .files
+            .keys()
+            .filter(|path| !new.files.contains_key(*path))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    /// Whether both manifests contain the same files
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
 /// Errors that can occur when working with manifests
 #[derive(Debug, Error)]
 pub enum ManifestError {
@@ -129,30 +129,40 @@ impl Reference {
             });
         }
 
-        // Docker registry naming rules
-        let valid_chars = namespace.chars().all(|c| {
-            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-' || c == '_'
-        });
+        // Nested namespaces (e.g. "org/user") are validated segment by segment
+        for segment in namespace.split('/') {
+            if segment.is_empty() {
+                return Err(ReferenceError::InvalidNamespace {
+                    namespace: namespace.to_string(),
+                    reason: "Namespace segments cannot be empty".to_string(),
+                });
+            }
 
-        if !valid_chars {
-            return Err(ReferenceError::InvalidNamespace {
-                namespace: namespace.to_string(),
-                reason: "Namespace can only contain lowercase letters, digits, dots, dashes, and underscores".to_string(),
+            // Docker registry naming rules
+            let valid_chars = segment.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-' || c == '_'
             });
-        }
 
-        // Cannot start or end with special characters
-        if namespace.starts_with('.')
-            || namespace.starts_with('-')
-            || namespace.starts_with('_')
-            || namespace.ends_with('.')
-            || namespace.ends_with('-')
-            || namespace.ends_with('_')
-        {
-            return Err(ReferenceError::InvalidNamespace {
-                namespace: namespace.to_string(),
-                reason: "Namespace cannot start or end with '.', '-', or '_'".to_string(),
-            });
+            if !valid_chars {
+                return Err(ReferenceError::InvalidNamespace {
+                    namespace: namespace.to_string(),
+                    reason: "Namespace can only contain lowercase letters, digits, dots, dashes, and underscores".to_string(),
+                });
+            }
+
+            // Cannot start or end with special characters
+            if segment.starts_with('.')
+                || segment.starts_with('-')
+                || segment.starts_with('_')
+                || segment.ends_with('.')
+                || segment.ends_with('-')
+                || segment.ends_with('_')
+            {
+                return Err(ReferenceError::InvalidNamespace {
+                    namespace: namespace.to_string(),
+                    reason: "Namespace cannot start or end with '.', '-', or '_'".to_string(),
+                });
+            }
         }
 
         Ok(())
@@ -1,24 +1,101 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Write};
 use std::sync::Arc;
 use time;
+use tracing::Instrument;
+
+use papermake::error::{CompilationError, DiagnosticInfo, DiagnosticSeverity};
+use papermake::{
+    InMemoryFileSystem, InputField, LintFinding, PapermakeWorld, RenderFileSystem, RenderOptions,
+};
 
 use crate::{
-    address::ContentAddress,
-    bundle::{TemplateBundle, TemplateInfo},
-    error::{RegistryError, StorageError},
-    manifest::Manifest,
+    access::AccessPolicy,
+    address::{ContentAddress, DEFAULT_SPILL_THRESHOLD, HashedContent},
+    bundle::{TemplateBundle, TemplateInfo, TemplateMetadata, TemplateValidationError},
+    cache::LruCache,
+    error::{
+        CacheError, ContentAddressingError, RegistryError, SignatureError, StorageError,
+        TemplateError,
+    },
+    index::{REFS_INDEX_KEY, RefsIndex},
+    manifest::{Manifest, ManifestDiff},
     reference::Reference,
     render_storage::{
-        AnalyticsQuery, AnalyticsResult, RenderRecord, RenderStorage, RenderStorageError,
+        AnalyticsQuery, AnalyticsResult, Page, RenderRecord, RenderStorage, RenderStorageError,
     },
     storage::{BlobStorage, filesystem::RegistryFileSystem},
 };
 
+/// Evictions after which unused memoized compilation results are dropped
+///
+/// Passed to [`papermake::evict_cache`] after every render, matching what
+/// the Typst CLI uses in watch mode.
+const COMPILATION_CACHE_MAX_AGE: usize = 10;
+
+/// Render records read per render storage query during [`Registry::gc`]
+const GC_RENDER_PAGE_SIZE: u32 = 1000;
+
 /// Core registry for template publishing and resolution
 pub struct Registry<S: BlobStorage, R: RenderStorage> {
     storage: Arc<S>,
     render_storage: Option<Arc<R>>,
+    verifying_key: Option<VerifyingKey>,
+    refs_index: bool,
+    index_lock: tokio::sync::Mutex<()>,
+    template_cache: Option<std::sync::Mutex<LruCache<PreparedTemplate>>>,
+    namespace_defaults: HashMap<String, TemplateMetadata>,
+    verify_integrity: bool,
+    compile_check: bool,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
+    limits: RegistryLimits,
+}
+
+/// Size limits enforced on publish, see [`Registry::with_limits`]
+///
+/// `None` leaves the respective dimension unlimited, which is the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegistryLimits {
+    /// Maximum total size of all files of a bundle in bytes
+    pub max_bundle_bytes: Option<u64>,
+    /// Maximum number of files in a bundle, including `main.typ`
+    pub max_file_count: Option<usize>,
+    /// Maximum size of any single file in bytes
+    pub max_single_file_bytes: Option<u64>,
+}
+
+impl RegistryLimits {
+    /// Check `bundle` against the limits
+    fn check(&self, bundle: &TemplateBundle) -> Result<(), TemplateError> {
+        let sizes: Vec<u64> = std::iter::once(bundle.main_typ().len())
+            .chain(bundle.files().values().map(Vec::len))
+            .map(|size| size as u64)
+            .collect();
+
+        if let Some(limit) = self.max_single_file_bytes
+            && let Some(&size) = sizes.iter().find(|&&size| size > limit)
+        {
+            return Err(TemplateError::too_large(size, limit));
+        }
+        if let Some(limit) = self.max_file_count
+            && sizes.len() > limit
+        {
+            return Err(TemplateError::invalid(format!(
+                "Bundle has {} files (max: {})",
+                sizes.len(),
+                limit
+            )));
+        }
+        let total: u64 = sizes.iter().sum();
+        if let Some(limit) = self.max_bundle_bytes
+            && total > limit
+        {
+            return Err(TemplateError::too_large(total, limit));
+        }
+        Ok(())
+    }
 }
 
 /// Result of a render operation with tracking
@@ -32,6 +109,218 @@ pub struct RenderResult {
     pub pdf_hash: String,
     /// Render duration in milliseconds
     pub duration_ms: u32,
+    /// Whether this is an earlier render reused instead of compiled
+    ///
+    /// `render_id` and `duration_ms` are then those of the earlier render.
+    pub cached: bool,
+}
+
+/// Outcome of a [`Registry::gc`] run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GcReport {
+    /// Number of unreachable keys deleted
+    pub deleted: usize,
+    /// Number of reachable keys kept
+    pub retained: usize,
+    /// Total size of the deleted content in bytes
+    pub bytes_reclaimed: u64,
+}
+
+/// Outcome of a [`Registry::publish_with_report`] call
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PublishReport {
+    /// Hash of the published manifest
+    pub manifest_hash: String,
+    /// Number of file blobs written to storage
+    pub uploaded: usize,
+    /// Number of file blobs skipped because storage already had them
+    pub deduplicated: usize,
+}
+
+/// A resolved template ready to render many data sets
+///
+/// Created by [`Registry::prepare`]. The reference is resolved and the
+/// manifest loaded once; every subsequent render reuses the same Typst world,
+/// so template sources, imports and assets are fetched and parsed only once.
+#[derive(Clone)]
+pub struct PreparedTemplate {
+    reference: String,
+    manifest_hash: String,
+    entrypoint: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    world: PapermakeWorld,
+    schema: Option<serde_json::Value>,
+    defaults: Option<serde_json::Value>,
+}
+
+impl PreparedTemplate {
+    /// The reference this template was prepared from
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// The manifest hash the reference resolved to
+    pub fn manifest_hash(&self) -> &str {
+        &self.manifest_hash
+    }
+
+    /// The template's `schema.json`, if the bundle has one
+    pub fn schema(&self) -> Option<&serde_json::Value> {
+        self.schema.as_ref()
+    }
+
+    /// The template's `defaults.json`, if the bundle has one
+    pub fn defaults(&self) -> Option<&serde_json::Value> {
+        self.defaults.as_ref()
+    }
+
+    /// Render the template with the given data, returning the PDF bytes
+    ///
+    /// Data is merged over the template's defaults and then validated
+    /// against its schema, if it has them.
+    pub fn render(&mut self, data: &serde_json::Value) -> Result<Vec<u8>, RegistryError> {
+        let _span = self.render_span().entered();
+        let data = self.apply_defaults(data);
+        self.validate(&data)?;
+
+        let render_result = papermake::render_template_with_cache(
+            self.entrypoint.clone(),
+            self.file_system.clone(),
+            data,
+            Some(&mut self.world),
+        )
+        .map_err(RegistryError::Compilation)?;
+        papermake::evict_cache(COMPILATION_CACHE_MAX_AGE);
+
+        pdf_bytes(render_result)
+    }
+
+    /// Render the template with explicit render options
+    ///
+    /// Validates data against the schema unless `options.skip_validation`
+    /// is set. Unlike [`PreparedTemplate::render`] this does not reuse the
+    /// prepared world, since options such as fonts or page defaults change
+    /// how it is built.
+    pub fn render_with_options(
+        &self,
+        data: &serde_json::Value,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, RegistryError> {
+        let _span = self.render_span().entered();
+        let data = self.apply_defaults(data);
+        if !options.skip_validation {
+            self.validate(&data)?;
+        }
+
+        let render_result = papermake::render_template_with_options(
+            self.entrypoint.clone(),
+            self.file_system.clone(),
+            &data,
+            options,
+        )
+        .map_err(RegistryError::Compilation)?;
+        papermake::evict_cache(COMPILATION_CACHE_MAX_AGE);
+
+        pdf_bytes(render_result)
+    }
+
+    /// Span covering one render, parent of the `compile` and `export` spans
+    ///
+    /// The render data is never attached to spans, since it may hold
+    /// personal or otherwise sensitive information.
+    fn render_span(&self) -> tracing::Span {
+        tracing::info_span!("render", manifest_hash = %self.manifest_hash)
+    }
+
+    /// Deep-merge `data` over the template's defaults, if it has any
+    fn apply_defaults(&self, data: &serde_json::Value) -> serde_json::Value {
+        match &self.defaults {
+            Some(defaults) => merge_over_defaults(defaults, data),
+            None => data.clone(),
+        }
+    }
+
+    /// Validate `data` against the template's schema, if it has one
+    fn validate(&self, data: &serde_json::Value) -> Result<(), RegistryError> {
+        match &self.schema {
+            Some(schema) => papermake::validate_data(data, schema)
+                .map_err(|e| RegistryError::Compilation(e.into())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Merge `data` over `defaults`
+///
+/// Objects are merged key by key, recursively. Anything else in `data`,
+/// including arrays and `null`, replaces the default wholesale.
+fn merge_over_defaults(
+    defaults: &serde_json::Value,
+    data: &serde_json::Value,
+) -> serde_json::Value {
+    match (defaults, data) {
+        (serde_json::Value::Object(defaults), serde_json::Value::Object(data)) => {
+            let mut merged = defaults.clone();
+            for (key, value) in data {
+                let value = match defaults.get(key) {
+                    Some(default) => merge_over_defaults(default, value),
+                    None => value.clone(),
+                };
+                merged.insert(key.clone(), value);
+            }
+            serde_json::Value::Object(merged)
+        }
+        (_, data) => data.clone(),
+    }
+}
+
+/// Split `reference#entrypoint` into the reference and the entrypoint name
+fn split_entrypoint(reference: &str) -> (&str, Option<&str>) {
+    match reference.split_once('#') {
+        Some((reference, entrypoint)) => (reference, Some(entrypoint)),
+        None => (reference, None),
+    }
+}
+
+/// Path of the file to compile for `reference`, `main.typ` unless `entrypoint` names another
+fn entrypoint_path<'a>(
+    manifest: &'a Manifest,
+    reference: &str,
+    entrypoint: Option<&str>,
+) -> Result<&'a str, RegistryError> {
+    match entrypoint {
+        Some(name) => manifest
+            .named_entrypoint(name)
+            .map(String::as_str)
+            .ok_or_else(|| {
+                RegistryError::Template(crate::error::TemplateError::not_found(reference))
+            }),
+        None => Ok(&manifest.entrypoint),
+    }
+}
+
+/// Extract the PDF from a render result, turning render errors into a `RegistryError`
+fn pdf_bytes(render_result: papermake::RenderResult) -> Result<Vec<u8>, RegistryError> {
+    // Check if rendering was successful
+    if render_result.success {
+        render_result.pdf.ok_or_else(|| {
+            RegistryError::Template(crate::error::TemplateError::invalid(
+                "Rendering succeeded but no PDF was generated",
+            ))
+        })
+    } else {
+        // Keep the structured diagnostics so callers can report locations
+        let diagnostics: Vec<papermake::error::DiagnosticInfo> =
+            render_result.errors.into_iter().map(Into::into).collect();
+
+        Err(RegistryError::Compilation(
+            papermake::error::CompilationError::TypstError {
+                error_count: diagnostics.len(),
+                diagnostics,
+            }
+            .into(),
+        ))
+    }
 }
 
 // Implementation for Registry with blob storage only
@@ -41,6 +330,15 @@ impl<S: BlobStorage + 'static, R: RenderStorage> Registry<S, R> {
         Self {
             storage: Arc::new(storage),
             render_storage: Some(Arc::new(render_storage)),
+            verifying_key: None,
+            refs_index: false,
+            index_lock: tokio::sync::Mutex::new(()),
+            template_cache: None,
+            namespace_defaults: HashMap::new(),
+            verify_integrity: false,
+            compile_check: false,
+            access_policy: None,
+            limits: RegistryLimits::default(),
         }
     }
 }
@@ -52,6 +350,15 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         Self {
             storage: Arc::new(storage),
             render_storage: Some(Arc::new(render_storage)),
+            verifying_key: None,
+            refs_index: false,
+            index_lock: tokio::sync::Mutex::new(()),
+            template_cache: None,
+            namespace_defaults: HashMap::new(),
+            verify_integrity: false,
+            compile_check: false,
+            access_policy: None,
+            limits: RegistryLimits::default(),
         }
     }
 
@@ -60,6 +367,15 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         Registry {
             storage: Arc::new(storage),
             render_storage: None,
+            verifying_key: None,
+            refs_index: false,
+            index_lock: tokio::sync::Mutex::new(()),
+            template_cache: None,
+            namespace_defaults: HashMap::new(),
+            verify_integrity: false,
+            compile_check: false,
+            access_policy: None,
+            limits: RegistryLimits::default(),
         }
     }
 }
@@ -71,12 +387,123 @@ impl<S: BlobStorage + 'static> Registry<S, crate::render_storage::MemoryRenderSt
         Self {
             storage: Arc::new(storage),
             render_storage: None,
+            verifying_key: None,
+            refs_index: false,
+            index_lock: tokio::sync::Mutex::new(()),
+            template_cache: None,
+            namespace_defaults: HashMap::new(),
+            verify_integrity: false,
+            compile_check: false,
+            access_policy: None,
+            limits: RegistryLimits::default(),
         }
     }
 }
 
 // Shared implementation for all registry types
 impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
+    /// Require manifests to carry a valid ed25519 signature from this key
+    ///
+    /// Once configured, `resolve` and `render` reject manifests whose detached
+    /// signature is missing or does not verify. The signature only covers the
+    /// manifest hash, so every file blob read for rendering (entrypoint,
+    /// schema, defaults and imports) is then also checked against its hash in
+    /// the manifest, as with [`Registry::with_integrity_verification`].
+    pub fn with_verifying_key(mut self, verifying_key: VerifyingKey) -> Self {
+        self.verifying_key = Some(verifying_key);
+        self
+    }
+
+    /// Maintain a reference index for fast template listing
+    ///
+    /// Publishing then also updates the index blob at `index/refs.json` and
+    /// `list_templates` reads it instead of scanning `refs/`. When enabling
+    /// this on a registry that already has refs, call
+    /// [`Registry::rebuild_index`] once; see [`crate::index`] for how the
+    /// index is kept consistent with the refs.
+    pub fn with_refs_index(mut self) -> Self {
+        self.refs_index = true;
+        self
+    }
+
+    /// Re-hash manifests and file blobs when loading them for rendering
+    ///
+    /// Loads then go through [`Registry::get_verified`], so content that was
+    /// corrupted in storage fails with `IntegrityCheckFailed` instead of
+    /// rendering a broken PDF. This costs one extra SHA-256 pass per load.
+    pub fn with_integrity_verification(mut self) -> Self {
+        self.verify_integrity = true;
+        self
+    }
+
+    /// Reject bundles that fail [`Registry::check`] on publish
+    ///
+    /// Publishing then fails with a `TypstError` carrying the error
+    /// diagnostics instead of storing a template that cannot render.
+    /// Warnings do not block publishing. The check runs with empty data, so
+    /// templates must tolerate missing fields to be published.
+    pub fn with_compile_check(mut self) -> Self {
+        self.compile_check = true;
+        self
+    }
+
+    /// Reject bundles exceeding `limits` on publish
+    ///
+    /// Oversized files or bundles fail with [`TemplateError::TooLarge`],
+    /// too many files with [`TemplateError::Invalid`], before anything is
+    /// stored. Forks reuse stored files and are not checked.
+    pub fn with_limits(mut self, limits: RegistryLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Check read access with `policy` when resolving references
+    ///
+    /// Without a policy every reference resolves for everyone. See
+    /// [`crate::access`] for which methods check on behalf of which user.
+    /// With a policy, a reference resolved by digest alone must name a
+    /// namespace in which some tag points to that manifest.
+    pub fn with_access_policy(mut self, policy: impl AccessPolicy + 'static) -> Self {
+        self.access_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Cache up to `capacity` prepared templates by manifest hash
+    ///
+    /// Repeated renders of the same template then only resolve the
+    /// reference; the manifest, entrypoint and schema are not fetched again
+    /// and imports and assets already loaded by the cached world are reused.
+    /// Because the world is kept, Typst's memoized evaluation and layout of
+    /// the template also carry over, and only work depending on the new
+    /// data is redone.
+    /// Manifests are immutable, so cached entries never go stale; the least
+    /// recently used one is evicted when the cache is full.
+    pub fn with_template_cache(mut self, capacity: usize) -> Self {
+        self.template_cache = Some(std::sync::Mutex::new(LruCache::new(capacity)));
+        self
+    }
+
+    /// Default metadata for templates published under `namespace`
+    ///
+    /// On publish, blank fields of the bundle's metadata (e.g. an empty
+    /// author) are filled from the defaults of the template's namespace,
+    /// i.e. `acme` when publishing `acme/invoice`. Values set in the bundle
+    /// always take precedence over the defaults. Templates without a
+    /// namespace get no defaults.
+    pub fn with_namespace_defaults(
+        mut self,
+        namespace: impl Into<String>,
+        defaults: TemplateMetadata,
+    ) -> Self {
+        self.namespace_defaults.insert(namespace.into(), defaults);
+        self
+    }
+
+    /// Default metadata configured for `namespace`, if any
+    pub fn namespace_defaults(&self, namespace: &str) -> Option<&TemplateMetadata> {
+        self.namespace_defaults.get(namespace)
+    }
+
     /// Publish a template bundle to the registry
     ///
     /// This method implements the "store files → create manifest → update refs" workflow:
@@ -93,42 +520,366 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         namespace: &str,
         tag: &str,
     ) -> Result<String, RegistryError> {
-        // Step 1: Validate the bundle
-        bundle.validate().map_err(|e| {
-            RegistryError::Template(crate::error::TemplateError::invalid(e.to_string()))
+        let report = self.publish_with_report(bundle, namespace, tag).await?;
+
+        // Return the manifest hash for content-addressable access
+        Ok(report.manifest_hash)
+    }
+
+    /// Publish a template bundle, reporting which blobs had to be uploaded
+    ///
+    /// Like [`Registry::publish`]. Blobs already in storage, e.g. assets
+    /// shared with a previously published version, are not uploaded again;
+    /// the report counts them as deduplicated.
+    pub async fn publish_with_report(
+        &self,
+        bundle: TemplateBundle,
+        namespace: &str,
+        tag: &str,
+    ) -> Result<PublishReport, RegistryError> {
+        let report = self.store_bundle(bundle, namespace).await?;
+
+        // Step 5: Update reference (tag)
+        self.update_ref(namespace, tag, &report.manifest_hash)
+            .await?;
+
+        Ok(report)
+    }
+
+    /// Publish a template bundle and move `latest` along if `tag` is the newest version
+    ///
+    /// Tags are compared as numeric versions with an optional `v` prefix, so
+    /// `v10` is newer than `v2`. `latest` is only updated if `tag` parses as
+    /// a version and no other tag of the template parses as a greater one;
+    /// publishing a backport like `v1.1` after `v2` leaves `latest` alone.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use papermake_registry::Registry;
+    /// # use papermake_registry::storage::blob_storage::MemoryStorage;
+    /// # use papermake_registry::bundle::{TemplateBundle, TemplateMetadata};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let registry = Registry::new_storage_only(MemoryStorage::new());
+    /// let bundle = TemplateBundle::new(
+    ///     b"= Invoice".to_vec(),
+    ///     TemplateMetadata::new("Invoice", "john@example.com"),
+    /// );
+    ///
+    /// let hash = registry
+    ///     .publish_and_update_latest(bundle, "john/invoice", "v1.2.0")
+    ///     .await?;
+    /// assert_eq!(registry.resolve("john/invoice:latest").await?, hash);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn publish_and_update_latest(
+        &self,
+        bundle: TemplateBundle,
+        namespace: &str,
+        tag: &str,
+    ) -> Result<String, RegistryError> {
+        let manifest_hash = self.publish(bundle, namespace, tag).await?;
+
+        let Some(version) = parse_version(tag) else {
+            return Ok(manifest_hash);
+        };
+        let tags = self.list_tags(namespace).await?;
+        let is_newest = tags
+            .iter()
+            .filter(|other| other.as_str() != tag)
+            .filter_map(|other| parse_version(other))
+            .all(|other| other <= version);
+
+        if is_newest {
+            self.update_ref(namespace, "latest", &manifest_hash).await?;
+        }
+
+        Ok(manifest_hash)
+    }
+
+    /// Publish a bundle archive written by [`TemplateBundle::to_tar`]
+    ///
+    /// Reading the archive is blocking. Publishing an archive exported with
+    /// [`Registry::export_tar`] reproduces the original manifest hash.
+    pub async fn publish_tar<Rd: Read>(
+        &self,
+        reader: Rd,
+        namespace: &str,
+        tag: &str,
+    ) -> Result<String, RegistryError> {
+        let bundle = TemplateBundle::from_tar(reader).map_err(|e| {
+            RegistryError::Template(TemplateError::conversion_failed(e.to_string()))
         })?;
+        self.publish(bundle, namespace, tag).await
+    }
 
-        // Step 2: Store individual files as blobs
-        let mut file_hashes = BTreeMap::new();
+    /// Publish a template bundle together with a detached ed25519 signature
+    ///
+    /// The signature is computed over the manifest hash and stored at
+    /// `signatures/sha256/{hash}` before the reference is updated, so a tag
+    /// never points at an unsigned manifest.
+    pub async fn publish_signed(
+        &self,
+        bundle: TemplateBundle,
+        namespace: &str,
+        tag: &str,
+        signing_key: &SigningKey,
+    ) -> Result<String, RegistryError> {
+        let manifest_hash = self.store_bundle(bundle, namespace).await?.manifest_hash;
 
-        // Store main.typ
-        let main_hash = ContentAddress::hash(bundle.main_typ());
-        let main_blob_key = ContentAddress::blob_key(&main_hash);
+        let signature = signing_key.sign(manifest_hash.as_bytes());
+        let signature_key = ContentAddress::signature_key(&manifest_hash);
         self.storage
-            .put(&main_blob_key, bundle.main_typ().to_vec())
+            .put(&signature_key, signature.to_bytes().to_vec())
             .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
-        file_hashes.insert("main.typ".to_string(), main_hash);
+            .map_err(|e| RegistryError::Storage(e.into()))?;
 
-        // Store additional files
-        for (file_path, file_content) in bundle.files() {
-            let file_hash = ContentAddress::hash(file_content);
-            let file_blob_key = ContentAddress::blob_key(&file_hash);
+        self.update_ref(namespace, tag, &manifest_hash).await?;
+
+        Ok(manifest_hash)
+    }
+
+    /// Compile a bundle without data and return its diagnostics
+    ///
+    /// A dry run of [`papermake::check_template`] over the bundle's files,
+    /// so syntax errors and unresolved imports surface before publishing.
+    /// Nothing is stored. An empty result means the bundle compiles cleanly.
+    pub fn check(&self, bundle: &TemplateBundle) -> Result<Vec<DiagnosticInfo>, RegistryError> {
+        let main_typ = bundle.main_typ_string().map_err(|e| {
+            RegistryError::Template(TemplateError::invalid(format!(
+                "main.typ is not valid UTF-8: {}",
+                e
+            )))
+        })?;
+
+        // Typst requests files by their absolute path within the template
+        let mut file_system = InMemoryFileSystem::new();
+        for (path, content) in bundle.files() {
+            file_system.add_file(
+                format!("/{}", path.trim_start_matches('/')),
+                content.clone(),
+            );
+        }
+
+        Ok(papermake::check_template(main_typ, Arc::new(file_system))?)
+    }
+
+    /// Scan a bundle's main template for likely data mistakes
+    ///
+    /// Runs [`papermake::lint_template`] against the bundle's schema, if it
+    /// has one, reporting reads of fields the schema does not define and
+    /// templates that never read their data. Nothing is compiled or stored.
+    pub fn lint(&self, bundle: &TemplateBundle) -> Result<Vec<LintFinding>, RegistryError> {
+        let main_typ = bundle.main_typ_string().map_err(|e| {
+            RegistryError::Template(TemplateError::invalid(format!(
+                "main.typ is not valid UTF-8: {}",
+                e
+            )))
+        })?;
+        let schema = bundle
+            .schema()
+            .map(|schema| serde_json::from_slice::<serde_json::Value>(schema))
+            .transpose()
+            .map_err(|e| {
+                RegistryError::Template(TemplateError::invalid(format!(
+                    "schema.json is not valid JSON: {}",
+                    e
+                )))
+            })?;
+
+        Ok(papermake::lint_template(&main_typ, schema.as_ref()))
+    }
+
+    /// Compute the manifest hash publishing `bundle` under `namespace_path` would produce
+    ///
+    /// The digest covers every file's content hash and the metadata after
+    /// namespace defaults are applied, so it can be used to build a pinned
+    /// `namespace/name:tag@sha256:...` reference before publishing. Nothing
+    /// is stored and the bundle is not validated.
+    pub fn digest(
+        &self,
+        bundle: &TemplateBundle,
+        namespace_path: &str,
+    ) -> Result<String, RegistryError> {
+        let mut bundle = bundle.clone();
+        self.apply_namespace_defaults(&mut bundle, namespace_path);
+        Self::bundle_manifest(&bundle)?.digest().map_err(|e| {
+            RegistryError::ContentAddressing(ContentAddressingError::manifest_error(e.to_string()))
+        })
+    }
+
+    /// Fork a template into a new namespace and name
+    ///
+    /// Resolves `source_reference` and publishes a copy of its manifest as
+    /// `new_namespace/new_name:latest`. File blobs are content-addressed, so
+    /// the fork references the source's blobs rather than copying them. The
+    /// original author is kept and the fork's metadata records the source as
+    /// `namespace/name@sha256:...` in [`TemplateMetadata::forked_from`].
+    ///
+    /// Fails with [`TemplateError::AlreadyExists`] if the target already has
+    /// tags. The fork is unsigned; use [`Registry::publish_signed`] to sign
+    /// it if the registry verifies signatures.
+    pub async fn fork(
+        &self,
+        source_reference: &str,
+        new_namespace: &str,
+        new_name: &str,
+    ) -> Result<String, RegistryError> {
+        let source = Reference::parse(source_reference)?;
+        let (source_hash, source_manifest) = self.resolve_manifest(source_reference).await?;
+
+        let target = Reference::parse(&format!("{}/{}", new_namespace, new_name))?;
+        let target_path = target.full_name();
+
+        let existing_refs = self
+            .storage
+            .list_keys(&format!("refs/{}/", target_path))
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+        if !existing_refs.is_empty() {
+            return Err(RegistryError::Template(TemplateError::already_exists(
+                target_path,
+            )));
+        }
+
+        let mut metadata = source_manifest.metadata.clone();
+        metadata.forked_from = Some(format!("{}@{}", source.full_name(), source_hash));
+
+        let manifest = Manifest::new(source_manifest.files, metadata).map_err(|e| {
+            RegistryError::ContentAddressing(crate::error::ContentAddressingError::manifest_error(
+                e.to_string(),
+            ))
+        })?;
+        let manifest_hash = self.store_manifest(&manifest).await?;
+
+        self.update_ref(&target_path, "latest", &manifest_hash)
+            .await?;
+
+        Ok(manifest_hash)
+    }
+
+    /// Store everything read from `reader` as a content-addressed blob
+    ///
+    /// Unlike publishing a [`TemplateBundle`], which holds every file in
+    /// memory, the content is hashed while it is read and large inputs are
+    /// spilled to a temporary file (see [`ContentAddress::hash_reader`]), then
+    /// uploaded with [`BlobStorage::put_stream`]. Reading is blocking.
+    ///
+    /// Returns the content hash, usable as a file entry in a manifest.
+    pub async fn store_blob_from_reader<Rd: Read>(
+        &self,
+        reader: Rd,
+    ) -> Result<String, RegistryError> {
+        let (hash, content) = ContentAddress::hash_reader(reader, DEFAULT_SPILL_THRESHOLD)
+            .map_err(|e| {
+                RegistryError::Storage(StorageError::backend(format!(
+                    "Failed to read blob content: {}",
+                    e
+                )))
+            })?;
+
+        self.storage
+            .put_stream(&ContentAddress::blob_key(&hash), content)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        Ok(hash)
+    }
+
+    /// Store bundle files and manifest as content-addressed blobs (publish steps 1-4)
+    async fn store_bundle(
+        &self,
+        mut bundle: TemplateBundle,
+        namespace_path: &str,
+    ) -> Result<PublishReport, RegistryError> {
+        self.apply_namespace_defaults(&mut bundle, namespace_path);
+
+        // Step 1: Validate the bundle
+        self.limits
+            .check(&bundle)
+            .map_err(RegistryError::Template)?;
+        bundle.validate().map_err(|e| match e {
+            TemplateValidationError::MissingFile(path) => {
+                RegistryError::Template(TemplateError::missing_file(path))
+            }
+            e => RegistryError::Template(TemplateError::invalid(e.to_string())),
+        })?;
+        if self.compile_check {
+            let errors: Vec<DiagnosticInfo> = self
+                .check(&bundle)?
+                .into_iter()
+                .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error)
+                .collect();
+            if !errors.is_empty() {
+                return Err(RegistryError::Compilation(
+                    CompilationError::TypstError {
+                        error_count: errors.len(),
+                        diagnostics: errors,
+                    }
+                    .into(),
+                ));
+            }
+        }
+
+        // Step 2: Store individual files as blobs, skipping those already stored
+        let mut report = PublishReport::default();
+        let contents =
+            std::iter::once(bundle.main_typ()).chain(bundle.files().values().map(Vec::as_slice));
+        for content in contents {
+            let blob_key = ContentAddress::blob_key(&ContentAddress::hash(content));
+            let exists = self
+                .storage
+                .exists(&blob_key)
+                .await
+                .map_err(|e| RegistryError::Storage(e.into()))?;
+            if exists {
+                report.deduplicated += 1;
+                continue;
+            }
             self.storage
-                .put(&file_blob_key, file_content.clone())
+                .put(&blob_key, content.to_vec())
                 .await
-                .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
-            file_hashes.insert(file_path.clone(), file_hash);
+                .map_err(|e| RegistryError::Storage(e.into()))?;
+            report.uploaded += 1;
         }
 
         // Step 3: Create manifest
-        let manifest = Manifest::new(file_hashes, bundle.metadata().clone()).map_err(|e| {
+        let manifest = Self::bundle_manifest(&bundle)?;
+
+        // Step 4: Store manifest
+        report.manifest_hash = self.store_manifest(&manifest).await?;
+        Ok(report)
+    }
+
+    /// Merge the defaults configured for the namespace of `namespace_path`
+    fn apply_namespace_defaults(&self, bundle: &mut TemplateBundle, namespace_path: &str) {
+        if let Some((namespace, _)) = namespace_path.rsplit_once('/')
+            && let Some(defaults) = self.namespace_defaults(namespace)
+        {
+            bundle.metadata_mut().merge_defaults(defaults);
+        }
+    }
+
+    /// Build the manifest mapping each of the bundle's files to its content hash
+    fn bundle_manifest(bundle: &TemplateBundle) -> Result<Manifest, RegistryError> {
+        let mut file_hashes = BTreeMap::new();
+        file_hashes.insert(
+            "main.typ".to_string(),
+            ContentAddress::hash(bundle.main_typ()),
+        );
+        for (file_path, file_content) in bundle.files() {
+            file_hashes.insert(file_path.clone(), ContentAddress::hash(file_content));
+        }
+
+        Manifest::new(file_hashes, bundle.metadata().clone()).map_err(|e| {
             RegistryError::ContentAddressing(crate::error::ContentAddressingError::manifest_error(
                 e.to_string(),
             ))
-        })?;
+        })
+    }
 
-        // Step 4: Store manifest
+    /// Store a manifest as a content-addressed blob, returning its hash
+    async fn store_manifest(&self, manifest: &Manifest) -> Result<String, RegistryError> {
         let manifest_bytes = manifest.to_bytes().map_err(|e| {
             RegistryError::ContentAddressing(crate::error::ContentAddressingError::manifest_error(
                 e.to_string(),
@@ -139,37 +890,388 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         self.storage
             .put(&manifest_key, manifest_bytes)
             .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
+            .map_err(|e| RegistryError::Storage(e.into()))?;
 
-        // Step 5: Update reference (tag)
+        Ok(manifest_hash)
+    }
+
+    /// Point a reference (tag) at a manifest hash
+    async fn update_ref(
+        &self,
+        namespace: &str,
+        tag: &str,
+        manifest_hash: &str,
+    ) -> Result<(), RegistryError> {
         let ref_key = ContentAddress::ref_key(namespace, tag);
         self.storage
             .put(&ref_key, manifest_hash.as_bytes().to_vec())
             .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        // The ref is the source of truth, so it is written before the index
+        if self.refs_index {
+            let _guard = self.index_lock.lock().await;
+            let mut index = self.read_refs_index().await?.unwrap_or_default();
+            index.set(namespace, tag, manifest_hash);
+            self.write_refs_index(&index).await?;
+        }
 
-        // Return the manifest hash for content-addressable access
-        Ok(manifest_hash)
+        Ok(())
     }
 
-    /// Resolve a template reference to its manifest hash
+    /// List the tags of one template, e.g. `"john/invoice"`
     ///
-    /// This method implements the "tag → manifest hash lookup" workflow:
-    /// 1. Parses the reference string (namespace/name:tag[@hash])
-    /// 2. Looks up the reference in storage to get the manifest hash
-    /// 3. Optionally verifies the hash if provided in the reference
-    /// 4. Returns the manifest hash for content-addressable access
+    /// Tags are sorted alphabetically, except that `latest` comes first if
+    /// present. Any tag or hash in `namespace_and_name` is ignored.
     ///
-    /// # Examples
-    /// - `"invoice:latest"` → resolves official template
-    /// - `"john/invoice:v1.0.0"` → resolves user template
-    /// - `"john/invoice:latest@sha256:abc123"` → resolves with hash verification
-    pub async fn resolve(&self, reference: &str) -> Result<String, RegistryError> {
-        // Step 1: Parse the reference
-        let parsed_ref = Reference::parse(reference)?;
+    /// # Errors
+    /// Returns `TemplateError::NotFound` if the template has no tags, or
+    /// `RegistryError::AccessDenied` if the access policy denies anonymous
+    /// reads of its namespace.
+    pub async fn list_tags(&self, namespace_and_name: &str) -> Result<Vec<String>, RegistryError> {
+        let parsed = Reference::parse(namespace_and_name)?;
+        self.check_read(&parsed, None)?;
+        let namespace_path = parsed.full_name();
+
+        let mut tags: Vec<String> = if self.refs_index
+            && let Some(index) = self.read_refs_index().await?
+        {
+            index
+                .templates
+                .get(&namespace_path)
+                .map(|tags| tags.keys().cloned().collect())
+                .unwrap_or_default()
+        } else {
+            self.storage
+                .list_keys(&format!("refs/{}/", namespace_path))
+                .await
+                .map_err(|e| RegistryError::Storage(e.into()))?
+                .iter()
+                .filter_map(|ref_key| Self::parse_ref_key(ref_key))
+                // "refs/invoice/" also matches templates in a namespace called "invoice"
+                .filter(|(path, _)| *path == namespace_path)
+                .map(|(_, tag)| tag)
+                .collect()
+        };
 
-        // Step 2: Build the namespace/tag path for storage lookup
-        let namespace_path = match &parsed_ref.namespace {
+        if tags.is_empty() {
+            return Err(RegistryError::Template(TemplateError::not_found(
+                namespace_path,
+            )));
+        }
+
+        tags.sort_by(|a, b| (a != "latest", a).cmp(&(b != "latest", b)));
+        Ok(tags)
+    }
+
+    /// Delete a single tag, e.g. `"john/invoice:v1"`
+    ///
+    /// Only the reference is removed; the manifest and file blobs it pointed
+    /// to stay in storage until [`Registry::gc`] collects them.
+    ///
+    /// As when resolving, a reference without a tag means `latest`.
+    ///
+    /// # Errors
+    /// Returns `TemplateError::NotFound` if the tag does not exist.
+    pub async fn delete_tag(&self, reference: &str) -> Result<(), RegistryError> {
+        let parsed = Reference::parse(reference)?;
+        let tag = parsed.tag_or_default();
+        let namespace_path = parsed.full_name();
+
+        let ref_key = ContentAddress::ref_key(&namespace_path, tag);
+        let exists = self
+            .storage
+            .exists(&ref_key)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+        if !exists {
+            return Err(RegistryError::Template(TemplateError::not_found(reference)));
+        }
+
+        self.storage
+            .delete(&ref_key)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        // As in update_ref, the ref is changed before the index
+        if self.refs_index {
+            let _guard = self.index_lock.lock().await;
+            let mut index = self.read_refs_index().await?.unwrap_or_default();
+            index.remove(&namespace_path, tag);
+            self.write_refs_index(&index).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reconstruct the reference index from a full scan of `refs/`
+    ///
+    /// Use this to recover from a missing, corrupt or stale index. Returns
+    /// the rebuilt index, which is also stored.
+    pub async fn rebuild_index(&self) -> Result<RefsIndex, RegistryError> {
+        let _guard = self.index_lock.lock().await;
+
+        let ref_keys = self
+            .storage
+            .list_keys("refs/")
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        let mut index = RefsIndex::new();
+        for ref_key in ref_keys {
+            let Some((namespace_path, tag)) = Self::parse_ref_key(&ref_key) else {
+                continue;
+            };
+
+            let manifest_hash_bytes = self
+                .storage
+                .get(&ref_key)
+                .await
+                .map_err(|e| RegistryError::Storage(e.into()))?;
+
+            // Skip invalid references, as list_templates does
+            if let Ok(manifest_hash) = String::from_utf8(manifest_hash_bytes) {
+                index.set(&namespace_path, &tag, &manifest_hash);
+            }
+        }
+
+        self.write_refs_index(&index).await?;
+        Ok(index)
+    }
+
+    /// Delete stored content that is no longer reachable
+    ///
+    /// Walks every ref under `refs/`, loads the manifests they point to and
+    /// marks those manifests, their signatures and their file blobs as live.
+    /// Manifests a render in render storage used stay live the same way, as
+    /// do that render's inputs and PDF. Render records are read page by page.
+    /// Every other key under `blobs/`, `manifests/`, `signatures/`, `data/`
+    /// and `pdfs/` is deleted; sizes come from `head`, not from downloading.
+    ///
+    /// Safe to run alongside reads through refs and alongside
+    /// [`Registry::render_pinned`] of a manifest that was rendered before.
+    /// A digest neither a ref nor a recorded render points to is deleted, so
+    /// reading it by digest afterwards fails with `NotFound`. Not safe to run
+    /// alongside publishing: a bundle's blobs are stored before its ref is
+    /// written, so a concurrent publish may lose them.
+    pub async fn gc(&self) -> Result<GcReport, RegistryError> {
+        let mut live = HashSet::new();
+        // Manifests to keep, and whether a ref (rather than a render) points to them
+        let mut manifests: Vec<(String, bool)> = Vec::new();
+
+        let ref_keys = self
+            .storage
+            .list_keys("refs/")
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+        for ref_key in ref_keys {
+            let manifest_hash_bytes = self
+                .storage
+                .get(&ref_key)
+                .await
+                .map_err(|e| RegistryError::Storage(e.into()))?;
+            if let Ok(manifest_hash) = String::from_utf8(manifest_hash_bytes) {
+                manifests.push((manifest_hash, true));
+            }
+        }
+
+        if let Some(render_storage) = &self.render_storage {
+            let mut page = Page::new(GC_RENDER_PAGE_SIZE);
+            loop {
+                let records = render_storage.list_renders(page.clone()).await?;
+                let Some(last) = records.last() else {
+                    break;
+                };
+                page = page.after(last);
+                let full_page = records.len() == GC_RENDER_PAGE_SIZE as usize;
+
+                for record in records {
+                    live.insert(ContentAddress::data_key(&record.data_hash));
+                    live.insert(ContentAddress::pdf_key(&record.pdf_hash));
+                    // Keeps pinned manifests renderable by digest
+                    if ContentAddress::is_valid_hash(&record.manifest_hash) {
+                        manifests.push((record.manifest_hash, false));
+                    }
+                }
+                if !full_page {
+                    break;
+                }
+            }
+        }
+
+        for (manifest_hash, from_ref) in manifests {
+            if !live.insert(ContentAddress::manifest_key(&manifest_hash)) {
+                continue;
+            }
+            live.insert(ContentAddress::signature_key(&manifest_hash));
+
+            // A ref to an unreadable manifest aborts instead of risking live
+            // content, while a render may name a manifest that is long gone
+            let manifest = match self.load_manifest(&manifest_hash).await {
+                Ok(manifest) => manifest,
+                Err(RegistryError::Storage(StorageError::NotFound { .. })) if !from_ref => {
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            live.extend(
+                manifest
+                    .files
+                    .values()
+                    .map(|hash| ContentAddress::blob_key(hash)),
+            );
+        }
+
+        let mut report = GcReport::default();
+        for prefix in ["blobs/", "manifests/", "signatures/", "data/", "pdfs/"] {
+            let keys = self
+                .storage
+                .list_keys(prefix)
+                .await
+                .map_err(|e| RegistryError::Storage(e.into()))?;
+
+            for key in keys {
+                if live.contains(&key) {
+                    report.retained += 1;
+                    continue;
+                }
+
+                let size = match self.storage.head(&key).await {
+                    Ok(meta) => meta.size,
+                    // Already removed, e.g. by another gc run
+                    Err(crate::storage::blob_storage::StorageError::NotFound(_)) => continue,
+                    Err(e) => {
+                        return Err(RegistryError::Storage(e.into()));
+                    }
+                };
+                self.storage
+                    .delete(&key)
+                    .await
+                    .map_err(|e| RegistryError::Storage(e.into()))?;
+
+                report.deleted += 1;
+                report.bytes_reclaimed += size;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Read the reference index, or `None` if it is missing or unreadable
+    async fn read_refs_index(&self) -> Result<Option<RefsIndex>, RegistryError> {
+        match self.storage.get(REFS_INDEX_KEY).await {
+            Ok(bytes) => Ok(RefsIndex::from_bytes(&bytes).ok()),
+            Err(crate::storage::blob_storage::StorageError::NotFound(_)) => Ok(None),
+            Err(e) => Err(RegistryError::Storage(e.into())),
+        }
+    }
+
+    async fn write_refs_index(&self, index: &RefsIndex) -> Result<(), RegistryError> {
+        self.storage
+            .put(REFS_INDEX_KEY, index.to_bytes()?)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))
+    }
+
+    /// Verify the detached signature of a manifest against the configured key
+    ///
+    /// No-op when the registry has no verifying key configured.
+    async fn verify_signature(&self, manifest_hash: &str) -> Result<(), RegistryError> {
+        let Some(verifying_key) = &self.verifying_key else {
+            return Ok(());
+        };
+
+        let signature_key = ContentAddress::signature_key(manifest_hash);
+        let signature_bytes = self
+            .storage
+            .get(&signature_key)
+            .await
+            .map_err(|e| match e {
+                crate::storage::blob_storage::StorageError::NotFound(_) => {
+                    RegistryError::Signature(SignatureError::missing(manifest_hash))
+                }
+                _ => RegistryError::Storage(e.into()),
+            })?;
+
+        let signature = Signature::from_slice(&signature_bytes).map_err(|e| {
+            RegistryError::Signature(SignatureError::invalid(manifest_hash, e.to_string()))
+        })?;
+
+        verifying_key
+            .verify(manifest_hash.as_bytes(), &signature)
+            .map_err(|e| {
+                RegistryError::Signature(SignatureError::invalid(manifest_hash, e.to_string()))
+            })
+    }
+
+    /// Resolve a template reference to its manifest hash
+    ///
+    /// This method implements the "tag → manifest hash lookup" workflow:
+    /// 1. Parses the reference string (namespace/name:tag[@hash])
+    /// 2. Looks up the reference in storage to get the manifest hash
+    /// 3. Optionally verifies the hash if provided in the reference
+    /// 4. Verifies the manifest signature if a verifying key is configured
+    /// 5. Returns the manifest hash for content-addressable access
+    ///
+    /// If the reference carries a hash and the tag does not exist, the
+    /// manifest is resolved by digest alone, as with OCI's `name@sha256:...`.
+    /// Without an access policy the digest is not checked against the
+    /// template name, so any stored manifest can be reached this way; with
+    /// one, a template in the reference's namespace must be tagged with it.
+    ///
+    /// # Examples
+    /// - `"invoice:latest"` → resolves official template
+    /// - `"john/invoice:v1.0.0"` → resolves user template
+    /// - `"john/invoice:latest@sha256:abc123"` → resolves with hash verification
+    /// - `"john/invoice@sha256:abc123"` → resolves by digest, even without a `latest` tag
+    ///
+    /// With an access policy configured, the reference is resolved as an
+    /// anonymous caller; see [`Registry::resolve_as`].
+    pub async fn resolve(&self, reference: &str) -> Result<String, RegistryError> {
+        self.resolve_for(reference, None).await
+    }
+
+    /// Resolve a template reference on behalf of `user`
+    ///
+    /// Like [`Registry::resolve`], but the access policy is asked whether
+    /// `user` may read the template's namespace.
+    ///
+    /// # Errors
+    /// Returns `RegistryError::AccessDenied` if the policy denies the read,
+    /// whether or not the template exists.
+    pub async fn resolve_as(&self, reference: &str, user: &str) -> Result<String, RegistryError> {
+        self.resolve_for(reference, Some(user)).await
+    }
+
+    /// Resolve a template reference to its manifest hash and parsed manifest
+    ///
+    /// Like [`Registry::resolve`], additionally loading the manifest so the
+    /// template's files and metadata can be inspected without a second
+    /// lookup. The manifest is checked against its hash as in rendering.
+    pub async fn resolve_manifest(
+        &self,
+        reference: &str,
+    ) -> Result<(String, Manifest), RegistryError> {
+        let manifest_hash = self.resolve(reference).await?;
+        let manifest = self
+            .load_manifest(&manifest_hash)
+            .instrument(tracing::info_span!("load_manifest", manifest_hash = %manifest_hash))
+            .await?;
+        Ok((manifest_hash, manifest))
+    }
+
+    /// Resolve a reference, checking read access for `user` (`None` for anonymous)
+    async fn resolve_for(
+        &self,
+        reference: &str,
+        user: Option<&str>,
+    ) -> Result<String, RegistryError> {
+        // Step 1: Parse the reference and check access before any lookup
+        let parsed_ref = Reference::parse(reference)?;
+        self.check_read(&parsed_ref, user)?;
+
+        // Step 2: Build the namespace/tag path for storage lookup
+        let namespace_path = match &parsed_ref.namespace {
             Some(ns) => format!("{}/{}", ns, parsed_ref.name),
             None => parsed_ref.name.clone(),
         };
@@ -177,12 +1279,19 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         let ref_key = ContentAddress::ref_key(&namespace_path, tag);
 
         // Step 3: Look up the manifest hash from storage
-        let manifest_hash_bytes = self.storage.get(&ref_key).await.map_err(|e| match e {
-            crate::storage::blob_storage::StorageError::NotFound(_) => {
-                RegistryError::Template(crate::error::TemplateError::not_found(reference))
+        let manifest_hash_bytes = match self.storage.get(&ref_key).await {
+            Ok(bytes) => bytes,
+            Err(crate::storage::blob_storage::StorageError::NotFound(_)) => {
+                // Fall back to the digest when the tag does not exist
+                return match &parsed_ref.hash {
+                    Some(hash) => self.resolve_digest(reference, hash).await,
+                    None => Err(RegistryError::Template(
+                        crate::error::TemplateError::not_found(reference),
+                    )),
+                };
             }
-            _ => RegistryError::Storage(StorageError::backend(e.to_string())),
-        })?;
+            Err(e) => return Err(RegistryError::Storage(e.into())),
+        };
 
         let manifest_hash = String::from_utf8(manifest_hash_bytes).map_err(|e| {
             RegistryError::Storage(StorageError::backend(format!(
@@ -192,36 +1301,139 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         })?;
 
         // Step 4: Verify hash if provided in reference
-        if let Some(expected_hash) = &parsed_ref.hash {
-            if &manifest_hash != expected_hash {
-                return Err(RegistryError::Reference(
-                    crate::error::ReferenceError::hash_mismatch(
-                        reference.to_string(),
-                        expected_hash.clone(),
-                        manifest_hash,
-                    ),
-                ));
-            }
+        if let Some(expected_hash) = &parsed_ref.hash
+            && &manifest_hash != expected_hash
+        {
+            return Err(RegistryError::Reference(
+                crate::error::ReferenceError::hash_mismatch(
+                    reference.to_string(),
+                    expected_hash.clone(),
+                    manifest_hash,
+                ),
+            ));
         }
 
+        // Step 5: Verify manifest signature if required
+        self.verify_signature(&manifest_hash).await?;
+
         // Return the manifest hash
         Ok(manifest_hash)
     }
 
+    /// Ask the access policy whether `user` may read `reference`
+    fn check_read(&self, reference: &Reference, user: Option<&str>) -> Result<(), RegistryError> {
+        if self.can_read(reference.namespace.as_deref(), user) {
+            return Ok(());
+        }
+
+        Err(RegistryError::AccessDenied(format!(
+            "{} may not read {}",
+            user.unwrap_or("anonymous user"),
+            reference.full_name()
+        )))
+    }
+
+    /// Whether the access policy, if any, lets `user` read `namespace`
+    fn can_read(&self, namespace: Option<&str>, user: Option<&str>) -> bool {
+        self.access_policy
+            .as_ref()
+            .is_none_or(|policy| policy.can_read(namespace, user))
+    }
+
+    /// Resolve a manifest by its hash alone, without a tag
+    ///
+    /// With an access policy the manifest must be tagged by a template in
+    /// the namespace of `reference`, which the caller has checked; otherwise
+    /// a digest would reach templates in namespaces the caller may not read.
+    async fn resolve_digest(&self, reference: &str, hash: &str) -> Result<String, RegistryError> {
+        if self.access_policy.is_some() {
+            let parsed = Reference::parse(reference)?;
+            if !self
+                .tagged_in_namespace(parsed.namespace.as_deref(), hash)
+                .await?
+            {
+                return Err(RegistryError::AccessDenied(format!(
+                    "{} is not tagged in {}",
+                    hash,
+                    parsed.namespace.as_deref().unwrap_or("the root namespace")
+                )));
+            }
+        }
+
+        let exists = self
+            .storage
+            .exists(&ContentAddress::manifest_key(hash))
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+        if !exists {
+            return Err(RegistryError::Template(
+                crate::error::TemplateError::not_found(reference),
+            ));
+        }
+
+        self.verify_signature(hash).await?;
+        Ok(hash.to_string())
+    }
+
+    /// Whether a template directly in `namespace` has a tag pointing to `hash`
+    async fn tagged_in_namespace(
+        &self,
+        namespace: Option<&str>,
+        hash: &str,
+    ) -> Result<bool, RegistryError> {
+        let in_namespace = |namespace_path: &str| {
+            Self::parse_namespace_path(namespace_path).0.as_deref() == namespace
+        };
+
+        if self.refs_index
+            && let Some(index) = self.read_refs_index().await?
+        {
+            return Ok(index.templates.iter().any(|(namespace_path, tags)| {
+                in_namespace(namespace_path) && tags.values().any(|tagged| tagged == hash)
+            }));
+        }
+
+        let prefix = match namespace {
+            Some(namespace) => format!("refs/{}/", namespace),
+            None => "refs/".to_string(),
+        };
+        let ref_keys = self
+            .storage
+            .list_keys(&prefix)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+        for ref_key in ref_keys {
+            if !Self::parse_ref_key(&ref_key).is_some_and(|(path, _)| in_namespace(&path)) {
+                continue;
+            }
+            match self.storage.get(&ref_key).await {
+                Ok(tagged) if tagged == hash.as_bytes() => return Ok(true),
+                Ok(_) | Err(crate::storage::blob_storage::StorageError::NotFound(_)) => {}
+                Err(e) => return Err(RegistryError::Storage(e.into())),
+            }
+        }
+
+        Ok(false)
+    }
+
     /// Render a template to PDF using JSON data
     ///
     /// This method implements the end-to-end template rendering workflow:
     /// 1. Resolves the template reference to get the manifest hash
     /// 2. Loads the manifest from storage to get file mappings
     /// 3. Creates a RegistryFileSystem that resolves files through blob storage
-    /// 4. Uses papermake to render the template with the provided data
+    /// 4. Validates the data against the bundle's `schema.json`, if present
+    /// 5. Uses papermake to render the template with the provided data
     ///
     /// # Arguments
-    /// * `reference` - Template reference (e.g., "john/invoice:latest")
+    /// * `reference` - Template reference (e.g., "john/invoice:latest").
+    ///   Append `#name` (e.g. "john/docs:latest#receipt") to compile one of
+    ///   the bundle's named entrypoints instead of `main.typ`
     /// * `data` - JSON data to inject into the template
     ///
     /// # Returns
-    /// Returns the PDF bytes on successful rendering
+    /// Returns the PDF bytes on successful rendering, or
+    /// `TemplateError::NotFound` for an entrypoint the bundle does not declare
     ///
     /// # Examples
     /// ```rust,no_run
@@ -231,7 +1443,7 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let storage = MemoryStorage::new();
-    /// let registry = Registry::new(storage);
+    /// let registry = Registry::new_storage_only(storage);
     ///
     /// let pdf_bytes = registry.render(
     ///     "john/invoice:latest",
@@ -250,95 +1462,442 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         reference: &str,
         data: &serde_json::Value,
     ) -> Result<Vec<u8>, RegistryError> {
-        // Step 1: Resolve the template reference to get manifest hash
-        let manifest_hash = self.resolve(reference).await?;
+        self.prepare(reference).await?.render(data)
+    }
 
-        // Step 2: Load the manifest from storage
-        let manifest_key = ContentAddress::manifest_key(&manifest_hash);
-        let manifest_bytes = self.storage.get(&manifest_key).await.map_err(|e| {
-            RegistryError::Storage(StorageError::backend(format!(
-                "Failed to load manifest {}: {}",
-                manifest_hash, e
-            )))
-        })?;
+    /// Render a template to PDF on behalf of `user`
+    ///
+    /// Like [`Registry::render`], resolving the reference as with
+    /// [`Registry::resolve_as`].
+    pub async fn render_as(
+        &self,
+        reference: &str,
+        data: &serde_json::Value,
+        user: &str,
+    ) -> Result<Vec<u8>, RegistryError> {
+        self.prepare_as(reference, user).await?.render(data)
+    }
 
-        let manifest = Manifest::from_bytes(&manifest_bytes).map_err(|e| {
-            RegistryError::ContentAddressing(crate::error::ContentAddressingError::manifest_error(
-                e.to_string(),
-            ))
-        })?;
+    /// Render a template by reference with explicit render options
+    ///
+    /// Like [`Registry::render`], applying `options` to the render. Set
+    /// `options.skip_validation` to bypass the template's `schema.json`.
+    pub async fn render_with_options(
+        &self,
+        reference: &str,
+        data: &serde_json::Value,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, RegistryError> {
+        self.prepare(reference)
+            .await?
+            .render_with_options(data, options)
+    }
 
-        // Step 3: Get the entrypoint content
-        let entrypoint_hash = manifest.entrypoint_hash().ok_or_else(|| {
-            RegistryError::Template(crate::error::TemplateError::invalid(
-                "Manifest missing entrypoint hash",
-            ))
-        })?;
+    /// Resolve a template once for rendering many data sets
+    ///
+    /// Performs steps 1-4 of [`Registry::render`]: resolves the reference,
+    /// loads the manifest and entrypoint and creates the file system. The
+    /// returned [`PreparedTemplate`] can then render any number of data sets
+    /// without touching the reference again.
+    pub async fn prepare(&self, reference: &str) -> Result<PreparedTemplate, RegistryError> {
+        self.prepare_for(reference, None).await
+    }
 
-        let entrypoint_key = ContentAddress::blob_key(entrypoint_hash);
-        let entrypoint_bytes = self.storage.get(&entrypoint_key).await.map_err(|e| {
-            RegistryError::Storage(StorageError::backend(format!(
-                "Failed to load entrypoint file: {}",
-                e
-            )))
-        })?;
+    /// Resolve a template once for rendering on behalf of `user`
+    ///
+    /// Like [`Registry::prepare`], resolving the reference as with
+    /// [`Registry::resolve_as`].
+    pub async fn prepare_as(
+        &self,
+        reference: &str,
+        user: &str,
+    ) -> Result<PreparedTemplate, RegistryError> {
+        self.prepare_for(reference, Some(user)).await
+    }
 
-        let entrypoint_content = String::from_utf8(entrypoint_bytes).map_err(|e| {
-            RegistryError::Template(crate::error::TemplateError::invalid(format!(
-                "Entrypoint file is not valid UTF-8: {}",
-                e
-            )))
-        })?;
+    /// Prepare a template, checking read access for `user` (`None` for anonymous)
+    async fn prepare_for(
+        &self,
+        reference: &str,
+        user: Option<&str>,
+    ) -> Result<PreparedTemplate, RegistryError> {
+        // Step 1: Resolve the template reference to get manifest hash
+        let (template_ref, entrypoint) = split_entrypoint(reference);
+        let manifest_hash = self
+            .resolve_for(template_ref, user)
+            .instrument(tracing::info_span!("resolve", reference))
+            .await?;
+
+        let cache_key = match entrypoint {
+            Some(entrypoint) => format!("{}#{}", manifest_hash, entrypoint),
+            None => manifest_hash.clone(),
+        };
+        if let Some(cache) = &self.template_cache {
+            let cached = cache.lock().map_err(CacheError::from)?.get(&cache_key);
+            if let Some(mut prepared) = cached {
+                prepared.reference = reference.to_string();
+                return Ok(prepared);
+            }
+        }
 
-        // Step 4: Create RegistryFileSystem for resolving imports
-        let file_system = RegistryFileSystem::new(self.storage.clone(), manifest)?;
-
-        // Step 5: Render the template using papermake
-        let render_result =
-            papermake::render_template(entrypoint_content, Arc::new(file_system), data)
-                .map_err(RegistryError::Compilation)?;
-
-        // Check if rendering was successful
-        if render_result.success {
-            render_result.pdf.ok_or_else(|| {
-                RegistryError::Template(crate::error::TemplateError::invalid(
-                    "Rendering succeeded but no PDF was generated",
-                ))
-            })
-        } else {
-            // Collect error messages
-            let error_messages: Vec<String> =
-                render_result.errors.iter().map(|e| e.to_string()).collect();
+        let prepared = self
+            .load_prepared(reference, manifest_hash, entrypoint)
+            .await?;
 
-            Err(RegistryError::Template(
-                crate::error::TemplateError::invalid(format!(
-                    "Template rendering failed: {}",
-                    error_messages.join("; ")
-                )),
-            ))
+        if let Some(cache) = &self.template_cache {
+            cache
+                .lock()
+                .map_err(CacheError::from)?
+                .insert(cache_key, prepared.clone());
         }
+
+        Ok(prepared)
     }
 
-    /// List all templates in the registry
+    /// Get the contents of one file of a template bundle by reference
     ///
-    /// This method scans all references in storage and groups them by template
-    /// to provide a comprehensive list of available templates with their metadata.
+    /// `path` is relative to the bundle root, e.g. `main.typ` or
+    /// `assets/logo.png`; a leading `/` is ignored.
     ///
-    /// # Returns
-    /// Returns a vector of `TemplateInfo` structs containing:
-    /// - Template name and namespace
-    /// - Available tags
-    /// - Latest manifest hash (from "latest" tag or newest tag)
-    /// - Template metadata from the manifest
+    /// # Errors
+    /// Returns `TemplateError::NotFound` if the bundle has no such file.
+    pub async fn get_template_file(
+        &self,
+        reference: &str,
+        path: &str,
+    ) -> Result<Vec<u8>, RegistryError> {
+        let (_, manifest) = self.resolve_manifest(reference).await?;
+
+        let path = path.trim_start_matches('/');
+        let file_hash = manifest.get_file_hash(path).ok_or_else(|| {
+            RegistryError::Template(crate::error::TemplateError::not_found(format!(
+                "{}/{}",
+                reference, path
+            )))
+        })?;
+
+        self.load_blob(file_hash, path).await
+    }
+
+    /// Example data for previewing a template, e.g. in an editor
     ///
-    /// # Examples
-    /// ```rust,no_run
-    /// use papermake_registry::Registry;
-    /// use papermake_registry::storage::blob_storage::MemoryStorage;
+    /// Generated from the template's `schema.json` with
+    /// [`papermake::generate_sample_data`], with the values of its
+    /// `defaults.json` taking precedence. A template without a schema yields
+    /// its defaults, or an empty object.
+    pub async fn sample_data(&self, reference: &str) -> Result<serde_json::Value, RegistryError> {
+        let (_, manifest) = self.resolve_manifest(reference).await?;
+        let schema = self.load_json_file(&manifest, "schema.json").await?;
+        let defaults = self.load_json_file(&manifest, "defaults.json").await?;
+
+        let sample = match schema {
+            Some(schema) => papermake::generate_sample_data(&schema),
+            None => serde_json::json!({}),
+        };
+        Ok(match defaults {
+            Some(defaults) => merge_over_defaults(&sample, &defaults),
+            None => sample,
+        })
+    }
+
+    /// List the input fields the template behind `reference` consumes
+    ///
+    /// Combines the template's `schema.json`, if any, with the fields its
+    /// main template reads, see [`papermake::describe_inputs`]. Meant for
+    /// generating data-entry forms. A `reference#entrypoint` describes the
+    /// named entrypoint instead of `main.typ`.
+    pub async fn describe_inputs(&self, reference: &str) -> Result<Vec<InputField>, RegistryError> {
+        let (template_ref, entrypoint) = split_entrypoint(reference);
+        let (_, manifest) = self.resolve_manifest(template_ref).await?;
+        let main_typ = self
+            .load_entrypoint(
+                &manifest,
+                entrypoint_path(&manifest, reference, entrypoint)?,
+            )
+            .await?;
+        let schema = self.load_json_file(&manifest, "schema.json").await?;
+
+        Ok(papermake::describe_inputs(&main_typ, schema.as_ref()))
+    }
+
+    /// Write the bundle behind `reference` as a tar archive
+    ///
+    /// See [`TemplateBundle::to_tar`] for the layout. The archive carries the
+    /// manifest's metadata unchanged, so [`Registry::publish_tar`] restores
+    /// the same manifest in another registry. Writing is blocking.
+    pub async fn export_tar<W: Write>(
+        &self,
+        reference: &str,
+        writer: W,
+    ) -> Result<(), RegistryError> {
+        let (_, manifest) = self.resolve_manifest(reference).await?;
+
+        let main_hash = manifest
+            .get_file_hash(&manifest.entrypoint)
+            .ok_or_else(|| TemplateError::missing_file(&manifest.entrypoint))?;
+        let mut bundle = TemplateBundle::new(
+            self.load_blob(main_hash, &manifest.entrypoint).await?,
+            manifest.metadata.clone(),
+        );
+        for (path, file_hash) in &manifest.files {
+            if *path != manifest.entrypoint {
+                let content = self.load_blob(file_hash, path).await?;
+                bundle = bundle.add_file(path, content);
+            }
+        }
+
+        bundle
+            .to_tar(writer)
+            .map_err(|e| RegistryError::Template(TemplateError::conversion_failed(e.to_string())))
+    }
+
+    /// Compare the files of two template versions
+    ///
+    /// `ref_a` is treated as the older version: files only in `ref_b` are
+    /// reported as added, files only in `ref_a` as removed. Changed files
+    /// whose content is valid UTF-8 in both versions also get a unified
+    /// line diff in [`ManifestDiff::text_diffs`].
+    pub async fn diff(&self, ref_a: &str, ref_b: &str) -> Result<ManifestDiff, RegistryError> {
+        let (_, old) = self.resolve_manifest(ref_a).await?;
+        let (_, new) = self.resolve_manifest(ref_b).await?;
+
+        let mut diff = ManifestDiff::between(&old, &new);
+        for path in &diff.changed {
+            let old_content = self.load_blob(&old.files[path], path).await?;
+            let new_content = self.load_blob(&new.files[path], path).await?;
+
+            if let (Ok(old_text), Ok(new_text)) = (
+                std::str::from_utf8(&old_content),
+                std::str::from_utf8(&new_content),
+            ) {
+                let text_diff = similar::TextDiff::from_lines(old_text, new_text)
+                    .unified_diff()
+                    .header(
+                        &format!("{}/{}", ref_a, path),
+                        &format!("{}/{}", ref_b, path),
+                    )
+                    .to_string();
+                diff.text_diffs.insert(path.clone(), text_diff);
+            }
+        }
+
+        Ok(diff)
+    }
+
+    /// Load a file blob by hash, naming `path` in errors
+    async fn load_blob(&self, file_hash: &str, path: &str) -> Result<Vec<u8>, RegistryError> {
+        self.storage
+            .get(&ContentAddress::blob_key(file_hash))
+            .await
+            .map_err(|e| {
+                RegistryError::Storage(
+                    StorageError::from(e).with_context(format!("Failed to load {}", path)),
+                )
+            })
+    }
+
+    /// Fetch content-addressed content and check it against the hash in its key
+    ///
+    /// `key` must be a content-addressed key such as
+    /// `blobs/sha256/{hash}` or `manifests/sha256/{hash}`.
+    ///
+    /// # Errors
+    /// Returns `ContentAddressingError::InvalidHashFormat` if `key` holds no
+    /// hash and `ContentAddressingError::IntegrityCheckFailed` if the fetched
+    /// bytes hash to a different value.
+    pub async fn get_verified(&self, key: &str) -> Result<Vec<u8>, RegistryError> {
+        let expected = key
+            .rsplit_once("/sha256/")
+            .map(|(_, hash_value)| format!("sha256:{}", hash_value))
+            .filter(|hash| ContentAddress::is_valid_hash(hash))
+            .ok_or_else(|| ContentAddressingError::invalid_hash_format(key))?;
+
+        let content = self.storage.get(key).await.map_err(|e| {
+            RegistryError::Storage(
+                StorageError::from(e).with_context(format!("Failed to load {}", key)),
+            )
+        })?;
+
+        let actual = ContentAddress::hash(&content);
+        if actual != expected {
+            return Err(ContentAddressingError::integrity_check_failed(expected, actual).into());
+        }
+
+        Ok(content)
+    }
+
+    /// Whether blobs loaded for rendering are checked against their hash
+    fn verifies_content(&self) -> bool {
+        self.verify_integrity || self.verifying_key.is_some()
+    }
+
+    /// Load and parse a manifest, checking it against its hash for signed registries
+    /// or when integrity verification is enabled
+    async fn load_manifest(&self, manifest_hash: &str) -> Result<Manifest, RegistryError> {
+        let manifest_key = ContentAddress::manifest_key(manifest_hash);
+        let manifest_bytes = if self.verify_integrity {
+            self.get_verified(&manifest_key).await?
+        } else {
+            let manifest_bytes = self.storage.get(&manifest_key).await.map_err(|e| {
+                RegistryError::Storage(
+                    StorageError::from(e)
+                        .with_context(format!("Failed to load manifest {}", manifest_hash)),
+                )
+            })?;
+
+            // Signed manifests must still match the hash they were signed under
+            if self.verifying_key.is_some()
+                && !ContentAddress::verify(&manifest_bytes, manifest_hash)
+            {
+                return Err(RegistryError::Signature(SignatureError::invalid(
+                    manifest_hash,
+                    "manifest content does not match signed hash",
+                )));
+            }
+            manifest_bytes
+        };
+
+        Manifest::from_bytes(&manifest_bytes).map_err(|e| {
+            RegistryError::ContentAddressing(crate::error::ContentAddressingError::manifest_error(
+                e.to_string(),
+            ))
+        })
+    }
+
+    /// Load the manifest, entrypoint and schema for a resolved reference
+    ///
+    /// `entrypoint` names one of the manifest's named entrypoints to compile
+    /// instead of `main.typ`.
+    async fn load_prepared(
+        &self,
+        reference: &str,
+        manifest_hash: String,
+        entrypoint: Option<&str>,
+    ) -> Result<PreparedTemplate, RegistryError> {
+        // Step 2: Load the manifest from storage
+        let manifest = self
+            .load_manifest(&manifest_hash)
+            .instrument(tracing::info_span!("load_manifest", manifest_hash = %manifest_hash))
+            .await?;
+
+        // Step 3: Get the entrypoint content, data schema and defaults
+        let fetch_span = tracing::info_span!(
+            "fetch_blobs",
+            manifest_hash = %manifest_hash,
+            entrypoint_bytes = tracing::field::Empty
+        );
+        let (entrypoint_content, schema, defaults) = async {
+            let entrypoint_path = entrypoint_path(&manifest, reference, entrypoint)?;
+            let entrypoint_content = self.load_entrypoint(&manifest, entrypoint_path).await?;
+            tracing::Span::current().record("entrypoint_bytes", entrypoint_content.len());
+
+            let schema = self.load_json_file(&manifest, "schema.json").await?;
+            let defaults = self.load_json_file(&manifest, "defaults.json").await?;
+            Ok::<_, RegistryError>((entrypoint_content, schema, defaults))
+        }
+        .instrument(fetch_span)
+        .await?;
+
+        // Step 4: Create RegistryFileSystem for resolving imports
+        let mut registry_file_system = RegistryFileSystem::new(self.storage.clone(), manifest)?;
+        if self.verifies_content() {
+            registry_file_system = registry_file_system.with_verification();
+        }
+        let file_system: Arc<dyn RenderFileSystem> = Arc::new(registry_file_system);
+        let world = PapermakeWorld::with_file_system(
+            entrypoint_content.clone(),
+            String::new(),
+            file_system.clone(),
+        );
+
+        Ok(PreparedTemplate {
+            reference: reference.to_string(),
+            manifest_hash,
+            entrypoint: entrypoint_content,
+            file_system,
+            world,
+            schema,
+            defaults,
+        })
+    }
+
+    /// Load the template source at entrypoint `path` of a manifest
+    async fn load_entrypoint(
+        &self,
+        manifest: &Manifest,
+        path: &str,
+    ) -> Result<String, RegistryError> {
+        let entrypoint_hash = manifest.get_file_hash(path).ok_or_else(|| {
+            RegistryError::Template(crate::error::TemplateError::invalid(format!(
+                "Manifest missing hash for entrypoint {}",
+                path
+            )))
+        })?;
+
+        let entrypoint_key = ContentAddress::blob_key(entrypoint_hash);
+        let entrypoint_bytes = if self.verifies_content() {
+            self.get_verified(&entrypoint_key).await?
+        } else {
+            self.storage.get(&entrypoint_key).await.map_err(|e| {
+                RegistryError::Storage(
+                    StorageError::from(e).with_context("Failed to load entrypoint file"),
+                )
+            })?
+        };
+
+        String::from_utf8(entrypoint_bytes).map_err(|e| {
+            RegistryError::Template(crate::error::TemplateError::invalid(format!(
+                "Entrypoint file is not valid UTF-8: {}",
+                e
+            )))
+        })
+    }
+
+    /// Load and parse a JSON file from the manifest, if present
+    async fn load_json_file(
+        &self,
+        manifest: &Manifest,
+        path: &str,
+    ) -> Result<Option<serde_json::Value>, RegistryError> {
+        let Some(hash) = manifest.get_file_hash(path) else {
+            return Ok(None);
+        };
+
+        let bytes = if self.verifies_content() {
+            self.get_verified(&ContentAddress::blob_key(hash)).await?
+        } else {
+            self.load_blob(hash, path).await?
+        };
+        serde_json::from_slice(&bytes).map(Some).map_err(|e| {
+            RegistryError::Template(crate::error::TemplateError::invalid(format!(
+                "{} is not valid JSON: {}",
+                path, e
+            )))
+        })
+    }
+
+    /// List all templates in the registry
+    ///
+    /// This method scans all references in storage and groups them by template
+    /// to provide a comprehensive list of available templates with their metadata.
+    /// With an access policy, only templates an anonymous caller may read
+    /// are listed.
+    ///
+    /// # Returns
+    /// Returns a vector of `TemplateInfo` structs containing:
+    /// - Template name and namespace
+    /// - Available tags
+    /// - Latest manifest hash (from "latest" tag or newest tag)
+    /// - Template metadata from the manifest
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use papermake_registry::Registry;
+    /// use papermake_registry::storage::blob_storage::MemoryStorage;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let storage = MemoryStorage::new();
-    /// let registry = Registry::new(storage);
+    /// let registry = Registry::new_storage_only(storage);
     ///
     /// let templates = registry.list_templates().await?;
     /// for template in templates {
@@ -350,101 +1909,108 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
     /// # }
     /// ```
     pub async fn list_templates(&self) -> Result<Vec<TemplateInfo>, RegistryError> {
+        // Read the reference index in a single GET when maintained
+        if self.refs_index
+            && let Some(index) = self.read_refs_index().await?
+        {
+            return Ok(self.list_templates_from_index(index).await);
+        }
+
         // Step 1: List all reference keys with "refs/" prefix
         let ref_keys = self
             .storage
             .list_keys("refs/")
             .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
+            .map_err(|e| RegistryError::Storage(e.into()))?;
 
-        // Step 2: Parse reference keys to extract template information
-        let mut templates_map: BTreeMap<String, (Vec<String>, Option<String>)> = BTreeMap::new();
+        // Step 2: Group tags by template
+        let mut templates_map: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
         for ref_key in ref_keys {
             // Parse reference key: "refs/{namespace}/{tag}" or "refs/{namespace}/{name}/{tag}"
-            if let Some(parsed) = Self::parse_ref_key(&ref_key) {
-                let (namespace_path, tag) = parsed;
-
-                // Add this tag to the template's tag list
-                let entry = templates_map
-                    .entry(namespace_path.clone())
-                    .or_insert((Vec::new(), None));
-                entry.0.push(tag.clone());
-
-                // If this is the "latest" tag, remember it for getting metadata
-                if tag == "latest" {
-                    entry.1 = Some(ref_key.clone());
-                }
+            if let Some((namespace_path, tag)) = Self::parse_ref_key(&ref_key) {
+                templates_map.entry(namespace_path).or_default().push(tag);
             }
         }
 
         // Step 3: For each unique template, resolve metadata
         let mut template_infos = Vec::new();
 
-        for (namespace_path, (mut tags, latest_ref_key)) in templates_map {
+        for (namespace_path, mut tags) in templates_map {
+            // Skip templates the access policy hides from anonymous callers
+            let (namespace, name) = Self::parse_namespace_path(&namespace_path);
+            if !self.can_read(namespace.as_deref(), None) {
+                continue;
+            }
+
             // Sort tags for consistent output
             tags.sort();
 
             // Use "latest" tag if available, otherwise use the first tag alphabetically
-            let ref_key_to_use = latest_ref_key.unwrap_or_else(|| {
-                format!(
-                    "refs/{}/{}",
-                    namespace_path,
-                    tags.first().unwrap_or(&"latest".to_string())
-                )
-            });
+            let tag = if tags.iter().any(|tag| tag == "latest") {
+                "latest"
+            } else {
+                &tags[0]
+            };
+
+            // Skip templates whose reference or manifest cannot be loaded
+            let reference = format!("{}:{}", namespace_path, tag);
+            let Ok((manifest_hash, manifest)) = self.resolve_manifest(&reference).await else {
+                continue;
+            };
+
+            template_infos.push(TemplateInfo::new(
+                name,
+                namespace,
+                tags,
+                manifest_hash,
+                manifest.metadata,
+            ));
+        }
 
-            // Get the manifest hash for this reference
-            match self.storage.get(&ref_key_to_use).await {
-                Ok(manifest_hash_bytes) => {
-                    let manifest_hash = match String::from_utf8(manifest_hash_bytes) {
-                        Ok(hash) => hash,
-                        Err(_) => continue, // Skip invalid UTF-8
-                    };
+        // Sort templates by full name for consistent output
+        template_infos.sort_by_key(|a| a.full_name());
 
-                    // Load the manifest to get metadata
-                    let manifest_key = ContentAddress::manifest_key(&manifest_hash);
-                    match self.storage.get(&manifest_key).await {
-                        Ok(manifest_bytes) => {
-                            match Manifest::from_bytes(&manifest_bytes) {
-                                Ok(manifest) => {
-                                    // Parse namespace and name from namespace_path
-                                    let (namespace, name) =
-                                        Self::parse_namespace_path(&namespace_path);
-
-                                    let template_info = TemplateInfo::new(
-                                        name,
-                                        namespace,
-                                        tags,
-                                        manifest_hash,
-                                        manifest.metadata.clone(),
-                                    );
-
-                                    template_infos.push(template_info);
-                                }
-                                Err(_) => {
-                                    // Skip templates with invalid manifests
-                                    continue;
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            // Skip templates with missing manifests
-                            continue;
-                        }
-                    }
-                }
-                Err(_) => {
-                    // Skip invalid references
-                    continue;
-                }
+        Ok(template_infos)
+    }
+
+    /// Build template infos from the reference index, skipping broken entries
+    async fn list_templates_from_index(&self, index: RefsIndex) -> Vec<TemplateInfo> {
+        let mut template_infos = Vec::new();
+
+        for (namespace_path, tags) in index.templates {
+            // Skip templates the access policy hides, as resolving does
+            let (namespace, name) = Self::parse_namespace_path(&namespace_path);
+            if !self.can_read(namespace.as_deref(), None) {
+                continue;
             }
+
+            // Use "latest" tag if available, otherwise the first tag alphabetically
+            let Some(manifest_hash) = tags.get("latest").or_else(|| tags.values().next()) else {
+                continue;
+            };
+
+            let manifest_key = ContentAddress::manifest_key(manifest_hash);
+            let Ok(manifest_bytes) = self.storage.get(&manifest_key).await else {
+                continue;
+            };
+            let Ok(manifest) = Manifest::from_bytes(&manifest_bytes) else {
+                continue;
+            };
+
+            template_infos.push(TemplateInfo::new(
+                name,
+                namespace,
+                tags.keys().cloned().collect(),
+                manifest_hash.clone(),
+                manifest.metadata,
+            ));
         }
 
         // Sort templates by full name for consistent output
-        template_infos.sort_by(|a, b| a.full_name().cmp(&b.full_name()));
+        template_infos.sort_by_key(|a| a.full_name());
 
-        Ok(template_infos)
+        template_infos
     }
 
     /// Parse a reference key to extract namespace/name path and tag
@@ -474,6 +2040,15 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         Some((namespace_path, tag))
     }
 
+    /// Extract the bare template name from a parsed reference
+    ///
+    /// Examples:
+    /// - "invoice:latest" -> "invoice"
+    /// - "john/invoice:latest" -> "invoice"
+    fn extract_template_name(reference: &Reference) -> String {
+        reference.name.clone()
+    }
+
     /// Parse namespace path to extract namespace and name
     ///
     /// Examples:
@@ -547,71 +2122,294 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
         reference: &str,
         data: &serde_json::Value,
     ) -> Result<RenderResult, RegistryError> {
-        // Step 1: Parse template reference to extract name/tag
-        let parsed_ref = Reference::parse(reference)?;
-        let template_name = parsed_ref.full_name();
-        let template_tag = parsed_ref.tag.unwrap_or_else(|| "latest".to_string());
+        // Step 1-2: Store the input data as content-addressable blob
+        let data_hash = self.store_render_data(data).await?;
 
-        // Step 2: Hash input data and store as content-addressable blob
-        let data_bytes = serde_json::to_vec(data)?;
-        let data_hash = ContentAddress::hash(&data_bytes);
-        let data_key = ContentAddress::data_key(&data_hash);
+        // Step 3: Measure total operation time including resolution
+        let start_time = std::time::Instant::now();
 
-        // Store data blob for future retrieval
-        self.storage
-            .put(&data_key, data_bytes)
+        // Step 4: Try to resolve and render - catch all failures
+        let result: Result<(String, Vec<u8>), RegistryError> = async {
+            let mut prepared = self.prepare(reference).await?;
+            let pdf_bytes = prepared.render(data)?;
+            Ok((prepared.manifest_hash, pdf_bytes))
+        }
+        .await;
+
+        let duration_ms = start_time.elapsed().as_millis() as u32;
+
+        self.record_render(reference, data_hash, result, duration_ms)
             .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
+    }
 
-        let manifest_hash = self.resolve(reference).await?;
+    /// Render a template with explicit render options and store the result with tracking
+    ///
+    /// Like [`Registry::render_and_store`], rendering as
+    /// [`Registry::render_with_options`] does. With `options.use_cache`, a
+    /// successful render of the same reference and data is returned instead
+    /// when the reference still resolves to the manifest that render used;
+    /// its record and stored PDF are reused and no new render is recorded.
+    /// Reuse needs render storage.
+    pub async fn render_and_store_with_options(
+        &self,
+        reference: &str,
+        data: &serde_json::Value,
+        options: &RenderOptions,
+    ) -> Result<RenderResult, RegistryError> {
+        if options.use_cache
+            && let Some(cached) = self.cached_render(reference, data).await?
+        {
+            return Ok(cached);
+        }
 
-        // Step 3: Measure total operation time including resolution
-        let start_time = std::time::Instant::now();
+        let data_hash = self.store_render_data(data).await?;
 
-        // Step 4: Try to resolve and render - catch all failures
+        let start_time = std::time::Instant::now();
         let result: Result<(String, Vec<u8>), RegistryError> = async {
-            let pdf_bytes = self.render(reference, data).await?;
-            Ok((manifest_hash, pdf_bytes))
+            let prepared = self.prepare(reference).await?;
+            let pdf_bytes = prepared.render_with_options(data, options)?;
+            Ok((prepared.manifest_hash, pdf_bytes))
         }
         .await;
+        let duration_ms = start_time.elapsed().as_millis() as u32;
+
+        self.record_render(reference, data_hash, result, duration_ms)
+            .await
+    }
+
+    /// Render a prepared template and store the result with tracking
+    ///
+    /// Behaves like [`Registry::render_and_store`] but reuses the resolved
+    /// manifest and Typst world of `prepared`, so rendering many data sets
+    /// against the same template resolves the reference only once.
+    pub async fn render_prepared_and_store(
+        &self,
+        prepared: &mut PreparedTemplate,
+        data: &serde_json::Value,
+    ) -> Result<RenderResult, RegistryError> {
+        let data_hash = self.store_render_data(data).await?;
+
+        let start_time = std::time::Instant::now();
+        let result = prepared
+            .render(data)
+            .map(|pdf_bytes| (prepared.manifest_hash.clone(), pdf_bytes));
+        let duration_ms = start_time.elapsed().as_millis() as u32;
+
+        self.record_render(&prepared.reference, data_hash, result, duration_ms)
+            .await
+    }
+
+    /// Store and record a render the caller compiled itself
+    ///
+    /// For callers that compile outside the async runtime, e.g. with
+    /// [`PreparedTemplate::render`] on a blocking thread under a timeout.
+    /// `outcome` holds the manifest hash and PDF of a successful render or
+    /// the error it failed with, and `duration_ms` the time it took. The
+    /// data, PDF and render record are then stored as by
+    /// [`Registry::render_and_store`], under `reference`.
+    pub async fn store_rendered(
+        &self,
+        reference: &str,
+        data: &serde_json::Value,
+        outcome: Result<(String, Vec<u8>), RegistryError>,
+        duration_ms: u32,
+    ) -> Result<RenderResult, RegistryError> {
+        let data_hash = self.store_render_data(data).await?;
+        self.record_render(reference, data_hash, outcome, duration_ms)
+            .await
+    }
+
+    /// Render a specific manifest while tracking the render under `reference`
+    ///
+    /// Behaves like [`Registry::render_and_store`], except that files are
+    /// loaded from `manifest_hash` instead of whatever `reference` currently
+    /// resolves to. The render record keeps the friendly `reference` and the
+    /// pinned hash, so a new build can be canaried before moving its tag.
+    /// The reference need not resolve, but read access to it is checked.
+    ///
+    /// # Errors
+    /// Returns `ReferenceError::InvalidHash` for a malformed hash. A hash
+    /// without a stored manifest is recorded as a failed render and returned
+    /// as `TemplateError::NotFound`.
+    pub async fn render_pinned(
+        &self,
+        reference: &str,
+        manifest_hash: &str,
+        data: &serde_json::Value,
+    ) -> Result<RenderResult, RegistryError> {
+        let (template_ref, entrypoint) = split_entrypoint(reference);
+        self.check_read(&Reference::parse(template_ref)?, None)?;
+        if !ContentAddress::is_valid_hash(manifest_hash) {
+            return Err(RegistryError::Reference(
+                crate::error::ReferenceError::invalid_hash(manifest_hash),
+            ));
+        }
 
+        let data_hash = self.store_render_data(data).await?;
+
+        let start_time = std::time::Instant::now();
+        let result: Result<(String, Vec<u8>), RegistryError> = async {
+            let manifest_hash = self.resolve_digest(template_ref, manifest_hash).await?;
+            let mut prepared = self
+                .load_prepared(reference, manifest_hash, entrypoint)
+                .await?;
+            let pdf_bytes = prepared.render(data)?;
+            Ok((prepared.manifest_hash, pdf_bytes))
+        }
+        .await;
         let duration_ms = start_time.elapsed().as_millis() as u32;
 
+        self.record_render(reference, data_hash, result, duration_ms)
+            .await
+    }
+
+    /// Render one template against many data rows, tracking each render
+    ///
+    /// The reference is resolved and the template prepared once (see
+    /// [`Registry::prepare`]); every row then reuses the same Typst world
+    /// with fresh `sys.inputs.data`, so template sources and assets are
+    /// loaded and parsed a single time. Rows are rendered in order and each
+    /// is stored and recorded like [`Registry::render_and_store`].
+    ///
+    /// # Returns
+    /// One result per row, in input order. A failing row does not affect the
+    /// others.
+    ///
+    /// # Errors
+    /// Returns an error without rendering any row if the template cannot be
+    /// prepared.
+    pub async fn render_batch(
+        &self,
+        reference: &str,
+        data: &[serde_json::Value],
+    ) -> Result<Vec<Result<RenderResult, RegistryError>>, RegistryError> {
+        let mut prepared = self.prepare(reference).await?;
+
+        let mut results = Vec::with_capacity(data.len());
+        for row in data {
+            results.push(self.render_prepared_and_store(&mut prepared, row).await);
+        }
+        Ok(results)
+    }
+
+    /// A prior successful render of `data` with the current template of `reference`
+    async fn cached_render(
+        &self,
+        reference: &str,
+        data: &serde_json::Value,
+    ) -> Result<Option<RenderResult>, RegistryError> {
+        let Some(render_storage) = &self.render_storage else {
+            return Ok(None);
+        };
+        let data_hash = ContentAddress::hash(&serde_json::to_vec(data)?);
+        let Some(record) = render_storage.find_by_inputs(reference, &data_hash).await? else {
+            return Ok(None);
+        };
+
+        // Tags move, so the render only counts if it used today's manifest
+        let (template_ref, _) = split_entrypoint(reference);
+        if self.resolve(template_ref).await.ok() != Some(record.manifest_hash.clone()) {
+            return Ok(None);
+        }
+
+        let pdf_bytes = match self
+            .storage
+            .get(&ContentAddress::pdf_key(&record.pdf_hash))
+            .await
+        {
+            Ok(pdf_bytes) => pdf_bytes,
+            Err(crate::storage::blob_storage::StorageError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(RegistryError::Storage(e.into())),
+        };
+
+        Ok(Some(RenderResult {
+            render_id: record.render_id,
+            pdf_bytes,
+            pdf_hash: record.pdf_hash,
+            duration_ms: record.duration_ms,
+            cached: true,
+        }))
+    }
+
+    /// Hash input data and store it as content-addressable blob, returning its hash
+    async fn store_render_data(&self, data: &serde_json::Value) -> Result<String, RegistryError> {
+        let data_bytes = serde_json::to_vec(data)?;
+        let data_hash = ContentAddress::hash(&data_bytes);
+        let data_key = ContentAddress::data_key(&data_hash);
+
+        // Store data blob for future retrieval
+        self.storage
+            .put(&data_key, data_bytes)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        Ok(data_hash)
+    }
+
+    /// Store the PDF and render record for a finished render
+    async fn record_render(
+        &self,
+        reference: &str,
+        data_hash: String,
+        result: Result<(String, Vec<u8>), RegistryError>,
+        duration_ms: u32,
+    ) -> Result<RenderResult, RegistryError> {
+        // Parse template reference to extract name/tag
+        let parsed_ref = Reference::parse(split_entrypoint(reference).0)?;
+        let template_name = Self::extract_template_name(&parsed_ref);
+        let template_tag = parsed_ref.tag.unwrap_or_else(|| "latest".to_string());
+
         // Step 5: Handle overall success/failure
         match result {
             Ok((manifest_hash, pdf_bytes)) => {
                 // Hash and store PDF as content-addressable blob
                 let pdf_hash = ContentAddress::hash(&pdf_bytes);
                 let pdf_key = ContentAddress::pdf_key(&pdf_hash);
+                let store_span = tracing::info_span!(
+                    "store",
+                    manifest_hash = %manifest_hash,
+                    pdf_hash = %pdf_hash,
+                    bytes = pdf_bytes.len()
+                );
+
+                let render_id = async {
+                    // Large PDFs take the backend's streaming path (multipart on S3)
+                    let stored = if pdf_bytes.len() > DEFAULT_SPILL_THRESHOLD {
+                        self.storage
+                            .put_stream(&pdf_key, HashedContent::Memory(pdf_bytes.clone()))
+                            .await
+                    } else {
+                        self.storage.put(&pdf_key, pdf_bytes.clone()).await
+                    };
+                    stored.map_err(|e| RegistryError::Storage(e.into()))?;
+
+                    // Step 6: Generate UUIDv7 for time-sortable render ID
+                    let render_id = uuid::Uuid::now_v7().to_string();
+
+                    // Step 7: Create successful render record with explicit render_id
+                    let record = RenderRecord {
+                        render_id: render_id.clone(),
+                        timestamp: time::OffsetDateTime::now_utc(),
+                        template_ref: reference.to_string(),
+                        template_name,
+                        template_tag,
+                        manifest_hash,
+                        data_hash,
+                        pdf_hash: pdf_hash.clone(),
+                        success: true,
+                        duration_ms,
+                        pdf_size_bytes: pdf_bytes.len() as u32,
+                        error: None,
+                    };
 
-                self.storage
-                    .put(&pdf_key, pdf_bytes.clone())
-                    .await
-                    .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
-
-                // Step 6: Generate UUIDv7 for time-sortable render ID
-                let render_id = uuid::Uuid::now_v7().to_string();
-
-                // Step 7: Create successful render record with explicit render_id
-                let record = RenderRecord {
-                    render_id: render_id.clone(),
-                    timestamp: time::OffsetDateTime::now_utc(),
-                    template_ref: reference.to_string(),
-                    template_name,
-                    template_tag,
-                    manifest_hash,
-                    data_hash,
-                    pdf_hash: pdf_hash.clone(),
-                    success: true,
-                    duration_ms,
-                    pdf_size_bytes: pdf_bytes.len() as u32,
-                    error: None,
-                };
-
-                // Step 8: Store render record (if render storage available)
-                if let Some(render_storage) = &self.render_storage {
-                    render_storage.store_render(record).await?;
-                }
+                    // Step 8: Store render record (if render storage available)
+                    if let Some(render_storage) = &self.render_storage {
+                        render_storage.store_render(record).await?;
+                    }
+                    Ok::<_, RegistryError>(render_id)
+                }
+                .instrument(store_span)
+                .await?;
 
                 // Step 9: Return success result
                 Ok(RenderResult {
@@ -619,6 +2417,7 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
                     pdf_bytes,
                     pdf_hash,
                     duration_ms,
+                    cached: false,
                 })
             }
             Err(render_error) => {
@@ -645,211 +2444,1154 @@ impl<S: BlobStorage + 'static, R: RenderStorage + 'static> Registry<S, R> {
                     render_storage.store_render(record).await?;
                 }
 
-                // Return original error
-                Err(render_error)
-            }
-        }
-    }
+                // Return original error
+                Err(render_error)
+            }
+        }
+    }
+
+    /// Provision blob and render storage for use
+    ///
+    /// Runs [`BlobStorage::bootstrap`], e.g. creating the S3 bucket, and then
+    /// [`RenderStorage::bootstrap`], e.g. creating the ClickHouse tables.
+    /// Both are idempotent, so this is safe to call on every startup.
+    ///
+    /// # Errors
+    /// Returns `RegistryError::Storage` or `RegistryError::RenderStorage`
+    /// naming the backend that could not be provisioned.
+    pub async fn bootstrap(&self) -> Result<(), RegistryError> {
+        self.storage
+            .bootstrap()
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        if let Some(render_storage) = &self.render_storage {
+            render_storage.bootstrap().await?;
+        }
+        Ok(())
+    }
+
+    /// Check that blob storage is reachable, see [`BlobStorage::ping`]
+    pub async fn ping_storage(&self) -> Result<(), RegistryError> {
+        self.storage
+            .ping()
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))
+    }
+
+    /// Check that render storage is reachable, see [`RenderStorage::ping`]
+    ///
+    /// Succeeds without checking anything if no render storage is configured.
+    pub async fn ping_render_storage(&self) -> Result<(), RegistryError> {
+        match &self.render_storage {
+            Some(render_storage) => Ok(render_storage.ping().await?),
+            None => Ok(()),
+        }
+    }
+
+    /// Get recent render records
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of records to return
+    ///
+    /// # Returns
+    /// Returns a vector of recent `RenderRecord`s sorted by timestamp (newest first)
+    ///
+    /// # Errors
+    /// Returns error if no render storage is configured or if query fails
+    pub async fn list_recent_renders(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<RenderRecord>, RegistryError> {
+        if let Some(render_storage) = &self.render_storage {
+            Ok(render_storage.list_recent_renders(limit).await?)
+        } else {
+            Err(RegistryError::RenderStorage(
+                RenderStorageError::Connection("No render storage configured".to_string()),
+            ))
+        }
+    }
+
+    /// Get one page of render records, newest first, see [`RenderStorage::list_renders`]
+    ///
+    /// # Errors
+    /// Returns error if no render storage is configured or if query fails
+    pub async fn list_renders(&self, page: Page) -> Result<Vec<RenderRecord>, RegistryError> {
+        if let Some(render_storage) = &self.render_storage {
+            Ok(render_storage.list_renders(page).await?)
+        } else {
+            Err(RegistryError::RenderStorage(
+                RenderStorageError::Connection("No render storage configured".to_string()),
+            ))
+        }
+    }
+
+    /// Get render input data by render ID
+    ///
+    /// Retrieves the original JSON data used for a specific render operation
+    /// using content-addressable storage.
+    ///
+    /// # Arguments
+    /// * `render_id` - UUIDv7 render identifier
+    ///
+    /// # Returns
+    /// Returns the original JSON data used for rendering
+    ///
+    /// # Errors
+    /// Returns error if render not found, data not found, or deserialization fails
+    pub async fn get_render_data(
+        &self,
+        render_id: &str,
+    ) -> Result<serde_json::Value, RegistryError> {
+        // 1. Get render record from render storage
+        let render_storage = self.render_storage.as_ref().ok_or_else(|| {
+            RegistryError::RenderStorage(RenderStorageError::Connection(
+                "No render storage configured".to_string(),
+            ))
+        })?;
+
+        let record = render_storage.get_render(render_id).await?.ok_or_else(|| {
+            RegistryError::RenderStorage(RenderStorageError::NotFound(render_id.to_string()))
+        })?;
+
+        // 2. Retrieve data blob using content addressing
+        let data_key = ContentAddress::data_key(&record.data_hash);
+        let data_bytes = self
+            .storage
+            .get(&data_key)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+
+        // 3. Deserialize JSON data
+        let data: serde_json::Value = serde_json::from_slice(&data_bytes)?;
+        Ok(data)
+    }
+
+    /// Get rendered PDF by render ID
+    ///
+    /// Retrieves the PDF output for a specific render operation
+    /// using content-addressable storage.
+    ///
+    /// # Arguments
+    /// * `render_id` - UUIDv7 render identifier
+    ///
+    /// # Returns
+    /// Returns the PDF bytes for the rendered template
+    ///
+    /// # Errors
+    /// Returns error if render not found, render failed, or PDF not found
+    pub async fn get_render_pdf(&self, render_id: &str) -> Result<Vec<u8>, RegistryError> {
+        let pdf_key = self.render_pdf_key(render_id).await?;
+        let pdf_bytes = self
+            .storage
+            .get(&pdf_key)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))?;
+        Ok(pdf_bytes)
+    }
+
+    /// Get a time-limited download URL for a rendered PDF
+    ///
+    /// Lets clients fetch large PDFs straight from blob storage instead of
+    /// through the registry.
+    ///
+    /// # Arguments
+    /// * `render_id` - UUIDv7 render identifier
+    /// * `ttl` - How long the URL stays valid
+    ///
+    /// # Returns
+    /// Returns `None` if the blob storage cannot issue such URLs; use
+    /// [`Registry::get_render_pdf`] then.
+    ///
+    /// # Errors
+    /// Returns error if render not found, render failed, or signing fails
+    pub async fn get_render_pdf_url(
+        &self,
+        render_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>, RegistryError> {
+        let pdf_key = self.render_pdf_key(render_id).await?;
+        self.storage
+            .presigned_url(&pdf_key, ttl)
+            .await
+            .map_err(|e| RegistryError::Storage(e.into()))
+    }
+
+    /// Blob key of the PDF produced by a successful render
+    async fn render_pdf_key(&self, render_id: &str) -> Result<String, RegistryError> {
+        // 1. Get render record from render storage
+        let render_storage = self.render_storage.as_ref().ok_or_else(|| {
+            RegistryError::RenderStorage(RenderStorageError::Connection(
+                "No render storage configured".to_string(),
+            ))
+        })?;
+
+        let record = render_storage.get_render(render_id).await?.ok_or_else(|| {
+            RegistryError::RenderStorage(RenderStorageError::NotFound(render_id.to_string()))
+        })?;
+
+        // 2. Check if render was successful
+        if !record.success {
+            return Err(RegistryError::RenderStorage(
+                RenderStorageError::InvalidQuery("Render failed, no PDF available".to_string()),
+            ));
+        }
+
+        // 3. Locate PDF blob using content addressing
+        Ok(ContentAddress::pdf_key(&record.pdf_hash))
+    }
+
+    /// Get render analytics based on query type
+    ///
+    /// Supports various analytics queries for render volume, template statistics,
+    /// and performance metrics.
+    ///
+    /// # Arguments
+    /// * `query` - The type of analytics query to perform
+    ///
+    /// # Returns
+    /// Returns `AnalyticsResult` containing the requested analytics data
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use papermake_registry::{Registry, AnalyticsQuery, Granularity};
+    /// use papermake_registry::storage::blob_storage::MemoryStorage;
+    /// use papermake_registry::render_storage::MemoryRenderStorage;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let storage = MemoryStorage::new();
+    /// let render_storage = MemoryRenderStorage::new();
+    /// let registry = Registry::new(storage, render_storage);
+    ///
+    /// // Get render volume over last 30 days
+    /// let volume_result = registry.get_render_analytics(
+    ///     AnalyticsQuery::VolumeOverTime { days: 30, granularity: Granularity::Day }
+    /// ).await?;
+    ///
+    /// // Get template statistics
+    /// let template_stats = registry.get_render_analytics(
+    ///     AnalyticsQuery::TemplateStats
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_render_analytics(
+        &self,
+        query: AnalyticsQuery,
+    ) -> Result<AnalyticsResult, RegistryError> {
+        let render_storage = self.render_storage.as_ref().ok_or_else(|| {
+            RegistryError::RenderStorage(RenderStorageError::Connection(
+                "No render storage configured".to_string(),
+            ))
+        })?;
+
+        match query {
+            AnalyticsQuery::VolumeOverTime { days, granularity } => {
+                let volume = render_storage
+                    .render_volume_over_time(days, granularity)
+                    .await?;
+                Ok(AnalyticsResult::Volume(volume))
+            }
+            AnalyticsQuery::TemplateStats => {
+                let stats = render_storage.total_renders_per_template().await?;
+                Ok(AnalyticsResult::Templates(stats))
+            }
+            AnalyticsQuery::TemplateVolumeOverTime { days } => {
+                let points = render_storage.renders_per_template_over_time(days).await?;
+                Ok(AnalyticsResult::TemplateVolume(points))
+            }
+            AnalyticsQuery::DurationOverTime { days, granularity } => {
+                let duration = render_storage
+                    .average_duration_over_time(days, granularity)
+                    .await?;
+                Ok(AnalyticsResult::Duration(duration))
+            }
+            AnalyticsQuery::PercentileOverTime {
+                days,
+                granularity,
+                percentiles,
+            } => {
+                let points = render_storage
+                    .percentile_duration_over_time(days, granularity, &percentiles)
+                    .await?;
+                Ok(AnalyticsResult::Percentiles(points))
+            }
+        }
+    }
+}
+
+/// Parse a version tag like `v10`, `1.2.3` or `v2.0` into its numeric components
+///
+/// Trailing zero components are dropped, so `v2` and `v2.0` compare equal.
+/// Returns `None` for tags that are not purely numeric versions, such as
+/// `latest` or `1.0-beta`.
+fn parse_version(tag: &str) -> Option<Vec<u64>> {
+    let numeric = tag.strip_prefix(['v', 'V']).unwrap_or(tag);
+    let mut components = numeric
+        .split('.')
+        .map(|component| {
+            if component.is_empty() || !component.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            component.parse().ok()
+        })
+        .collect::<Option<Vec<u64>>>()?;
+
+    while components.len() > 1 && components.last() == Some(&0) {
+        components.pop();
+    }
+    Some(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        S3Storage,
+        access::NamespaceOwners,
+        bundle::TemplateMetadata,
+        render_storage::{Granularity, MemoryRenderStorage},
+        storage::{blob_storage::MemoryStorage, instrumented::InstrumentedStorage},
+    };
+    use papermake::LintRule;
+
+    fn create_test_bundle() -> TemplateBundle {
+        let metadata = TemplateMetadata::new("Test Template", "test@example.com");
+        let main_content = br#"#let data = json.decode(sys.inputs.data)
+= Test Template
+Hello #data.name"#
+            .to_vec();
+
+        TemplateBundle::new(main_content, metadata)
+            .add_file("assets/logo.png", b"fake_png_data".to_vec())
+            .with_schema(
+                br#"{"type": "object", "properties": {"name": {"type": "string"}}}"#.to_vec(),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_registry_publish() {
+        let storage = MemoryStorage::new();
+        let registry = Registry::new_storage_only(storage);
+        let bundle = create_test_bundle();
+
+        let manifest_hash = registry
+            .publish(bundle, "test-user/test-template", "latest")
+            .await
+            .unwrap();
+
+        assert!(manifest_hash.starts_with("sha256:"));
+        assert_eq!(manifest_hash.len(), 71); // "sha256:" + 64 hex chars
+    }
+
+    #[tokio::test]
+    async fn test_store_blob_from_reader() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let content = vec![7u8; 100_000];
+
+        let hash = registry.store_blob_from_reader(&content[..]).await.unwrap();
+
+        assert_eq!(hash, ContentAddress::hash(&content));
+        assert_eq!(
+            registry
+                .storage
+                .get(&ContentAddress::blob_key(&hash))
+                .await
+                .unwrap(),
+            content
+        );
+    }
+
+    #[tokio::test]
+    async fn test_access_policy_denies_cross_namespace_reads() {
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_access_policy(NamespaceOwners::new().with_public("acme"));
+        let hash = registry
+            .publish(create_test_bundle(), "alice/invoice", "latest")
+            .await
+            .unwrap();
+        registry
+            .publish(create_test_bundle(), "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            registry
+                .resolve_as("alice/invoice:latest", "alice")
+                .await
+                .unwrap(),
+            hash
+        );
+        for result in [
+            registry.resolve_as("alice/invoice:latest", "bob").await,
+            registry.resolve("alice/invoice:latest").await,
+            // Denied before the lookup, so missing templates look the same
+            registry.resolve_as("alice/missing:latest", "bob").await,
+        ] {
+            assert!(
+                matches!(result, Err(RegistryError::AccessDenied(_))),
+                "{:?}",
+                result
+            );
+        }
+        let data = serde_json::json!({"name": "Bob"});
+        assert!(matches!(
+            registry
+                .render_as("alice/invoice:latest", &data, "bob")
+                .await,
+            Err(RegistryError::AccessDenied(_))
+        ));
+
+        // Public namespaces are readable by everyone
+        assert!(
+            registry
+                .resolve_as("acme/invoice:latest", "bob")
+                .await
+                .is_ok()
+        );
+        assert!(registry.render("acme/invoice:latest", &data).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_access_policy_denies_cross_namespace_digests() {
+        let registry = Registry::new(
+            MemoryStorage::new(),
+            crate::render_storage::MemoryRenderStorage::new(),
+        )
+        .with_access_policy(NamespaceOwners::new().with_public("acme"));
+        let private_hash = registry
+            .publish(create_test_bundle(), "alice/letter", "latest")
+            .await
+            .unwrap();
+        let acme_hash = registry
+            .publish(
+                TemplateBundle::new(
+                    b"= Acme #data.name".to_vec(),
+                    TemplateMetadata::new("Acme", "test@example.com"),
+                ),
+                "acme/invoice",
+                "latest",
+            )
+            .await
+            .unwrap();
+        let data = serde_json::json!({"name": "Bob"});
+
+        // A digest from another namespace is denied even for a missing tag
+        let smuggled = format!("acme/x@{}", private_hash);
+        for result in [
+            registry.resolve(&smuggled).await,
+            registry.resolve_as(&smuggled, "bob").await,
+            registry
+                .render_pinned("acme/invoice:latest", &private_hash, &data)
+                .await
+                .map(|result| result.pdf_hash),
+        ] {
+            assert!(
+                matches!(result, Err(RegistryError::AccessDenied(_))),
+                "{:?}",
+                result
+            );
+        }
+
+        // Digests tagged in the checked namespace still resolve
+        assert_eq!(
+            registry
+                .resolve(&format!("acme/x@{}", acme_hash))
+                .await
+                .unwrap(),
+            acme_hash
+        );
+        assert_eq!(
+            registry
+                .resolve_as(&format!("alice/x@{}", private_hash), "alice")
+                .await
+                .unwrap(),
+            private_hash
+        );
+    }
+
+    #[tokio::test]
+    async fn test_access_policy_filters_listings() {
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_refs_index()
+            .with_access_policy(NamespaceOwners::new().with_public("acme"));
+        for reference in ["alice/letter", "acme/invoice", "invoice"] {
+            registry
+                .publish(create_test_bundle(), reference, "latest")
+                .await
+                .unwrap();
+        }
+
+        let listed: Vec<String> = registry
+            .list_templates()
+            .await
+            .unwrap()
+            .iter()
+            .map(|template| template.full_name())
+            .collect();
+        assert_eq!(listed, ["acme/invoice", "invoice"]);
+
+        assert_eq!(
+            registry.list_tags("acme/invoice").await.unwrap(),
+            ["latest"]
+        );
+        assert!(matches!(
+            registry.list_tags("alice/letter").await,
+            Err(RegistryError::AccessDenied(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_update_latest_uses_numeric_order() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let bundle = |content: &[u8]| {
+            TemplateBundle::new(
+                content.to_vec(),
+                TemplateMetadata::new("Invoice", "john@example.com"),
+            )
+        };
+
+        registry
+            .publish_and_update_latest(bundle(b"= v2"), "john/invoice", "v2")
+            .await
+            .unwrap();
+        let hash_v10 = registry
+            .publish_and_update_latest(bundle(b"= v10"), "john/invoice", "v10")
+            .await
+            .unwrap();
+        assert_eq!(
+            registry.resolve("john/invoice:latest").await.unwrap(),
+            hash_v10
+        );
+
+        // An older version published later does not move latest back
+        registry
+            .publish_and_update_latest(bundle(b"= v3"), "john/invoice", "v3")
+            .await
+            .unwrap();
+        assert_eq!(
+            registry.resolve("john/invoice:latest").await.unwrap(),
+            hash_v10
+        );
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v10"), Some(vec![10]));
+        assert_eq!(parse_version("1.2.3"), Some(vec![1, 2, 3]));
+        assert_eq!(parse_version("v2.0"), parse_version("2"));
+        assert!(parse_version("v10") > parse_version("v2.9"));
+        assert_eq!(parse_version("latest"), None);
+        assert_eq!(parse_version("1.0-beta"), None);
+        assert_eq!(parse_version("v"), None);
+    }
+
+    #[tokio::test]
+    async fn test_publish_applies_namespace_defaults() {
+        let registry = Registry::new_storage_only(MemoryStorage::new()).with_namespace_defaults(
+            "acme",
+            TemplateMetadata::new("Untitled", "templates@acme.com"),
+        );
+        let bundle =
+            TemplateBundle::new(b"= Invoice".to_vec(), TemplateMetadata::new("Invoice", ""));
+
+        let manifest_hash = registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        let manifest = registry.load_manifest(&manifest_hash).await.unwrap();
+        assert_eq!(
+            manifest.metadata,
+            TemplateMetadata::new("Invoice", "templates@acme.com")
+        );
+
+        // Defaults only apply to their own namespace
+        let bundle =
+            TemplateBundle::new(b"= Invoice".to_vec(), TemplateMetadata::new("Invoice", ""));
+        assert!(
+            registry
+                .publish(bundle, "other/invoice", "latest")
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_lint_reports_misspelled_field_and_missing_data() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"customer_name": {"type": "string"}}
+        });
+        let bundle = TemplateBundle::new(
+            b"= Invoice\nFor #data.customer_nme".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        )
+        .with_schema(serde_json::to_vec(&schema).unwrap());
+
+        let findings = registry.lint(&bundle).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, LintRule::UnknownField);
+        assert!(findings[0].message.contains("data.customer_nme"));
+        assert_eq!(findings[0].location.as_ref().unwrap().line, 2);
+
+        let bundle = TemplateBundle::new(
+            b"= Invoice\nFor ACME".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+        let findings = registry.lint(&bundle).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, LintRule::DataNeverRead);
+    }
+
+    #[tokio::test]
+    async fn test_sample_data_matches_schema_and_defaults() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["customer", "items"],
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } }
+                },
+                "items": { "type": "array", "items": { "type": "number" } },
+                "currency": { "enum": ["EUR", "USD"] }
+            }
+        });
+        let bundle = create_test_bundle()
+            .with_schema(serde_json::to_vec(&schema).unwrap())
+            .with_defaults(br#"{"currency": "USD"}"#.to_vec());
+
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        let sample = registry.sample_data("acme/invoice").await.unwrap();
+        assert_eq!(
+            sample,
+            serde_json::json!({
+                "customer": { "name": "name" },
+                "items": [0.0],
+                "currency": "USD"
+            })
+        );
+        assert!(papermake::validate_data(&sample, &schema).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_describe_inputs_combines_schema_and_source() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["customer", "total"],
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "vat_id": { "type": "string" }
+                    }
+                },
+                "total": { "type": "number" }
+            }
+        });
+        let bundle = TemplateBundle::new(
+            b"= Invoice for #data.customer.name\nTotal: #data.total".to_vec(),
+            TemplateMetadata::new("Invoice", "john@example.com"),
+        )
+        .with_schema(serde_json::to_vec(&schema).unwrap());
+
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        let fields = registry.describe_inputs("acme/invoice").await.unwrap();
+        let field = |path: &str| fields.iter().find(|field| field.path == path).unwrap();
+
+        let name = field("customer.name");
+        assert!(name.required);
+        assert!(name.used_in_source);
+        assert_eq!(name.type_hint.as_deref(), Some("string"));
+
+        let total = field("total");
+        assert!(total.required);
+        assert!(total.used_in_source);
+        assert_eq!(total.type_hint.as_deref(), Some("number"));
+
+        let vat_id = field("customer.vat_id");
+        assert!(!vat_id.required);
+        assert!(!vat_id.used_in_source);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tar_export_and_import_round_trip() {
+        let bundle = create_test_bundle()
+            .with_schema(br#"{"type": "object"}"#.to_vec())
+            .add_file("assets/logo.png", vec![0, 1, 2, 255]);
+
+        let source = Registry::new_storage_only(MemoryStorage::new());
+        let manifest_hash = source.publish(bundle, "acme/invoice", "v1").await.unwrap();
+
+        let mut archive = Vec::new();
+        source
+            .export_tar("acme/invoice:v1", &mut archive)
+            .await
+            .unwrap();
+
+        let target = Registry::new_storage_only(MemoryStorage::new());
+        let imported_hash = target
+            .publish_tar(archive.as_slice(), "acme/invoice", "v1")
+            .await
+            .unwrap();
+        assert_eq!(imported_hash, manifest_hash);
+        assert_eq!(
+            target
+                .get_template_file("acme/invoice:v1", "assets/logo.png")
+                .await
+                .unwrap(),
+            vec![0, 1, 2, 255]
+        );
+
+        let result = target
+            .publish_tar(&b"not a tar archive"[..], "acme/invoice", "v2")
+            .await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Template(
+                TemplateError::ConversionFailed { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_unresolved_import() {
+        let bundle = TemplateBundle::new(
+            b"#import \"lib/helpers.typ\": total\n= Invoice\nTotal: #total".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+
+        // Publishing rejects the missing import before compiling
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let result = registry
+            .publish(bundle.clone(), "acme/invoice", "latest")
+            .await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Template(TemplateError::MissingFile { filename }))
+                if filename == "lib/helpers.typ"
+        ));
+
+        let diagnostics = registry.check(&bundle).unwrap();
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.severity == DiagnosticSeverity::Error
+                    && d.message.contains("lib/helpers.typ")),
+            "diagnostics: {:?}",
+            diagnostics
+        );
+
+        // A compile check catches a present file lacking the imported name
+        let incomplete = bundle
+            .clone()
+            .add_file("lib/helpers.typ", b"#let subtotal = 42".to_vec());
+        let registry = Registry::new_storage_only(MemoryStorage::new()).with_compile_check();
+        let result = registry.publish(incomplete, "acme/invoice", "latest").await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Compilation(
+                papermake::PapermakeError::Compilation(CompilationError::TypstError { .. })
+            ))
+        ));
+        assert!(registry.resolve("acme/invoice:latest").await.is_err());
+
+        let fixed = bundle.add_file("lib/helpers.typ", b"#let total = 42".to_vec());
+        assert!(registry.check(&fixed).unwrap().is_empty());
+        registry
+            .publish(fixed, "acme/invoice", "latest")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fork_records_source_manifest() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let source_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let fork_hash = registry
+            .fork("john/invoice:latest", "alice", "invoice")
+            .await
+            .unwrap();
+        assert_ne!(fork_hash, source_hash);
+        assert_eq!(
+            registry.resolve("alice/invoice:latest").await.unwrap(),
+            fork_hash
+        );
+
+        let source = registry.load_manifest(&source_hash).await.unwrap();
+        let fork = registry.load_manifest(&fork_hash).await.unwrap();
+        assert_eq!(fork.files, source.files);
+        assert_eq!(fork.metadata.author, source.metadata.author);
+
+        let forked_from = Reference::parse(fork.metadata.forked_from.as_deref().unwrap()).unwrap();
+        assert_eq!(forked_from.full_name(), "john/invoice");
+        assert_eq!(forked_from.hash.as_deref(), Some(source_hash.as_str()));
+
+        // Forking onto an existing template is refused
+        assert!(matches!(
+            registry
+                .fork("john/invoice:latest", "alice", "invoice")
+                .await,
+            Err(RegistryError::Template(TemplateError::AlreadyExists { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_diff_classifies_file_changes() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let metadata = TemplateMetadata::new("Invoice", "john@example.com");
+
+        let v1 = TemplateBundle::new(b"= Invoice\nTotal: 10".to_vec(), metadata.clone())
+            .add_file("schema.json", b"{}".to_vec());
+        let v2 = TemplateBundle::new(b"= Invoice\nTotal: 20".to_vec(), metadata)
+            .add_file("schema.json", b"{}".to_vec())
+            .add_file("assets/logo.png", vec![0x89, 0x50, 0x4e, 0x47]);
+        registry.publish(v1, "john/invoice", "v1").await.unwrap();
+        registry.publish(v2, "john/invoice", "v2").await.unwrap();
+
+        let diff = registry
+            .diff("john/invoice:v1", "john/invoice:v2")
+            .await
+            .unwrap();
+
+        assert_eq!(diff.added, vec!["assets/logo.png"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed, vec!["main.typ"]);
+
+        let main_diff = &diff.text_diffs["main.typ"];
+        assert!(main_diff.contains("-Total: 10"));
+        assert!(main_diff.contains("+Total: 20"));
+
+        let reverse = registry
+            .diff("john/invoice:v2", "john/invoice:v1")
+            .await
+            .unwrap();
+        assert_eq!(reverse.removed, vec!["assets/logo.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_gc_removes_orphaned_blobs() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let storage = &registry.storage;
+        let metadata = TemplateMetadata::new("Invoice", "john@example.com");
+
+        let v1 = TemplateBundle::new(b"= Invoice v1".to_vec(), metadata.clone())
+            .add_file("schema.json", b"{}".to_vec());
+        let v1_hash = registry
+            .publish(v1, "john/invoice", "latest")
+            .await
+            .unwrap();
+        let v1_manifest = registry.load_manifest(&v1_hash).await.unwrap();
+
+        // Moving the tag orphans the old main.typ and manifest
+        let v2 = TemplateBundle::new(b"= Invoice v2".to_vec(), metadata)
+            .add_file("schema.json", b"{}".to_vec());
+        let v2_hash = registry
+            .publish(v2, "john/invoice", "latest")
+            .await
+            .unwrap();
+        let v2_manifest = registry.load_manifest(&v2_hash).await.unwrap();
+
+        let report = registry.gc().await.unwrap();
+        assert_eq!(report.deleted, 2);
+        assert_eq!(report.retained, 3);
+        assert_eq!(
+            report.bytes_reclaimed,
+            ("= Invoice v1".len() + v1_manifest.to_bytes().unwrap().len()) as u64
+        );
 
-    /// Get recent render records
-    ///
-    /// # Arguments
-    /// * `limit` - Maximum number of records to return
-    ///
-    /// # Returns
-    /// Returns a vector of recent `RenderRecord`s sorted by timestamp (newest first)
-    ///
-    /// # Errors
-    /// Returns error if no render storage is configured or if query fails
-    pub async fn list_recent_renders(
-        &self,
-        limit: u32,
-    ) -> Result<Vec<RenderRecord>, RegistryError> {
-        if let Some(render_storage) = &self.render_storage {
-            Ok(render_storage.list_recent_renders(limit).await?)
-        } else {
-            Err(RegistryError::RenderStorage(
-                RenderStorageError::Connection("No render storage configured".to_string()),
-            ))
+        let orphaned_main = ContentAddress::blob_key(&v1_manifest.files["main.typ"]);
+        assert!(!storage.exists(&orphaned_main).await.unwrap());
+        assert!(
+            !storage
+                .exists(&ContentAddress::manifest_key(&v1_hash))
+                .await
+                .unwrap()
+        );
+        for hash in v2_manifest.files.values() {
+            assert!(
+                storage
+                    .exists(&ContentAddress::blob_key(hash))
+                    .await
+                    .unwrap()
+            );
         }
+        assert!(
+            registry
+                .render("john/invoice:latest", &serde_json::json!({}))
+                .await
+                .is_ok()
+        );
+
+        // Nothing left to collect
+        assert_eq!(registry.gc().await.unwrap().deleted, 0);
     }
 
-    /// Get render input data by render ID
-    ///
-    /// Retrieves the original JSON data used for a specific render operation
-    /// using content-addressable storage.
-    ///
-    /// # Arguments
-    /// * `render_id` - UUIDv7 render identifier
-    ///
-    /// # Returns
-    /// Returns the original JSON data used for rendering
-    ///
-    /// # Errors
-    /// Returns error if render not found, data not found, or deserialization fails
-    pub async fn get_render_data(
-        &self,
-        render_id: &str,
-    ) -> Result<serde_json::Value, RegistryError> {
-        // 1. Get render record from render storage
-        let render_storage = self.render_storage.as_ref().ok_or_else(|| {
-            RegistryError::RenderStorage(RenderStorageError::Connection(
-                "No render storage configured".to_string(),
-            ))
-        })?;
+    #[tokio::test]
+    async fn test_gc_removes_orphaned_signatures() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let metadata = TemplateMetadata::new("Invoice", "john@example.com");
+
+        let v1 = TemplateBundle::new(b"= Invoice v1".to_vec(), metadata.clone());
+        let v1_hash = registry
+            .publish_signed(v1, "john/invoice", "latest", &signing_key)
+            .await
+            .unwrap();
+        let v2 = TemplateBundle::new(b"= Invoice v2".to_vec(), metadata);
+        let v2_hash = registry
+            .publish_signed(v2, "john/invoice", "latest", &signing_key)
+            .await
+            .unwrap();
 
-        let record = render_storage.get_render(render_id).await?.ok_or_else(|| {
-            RegistryError::RenderStorage(RenderStorageError::NotFound(render_id.to_string()))
-        })?;
+        registry.gc().await.unwrap();
 
-        // 2. Retrieve data blob using content addressing
-        let data_key = ContentAddress::data_key(&record.data_hash);
-        let data_bytes = self
-            .storage
-            .get(&data_key)
-            .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
+        let storage = &registry.storage;
+        assert!(
+            !storage
+                .exists(&ContentAddress::signature_key(&v1_hash))
+                .await
+                .unwrap()
+        );
+        assert!(
+            storage
+                .exists(&ContentAddress::signature_key(&v2_hash))
+                .await
+                .unwrap()
+        );
+    }
 
-        // 3. Deserialize JSON data
-        let data: serde_json::Value = serde_json::from_slice(&data_bytes)?;
-        Ok(data)
+    #[tokio::test]
+    async fn test_gc_reads_every_page_of_renders() {
+        let registry = Registry::new(MemoryStorage::new(), MemoryRenderStorage::new());
+        let render_storage = registry.render_storage.as_ref().unwrap();
+
+        // More records than one page, all sharing a timestamp
+        let count = GC_RENDER_PAGE_SIZE as usize + 5;
+        let timestamp = time::OffsetDateTime::now_utc();
+        for i in 0..count {
+            let data = format!("{{\"row\": {}}}", i).into_bytes();
+            let data_hash = ContentAddress::hash(&data);
+            registry
+                .storage
+                .put(&ContentAddress::data_key(&data_hash), data)
+                .await
+                .unwrap();
+            let mut record = RenderRecord::failure(
+                "john/invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                String::new(),
+                data_hash,
+                "boom".to_string(),
+                0,
+            );
+            record.timestamp = timestamp;
+            render_storage.store_render(record).await.unwrap();
+        }
+
+        let report = registry.gc().await.unwrap();
+        assert_eq!(report.deleted, 0);
+        assert_eq!(report.retained, count);
     }
 
-    /// Get rendered PDF by render ID
-    ///
-    /// Retrieves the PDF output for a specific render operation
-    /// using content-addressable storage.
-    ///
-    /// # Arguments
-    /// * `render_id` - UUIDv7 render identifier
-    ///
-    /// # Returns
-    /// Returns the PDF bytes for the rendered template
-    ///
-    /// # Errors
-    /// Returns error if render not found, render failed, or PDF not found
-    pub async fn get_render_pdf(&self, render_id: &str) -> Result<Vec<u8>, RegistryError> {
-        // 1. Get render record from render storage
-        let render_storage = self.render_storage.as_ref().ok_or_else(|| {
-            RegistryError::RenderStorage(RenderStorageError::Connection(
-                "No render storage configured".to_string(),
-            ))
-        })?;
+    #[tokio::test]
+    async fn test_gc_keeps_manifests_of_recorded_renders() {
+        let registry = Registry::new(MemoryStorage::new(), MemoryRenderStorage::new());
+        let metadata = TemplateMetadata::new("Invoice", "john@example.com");
+        let data = serde_json::json!({});
+
+        let v1 = TemplateBundle::new(b"= Invoice v1".to_vec(), metadata.clone());
+        let v1_hash = registry
+            .publish(v1, "john/invoice", "latest")
+            .await
+            .unwrap();
+        let v2 = TemplateBundle::new(b"= Invoice v2".to_vec(), metadata);
+        registry
+            .publish(v2, "john/invoice", "latest")
+            .await
+            .unwrap();
 
-        let record = render_storage.get_render(render_id).await?.ok_or_else(|| {
-            RegistryError::RenderStorage(RenderStorageError::NotFound(render_id.to_string()))
-        })?;
+        // Only the recorded render still points to v1
+        registry
+            .render_pinned("john/invoice:latest", &v1_hash, &data)
+            .await
+            .unwrap();
 
-        // 2. Check if render was successful
-        if !record.success {
-            return Err(RegistryError::RenderStorage(
-                RenderStorageError::InvalidQuery("Render failed, no PDF available".to_string()),
-            ));
-        }
+        let report = registry.gc().await.unwrap();
+        assert_eq!(report.deleted, 0);
 
-        // 3. Retrieve PDF blob using content addressing
-        let pdf_key = ContentAddress::pdf_key(&record.pdf_hash);
-        let pdf_bytes = self
-            .storage
-            .get(&pdf_key)
+        let result = registry
+            .render_pinned("john/invoice:latest", &v1_hash, &data)
             .await
-            .map_err(|e| RegistryError::Storage(StorageError::backend(e.to_string())))?;
-        Ok(pdf_bytes)
+            .unwrap();
+        assert!(result.pdf_bytes.starts_with(b"%PDF"));
     }
 
-    /// Get render analytics based on query type
-    ///
-    /// Supports various analytics queries for render volume, template statistics,
-    /// and performance metrics.
-    ///
-    /// # Arguments
-    /// * `query` - The type of analytics query to perform
-    ///
-    /// # Returns
-    /// Returns `AnalyticsResult` containing the requested analytics data
-    ///
-    /// # Examples
-    /// ```rust,no_run
-    /// use papermake_registry::{Registry, AnalyticsQuery};
-    /// use papermake_registry::storage::blob_storage::MemoryStorage;
-    /// use papermake_registry::render_storage::MemoryRenderStorage;
-    ///
-    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let storage = MemoryStorage::new();
-    /// let render_storage = MemoryRenderStorage::new();
-    /// let registry = Registry::new(storage, render_storage);
-    ///
-    /// // Get render volume over last 30 days
-    /// let volume_result = registry.get_render_analytics(
-    ///     AnalyticsQuery::VolumeOverTime { days: 30 }
-    /// ).await?;
-    ///
-    /// // Get template statistics
-    /// let template_stats = registry.get_render_analytics(
-    ///     AnalyticsQuery::TemplateStats
-    /// ).await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn get_render_analytics(
-        &self,
-        query: AnalyticsQuery,
-    ) -> Result<AnalyticsResult, RegistryError> {
-        let render_storage = self.render_storage.as_ref().ok_or_else(|| {
-            RegistryError::RenderStorage(RenderStorageError::Connection(
-                "No render storage configured".to_string(),
-            ))
-        })?;
-
-        match query {
-            AnalyticsQuery::VolumeOverTime { days } => {
-                let volume = render_storage.render_volume_over_time(days).await?;
-                Ok(AnalyticsResult::Volume(volume))
-            }
-            AnalyticsQuery::TemplateStats => {
-                let stats = render_storage.total_renders_per_template().await?;
-                Ok(AnalyticsResult::Templates(stats))
-            }
-            AnalyticsQuery::DurationOverTime { days } => {
-                let duration = render_storage.average_duration_over_time(days).await?;
-                Ok(AnalyticsResult::Duration(duration))
-            }
+    #[tokio::test]
+    async fn test_list_and_delete_tags() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        for tag in ["v2", "latest", "v1"] {
+            registry
+                .publish(create_test_bundle(), "john/invoice", tag)
+                .await
+                .unwrap();
         }
+        // A template in a namespace named like the official one is not listed
+        registry
+            .publish(create_test_bundle(), "invoice", "v1")
+            .await
+            .unwrap();
+        registry
+            .publish(create_test_bundle(), "invoice/draft", "v9")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            registry.list_tags("john/invoice").await.unwrap(),
+            vec!["latest", "v1", "v2"]
+        );
+        assert_eq!(registry.list_tags("invoice").await.unwrap(), vec!["v1"]);
+
+        registry.delete_tag("john/invoice:v1").await.unwrap();
+
+        assert_eq!(
+            registry.list_tags("john/invoice").await.unwrap(),
+            vec!["latest", "v2"]
+        );
+        assert!(registry.resolve("john/invoice:v1").await.is_err());
+        assert!(registry.resolve("john/invoice:latest").await.is_ok());
+        assert!(registry.resolve("john/invoice:v2").await.is_ok());
+
+        assert!(matches!(
+            registry.delete_tag("john/invoice:v1").await,
+            Err(RegistryError::Template(TemplateError::NotFound { .. }))
+        ));
+
+        registry.delete_tag("john/invoice").await.unwrap();
+        assert_eq!(
+            registry.list_tags("john/invoice").await.unwrap(),
+            vec!["v2"]
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{S3Storage, bundle::TemplateMetadata, storage::blob_storage::MemoryStorage};
+    #[tokio::test]
+    async fn test_delete_tag_updates_index() {
+        let registry = Registry::new_storage_only(MemoryStorage::new()).with_refs_index();
+        registry
+            .publish(create_test_bundle(), "john/invoice", "v1")
+            .await
+            .unwrap();
+        registry
+            .publish(create_test_bundle(), "john/invoice", "v2")
+            .await
+            .unwrap();
 
-    fn create_test_bundle() -> TemplateBundle {
-        let metadata = TemplateMetadata::new("Test Template", "test@example.com");
-        let main_content = br#"#let data = json.decode(sys.inputs.data)
-= Test Template
-Hello #data.name"#
-            .to_vec();
+        registry.delete_tag("john/invoice:v2").await.unwrap();
 
-        TemplateBundle::new(main_content, metadata)
-            .add_file("assets/logo.png", b"fake_png_data".to_vec())
-            .with_schema(
-                br#"{"type": "object", "properties": {"name": {"type": "string"}}}"#.to_vec(),
-            )
+        assert_eq!(
+            registry.list_tags("john/invoice").await.unwrap(),
+            vec!["v1"]
+        );
+        let index = registry.read_refs_index().await.unwrap().unwrap();
+        assert_eq!(index.get("john/invoice", "v2"), None);
     }
 
     #[tokio::test]
-    async fn test_registry_publish() {
-        let storage = MemoryStorage::new();
-        let registry = Registry::new_storage_only(storage);
-        let bundle = create_test_bundle();
+    async fn test_digest_matches_published_manifest() {
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_namespace_defaults("john", TemplateMetadata::new("", "john@example.com"));
+        // The author is filled in from the namespace defaults on publish
+        let bundle =
+            TemplateBundle::new(b"= Invoice".to_vec(), TemplateMetadata::new("Invoice", ""))
+                .add_file("assets/logo.svg", b"<svg/>".to_vec());
 
+        let digest = registry.digest(&bundle, "john/invoice").unwrap();
         let manifest_hash = registry
-            .publish(bundle, "test-user/test-template", "latest")
+            .publish(bundle.clone(), "john/invoice", "v1")
             .await
             .unwrap();
+        assert_eq!(digest, manifest_hash);
+        assert_eq!(registry.resolve("john/invoice:v1").await.unwrap(), digest);
+        assert_eq!(
+            registry
+                .resolve(&format!("john/invoice:v1@{}", digest))
+                .await
+                .unwrap(),
+            digest
+        );
 
-        assert!(manifest_hash.starts_with("sha256:"));
-        assert_eq!(manifest_hash.len(), 71); // "sha256:" + 64 hex chars
+        let wrong = ContentAddress::hash(b"not the manifest");
+        assert!(matches!(
+            registry
+                .resolve(&format!("john/invoice:v1@{}", wrong))
+                .await,
+            Err(RegistryError::Reference(
+                crate::error::ReferenceError::HashMismatch { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_by_digest_without_tag() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "v1")
+            .await
+            .unwrap();
+
+        // No "latest" tag exists, so only the digest can resolve this
+        let reference = format!("john/invoice@{}", manifest_hash);
+        assert_eq!(registry.resolve(&reference).await.unwrap(), manifest_hash);
+
+        let pdf = registry
+            .render(&reference, &serde_json::json!({"name": "Alice"}))
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // An existing tag still has to match the digest
+        let other_hash = registry
+            .publish(
+                TemplateBundle::new(
+                    b"Other".to_vec(),
+                    TemplateMetadata::new("Other", "john@example.com"),
+                ),
+                "john/other",
+                "latest",
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            registry
+                .resolve(&format!("john/invoice:v1@{}", other_hash))
+                .await,
+            Err(RegistryError::Reference(_))
+        ));
+
+        let unknown = format!("john/invoice@{}", ContentAddress::hash(b"missing"));
+        assert!(matches!(
+            registry.resolve(&unknown).await,
+            Err(RegistryError::Template(TemplateError::NotFound { .. }))
+        ));
     }
 
     #[tokio::test]
@@ -952,7 +3694,24 @@ Hello #data.name"#
         // Then resolve it back
         let resolved_hash = registry.resolve("john/invoice:latest").await.unwrap();
 
-        assert_eq!(manifest_hash, resolved_hash);
+        assert_eq!(manifest_hash, resolved_hash);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_manifest_returns_parsed_manifest() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let (resolved_hash, manifest) = registry
+            .resolve_manifest("john/invoice:latest")
+            .await
+            .unwrap();
+        assert_eq!(resolved_hash, manifest_hash);
+        assert_eq!(manifest.entrypoint, "main.typ");
+        assert!(manifest.get_file_hash("main.typ").is_some());
     }
 
     #[tokio::test]
@@ -1296,141 +4055,717 @@ Content: #data.content"#
         let storage = MemoryStorage::new();
         let registry = Registry::new_storage_only(storage);
 
-        let templates = registry.list_templates().await.unwrap();
-        assert!(templates.is_empty());
-    }
+        let templates = registry.list_templates().await.unwrap();
+        assert!(templates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registry_list_templates_single() {
+        let storage = MemoryStorage::new();
+        let registry = Registry::new_storage_only(storage);
+        let bundle = create_test_bundle();
+
+        // Publish a template
+        let _manifest_hash = registry
+            .publish(bundle, "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let templates = registry.list_templates().await.unwrap();
+        assert_eq!(templates.len(), 1);
+
+        let template = &templates[0];
+        assert_eq!(template.name, "invoice");
+        assert_eq!(template.namespace, Some("john".to_string()));
+        assert_eq!(template.tags, vec!["latest"]);
+        assert_eq!(template.metadata.name, "Test Template");
+        assert_eq!(template.metadata.author, "test@example.com");
+        assert_eq!(template.full_name(), "john/invoice");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_registry_list_templates_no_namespace() {
+        unsafe {
+            std::env::set_var("S3_ENDPOINT_URL", "http://localhost:9000");
+            std::env::set_var("S3_ACCESS_KEY_ID", "minioadmin");
+            std::env::set_var("S3_SECRET_ACCESS_KEY", "minioadmin");
+            std::env::set_var("S3_BUCKET", "papermake-registry-test");
+            std::env::set_var("S3_REGION", "us-east-1");
+        }
+        let storage = S3Storage::from_env().unwrap();
+        storage.ensure_bucket().await.unwrap();
+        let registry = Registry::new_storage_only(storage);
+        let bundle = create_test_bundle();
+
+        // Publish a template
+        let _manifest_hash = registry.publish(bundle, "invoice", "latest").await.unwrap();
+
+        let templates = registry.list_templates().await.unwrap();
+        assert_eq!(templates.len(), 1);
+
+        let template = &templates[0];
+        assert_eq!(template.name, "invoice");
+        assert_eq!(template.namespace, None);
+        assert_eq!(template.tags, vec!["latest"]);
+        assert_eq!(template.metadata.name, "Test Template");
+        assert_eq!(template.metadata.author, "test@example.com");
+        assert_eq!(template.full_name(), "invoice");
+    }
+
+    #[tokio::test]
+    async fn test_registry_list_templates_multiple_tags() {
+        let storage = MemoryStorage::new();
+        let registry = Registry::new_storage_only(storage);
+        let bundle1 = create_test_bundle();
+        let bundle2 = create_test_bundle();
+
+        // Publish same template with different tags
+        registry
+            .publish(bundle1, "john/invoice", "latest")
+            .await
+            .unwrap();
+        registry
+            .publish(bundle2, "john/invoice", "v1.0.0")
+            .await
+            .unwrap();
+
+        let templates = registry.list_templates().await.unwrap();
+        assert_eq!(templates.len(), 1);
+
+        let template = &templates[0];
+        assert_eq!(template.name, "invoice");
+        assert_eq!(template.namespace, Some("john".to_string()));
+
+        // Tags should be sorted
+        let mut expected_tags = vec!["latest", "v1.0.0"];
+        expected_tags.sort();
+        assert_eq!(template.tags, expected_tags);
+    }
+
+    #[tokio::test]
+    async fn test_registry_list_templates_multiple_templates() {
+        let storage = MemoryStorage::new();
+        let registry = Registry::new_storage_only(storage);
+
+        // Create different bundles
+        let metadata1 = TemplateMetadata::new("Invoice Template", "john@example.com");
+        let metadata2 = TemplateMetadata::new("Letter Template", "alice@example.com");
+        let metadata3 = TemplateMetadata::new("Official Invoice", "admin@example.com");
+
+        let bundle1 = TemplateBundle::new(b"invoice content".to_vec(), metadata1);
+        let bundle2 = TemplateBundle::new(b"letter content".to_vec(), metadata2);
+        let bundle3 = TemplateBundle::new(b"official content".to_vec(), metadata3);
+
+        // Publish templates in different namespaces
+        registry
+            .publish(bundle1, "john/invoice", "latest")
+            .await
+            .unwrap();
+        registry
+            .publish(bundle2, "alice/letter", "latest")
+            .await
+            .unwrap();
+        registry
+            .publish(bundle3, "invoice", "official")
+            .await
+            .unwrap(); // No namespace
+
+        let templates = registry.list_templates().await.unwrap();
+        assert_eq!(templates.len(), 3);
+
+        // Templates should be sorted by full name
+        assert_eq!(templates[0].full_name(), "alice/letter");
+        assert_eq!(templates[1].full_name(), "invoice");
+        assert_eq!(templates[2].full_name(), "john/invoice");
+
+        // Check individual templates
+        assert_eq!(templates[0].namespace, Some("alice".to_string()));
+        assert_eq!(templates[0].name, "letter");
+        assert_eq!(templates[0].metadata.author, "alice@example.com");
+
+        assert_eq!(templates[1].namespace, None);
+        assert_eq!(templates[1].name, "invoice");
+        assert_eq!(templates[1].metadata.author, "admin@example.com");
+
+        assert_eq!(templates[2].namespace, Some("john".to_string()));
+        assert_eq!(templates[2].name, "invoice");
+        assert_eq!(templates[2].metadata.author, "john@example.com");
+    }
+
+    /// Storage sharing one MemoryStorage and counting list and get operations
+    #[derive(Clone, Default)]
+    struct CountingStorage {
+        inner: Arc<MemoryStorage>,
+        list_calls: Arc<std::sync::atomic::AtomicUsize>,
+        ref_gets: Arc<std::sync::atomic::AtomicUsize>,
+        gets: Arc<std::sync::Mutex<Vec<String>>>,
+        puts: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl CountingStorage {
+        /// Number of `get` calls for `key` so far
+        fn gets_of(&self, key: &str) -> usize {
+            self.gets
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|k| *k == key)
+                .count()
+        }
+
+        /// Number of `put` calls for `key` so far
+        fn puts_of(&self, key: &str) -> usize {
+            self.puts
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|k| *k == key)
+                .count()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BlobStorage for CountingStorage {
+        async fn put(
+            &self,
+            key: &str,
+            data: Vec<u8>,
+        ) -> Result<(), crate::storage::blob_storage::StorageError> {
+            self.puts.lock().unwrap().push(key.to_string());
+            self.inner.put(key, data).await
+        }
+
+        async fn get(
+            &self,
+            key: &str,
+        ) -> Result<Vec<u8>, crate::storage::blob_storage::StorageError> {
+            if key.starts_with("refs/") {
+                self.ref_gets
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+            self.gets.lock().unwrap().push(key.to_string());
+            self.inner.get(key).await
+        }
+
+        async fn exists(
+            &self,
+            key: &str,
+        ) -> Result<bool, crate::storage::blob_storage::StorageError> {
+            self.inner.exists(key).await
+        }
+
+        async fn delete(
+            &self,
+            key: &str,
+        ) -> Result<(), crate::storage::blob_storage::StorageError> {
+            self.inner.delete(key).await
+        }
+
+        async fn list_keys(
+            &self,
+            prefix: &str,
+        ) -> Result<Vec<String>, crate::storage::blob_storage::StorageError> {
+            self.list_calls
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.inner.list_keys(prefix).await
+        }
+    }
+
+    /// Storage flipping one byte of every value read for a chosen key
+    #[derive(Clone, Default)]
+    struct CorruptingStorage {
+        inner: Arc<MemoryStorage>,
+        corrupt_key: Arc<std::sync::Mutex<Option<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl BlobStorage for CorruptingStorage {
+        async fn put(
+            &self,
+            key: &str,
+            data: Vec<u8>,
+        ) -> Result<(), crate::storage::blob_storage::StorageError> {
+            self.inner.put(key, data).await
+        }
+
+        async fn get(
+            &self,
+            key: &str,
+        ) -> Result<Vec<u8>, crate::storage::blob_storage::StorageError> {
+            let mut data = self.inner.get(key).await?;
+            if self.corrupt_key.lock().unwrap().as_deref() == Some(key) {
+                data[0] ^= 0x01;
+            }
+            Ok(data)
+        }
+
+        async fn exists(
+            &self,
+            key: &str,
+        ) -> Result<bool, crate::storage::blob_storage::StorageError> {
+            self.inner.exists(key).await
+        }
+
+        async fn delete(
+            &self,
+            key: &str,
+        ) -> Result<(), crate::storage::blob_storage::StorageError> {
+            self.inner.delete(key).await
+        }
+
+        async fn list_keys(
+            &self,
+            prefix: &str,
+        ) -> Result<Vec<String>, crate::storage::blob_storage::StorageError> {
+            self.inner.list_keys(prefix).await
+        }
+    }
+
+    /// Storage whose backend refuses to be provisioned
+    #[derive(Default)]
+    struct UnprovisionedStorage {
+        inner: MemoryStorage,
+    }
+
+    #[async_trait::async_trait]
+    impl BlobStorage for UnprovisionedStorage {
+        async fn put(
+            &self,
+            key: &str,
+            data: Vec<u8>,
+        ) -> Result<(), crate::storage::blob_storage::StorageError> {
+            self.inner.put(key, data).await
+        }
+
+        async fn get(
+            &self,
+            key: &str,
+        ) -> Result<Vec<u8>, crate::storage::blob_storage::StorageError> {
+            self.inner.get(key).await
+        }
+
+        async fn exists(
+            &self,
+            key: &str,
+        ) -> Result<bool, crate::storage::blob_storage::StorageError> {
+            self.inner.exists(key).await
+        }
+
+        async fn delete(
+            &self,
+            key: &str,
+        ) -> Result<(), crate::storage::blob_storage::StorageError> {
+            self.inner.delete(key).await
+        }
+
+        async fn list_keys(
+            &self,
+            prefix: &str,
+        ) -> Result<Vec<String>, crate::storage::blob_storage::StorageError> {
+            self.inner.list_keys(prefix).await
+        }
+
+        async fn bootstrap(&self) -> Result<(), crate::storage::blob_storage::StorageError> {
+            Err(crate::storage::blob_storage::StorageError::AccessDenied(
+                "create bucket 'templates'".to_string(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_memory_backends_is_noop() {
+        let storage = MemoryStorage::new();
+        let registry = Registry::new(storage, MemoryRenderStorage::new());
+
+        registry.bootstrap().await.unwrap();
+        // Idempotent, and leaves nothing behind
+        registry.bootstrap().await.unwrap();
+        assert!(registry.storage.list_keys("").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_blob_is_not_found_storage_error() {
+        let registry = Registry::new(MemoryStorage::new(), MemoryRenderStorage::new());
+        registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+        for key in registry.storage.list_keys("blobs/").await.unwrap() {
+            registry.storage.delete(&key).await.unwrap();
+        }
+
+        let error = registry
+            .render("john/invoice:latest", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, RegistryError::Storage(StorageError::NotFound { .. })),
+            "{:?}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_failure_is_typed_error() {
+        let registry = Registry::new(UnprovisionedStorage::default(), MemoryRenderStorage::new());
+
+        let error = registry.bootstrap().await.unwrap_err();
+        assert!(
+            matches!(
+                error,
+                RegistryError::Storage(StorageError::AccessDenied { .. })
+            ),
+            "{:?}",
+            error
+        );
+        assert!(error.to_string().contains("create bucket 'templates'"));
+    }
+
+    #[tokio::test]
+    async fn test_integrity_verification_detects_corruption() {
+        let storage = CorruptingStorage::default();
+        let registry = Registry::new_storage_only(storage.clone()).with_integrity_verification();
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+        let data = serde_json::json!({"name": "Alice"});
+        assert!(registry.render("john/invoice:latest", &data).await.is_ok());
+
+        let manifest = registry.load_manifest(&manifest_hash).await.unwrap();
+        let entrypoint_key = ContentAddress::blob_key(manifest.entrypoint_hash().unwrap());
+        *storage.corrupt_key.lock().unwrap() = Some(entrypoint_key.clone());
+
+        assert!(matches!(
+            registry.get_verified(&entrypoint_key).await,
+            Err(RegistryError::ContentAddressing(
+                ContentAddressingError::IntegrityCheckFailed { .. }
+            ))
+        ));
+        assert!(matches!(
+            registry.render("john/invoice:latest", &data).await,
+            Err(RegistryError::ContentAddressing(
+                ContentAddressingError::IntegrityCheckFailed { .. }
+            ))
+        ));
+
+        // A corrupted manifest is caught as well
+        *storage.corrupt_key.lock().unwrap() = Some(ContentAddress::manifest_key(&manifest_hash));
+        assert!(matches!(
+            registry.render("john/invoice:latest", &data).await,
+            Err(RegistryError::ContentAddressing(
+                ContentAddressingError::IntegrityCheckFailed { .. }
+            ))
+        ));
+
+        // Without verification the corrupted entrypoint is used as is
+        let unverified = Registry::new_storage_only(storage.clone());
+        *storage.corrupt_key.lock().unwrap() = Some(entrypoint_key);
+        assert!(!matches!(
+            unverified.render("john/invoice:latest", &data).await,
+            Err(RegistryError::ContentAddressing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_publish_rejects_oversized_file() {
+        let registry =
+            Registry::new_storage_only(MemoryStorage::new()).with_limits(RegistryLimits {
+                max_single_file_bytes: Some(1024),
+                ..Default::default()
+            });
+        let bundle = create_test_bundle().add_file("assets/scan.png", vec![0; 4096]);
+
+        let result = registry.publish(bundle, "john/invoice", "latest").await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Template(TemplateError::TooLarge {
+                size: 4096,
+                limit: 1024
+            }))
+        ));
+        assert!(registry.storage.is_empty());
+
+        // Within the limits, publishing succeeds
+        registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publish_rejects_too_many_files() {
+        let registry =
+            Registry::new_storage_only(MemoryStorage::new()).with_limits(RegistryLimits {
+                max_file_count: Some(3),
+                max_bundle_bytes: Some(1024 * 1024),
+                ..Default::default()
+            });
+
+        // main.typ, logo and schema are three files
+        let bundle = create_test_bundle().add_file("lib/helpers.typ", b"#let x = 1".to_vec());
+        let result = registry.publish(bundle, "john/invoice", "latest").await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Template(TemplateError::Invalid { .. }))
+        ));
+        assert!(registry.storage.is_empty());
+
+        let bundle = TemplateBundle::new(
+            vec![b'='; 2 * 1024 * 1024],
+            TemplateMetadata::new("Huge", "test@example.com"),
+        );
+        let result = registry.publish(bundle, "john/huge", "latest").await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Template(TemplateError::TooLarge { size, .. }))
+                if size == 2 * 1024 * 1024
+        ));
+        assert!(registry.storage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_skips_blobs_already_stored() {
+        let storage = CountingStorage::default();
+        let registry = Registry::new_storage_only(storage.clone());
+        let logo = vec![0x89; 512 * 1024];
+        let logo_key = ContentAddress::blob_key(&ContentAddress::hash(&logo));
+
+        let v1 = TemplateBundle::new(
+            b"#image(\"assets/logo.png\")\n= Invoice v1".to_vec(),
+            TemplateMetadata::new("Invoice", "john@example.com"),
+        )
+        .add_file("assets/logo.png", logo.clone());
+        let report = registry
+            .publish_with_report(v1, "john/invoice", "v1")
+            .await
+            .unwrap();
+        assert_eq!(report.uploaded, 2);
+        assert_eq!(report.deduplicated, 0);
+        assert_eq!(
+            registry.resolve("john/invoice:v1").await.unwrap(),
+            report.manifest_hash
+        );
+
+        let v2 = TemplateBundle::new(
+            b"#image(\"assets/logo.png\")\n= Invoice v2".to_vec(),
+            TemplateMetadata::new("Invoice", "john@example.com"),
+        )
+        .add_file("assets/logo.png", logo);
+        let report = registry
+            .publish_with_report(v2, "john/invoice", "v2")
+            .await
+            .unwrap();
+        assert_eq!(report.uploaded, 1);
+        assert_eq!(report.deduplicated, 1);
+        assert_eq!(storage.puts_of(&logo_key), 1);
+
+        let manifest = registry.load_manifest(&report.manifest_hash).await.unwrap();
+        assert_eq!(
+            ContentAddress::blob_key(&manifest.files["assets/logo.png"]),
+            logo_key
+        );
+    }
+
+    #[tokio::test]
+    async fn test_template_cache_skips_blob_fetches() {
+        let storage = CountingStorage::default();
+        let registry = Registry::new_storage_only(storage.clone()).with_template_cache(4);
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let manifest_key = ContentAddress::manifest_key(&manifest_hash);
+        let manifest =
+            Manifest::from_bytes(&storage.inner.get(&manifest_key).await.unwrap()).unwrap();
+        let entrypoint_key = ContentAddress::blob_key(manifest.entrypoint_hash().unwrap());
 
-    #[tokio::test]
-    async fn test_registry_list_templates_single() {
-        let storage = MemoryStorage::new();
-        let registry = Registry::new_storage_only(storage);
-        let bundle = create_test_bundle();
+        let first = registry
+            .render("john/invoice:latest", &serde_json::json!({"name": "Alice"}))
+            .await
+            .unwrap();
+        assert_eq!(storage.gets_of(&entrypoint_key), 1);
+        assert_eq!(storage.gets_of(&manifest_key), 1);
 
-        // Publish a template
-        let _manifest_hash = registry
+        let second = registry
+            .render("john/invoice:latest", &serde_json::json!({"name": "Bob"}))
+            .await
+            .unwrap();
+        assert_eq!(storage.gets_of(&entrypoint_key), 1);
+        assert_eq!(storage.gets_of(&manifest_key), 1);
+        assert_ne!(first, second);
+
+        // Moving the tag to a new manifest is picked up immediately
+        let bundle = TemplateBundle::new(
+            b"Updated #data.name".to_vec(),
+            TemplateMetadata::new("Test Template", "test@example.com"),
+        );
+        let new_hash = registry
             .publish(bundle, "john/invoice", "latest")
             .await
             .unwrap();
-
-        let templates = registry.list_templates().await.unwrap();
-        assert_eq!(templates.len(), 1);
-
-        let template = &templates[0];
-        assert_eq!(template.name, "invoice");
-        assert_eq!(template.namespace, Some("john".to_string()));
-        assert_eq!(template.tags, vec!["latest"]);
-        assert_eq!(template.metadata.name, "Test Template");
-        assert_eq!(template.metadata.author, "test@example.com");
-        assert_eq!(template.full_name(), "john/invoice");
+        let prepared = registry.prepare("john/invoice:latest").await.unwrap();
+        assert_eq!(prepared.manifest_hash(), new_hash);
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn test_registry_list_templates_no_namespace() {
-        unsafe {
-            std::env::set_var("S3_ENDPOINT_URL", "http://localhost:9000");
-            std::env::set_var("S3_ACCESS_KEY_ID", "minioadmin");
-            std::env::set_var("S3_SECRET_ACCESS_KEY", "minioadmin");
-            std::env::set_var("S3_BUCKET", "papermake-registry-test");
-            std::env::set_var("S3_REGION", "us-east-1");
+    async fn test_template_cache_reuses_world_across_renders() {
+        let bundle = TemplateBundle::new(
+            b"#import \"lib/helpers.typ\": total\n= Invoice\nFor #data.name\n#total".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        )
+        .add_file("lib/helpers.typ", b"#let total = [Total: 42]".to_vec());
+        let helpers_key =
+            ContentAddress::blob_key(&ContentAddress::hash(b"#let total = [Total: 42]"));
+
+        for (capacity, expected_fetches) in [(Some(4), 1), (None, 10)] {
+            let storage = InstrumentedStorage::new(MemoryStorage::new());
+            let stats = storage.stats();
+            let mut registry = Registry::new_storage_only(storage);
+            if let Some(capacity) = capacity {
+                registry = registry.with_template_cache(capacity);
+            }
+            registry
+                .publish(bundle.clone(), "acme/invoice", "latest")
+                .await
+                .unwrap();
+
+            let mut pdfs = HashSet::new();
+            for i in 0..10 {
+                let pdf = registry
+                    .render(
+                        "acme/invoice:latest",
+                        &serde_json::json!({"name": format!("Customer {}", i)}),
+                    )
+                    .await
+                    .unwrap();
+                pdfs.insert(pdf);
+            }
+
+            assert_eq!(pdfs.len(), 10, "every render must see its own data");
+            assert_eq!(stats.get_count(&helpers_key), expected_fetches);
         }
-        let storage = S3Storage::from_env().unwrap();
-        storage.ensure_bucket().await.unwrap();
-        let registry = Registry::new_storage_only(storage);
-        let bundle = create_test_bundle();
+    }
 
-        // Publish a template
-        let _manifest_hash = registry.publish(bundle, "invoice", "latest").await.unwrap();
+    #[tokio::test]
+    async fn test_without_template_cache_fetches_every_render() {
+        let storage = CountingStorage::default();
+        let registry = Registry::new_storage_only(storage.clone());
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
 
-        let templates = registry.list_templates().await.unwrap();
-        assert_eq!(templates.len(), 1);
+        for name in ["Alice", "Bob"] {
+            registry
+                .render("john/invoice:latest", &serde_json::json!({"name": name}))
+                .await
+                .unwrap();
+        }
 
-        let template = &templates[0];
-        assert_eq!(template.name, "invoice");
-        assert_eq!(template.namespace, None);
-        assert_eq!(template.tags, vec!["latest"]);
-        assert_eq!(template.metadata.name, "Test Template");
-        assert_eq!(template.metadata.author, "test@example.com");
-        assert_eq!(template.full_name(), "invoice");
+        let manifest_key = ContentAddress::manifest_key(&manifest_hash);
+        assert_eq!(storage.gets_of(&manifest_key), 2);
     }
 
     #[tokio::test]
-    async fn test_registry_list_templates_multiple_tags() {
-        let storage = MemoryStorage::new();
-        let registry = Registry::new_storage_only(storage);
-        let bundle1 = create_test_bundle();
-        let bundle2 = create_test_bundle();
+    async fn test_publish_updates_refs_index() {
+        let storage = CountingStorage::default();
+        let registry = Registry::new_storage_only(storage.clone()).with_refs_index();
 
-        // Publish same template with different tags
-        registry
-            .publish(bundle1, "john/invoice", "latest")
+        let hash1 = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
             .await
             .unwrap();
-        registry
-            .publish(bundle2, "john/invoice", "v1.0.0")
+        let hash2 = registry
+            .publish(
+                TemplateBundle::new(
+                    b"letter".to_vec(),
+                    TemplateMetadata::new("Letter", "alice@example.com"),
+                ),
+                "alice/letter",
+                "v1",
+            )
             .await
             .unwrap();
 
-        let templates = registry.list_templates().await.unwrap();
-        assert_eq!(templates.len(), 1);
-
-        let template = &templates[0];
-        assert_eq!(template.name, "invoice");
-        assert_eq!(template.namespace, Some("john".to_string()));
-
-        // Tags should be sorted
-        let mut expected_tags = vec!["latest", "v1.0.0"];
-        expected_tags.sort();
-        assert_eq!(template.tags, expected_tags);
+        let index_bytes = storage.inner.get(REFS_INDEX_KEY).await.unwrap();
+        let index = RefsIndex::from_bytes(&index_bytes).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get("john/invoice", "latest"), Some(hash1.as_str()));
+        assert_eq!(index.get("alice/letter", "v1"), Some(hash2.as_str()));
     }
 
     #[tokio::test]
-    async fn test_registry_list_templates_multiple_templates() {
-        let storage = MemoryStorage::new();
-        let registry = Registry::new_storage_only(storage);
-
-        // Create different bundles
-        let metadata1 = TemplateMetadata::new("Invoice Template", "john@example.com");
-        let metadata2 = TemplateMetadata::new("Letter Template", "alice@example.com");
-        let metadata3 = TemplateMetadata::new("Official Invoice", "admin@example.com");
-
-        let bundle1 = TemplateBundle::new(b"invoice content".to_vec(), metadata1);
-        let bundle2 = TemplateBundle::new(b"letter content".to_vec(), metadata2);
-        let bundle3 = TemplateBundle::new(b"official content".to_vec(), metadata3);
+    async fn test_list_templates_reads_refs_index() {
+        let storage = CountingStorage::default();
+        let registry = Registry::new_storage_only(storage.clone()).with_refs_index();
 
-        // Publish templates in different namespaces
+        for tag in ["latest", "v1", "v2"] {
+            registry
+                .publish(create_test_bundle(), "john/invoice", tag)
+                .await
+                .unwrap();
+        }
         registry
-            .publish(bundle1, "john/invoice", "latest")
+            .publish(create_test_bundle(), "invoice", "official")
             .await
             .unwrap();
-        registry
-            .publish(bundle2, "alice/letter", "latest")
+
+        let scan_registry = Registry::new_storage_only(storage.clone());
+        let scanned = scan_registry.list_templates().await.unwrap();
+        let list_calls = storage
+            .list_calls
+            .load(std::sync::atomic::Ordering::Relaxed);
+        let ref_gets = storage.ref_gets.load(std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(list_calls, 1);
+        assert!(ref_gets > 0);
+
+        let indexed = registry.list_templates().await.unwrap();
+
+        // No scan and no ref lookups, only the index and manifest GETs
+        assert_eq!(
+            storage
+                .list_calls
+                .load(std::sync::atomic::Ordering::Relaxed),
+            list_calls
+        );
+        assert_eq!(
+            storage.ref_gets.load(std::sync::atomic::Ordering::Relaxed),
+            ref_gets
+        );
+
+        assert_eq!(indexed.len(), 2);
+        for (indexed, scanned) in indexed.iter().zip(&scanned) {
+            assert_eq!(indexed.full_name(), scanned.full_name());
+            assert_eq!(indexed.tags, scanned.tags);
+            assert_eq!(indexed.latest_manifest_hash, scanned.latest_manifest_hash);
+        }
+        assert_eq!(indexed[1].tags, vec!["latest", "v1", "v2"]);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_index_from_refs() {
+        let storage = CountingStorage::default();
+
+        // Publish without maintaining the index
+        let registry = Registry::new_storage_only(storage.clone());
+        let hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
             .await
             .unwrap();
-        registry
-            .publish(bundle3, "invoice", "official")
-            .await
-            .unwrap(); // No namespace
-
-        let templates = registry.list_templates().await.unwrap();
-        assert_eq!(templates.len(), 3);
 
-        // Templates should be sorted by full name
-        assert_eq!(templates[0].full_name(), "alice/letter");
-        assert_eq!(templates[1].full_name(), "invoice");
-        assert_eq!(templates[2].full_name(), "john/invoice");
+        let registry = Registry::new_storage_only(storage.clone()).with_refs_index();
 
-        // Check individual templates
-        assert_eq!(templates[0].namespace, Some("alice".to_string()));
-        assert_eq!(templates[0].name, "letter");
-        assert_eq!(templates[0].metadata.author, "alice@example.com");
+        // Without an index, listing falls back to scanning refs
+        assert_eq!(registry.list_templates().await.unwrap().len(), 1);
 
-        assert_eq!(templates[1].namespace, None);
-        assert_eq!(templates[1].name, "invoice");
-        assert_eq!(templates[1].metadata.author, "admin@example.com");
+        let index = registry.rebuild_index().await.unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.get("john/invoice", "latest"), Some(hash.as_str()));
 
-        assert_eq!(templates[2].namespace, Some("john".to_string()));
-        assert_eq!(templates[2].name, "invoice");
-        assert_eq!(templates[2].metadata.author, "john@example.com");
+        let stored = RefsIndex::from_bytes(&storage.inner.get(REFS_INDEX_KEY).await.unwrap());
+        assert_eq!(stored.unwrap(), index);
     }
 
     #[tokio::test]
@@ -1481,61 +4816,359 @@ Content: #data.content"#
     }
 
     #[tokio::test]
-    async fn test_parse_namespace_path() {
-        // Test different namespace path formats
-        assert_eq!(
-            Registry::<MemoryStorage, crate::render_storage::MemoryRenderStorage>::parse_namespace_path("invoice"),
-            (None, "invoice".to_string())
+    async fn test_parse_namespace_path() {
+        // Test different namespace path formats
+        assert_eq!(
+            Registry::<MemoryStorage, crate::render_storage::MemoryRenderStorage>::parse_namespace_path("invoice"),
+            (None, "invoice".to_string())
+        );
+
+        assert_eq!(
+            Registry::<MemoryStorage, crate::render_storage::MemoryRenderStorage>::parse_namespace_path("john/invoice"),
+            (Some("john".to_string()), "invoice".to_string())
+        );
+
+        assert_eq!(
+            Registry::<MemoryStorage, crate::render_storage::MemoryRenderStorage>::parse_namespace_path("org/user/template"),
+            (Some("org/user".to_string()), "template".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_and_store_success() {
+        let storage = MemoryStorage::new();
+        let render_storage = crate::render_storage::MemoryRenderStorage::new();
+        let registry = Registry::new(storage, render_storage);
+        let bundle = create_test_bundle();
+
+        // First publish a template
+        let _manifest_hash = registry
+            .publish(bundle, "test-user/test-template", "latest")
+            .await
+            .unwrap();
+
+        // Test data for rendering
+        let test_data = serde_json::json!({
+            "name": "Test User"
+        });
+
+        // Render with storage tracking
+        let result = registry
+            .render_and_store("test-user/test-template:latest", &test_data)
+            .await
+            .unwrap();
+
+        // Verify result structure
+        assert!(!result.render_id.is_empty());
+        assert!(!result.pdf_bytes.is_empty());
+        assert!(result.pdf_hash.starts_with("sha256:"));
+        assert!(result.duration_ms > 0);
+
+        // Verify render record was stored
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].render_id, result.render_id);
+        assert_eq!(records[0].template_name, "test-template");
+        assert_eq!(records[0].template_tag, "latest");
+        assert!(records[0].success);
+    }
+
+    #[tokio::test]
+    async fn test_render_cache_reuses_prior_render() {
+        let storage = InstrumentedStorage::new(MemoryStorage::new());
+        let stats = storage.stats();
+        let registry = Registry::new(storage, crate::render_storage::MemoryRenderStorage::new());
+        registry
+            .publish(create_test_bundle(), "test-user/test-template", "latest")
+            .await
+            .unwrap();
+        let main_key =
+            ContentAddress::blob_key(&ContentAddress::hash(create_test_bundle().main_typ()));
+        let data = serde_json::json!({"name": "Test User"});
+        let options = RenderOptions::default().with_use_cache(true);
+
+        let first = registry
+            .render_and_store_with_options("test-user/test-template:latest", &data, &options)
+            .await
+            .unwrap();
+        assert!(!first.cached);
+
+        stats.reset();
+        let second = registry
+            .render_and_store_with_options("test-user/test-template:latest", &data, &options)
+            .await
+            .unwrap();
+        assert!(second.cached);
+        assert_eq!(second.render_id, first.render_id);
+        assert_eq!(second.pdf_bytes, first.pdf_bytes);
+        assert_eq!(stats.total_puts(), 0, "a cached render stores nothing");
+        assert_eq!(
+            stats.get_count(&main_key),
+            0,
+            "a cached render compiles nothing"
+        );
+        assert_eq!(registry.list_recent_renders(10).await.unwrap().len(), 1);
+
+        // Other data is rendered afresh
+        let other = registry
+            .render_and_store_with_options(
+                "test-user/test-template:latest",
+                &serde_json::json!({"name": "Someone Else"}),
+                &options,
+            )
+            .await
+            .unwrap();
+        assert!(!other.cached);
+        assert_ne!(other.render_id, first.render_id);
+    }
+
+    #[tokio::test]
+    async fn test_render_pinned_uses_older_manifest() {
+        let registry = Registry::new(
+            MemoryStorage::new(),
+            crate::render_storage::MemoryRenderStorage::new(),
+        );
+        let data = serde_json::json!({"number": 7});
+        let publish = |main_typ: &'static [u8]| {
+            registry.publish(
+                TemplateBundle::new(
+                    main_typ.to_vec(),
+                    TemplateMetadata::new("Invoice", "test@example.com"),
+                ),
+                "acme/invoice",
+                "latest",
+            )
+        };
+
+        let old_hash = publish(b"= Old invoice #data.number").await.unwrap();
+        let old_pdf = registry.render("acme/invoice:latest", &data).await.unwrap();
+        let new_hash = publish(b"= New invoice #data.number").await.unwrap();
+        let new_pdf = registry.render("acme/invoice:latest", &data).await.unwrap();
+        assert_ne!(old_pdf, new_pdf);
+
+        let pinned = registry
+            .render_pinned("acme/invoice:latest", &old_hash, &data)
+            .await
+            .unwrap();
+        assert_eq!(pinned.pdf_bytes, old_pdf);
+        assert_eq!(
+            registry.resolve("acme/invoice:latest").await.unwrap(),
+            new_hash
+        );
+
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].render_id, pinned.render_id);
+        assert_eq!(records[0].template_ref, "acme/invoice:latest");
+        assert_eq!(records[0].template_tag, "latest");
+        assert_eq!(records[0].manifest_hash, old_hash);
+
+        let unknown = ContentAddress::hash(b"no such manifest");
+        assert!(matches!(
+            registry
+                .render_pinned("acme/invoice:latest", &unknown, &data)
+                .await,
+            Err(RegistryError::Template(TemplateError::NotFound { .. }))
+        ));
+        assert!(matches!(
+            registry
+                .render_pinned("acme/invoice:latest", "sha256:abc", &data)
+                .await,
+            Err(RegistryError::Reference(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_render_named_entrypoints() {
+        let registry = Registry::new(
+            MemoryStorage::new(),
+            crate::render_storage::MemoryRenderStorage::new(),
+        )
+        .with_template_cache(4);
+        let bundle = TemplateBundle::new(
+            b"#import \"partials/header.typ\": header\n#header[Main for #data.customer]".to_vec(),
+            TemplateMetadata::new("Documents", "test@example.com"),
+        )
+        .add_file(
+            "partials/header.typ",
+            b"#let header(title) = [= #title]".to_vec(),
+        )
+        .add_file(
+            "invoice.typ",
+            b"#import \"partials/header.typ\": header\n#header[Invoice for #data.customer]"
+                .to_vec(),
+        )
+        .add_file(
+            "receipt.typ",
+            b"#import \"partials/header.typ\": header\n#header[Receipt for #data.customer]"
+                .to_vec(),
+        )
+        .with_entrypoint("invoice", "invoice.typ")
+        .with_entrypoint("receipt", "receipt.typ");
+        registry
+            .publish(bundle, "acme/documents", "latest")
+            .await
+            .unwrap();
+        let data = serde_json::json!({"customer": "Acme Corp"});
+
+        let main = registry
+            .render("acme/documents:latest", &data)
+            .await
+            .unwrap();
+        let invoice = registry
+            .render("acme/documents:latest#invoice", &data)
+            .await
+            .unwrap();
+        let receipt = registry
+            .render("acme/documents:latest#receipt", &data)
+            .await
+            .unwrap();
+        assert!(invoice.starts_with(b"%PDF") && receipt.starts_with(b"%PDF"));
+        assert_ne!(invoice, receipt);
+        assert_ne!(invoice, main);
+        assert_ne!(receipt, main);
+
+        // Cached templates are kept apart per entrypoint
+        let again = registry
+            .render("acme/documents:latest#receipt", &data)
+            .await
+            .unwrap();
+        assert_eq!(again, receipt);
+
+        let stored = registry
+            .render_and_store("acme/documents:latest#invoice", &data)
+            .await
+            .unwrap();
+        assert_eq!(stored.pdf_bytes, invoice);
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records[0].template_ref, "acme/documents:latest#invoice");
+        assert_eq!(records[0].template_tag, "latest");
+
+        assert!(matches!(
+            registry.render("acme/documents:latest#quote", &data).await,
+            Err(RegistryError::Template(TemplateError::NotFound { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_bundle_entrypoints_must_exist() {
+        let bundle = TemplateBundle::new(
+            b"= Main".to_vec(),
+            TemplateMetadata::new("Documents", "test@example.com"),
+        )
+        .with_entrypoint("receipt", "receipt.typ");
+        assert!(matches!(
+            bundle.validate(),
+            Err(crate::bundle::TemplateValidationError::MissingFile(path)) if path == "receipt.typ"
+        ));
+
+        let nested = bundle
+            .clone()
+            .add_file("variants/receipt.typ", b"= Receipt".to_vec())
+            .with_entrypoint("receipt", "variants/receipt.typ");
+        assert!(matches!(
+            nested.validate(),
+            Err(crate::bundle::TemplateValidationError::InvalidMetadata(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_render_cache_is_opt_in() {
+        let registry = Registry::new(
+            MemoryStorage::new(),
+            crate::render_storage::MemoryRenderStorage::new(),
         );
+        registry
+            .publish(create_test_bundle(), "test-user/test-template", "latest")
+            .await
+            .unwrap();
+        let data = serde_json::json!({"name": "Test User"});
 
-        assert_eq!(
-            Registry::<MemoryStorage, crate::render_storage::MemoryRenderStorage>::parse_namespace_path("john/invoice"),
-            (Some("john".to_string()), "invoice".to_string())
-        );
+        let first = registry
+            .render_and_store("test-user/test-template:latest", &data)
+            .await
+            .unwrap();
+        let second = registry
+            .render_and_store_with_options(
+                "test-user/test-template:latest",
+                &data,
+                &RenderOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert!(!second.cached);
+        assert_ne!(second.render_id, first.render_id);
+        assert_eq!(registry.list_recent_renders(10).await.unwrap().len(), 2);
+    }
 
-        assert_eq!(
-            Registry::<MemoryStorage, crate::render_storage::MemoryRenderStorage>::parse_namespace_path("org/user/template"),
-            (Some("org/user".to_string()), "template".to_string())
-        );
+    /// Records the name and fields of every span, in creation order
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<std::sync::Mutex<Vec<(String, String)>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanRecorder {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = String::new();
+            attrs.record(
+                &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    fields.push_str(&format!("{}={:?} ", field, value));
+                },
+            );
+            let name = attrs.metadata().name().to_string();
+            self.0.lock().unwrap().push((name, fields));
+        }
     }
 
     #[tokio::test]
-    async fn test_render_and_store_success() {
-        let storage = MemoryStorage::new();
-        let render_storage = crate::render_storage::MemoryRenderStorage::new();
-        let registry = Registry::new(storage, render_storage);
-        let bundle = create_test_bundle();
+    async fn test_render_and_store_emits_phase_spans() {
+        use tracing_subscriber::layer::SubscriberExt;
 
-        // First publish a template
-        let _manifest_hash = registry
-            .publish(bundle, "test-user/test-template", "latest")
+        let registry = Registry::new(
+            MemoryStorage::new(),
+            crate::render_storage::MemoryRenderStorage::new(),
+        );
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "test-user/test-template", "latest")
             .await
             .unwrap();
 
-        // Test data for rendering
-        let test_data = serde_json::json!({
-            "name": "Test User"
-        });
-
-        // Render with storage tracking
-        let result = registry
-            .render_and_store("test-user/test-template:latest", &test_data)
+        let recorder = SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        registry
+            .render_and_store(
+                "test-user/test-template:latest",
+                &serde_json::json!({"name": "secret-customer-name"}),
+            )
             .await
             .unwrap();
 
-        // Verify result structure
-        assert!(!result.render_id.is_empty());
-        assert!(!result.pdf_bytes.is_empty());
-        assert!(result.pdf_hash.starts_with("sha256:"));
-        assert!(result.duration_ms > 0);
-
-        // Verify render record was stored
-        let records = registry.list_recent_renders(10).await.unwrap();
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].render_id, result.render_id);
-        assert_eq!(records[0].template_name, "test-template");
-        assert_eq!(records[0].template_tag, "latest");
-        assert!(records[0].success);
+        let spans = recorder.0.lock().unwrap().clone();
+        let names: Vec<&str> = spans.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "resolve",
+                "load_manifest",
+                "fetch_blobs",
+                "render",
+                "compile",
+                "export",
+                "store"
+            ]
+        );
+        assert!(spans[1].1.contains(&manifest_hash), "{:?}", spans[1]);
+        assert!(spans[6].1.contains("bytes="), "{:?}", spans[6]);
+        // The render data must not leak into spans
+        assert!(
+            spans.iter().all(|(_, fields)| !fields.contains("secret")),
+            "{:?}",
+            spans
+        );
     }
 
     #[tokio::test]
@@ -1614,6 +5247,187 @@ Content: #data.content"#
         assert!(invalid_result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_prepare_renders_multiple_data_sets() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let mut prepared = registry.prepare("john/invoice:latest").await.unwrap();
+        assert_eq!(prepared.manifest_hash(), manifest_hash);
+        assert_eq!(prepared.reference(), "john/invoice:latest");
+
+        let first = prepared
+            .render(&serde_json::json!({"name": "Alice"}))
+            .unwrap();
+        let second = prepared
+            .render(&serde_json::json!({"name": "Bob"}))
+            .unwrap();
+
+        assert!(first.starts_with(b"%PDF"));
+        assert!(second.starts_with(b"%PDF"));
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_get_template_file() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let logo = registry
+            .get_template_file("john/invoice:latest", "/assets/logo.png")
+            .await
+            .unwrap();
+        assert_eq!(logo, b"fake_png_data");
+
+        let result = registry
+            .get_template_file("john/invoice:latest", "assets/missing.png")
+            .await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Template(
+                crate::error::TemplateError::NotFound { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_render_batch() {
+        let storage = MemoryStorage::new();
+        let render_storage = crate::render_storage::MemoryRenderStorage::new();
+        let registry = Registry::new(storage, render_storage);
+        registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let mut rows: Vec<serde_json::Value> = (0..100)
+            .map(|i| serde_json::json!({ "name": format!("Customer {}", i) }))
+            .collect();
+        // A row missing the field the template reads
+        rows[42] = serde_json::json!({});
+
+        let results = registry
+            .render_batch("john/invoice:latest", &rows)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 100);
+        for (i, result) in results.iter().enumerate() {
+            if i == 42 {
+                assert!(result.is_err());
+            } else {
+                assert!(result.as_ref().unwrap().pdf_bytes.starts_with(b"%PDF"));
+            }
+        }
+
+        // Results line up with their rows
+        for i in [0, 43, 99] {
+            let pdf = registry
+                .render("john/invoice:latest", &rows[i])
+                .await
+                .unwrap();
+            assert_eq!(results[i].as_ref().unwrap().pdf_bytes, pdf);
+        }
+
+        let records = registry.list_recent_renders(200).await.unwrap();
+        assert_eq!(records.len(), 100);
+        assert_eq!(records.iter().filter(|r| !r.success).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_render_batch_unknown_reference() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let rows = vec![serde_json::json!({ "name": "Alice" })];
+
+        assert!(
+            registry
+                .render_batch("john/missing:latest", &rows)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_prepared_and_store_records_each_render() {
+        let storage = MemoryStorage::new();
+        let render_storage = crate::render_storage::MemoryRenderStorage::new();
+        let registry = Registry::new(storage, render_storage);
+        let manifest_hash = registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let mut prepared = registry.prepare("john/invoice:latest").await.unwrap();
+        for name in ["Alice", "Bob"] {
+            registry
+                .render_prepared_and_store(&mut prepared, &serde_json::json!({"name": name}))
+                .await
+                .unwrap();
+        }
+
+        // Invalid data is recorded as a failed render
+        assert!(
+            registry
+                .render_prepared_and_store(&mut prepared, &serde_json::json!({}))
+                .await
+                .is_err()
+        );
+
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.iter().filter(|r| r.success).count(), 2);
+        assert!(
+            records
+                .iter()
+                .filter(|r| r.success)
+                .all(|r| r.manifest_hash == manifest_hash)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_rendered_records_external_render() {
+        let registry = Registry::new(
+            MemoryStorage::new(),
+            crate::render_storage::MemoryRenderStorage::new(),
+        );
+        registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+        let data = serde_json::json!({"name": "Alice"});
+
+        let mut prepared = registry.prepare("john/invoice:latest").await.unwrap();
+        let pdf_bytes = prepared.render(&data).unwrap();
+        let outcome = Ok((prepared.manifest_hash().to_string(), pdf_bytes.clone()));
+        let result = registry
+            .store_rendered(prepared.reference(), &data, outcome, 12)
+            .await
+            .unwrap();
+        assert_eq!(result.duration_ms, 12);
+        assert_eq!(
+            registry.get_render_pdf(&result.render_id).await.unwrap(),
+            pdf_bytes
+        );
+
+        let failed = Err(RegistryError::Template(TemplateError::invalid("timed out")));
+        assert!(
+            registry
+                .store_rendered("john/invoice:latest", &data, failed, 5)
+                .await
+                .is_err()
+        );
+
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records.iter().filter(|r| r.success).count(), 1);
+    }
+
     #[tokio::test]
     async fn test_render_and_store_failure_tracking() {
         let storage = MemoryStorage::new();
@@ -1688,7 +5502,10 @@ Content: #data.content"#
 
         // Test volume analytics
         let volume_result = registry
-            .get_render_analytics(AnalyticsQuery::VolumeOverTime { days: 1 })
+            .get_render_analytics(AnalyticsQuery::VolumeOverTime {
+                days: 1,
+                granularity: Granularity::Day,
+            })
             .await
             .unwrap();
         if let AnalyticsResult::Volume(volume_points) = volume_result {
@@ -1722,7 +5539,10 @@ Content: #data.content"#
 
         // Test duration analytics
         let duration_result = registry
-            .get_render_analytics(AnalyticsQuery::DurationOverTime { days: 1 })
+            .get_render_analytics(AnalyticsQuery::DurationOverTime {
+                days: 1,
+                granularity: Granularity::Day,
+            })
             .await
             .unwrap();
         if let AnalyticsResult::Duration(duration_points) = duration_result {
@@ -1731,6 +5551,22 @@ Content: #data.content"#
         } else {
             panic!("Expected Duration result");
         }
+
+        // Test percentile analytics
+        let percentile_result = registry
+            .get_render_analytics(AnalyticsQuery::PercentileOverTime {
+                days: 1,
+                granularity: Granularity::Day,
+                percentiles: vec![0.5, 0.9],
+            })
+            .await
+            .unwrap();
+        if let AnalyticsResult::Percentiles(percentile_points) = percentile_result {
+            assert!(!percentile_points.is_empty());
+            assert!(percentile_points.iter().all(|p| p.percentiles.len() == 2));
+        } else {
+            panic!("Expected Percentiles result");
+        }
     }
 
     #[tokio::test]
@@ -1795,4 +5631,264 @@ Content: #data.content"#
         assert_eq!(data1, data2);
         assert_eq!(data1, test_data);
     }
+
+    #[tokio::test]
+    async fn test_publish_signed_render_verifies() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_verifying_key(signing_key.verifying_key());
+
+        let manifest_hash = registry
+            .publish_signed(create_test_bundle(), "john/invoice", "latest", &signing_key)
+            .await
+            .unwrap();
+
+        let signature_key = ContentAddress::signature_key(&manifest_hash);
+        assert!(registry.storage.exists(&signature_key).await.unwrap());
+
+        let data = serde_json::json!({ "name": "Test Customer" });
+        let pdf_bytes = registry.render("john/invoice:latest", &data).await.unwrap();
+        assert!(pdf_bytes.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_manifest_fails_verification() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_verifying_key(signing_key.verifying_key());
+
+        let manifest_hash = registry
+            .publish_signed(create_test_bundle(), "john/invoice", "latest", &signing_key)
+            .await
+            .unwrap();
+
+        // Swap the manifest content while keeping the signed hash in place
+        let manifest_key = ContentAddress::manifest_key(&manifest_hash);
+        let mut manifest =
+            Manifest::from_bytes(&registry.storage.get(&manifest_key).await.unwrap()).unwrap();
+        manifest.metadata.author = "mallory@example.com".to_string();
+        registry
+            .storage
+            .put(&manifest_key, manifest.to_bytes().unwrap())
+            .await
+            .unwrap();
+
+        let data = serde_json::json!({ "name": "Test Customer" });
+        let result = registry.render("john/invoice:latest", &data).await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Signature(SignatureError::Invalid { .. }))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tampered_blob_of_signed_template_fails() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_verifying_key(signing_key.verifying_key());
+
+        let bundle = TemplateBundle::new(
+            b"#import \"header.typ\": title\n= #title".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        )
+        .add_file("header.typ", b"#let title = \"Invoice\"".to_vec())
+        .with_schema(br#"{"type": "object"}"#.to_vec());
+        let manifest_hash = registry
+            .publish_signed(bundle, "john/invoice", "latest", &signing_key)
+            .await
+            .unwrap();
+        let manifest = registry.load_manifest(&manifest_hash).await.unwrap();
+        let data = serde_json::json!({});
+
+        // Replace each blob in turn, keeping the signed manifest in place
+        let replacements: [(&str, &[u8]); 3] = [
+            ("main.typ", b"= Mallory"),
+            ("header.typ", b"#let title = \"Mallory\""),
+            ("schema.json", b"{}"),
+        ];
+        for (path, replacement) in replacements {
+            let blob_key = ContentAddress::blob_key(&manifest.files[path]);
+            let original = registry.storage.get(&blob_key).await.unwrap();
+            registry
+                .storage
+                .put(&blob_key, replacement.to_vec())
+                .await
+                .unwrap();
+
+            let result = registry.render("john/invoice:latest", &data).await;
+            assert!(result.is_err(), "tampered {} still rendered", path);
+
+            registry.storage.put(&blob_key, original).await.unwrap();
+        }
+        assert!(registry.render("john/invoice:latest", &data).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_manifest_rejected_when_key_configured() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_verifying_key(signing_key.verifying_key());
+
+        registry
+            .publish(create_test_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let result = registry.resolve("john/invoice:latest").await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Signature(SignatureError::Missing { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_signature_from_other_key_rejected() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let other_key = SigningKey::from_bytes(&[9u8; 32]);
+        let registry = Registry::new_storage_only(MemoryStorage::new())
+            .with_verifying_key(signing_key.verifying_key());
+
+        registry
+            .publish_signed(create_test_bundle(), "john/invoice", "latest", &other_key)
+            .await
+            .unwrap();
+
+        let result = registry.resolve("john/invoice:latest").await;
+        assert!(matches!(
+            result,
+            Err(RegistryError::Signature(SignatureError::Invalid { .. }))
+        ));
+    }
+
+    fn schema_bundle() -> TemplateBundle {
+        let metadata = TemplateMetadata::new("Invoice", "test@example.com");
+        TemplateBundle::new(b"Invoice for #data.customer.name".to_vec(), metadata).with_schema(
+            br#"{
+                "type": "object",
+                "required": ["customer"],
+                "properties": {
+                    "customer": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": {"type": "string"},
+                            "vat_id": {"type": "string"}
+                        }
+                    }
+                }
+            }"#
+            .to_vec(),
+        )
+    }
+
+    fn schema_error_path(result: Result<Vec<u8>, RegistryError>) -> String {
+        match result {
+            Err(RegistryError::Compilation(papermake::PapermakeError::Data(
+                papermake::error::DataError::SchemaValidation { path, .. },
+            ))) => path,
+            other => panic!(
+                "expected schema validation error, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_validates_missing_required_field() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        registry
+            .publish(schema_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let data = serde_json::json!({ "customer": { "nmae": "Acme" } });
+        let result = registry.render("john/invoice:latest", &data).await;
+        assert_eq!(schema_error_path(result), "/customer/name");
+
+        let data = serde_json::json!({ "customer": { "name": "Acme" } });
+        let pdf = registry.render("john/invoice:latest", &data).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn test_render_validates_type_mismatch() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        registry
+            .publish(schema_bundle(), "john/invoice", "latest")
+            .await
+            .unwrap();
+
+        let data = serde_json::json!({ "customer": { "name": "Acme", "vat_id": 42 } });
+        let result = registry.render("john/invoice:latest", &data).await;
+        assert_eq!(schema_error_path(result), "/customer/vat_id");
+
+        // Opting out renders anyway; the template never reads vat_id
+        let options = RenderOptions::default().with_skip_validation(true);
+        let pdf = registry
+            .render_with_options("john/invoice:latest", &data, &options)
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+    #[tokio::test]
+    async fn test_render_falls_back_to_defaults() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let bundle = TemplateBundle::new(
+            b"#assert.eq(data.currency, \"$\")\n#assert.eq(data.format.date, \"[day].[month]\")\nTotal: #data.currency#data.total".to_vec(),
+            TemplateMetadata::new("Report", "test@example.com"),
+        )
+        .with_defaults(br#"{"currency": "$", "format": {"date": "[day].[month]", "lang": "en"}}"#.to_vec())
+        .with_schema(br#"{"type": "object", "required": ["currency", "total"]}"#.to_vec());
+        registry
+            .publish(bundle, "john/report", "latest")
+            .await
+            .unwrap();
+
+        // The payload omits currency entirely and only part of format
+        let data = serde_json::json!({ "total": 10, "format": { "lang": "de" } });
+        let pdf = registry.render("john/report:latest", &data).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // A value in the payload wins over the default
+        let data = serde_json::json!({ "total": 10, "currency": "€" });
+        assert!(matches!(
+            registry.render("john/report:latest", &data).await,
+            Err(RegistryError::Compilation(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge_over_defaults() {
+        let defaults = serde_json::json!({
+            "currency": "$",
+            "format": { "date": "[day].[month]", "lang": "en" },
+            "tags": ["a", "b"]
+        });
+        let data = serde_json::json!({
+            "format": { "lang": "de" },
+            "tags": ["c"],
+            "total": 10
+        });
+
+        assert_eq!(
+            merge_over_defaults(&defaults, &data),
+            serde_json::json!({
+                "currency": "$",
+                "format": { "date": "[day].[month]", "lang": "de" },
+                "tags": ["c"],
+                "total": 10
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publish_rejects_non_object_defaults() {
+        let registry = Registry::new_storage_only(MemoryStorage::new());
+        let bundle = create_test_bundle().with_defaults(b"[1, 2]".to_vec());
+
+        assert!(matches!(
+            registry.publish(bundle, "john/invoice", "latest").await,
+            Err(RegistryError::Template(TemplateError::Invalid { .. }))
+        ));
+    }
 }
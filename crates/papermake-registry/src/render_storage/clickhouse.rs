@@ -3,11 +3,14 @@
 use async_trait::async_trait;
 use clickhouse::{Client, Row};
 use serde::{Deserialize, Serialize};
-use std::env;
 use time::{Duration, OffsetDateTime};
 
+use crate::config;
+
 use super::{
-    DurationPoint, RenderRecord, RenderStorage, RenderStorageError, TemplateStats, VolumePoint,
+    DurationPoint, Granularity, Page, PercentilePoint, PercentileValue, RenderRecord,
+    RenderStorage, RenderStorageError, TemplateStats, TemplateVolumePoint, VolumePoint,
+    validate_percentiles,
 };
 
 /// ClickHouse storage implementation for render records
@@ -90,18 +93,21 @@ impl TryFrom<ClickHouseRenderRecord> for RenderRecord {
 
 impl ClickHouseStorage {
     /// Create a new ClickHouse storage instance from environment variables
+    ///
+    /// Reads CLICKHOUSE_URL, CLICKHOUSE_USER, CLICKHOUSE_PASSWORD and
+    /// CLICKHOUSE_DATABASE, each of which can instead be read from a file
+    /// named by the same variable with a `_FILE` suffix.
     pub fn from_env() -> Result<Self, RenderStorageError> {
-        let url = env::var("CLICKHOUSE_URL")
-            .unwrap_or_else(|_| "http://localhost:8123".to_string());
-        
-        let user = env::var("CLICKHOUSE_USER")
-            .unwrap_or_else(|_| "default".to_string());
-        
-        let password = env::var("CLICKHOUSE_PASSWORD")
-            .unwrap_or_else(|_| "".to_string());
-        
-        let database = env::var("CLICKHOUSE_DATABASE")
-            .unwrap_or_else(|_| "papermake".to_string());
+        let env_or = |name: &str, default: &str| {
+            config::env_var(name)
+                .map(|value| value.unwrap_or_else(|| default.to_string()))
+                .map_err(|e| RenderStorageError::Connection(e.to_string()))
+        };
+
+        let url = env_or("CLICKHOUSE_URL", "http://localhost:8123")?;
+        let user = env_or("CLICKHOUSE_USER", "default")?;
+        let password = env_or("CLICKHOUSE_PASSWORD", "")?;
+        let database = env_or("CLICKHOUSE_DATABASE", "papermake")?;
 
         let mut client = Client::default()
             .with_url(url)
@@ -205,6 +211,33 @@ impl RenderStorage for ClickHouseStorage {
         Ok(records)
     }
 
+    async fn list_renders(&self, page: Page) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        // Renders stored in the same millisecond are ordered by render ID
+        let query = "SELECT * FROM renders \
+            WHERE timestamp < ? OR (timestamp = ? AND render_id < ?) \
+            ORDER BY timestamp DESC, render_id DESC LIMIT ?";
+        let before = page
+            .before
+            .map(|before| (before.unix_timestamp_nanos() / 1_000_000) as u64)
+            .unwrap_or(u64::MAX);
+        let before_id = page.before_id.unwrap_or_default();
+
+        let mut cursor = self.client
+            .query(query)
+            .bind(before)
+            .bind(before)
+            .bind(before_id)
+            .bind(page.limit)
+            .fetch::<ClickHouseRenderRecord>()?;
+
+        let mut records = Vec::new();
+        while let Some(ch_record) = cursor.next().await? {
+            records.push(ch_record.try_into()?);
+        }
+
+        Ok(records)
+    }
+
     async fn list_template_renders(
         &self,
         template_name: &str,
@@ -226,40 +259,68 @@ impl RenderStorage for ClickHouseStorage {
         Ok(records)
     }
 
-    async fn render_volume_over_time(&self, days: u32) -> Result<Vec<VolumePoint>, RenderStorageError> {
+    async fn find_by_inputs(
+        &self,
+        template_ref: &str,
+        data_hash: &str,
+    ) -> Result<Option<RenderRecord>, RenderStorageError> {
+        let query = r#"
+            SELECT * FROM renders
+            WHERE template_ref = ? AND data_hash = ? AND success = 1
+            ORDER BY timestamp DESC
+            LIMIT 1
+        "#;
+
+        let mut cursor = self.client
+            .query(query)
+            .bind(template_ref)
+            .bind(data_hash)
+            .fetch::<ClickHouseRenderRecord>()?;
+
+        if let Some(ch_record) = cursor.next().await? {
+            Ok(Some(ch_record.try_into()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn render_volume_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+    ) -> Result<Vec<VolumePoint>, RenderStorageError> {
         let cutoff_timestamp = (OffsetDateTime::now_utc() - Duration::days(days as i64))
             .unix_timestamp_nanos() as u64 / 1_000_000;
 
-        let query = r#"
+        let query = format!(
+            r#"
             SELECT 
-                toDate(toDateTime(timestamp / 1000)) as date,
+                toUnixTimestamp({}) as bucket,
                 count() as renders
             FROM renders 
             WHERE timestamp >= ?
-            GROUP BY date
-            ORDER BY date
-        "#;
+            GROUP BY bucket
+            ORDER BY bucket
+        "#,
+            bucket_expression(granularity)
+        );
 
         #[derive(Row, Deserialize)]
         struct VolumeRow {
-            date: u16, // ClickHouse date as days since 1900-01-01
+            bucket: u32, // Bucket start as unix seconds
             renders: u64,
         }
 
         let mut cursor = self.client
-            .query(query)
+            .query(&query)
             .bind(cutoff_timestamp)
             .fetch::<VolumeRow>()?;
 
         let mut points = Vec::new();
         while let Some(row) = cursor.next().await? {
-            // Convert ClickHouse date (days since 1900-01-01) to time::Date
-            let days_since_1900 = row.date as i32;
-            let days_since_unix_epoch = days_since_1900 - 25567; // Days from 1900-01-01 to 1970-01-01
-            
-            if let Ok(date) = time::Date::from_julian_day(days_since_unix_epoch + 2440588) { // Julian day adjustment
+            if let Ok(timestamp) = OffsetDateTime::from_unix_timestamp(row.bucket as i64) {
                 points.push(VolumePoint {
-                    date,
+                    timestamp,
                     renders: row.renders,
                 });
             }
@@ -299,43 +360,90 @@ impl RenderStorage for ClickHouseStorage {
         Ok(stats)
     }
 
+    async fn renders_per_template_over_time(
+        &self,
+        days: u32,
+    ) -> Result<Vec<TemplateVolumePoint>, RenderStorageError> {
+        let cutoff_timestamp = (OffsetDateTime::now_utc() - Duration::days(days as i64))
+            .unix_timestamp_nanos() as u64 / 1_000_000;
+
+        let query = format!(
+            r#"
+            SELECT 
+                template_name,
+                toUnixTimestamp({}) as day,
+                count() as renders
+            FROM renders 
+            WHERE timestamp >= ?
+            GROUP BY template_name, day
+            ORDER BY day, template_name
+        "#,
+            bucket_expression(Granularity::Day)
+        );
+
+        #[derive(Row, Deserialize)]
+        struct TemplateVolumeRow {
+            template_name: String,
+            day: u32, // Day start as unix seconds
+            renders: u64,
+        }
+
+        let mut cursor = self.client
+            .query(&query)
+            .bind(cutoff_timestamp)
+            .fetch::<TemplateVolumeRow>()?;
+
+        let mut points = Vec::new();
+        while let Some(row) = cursor.next().await? {
+            if let Ok(date) = OffsetDateTime::from_unix_timestamp(row.day as i64) {
+                points.push(TemplateVolumePoint {
+                    template_name: row.template_name,
+                    date,
+                    renders: row.renders,
+                });
+            }
+        }
+
+        Ok(points)
+    }
+
     async fn average_duration_over_time(
         &self,
         days: u32,
+        granularity: Granularity,
     ) -> Result<Vec<DurationPoint>, RenderStorageError> {
         let cutoff_timestamp = (OffsetDateTime::now_utc() - Duration::days(days as i64))
             .unix_timestamp_nanos() as u64 / 1_000_000;
 
-        let query = r#"
+        let query = format!(
+            r#"
             SELECT 
-                toDate(toDateTime(timestamp / 1000)) as date,
+                toUnixTimestamp({}) as bucket,
                 avg(duration_ms) as avg_duration_ms
             FROM renders 
             WHERE timestamp >= ? AND success = 1
-            GROUP BY date
-            ORDER BY date
-        "#;
+            GROUP BY bucket
+            ORDER BY bucket
+        "#,
+            bucket_expression(granularity)
+        );
 
         #[derive(Row, Deserialize)]
         struct DurationRow {
-            date: u16, // ClickHouse date as days since 1900-01-01
+            bucket: u32, // Bucket start as unix seconds
             avg_duration_ms: f64,
         }
 
         let mut cursor = self.client
-            .query(query)
+            .query(&query)
             .bind(cutoff_timestamp)
             .fetch::<DurationRow>()?;
 
         let mut points = Vec::new();
         while let Some(row) = cursor.next().await? {
-            // Convert ClickHouse date (days since 1900-01-01) to time::Date
-            let days_since_1900 = row.date as i32;
-            let days_since_unix_epoch = days_since_1900 - 25567; // Days from 1900-01-01 to 1970-01-01
-            
-            if let Ok(date) = time::Date::from_julian_day(days_since_unix_epoch + 2440588) { // Julian day adjustment
+            if let Ok(timestamp) = OffsetDateTime::from_unix_timestamp(row.bucket as i64) {
                 points.push(DurationPoint {
-                    date,
+                    timestamp,
                     avg_duration_ms: row.avg_duration_ms,
                 });
             }
@@ -343,6 +451,89 @@ impl RenderStorage for ClickHouseStorage {
 
         Ok(points)
     }
+
+    async fn percentile_duration_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+        percentiles: &[f64],
+    ) -> Result<Vec<PercentilePoint>, RenderStorageError> {
+        validate_percentiles(percentiles)?;
+
+        let cutoff_timestamp = (OffsetDateTime::now_utc() - Duration::days(days as i64))
+            .unix_timestamp_nanos() as u64 / 1_000_000;
+
+        // Validated above, so the levels are plain numbers safe to inline
+        let levels = percentiles
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let query = format!(
+            r#"
+            SELECT 
+                toUnixTimestamp({}) as bucket,
+                quantiles({})(duration_ms) as durations
+            FROM renders 
+            WHERE timestamp >= ? AND success = 1
+            GROUP BY bucket
+            ORDER BY bucket
+        "#,
+            bucket_expression(granularity),
+            levels
+        );
+
+        #[derive(Row, Deserialize)]
+        struct PercentileRow {
+            bucket: u32, // Bucket start as unix seconds
+            durations: Vec<f64>,
+        }
+
+        let mut cursor = self.client
+            .query(&query)
+            .bind(cutoff_timestamp)
+            .fetch::<PercentileRow>()?;
+
+        let mut points = Vec::new();
+        while let Some(row) = cursor.next().await? {
+            if let Ok(timestamp) = OffsetDateTime::from_unix_timestamp(row.bucket as i64) {
+                points.push(PercentilePoint {
+                    timestamp,
+                    percentiles: percentiles
+                        .iter()
+                        .zip(row.durations)
+                        .map(|(&percentile, duration_ms)| PercentileValue {
+                            percentile,
+                            duration_ms,
+                        })
+                        .collect(),
+                });
+            }
+        }
+
+        Ok(points)
+    }
+
+    /// Run a trivial query instead of reading the renders table
+    async fn ping(&self) -> Result<(), RenderStorageError> {
+        self.client.query("SELECT 1").fetch_one::<u8>().await?;
+        Ok(())
+    }
+
+    /// Create the renders table unless it exists, see [`ClickHouseStorage::init_schema`]
+    async fn bootstrap(&self) -> Result<(), RenderStorageError> {
+        self.init_schema().await
+    }
+}
+
+/// ClickHouse expression truncating the millisecond `timestamp` column to a UTC bucket start
+fn bucket_expression(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Hour => "toStartOfHour(toDateTime(timestamp / 1000, 'UTC'))",
+        Granularity::Day => "toStartOfDay(toDateTime(timestamp / 1000, 'UTC'))",
+        Granularity::Week => "toDateTime(toMonday(toDateTime(timestamp / 1000, 'UTC')), 'UTC')",
+    }
 }
 
 impl From<clickhouse::error::Error> for RenderStorageError {
@@ -5,9 +5,12 @@ pub mod types;
 #[cfg(feature = "clickhouse")]
 pub mod clickhouse;
 
+#[cfg(feature = "redis")]
+pub mod redis;
+
 #[cfg(test)]
 mod tests {
-    use super::{MemoryRenderStorage, RenderRecord, RenderStorage};
+    use super::{Granularity, MemoryRenderStorage, Page, RenderRecord, RenderStorage};
 
     #[tokio::test]
     async fn test_memory_render_storage_basic_operations() {
@@ -43,6 +46,81 @@ mod tests {
         assert_eq!(recent[0].render_id, render_id);
     }
 
+    #[tokio::test]
+    async fn test_memory_render_storage_pages_by_timestamp() {
+        let storage = MemoryRenderStorage::new();
+        let start = time::OffsetDateTime::now_utc();
+        for i in 0..25 {
+            let mut record = RenderRecord::success(
+                "invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                format!("sha256:data{}", i),
+                "sha256:pdf789".to_string(),
+                1000,
+                1024,
+            );
+            record.timestamp = start - time::Duration::seconds(i);
+            storage.store_render(record).await.unwrap();
+        }
+
+        let first = storage.list_renders(Page::new(20)).await.unwrap();
+        assert_eq!(first.len(), 20);
+        let second = storage
+            .list_renders(Page::new(20).after(first.last().unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 5);
+
+        // Newest first, every record exactly once
+        let data_hashes: Vec<String> = first
+            .iter()
+            .chain(&second)
+            .map(|r| r.data_hash.clone())
+            .collect();
+        let expected: Vec<String> = (0..25).map(|i| format!("sha256:data{}", i)).collect();
+        assert_eq!(data_hashes, expected);
+    }
+
+    #[tokio::test]
+    async fn test_memory_render_storage_pages_within_one_timestamp() {
+        let storage = MemoryRenderStorage::new();
+        let timestamp = time::OffsetDateTime::now_utc();
+        for i in 0..7 {
+            let mut record = RenderRecord::success(
+                "invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                format!("sha256:data{}", i),
+                "sha256:pdf789".to_string(),
+                1000,
+                1024,
+            );
+            record.timestamp = timestamp;
+            storage.store_render(record).await.unwrap();
+        }
+
+        // Page boundaries fall inside the shared timestamp
+        let mut seen = Vec::new();
+        let mut page = Page::new(3);
+        loop {
+            let records = storage.list_renders(page.clone()).await.unwrap();
+            let Some(last) = records.last() else {
+                break;
+            };
+            page = Page::new(3).after(last);
+            seen.extend(records.iter().map(|r| r.render_id.clone()));
+        }
+
+        let mut expected: Vec<String> = seen.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.dedup();
+        assert_eq!(seen.len(), 7);
+        assert_eq!(seen, expected);
+    }
+
     #[tokio::test]
     async fn test_memory_render_storage_template_filtering() {
         let storage = MemoryRenderStorage::new();
@@ -95,6 +173,52 @@ mod tests {
         assert_eq!(letterhead_renders[0].template_name, "letterhead");
     }
 
+    #[tokio::test]
+    async fn test_memory_render_storage_find_by_inputs() {
+        let storage = MemoryRenderStorage::new();
+        let record = |data_hash: &str| {
+            RenderRecord::success(
+                "invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                data_hash.to_string(),
+                "sha256:pdf789".to_string(),
+                1000,
+                1024,
+            )
+        };
+
+        let older = record("sha256:data456");
+        let mut newer = record("sha256:data456");
+        newer.timestamp = older.timestamp + time::Duration::seconds(1);
+        let newer_id = newer.render_id.clone();
+        storage.store_render(older).await.unwrap();
+        storage.store_render(newer).await.unwrap();
+        storage.store_render(record("sha256:other")).await.unwrap();
+        storage
+            .store_render(RenderRecord::failure(
+                "invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                "sha256:failing".to_string(),
+                "Compilation failed".to_string(),
+                10,
+            ))
+            .await
+            .unwrap();
+
+        let found = storage
+            .find_by_inputs("invoice:latest", "sha256:data456")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.render_id, newer_id);
+        assert!(storage.find_by_inputs("invoice:v2", "sha256:data456").await.unwrap().is_none());
+        assert!(storage.find_by_inputs("invoice:latest", "sha256:failing").await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_render_record_constructors() {
         let success_record = RenderRecord::success(
@@ -130,6 +254,152 @@ mod tests {
         assert_eq!(error_record.pdf_size_bytes, 0);
         assert!(error_record.pdf_hash.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_memory_render_storage_granularity() {
+        use time::{Duration, OffsetDateTime};
+
+        let storage = MemoryRenderStorage::new();
+        // Yesterday 01:00 UTC, so the renders below straddle a day boundary
+        let base = Granularity::Day.bucket_start(OffsetDateTime::now_utc()) - Duration::hours(23);
+
+        // Three renders in three distinct hours, spread over two days
+        for offset in [0, 1, 3] {
+            let mut record = RenderRecord::success(
+                "invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                "sha256:data456".to_string(),
+                "sha256:pdf789".to_string(),
+                1000,
+                1024,
+            );
+            record.timestamp = base - Duration::hours(offset);
+            storage.store_render(record).await.unwrap();
+        }
+
+        let hourly = storage.render_volume_over_time(3, Granularity::Hour).await.unwrap();
+        assert_eq!(hourly.len(), 3);
+        assert!(hourly.iter().all(|p| p.renders == 1));
+        assert!(hourly.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        let daily = storage.render_volume_over_time(3, Granularity::Day).await.unwrap();
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].renders, 1);
+        assert_eq!(daily[1].renders, 2);
+        assert!(daily.iter().all(|p| p.timestamp.time() == time::Time::MIDNIGHT));
+
+        let durations = storage.average_duration_over_time(3, Granularity::Hour).await.unwrap();
+        assert_eq!(durations.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_memory_render_storage_percentiles() {
+        let storage = MemoryRenderStorage::new();
+
+        // Durations 100, 200, ..., 1000 ms in the same hour, plus a failure
+        // that must not count
+        for i in 1..=10 {
+            let record = RenderRecord::success(
+                "invoice:latest".to_string(),
+                "invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                "sha256:data456".to_string(),
+                "sha256:pdf789".to_string(),
+                i * 100,
+                1024,
+            );
+            storage.store_render(record).await.unwrap();
+        }
+        let failure = RenderRecord::failure(
+            "invoice:latest".to_string(),
+            "invoice".to_string(),
+            "latest".to_string(),
+            "sha256:manifest123".to_string(),
+            "sha256:data456".to_string(),
+            "Timed out".to_string(),
+            60_000,
+        );
+        storage.store_render(failure).await.unwrap();
+
+        let points = storage
+            .percentile_duration_over_time(1, Granularity::Day, &[0.5, 0.9, 0.99])
+            .await
+            .unwrap();
+        assert_eq!(points.len(), 1);
+        let durations: Vec<f64> = points[0].percentiles.iter().map(|p| p.duration_ms).collect();
+        assert_eq!(durations, vec![500.0, 900.0, 1000.0]);
+        assert_eq!(points[0].percentiles[1].percentile, 0.9);
+
+        assert!(storage
+            .percentile_duration_over_time(1, Granularity::Day, &[90.0])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_memory_render_storage_renders_per_template_over_time() {
+        use time::{Duration, OffsetDateTime};
+
+        let storage = MemoryRenderStorage::new();
+        // Noon UTC today, so the offsets below land on distinct days
+        let today = Granularity::Day.bucket_start(OffsetDateTime::now_utc());
+        let noon = today + Duration::hours(12);
+
+        // (template, days ago); the last one is outside the queried window
+        let renders = [
+            ("invoice", 0),
+            ("invoice", 0),
+            ("letter", 0),
+            ("invoice", 1),
+            ("letter", 2),
+            ("letter", 2),
+            ("letter", 2),
+            ("invoice", 10),
+        ];
+        for (template, days_ago) in renders {
+            let mut record = RenderRecord::success(
+                format!("{}:latest", template),
+                template.to_string(),
+                "latest".to_string(),
+                "sha256:manifest123".to_string(),
+                "sha256:data456".to_string(),
+                "sha256:pdf789".to_string(),
+                1000,
+                1024,
+            );
+            record.timestamp = noon - Duration::days(days_ago);
+            storage.store_render(record).await.unwrap();
+        }
+
+        let points = storage.renders_per_template_over_time(3).await.unwrap();
+        let counts: Vec<(&str, OffsetDateTime, u64)> = points
+            .iter()
+            .map(|p| (p.template_name.as_str(), p.date, p.renders))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("letter", today - Duration::days(2), 3),
+                ("invoice", today - Duration::days(1), 1),
+                ("invoice", today, 2),
+                ("letter", today, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_granularity_bucket_start() {
+        use time::macros::datetime;
+
+        // Wednesday afternoon
+        let ts = datetime!(2024-05-15 14:37:12 UTC);
+        assert_eq!(Granularity::Hour.bucket_start(ts), datetime!(2024-05-15 14:00 UTC));
+        assert_eq!(Granularity::Day.bucket_start(ts), datetime!(2024-05-15 00:00 UTC));
+        assert_eq!(Granularity::Week.bucket_start(ts), datetime!(2024-05-13 00:00 UTC));
+    }
 }
 
 use async_trait::async_trait;
@@ -146,6 +416,13 @@ pub trait RenderStorage: Send + Sync {
     
     /// List recent render records with optional limit
     async fn list_recent_renders(&self, limit: u32) -> Result<Vec<RenderRecord>, RenderStorageError>;
+
+    /// List one page of render records, newest first
+    ///
+    /// Returns up to `page.limit` records past the page's cursor, ordered by
+    /// timestamp and render ID. Pass the last record to [`Page::after`] to
+    /// fetch the next page.
+    async fn list_renders(&self, page: Page) -> Result<Vec<RenderRecord>, RenderStorageError>;
     
     /// List renders for a specific template with optional limit
     async fn list_template_renders(
@@ -153,18 +430,65 @@ pub trait RenderStorage: Send + Sync {
         template_name: &str,
         limit: u32,
     ) -> Result<Vec<RenderRecord>, RenderStorageError>;
+
+    /// Find the newest successful render of `template_ref` with input data `data_hash`
+    async fn find_by_inputs(
+        &self,
+        template_ref: &str,
+        data_hash: &str,
+    ) -> Result<Option<RenderRecord>, RenderStorageError>;
     
-    /// Get render volume over time for analytics
-    async fn render_volume_over_time(&self, days: u32) -> Result<Vec<VolumePoint>, RenderStorageError>;
+    /// Get render volume over time for analytics, bucketed by `granularity`
+    async fn render_volume_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+    ) -> Result<Vec<VolumePoint>, RenderStorageError>;
     
     /// Get total renders per template for analytics
     async fn total_renders_per_template(&self) -> Result<Vec<TemplateStats>, RenderStorageError>;
+
+    /// Get renders per template and UTC day for analytics
+    ///
+    /// Points are ordered by date, then template name. Days without renders
+    /// of a template have no point.
+    async fn renders_per_template_over_time(
+        &self,
+        days: u32,
+    ) -> Result<Vec<TemplateVolumePoint>, RenderStorageError>;
     
-    /// Get average render duration over time for analytics
+    /// Get average render duration over time for analytics, bucketed by `granularity`
     async fn average_duration_over_time(
         &self,
         days: u32,
+        granularity: Granularity,
     ) -> Result<Vec<DurationPoint>, RenderStorageError>;
+
+    /// Get render duration percentiles over time for analytics, bucketed by `granularity`
+    ///
+    /// `percentiles` are fractions in `0.0..=1.0`, e.g. `[0.5, 0.9, 0.99]`.
+    /// Only successful renders are included, as for the average duration.
+    async fn percentile_duration_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+        percentiles: &[f64],
+    ) -> Result<Vec<PercentilePoint>, RenderStorageError>;
+
+    /// Check that the backend is reachable
+    ///
+    /// The default implementation lists the most recent render.
+    async fn ping(&self) -> Result<(), RenderStorageError> {
+        self.list_recent_renders(1).await.map(|_| ())
+    }
+
+    /// Provision the backend, e.g. create tables, if not done already
+    ///
+    /// Must be idempotent, as it runs on every startup. The default
+    /// implementation does nothing, for backends that need no setup.
+    async fn bootstrap(&self) -> Result<(), RenderStorageError> {
+        Ok(())
+    }
 }
 
 /// In-memory render storage implementation for testing
@@ -195,9 +519,22 @@ impl RenderStorage for MemoryRenderStorage {
     async fn list_recent_renders(&self, limit: u32) -> Result<Vec<RenderRecord>, RenderStorageError> {
         let records = self.records.read().await;
         let mut sorted_records = records.clone();
-        sorted_records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        sorted_records.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
         Ok(sorted_records.into_iter().take(limit as usize).collect())
     }
+
+    async fn list_renders(&self, page: Page) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        let records = self.records.read().await;
+        let mut sorted_records: Vec<RenderRecord> = records
+            .iter()
+            .filter(|r| page.includes(r.timestamp, &r.render_id))
+            .cloned()
+            .collect();
+        sorted_records.sort_by(|a, b| {
+            (b.timestamp, &b.render_id).cmp(&(a.timestamp, &a.render_id))
+        });
+        Ok(sorted_records.into_iter().take(page.limit as usize).collect())
+    }
     
     async fn list_template_renders(
         &self,
@@ -210,32 +547,49 @@ impl RenderStorage for MemoryRenderStorage {
             .filter(|r| r.template_name == template_name)
             .cloned()
             .collect();
-        filtered_records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        filtered_records.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
         Ok(filtered_records.into_iter().take(limit as usize).collect())
     }
+
+    async fn find_by_inputs(
+        &self,
+        template_ref: &str,
+        data_hash: &str,
+    ) -> Result<Option<RenderRecord>, RenderStorageError> {
+        let records = self.records.read().await;
+        Ok(records
+            .iter()
+            .filter(|r| r.success && r.template_ref == template_ref && r.data_hash == data_hash)
+            .max_by_key(|r| r.timestamp)
+            .cloned())
+    }
     
-    async fn render_volume_over_time(&self, days: u32) -> Result<Vec<VolumePoint>, RenderStorageError> {
+    async fn render_volume_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+    ) -> Result<Vec<VolumePoint>, RenderStorageError> {
         use std::collections::HashMap;
         use time::{Duration, OffsetDateTime};
         
         let records = self.records.read().await;
         let cutoff = OffsetDateTime::now_utc() - Duration::days(days as i64);
         
-        let mut daily_counts: HashMap<time::Date, u64> = HashMap::new();
+        let mut bucket_counts: HashMap<OffsetDateTime, u64> = HashMap::new();
         
         for record in records.iter() {
             if record.timestamp >= cutoff {
-                let date = record.timestamp.date();
-                *daily_counts.entry(date).or_insert(0) += 1;
+                let bucket = granularity.bucket_start(record.timestamp);
+                *bucket_counts.entry(bucket).or_insert(0) += 1;
             }
         }
         
-        let mut result: Vec<VolumePoint> = daily_counts
+        let mut result: Vec<VolumePoint> = bucket_counts
             .into_iter()
-            .map(|(date, renders)| VolumePoint { date, renders })
+            .map(|(timestamp, renders)| VolumePoint { timestamp, renders })
             .collect();
         
-        result.sort_by(|a, b| a.date.cmp(&b.date));
+        result.sort_by_key(|a| a.timestamp);
         Ok(result)
     }
     
@@ -257,13 +611,43 @@ impl RenderStorage for MemoryRenderStorage {
             })
             .collect();
         
-        result.sort_by(|a, b| b.total_renders.cmp(&a.total_renders));
+        result.sort_by_key(|s| std::cmp::Reverse(s.total_renders));
         Ok(result)
     }
+
+    async fn renders_per_template_over_time(
+        &self,
+        days: u32,
+    ) -> Result<Vec<TemplateVolumePoint>, RenderStorageError> {
+        use std::collections::BTreeMap;
+        use time::{Duration, OffsetDateTime};
+
+        let records = self.records.read().await;
+        let cutoff = OffsetDateTime::now_utc() - Duration::days(days as i64);
+
+        let mut day_counts: BTreeMap<(OffsetDateTime, String), u64> = BTreeMap::new();
+
+        for record in records.iter() {
+            if record.timestamp >= cutoff {
+                let day = Granularity::Day.bucket_start(record.timestamp);
+                *day_counts.entry((day, record.template_name.clone())).or_insert(0) += 1;
+            }
+        }
+
+        Ok(day_counts
+            .into_iter()
+            .map(|((date, template_name), renders)| TemplateVolumePoint {
+                template_name,
+                date,
+                renders,
+            })
+            .collect())
+    }
     
     async fn average_duration_over_time(
         &self,
         days: u32,
+        granularity: Granularity,
     ) -> Result<Vec<DurationPoint>, RenderStorageError> {
         use std::collections::HashMap;
         use time::{Duration, OffsetDateTime};
@@ -271,26 +655,67 @@ impl RenderStorage for MemoryRenderStorage {
         let records = self.records.read().await;
         let cutoff = OffsetDateTime::now_utc() - Duration::days(days as i64);
         
-        let mut daily_stats: HashMap<time::Date, (u64, u64)> = HashMap::new(); // (total_duration, count)
+        let mut bucket_stats: HashMap<OffsetDateTime, (u64, u64)> = HashMap::new(); // (total_duration, count)
         
         for record in records.iter() {
             if record.timestamp >= cutoff && record.success {
-                let date = record.timestamp.date();
-                let (total_duration, count) = daily_stats.entry(date).or_insert((0, 0));
+                let bucket = granularity.bucket_start(record.timestamp);
+                let (total_duration, count) = bucket_stats.entry(bucket).or_insert((0, 0));
                 *total_duration += record.duration_ms as u64;
                 *count += 1;
             }
         }
         
-        let mut result: Vec<DurationPoint> = daily_stats
+        let mut result: Vec<DurationPoint> = bucket_stats
             .into_iter()
-            .map(|(date, (total_duration, count))| DurationPoint {
-                date,
+            .map(|(timestamp, (total_duration, count))| DurationPoint {
+                timestamp,
                 avg_duration_ms: total_duration as f64 / count as f64,
             })
             .collect();
         
-        result.sort_by(|a, b| a.date.cmp(&b.date));
+        result.sort_by_key(|a| a.timestamp);
         Ok(result)
     }
+    
+    async fn percentile_duration_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+        percentiles: &[f64],
+    ) -> Result<Vec<PercentilePoint>, RenderStorageError> {
+        use std::collections::BTreeMap;
+        use time::{Duration, OffsetDateTime};
+        
+        validate_percentiles(percentiles)?;
+        
+        let records = self.records.read().await;
+        let cutoff = OffsetDateTime::now_utc() - Duration::days(days as i64);
+        
+        let mut bucket_durations: BTreeMap<OffsetDateTime, Vec<u32>> = BTreeMap::new();
+        
+        for record in records.iter() {
+            if record.timestamp >= cutoff && record.success {
+                let bucket = granularity.bucket_start(record.timestamp);
+                bucket_durations.entry(bucket).or_default().push(record.duration_ms);
+            }
+        }
+        
+        Ok(bucket_durations
+            .into_iter()
+            .map(|(timestamp, mut durations)| {
+                durations.sort_unstable();
+                PercentilePoint {
+                    timestamp,
+                    percentiles: percentiles
+                        .iter()
+                        .map(|&percentile| PercentileValue {
+                            percentile,
+                            duration_ms: nearest_rank(&durations, percentile),
+                        })
+                        .collect(),
+                }
+            })
+            .collect())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,661 @@
+//! Redis implementation of RenderStorage trait
+//!
+//! Each render record is stored as a hash at `{prefix}:render:{render_id}`.
+//! Sorted sets scored by timestamp (Unix milliseconds) index the records:
+//! `{prefix}:renders` holds every render and `{prefix}:template:{name}` the
+//! renders of one template. Per-template totals are kept in the sorted set
+//! `{prefix}:template_counts`. Time series are computed by scanning the
+//! timestamp index within the requested window. The ID of the newest
+//! successful render per template reference and data hash is kept at
+//! `{prefix}:inputs:{template_ref}:{data_hash}`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use redis::{AsyncCommands, aio::ConnectionManager};
+use time::{Duration, OffsetDateTime};
+
+use crate::config;
+
+use super::{
+    DurationPoint, Granularity, Page, PercentilePoint, PercentileValue, RenderRecord,
+    RenderStorage, RenderStorageError, TemplateStats, TemplateVolumePoint, VolumePoint,
+    nearest_rank, validate_percentiles,
+};
+
+/// Redis storage implementation for render records
+#[derive(Clone)]
+pub struct RedisRenderStorage {
+    connection: ConnectionManager,
+    key_prefix: String,
+}
+
+impl std::fmt::Debug for RedisRenderStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisRenderStorage")
+            .field("connection", &"<Redis ConnectionManager>")
+            .field("key_prefix", &self.key_prefix)
+            .finish()
+    }
+}
+
+impl RedisRenderStorage {
+    /// Connect to the Redis server at `url`, storing keys under `key_prefix`
+    pub async fn connect(url: &str, key_prefix: &str) -> Result<Self, RenderStorageError> {
+        let client =
+            redis::Client::open(url).map_err(|e| RenderStorageError::Connection(e.to_string()))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| RenderStorageError::Connection(e.to_string()))?;
+
+        Ok(Self {
+            connection,
+            key_prefix: key_prefix.to_string(),
+        })
+    }
+
+    /// Create a new Redis storage instance from environment variables
+    ///
+    /// Reads REDIS_URL (default `redis://localhost:6379`) and
+    /// REDIS_KEY_PREFIX (default `papermake`), each of which can instead be
+    /// read from a file named by the same variable with a `_FILE` suffix.
+    pub async fn from_env() -> Result<Self, RenderStorageError> {
+        let env_or = |name: &str, default: &str| {
+            config::env_var(name)
+                .map(|value| value.unwrap_or_else(|| default.to_string()))
+                .map_err(|e| RenderStorageError::Connection(e.to_string()))
+        };
+
+        let url = env_or("REDIS_URL", "redis://localhost:6379")?;
+        let key_prefix = env_or("REDIS_KEY_PREFIX", "papermake")?;
+
+        Self::connect(&url, &key_prefix).await
+    }
+
+    fn record_key(&self, render_id: &str) -> String {
+        format!("{}:render:{}", self.key_prefix, render_id)
+    }
+
+    fn renders_key(&self) -> String {
+        format!("{}:renders", self.key_prefix)
+    }
+
+    fn template_key(&self, template_name: &str) -> String {
+        format!("{}:template:{}", self.key_prefix, template_name)
+    }
+
+    fn template_counts_key(&self) -> String {
+        format!("{}:template_counts", self.key_prefix)
+    }
+
+    fn inputs_key(&self, template_ref: &str, data_hash: &str) -> String {
+        format!("{}:inputs:{}:{}", self.key_prefix, template_ref, data_hash)
+    }
+
+    /// Load the newest `limit` records of the timestamp index at `index_key`
+    async fn list_newest(
+        &self,
+        index_key: &str,
+        limit: u32,
+    ) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut connection = self.connection.clone();
+        let render_ids: Vec<String> = connection
+            .zrevrange(index_key, 0, limit as isize - 1)
+            .await
+            .map_err(query_error)?;
+
+        self.load_records(&render_ids).await
+    }
+
+    /// Load every record with a timestamp in the last `days` days
+    async fn list_since(&self, days: u32) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        let cutoff = OffsetDateTime::now_utc() - Duration::days(days as i64);
+
+        let mut connection = self.connection.clone();
+        let render_ids: Vec<String> = connection
+            .zrangebyscore(self.renders_key(), unix_millis(cutoff), "+inf")
+            .await
+            .map_err(query_error)?;
+
+        self.load_records(&render_ids).await
+    }
+
+    /// Fetch the hashes of `render_ids` in one round trip, skipping missing ones
+    async fn load_records(
+        &self,
+        render_ids: &[String],
+    ) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        if render_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipeline = redis::pipe();
+        for render_id in render_ids {
+            pipeline.hgetall(self.record_key(render_id));
+        }
+
+        let mut connection = self.connection.clone();
+        let hashes: Vec<HashMap<String, String>> = pipeline
+            .query_async(&mut connection)
+            .await
+            .map_err(query_error)?;
+
+        hashes
+            .into_iter()
+            .filter(|fields| !fields.is_empty())
+            .map(record_from_fields)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl RenderStorage for RedisRenderStorage {
+    async fn store_render(&self, record: RenderRecord) -> Result<(), RenderStorageError> {
+        let score = unix_millis(record.timestamp);
+
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .hset_multiple(self.record_key(&record.render_id), &record_fields(&record))
+            .ignore()
+            .zadd(self.renders_key(), &record.render_id, score)
+            .ignore()
+            .zadd(
+                self.template_key(&record.template_name),
+                &record.render_id,
+                score,
+            )
+            .ignore()
+            .zincr(self.template_counts_key(), &record.template_name, 1)
+            .ignore();
+        if record.success {
+            pipeline
+                .set(
+                    self.inputs_key(&record.template_ref, &record.data_hash),
+                    &record.render_id,
+                )
+                .ignore();
+        }
+
+        let mut connection = self.connection.clone();
+        pipeline
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(query_error)
+    }
+
+    async fn get_render(
+        &self,
+        render_id: &str,
+    ) -> Result<Option<RenderRecord>, RenderStorageError> {
+        let mut connection = self.connection.clone();
+        let fields: HashMap<String, String> = connection
+            .hgetall(self.record_key(render_id))
+            .await
+            .map_err(query_error)?;
+
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        record_from_fields(fields).map(Some)
+    }
+
+    async fn list_recent_renders(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        self.list_newest(&self.renders_key(), limit).await
+    }
+
+    async fn list_renders(&self, page: Page) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        if page.limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut connection = self.connection.clone();
+
+        // Renders sharing the cursor's millisecond come first, ordered by
+        // render ID like equal scores in the sorted set
+        let mut render_ids: Vec<String> = Vec::new();
+        if let (Some(before), Some(before_id)) = (page.before, &page.before_id) {
+            let score = unix_millis(before);
+            let same_millisecond: Vec<String> = connection
+                .zrevrangebyscore(self.renders_key(), score, score)
+                .await
+                .map_err(query_error)?;
+            render_ids.extend(
+                same_millisecond
+                    .into_iter()
+                    .filter(|render_id| render_id < before_id)
+                    .take(page.limit as usize),
+            );
+        }
+
+        let remaining = page.limit as usize - render_ids.len();
+        if remaining > 0 {
+            // "(" makes the upper score bound exclusive
+            let max = match page.before {
+                Some(before) => format!("({}", unix_millis(before)),
+                None => "+inf".to_string(),
+            };
+            let older: Vec<String> = connection
+                .zrevrangebyscore_limit(self.renders_key(), max, "-inf", 0, remaining as isize)
+                .await
+                .map_err(query_error)?;
+            render_ids.extend(older);
+        }
+
+        self.load_records(&render_ids).await
+    }
+
+    async fn list_template_renders(
+        &self,
+        template_name: &str,
+        limit: u32,
+    ) -> Result<Vec<RenderRecord>, RenderStorageError> {
+        self.list_newest(&self.template_key(template_name), limit)
+            .await
+    }
+
+    async fn find_by_inputs(
+        &self,
+        template_ref: &str,
+        data_hash: &str,
+    ) -> Result<Option<RenderRecord>, RenderStorageError> {
+        let mut connection = self.connection.clone();
+        let render_id: Option<String> = connection
+            .get(self.inputs_key(template_ref, data_hash))
+            .await
+            .map_err(query_error)?;
+
+        match render_id {
+            Some(render_id) => self.get_render(&render_id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn render_volume_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+    ) -> Result<Vec<VolumePoint>, RenderStorageError> {
+        let mut bucket_counts: BTreeMap<OffsetDateTime, u64> = BTreeMap::new();
+        for record in self.list_since(days).await? {
+            *bucket_counts
+                .entry(granularity.bucket_start(record.timestamp))
+                .or_insert(0) += 1;
+        }
+
+        Ok(bucket_counts
+            .into_iter()
+            .map(|(timestamp, renders)| VolumePoint { timestamp, renders })
+            .collect())
+    }
+
+    async fn total_renders_per_template(&self) -> Result<Vec<TemplateStats>, RenderStorageError> {
+        let mut connection = self.connection.clone();
+        let counts: Vec<(String, f64)> = connection
+            .zrevrange_withscores(self.template_counts_key(), 0, -1)
+            .await
+            .map_err(query_error)?;
+
+        Ok(counts
+            .into_iter()
+            .map(|(template_name, total_renders)| TemplateStats {
+                template_name,
+                total_renders: total_renders as u64,
+            })
+            .collect())
+    }
+
+    async fn renders_per_template_over_time(
+        &self,
+        days: u32,
+    ) -> Result<Vec<TemplateVolumePoint>, RenderStorageError> {
+        let mut day_counts: BTreeMap<(OffsetDateTime, String), u64> = BTreeMap::new();
+        for record in self.list_since(days).await? {
+            *day_counts
+                .entry((
+                    Granularity::Day.bucket_start(record.timestamp),
+                    record.template_name,
+                ))
+                .or_insert(0) += 1;
+        }
+
+        Ok(day_counts
+            .into_iter()
+            .map(|((date, template_name), renders)| TemplateVolumePoint {
+                template_name,
+                date,
+                renders,
+            })
+            .collect())
+    }
+
+    async fn average_duration_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+    ) -> Result<Vec<DurationPoint>, RenderStorageError> {
+        let mut bucket_stats: BTreeMap<OffsetDateTime, (u64, u64)> = BTreeMap::new(); // (total_duration, count)
+        for record in self.list_since(days).await? {
+            if record.success {
+                let (total_duration, count) = bucket_stats
+                    .entry(granularity.bucket_start(record.timestamp))
+                    .or_insert((0, 0));
+                *total_duration += record.duration_ms as u64;
+                *count += 1;
+            }
+        }
+
+        Ok(bucket_stats
+            .into_iter()
+            .map(|(timestamp, (total_duration, count))| DurationPoint {
+                timestamp,
+                avg_duration_ms: total_duration as f64 / count as f64,
+            })
+            .collect())
+    }
+
+    async fn percentile_duration_over_time(
+        &self,
+        days: u32,
+        granularity: Granularity,
+        percentiles: &[f64],
+    ) -> Result<Vec<PercentilePoint>, RenderStorageError> {
+        validate_percentiles(percentiles)?;
+
+        let mut bucket_durations: BTreeMap<OffsetDateTime, Vec<u32>> = BTreeMap::new();
+        for record in self.list_since(days).await? {
+            if record.success {
+                bucket_durations
+                    .entry(granularity.bucket_start(record.timestamp))
+                    .or_default()
+                    .push(record.duration_ms);
+            }
+        }
+
+        Ok(bucket_durations
+            .into_iter()
+            .map(|(timestamp, mut durations)| {
+                durations.sort_unstable();
+                PercentilePoint {
+                    timestamp,
+                    percentiles: percentiles
+                        .iter()
+                        .map(|&percentile| PercentileValue {
+                            percentile,
+                            duration_ms: nearest_rank(&durations, percentile),
+                        })
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+}
+
+fn query_error(error: redis::RedisError) -> RenderStorageError {
+    RenderStorageError::Query(error.to_string())
+}
+
+fn unix_millis(timestamp: OffsetDateTime) -> i64 {
+    (timestamp.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// Hash fields for a record; the timestamp is stored in Unix nanoseconds
+fn record_fields(record: &RenderRecord) -> Vec<(&'static str, String)> {
+    vec![
+        ("render_id", record.render_id.clone()),
+        (
+            "timestamp",
+            record.timestamp.unix_timestamp_nanos().to_string(),
+        ),
+        ("template_ref", record.template_ref.clone()),
+        ("template_name", record.template_name.clone()),
+        ("template_tag", record.template_tag.clone()),
+        ("manifest_hash", record.manifest_hash.clone()),
+        ("data_hash", record.data_hash.clone()),
+        ("pdf_hash", record.pdf_hash.clone()),
+        ("success", u8::from(record.success).to_string()),
+        ("duration_ms", record.duration_ms.to_string()),
+        ("pdf_size_bytes", record.pdf_size_bytes.to_string()),
+        ("error", record.error.clone().unwrap_or_default()),
+    ]
+}
+
+fn record_from_fields(
+    mut fields: HashMap<String, String>,
+) -> Result<RenderRecord, RenderStorageError> {
+    let mut take = |name: &str| {
+        fields
+            .remove(name)
+            .ok_or_else(|| RenderStorageError::Query(format!("Render record missing {}", name)))
+    };
+
+    let timestamp =
+        OffsetDateTime::from_unix_timestamp_nanos(parse_field("timestamp", &take("timestamp")?)?)
+            .map_err(|e| RenderStorageError::Query(format!("Invalid timestamp: {}", e)))?;
+    let error = take("error")?;
+
+    Ok(RenderRecord {
+        render_id: take("render_id")?,
+        timestamp,
+        template_ref: take("template_ref")?,
+        template_name: take("template_name")?,
+        template_tag: take("template_tag")?,
+        manifest_hash: take("manifest_hash")?,
+        data_hash: take("data_hash")?,
+        pdf_hash: take("pdf_hash")?,
+        success: take("success")? == "1",
+        duration_ms: parse_field("duration_ms", &take("duration_ms")?)?,
+        pdf_size_bytes: parse_field("pdf_size_bytes", &take("pdf_size_bytes")?)?,
+        error: if error.is_empty() { None } else { Some(error) },
+    })
+}
+
+fn parse_field<T: std::str::FromStr<Err = std::num::ParseIntError>>(
+    name: &str,
+    value: &str,
+) -> Result<T, RenderStorageError> {
+    value
+        .parse()
+        .map_err(|e| RenderStorageError::Query(format!("Invalid {} in render record: {}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Storage under a fresh key prefix on the server at REDIS_URL
+    async fn storage() -> RedisRenderStorage {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".into());
+        let prefix = format!("papermake-test-{}", uuid::Uuid::now_v7());
+        RedisRenderStorage::connect(&url, &prefix).await.unwrap()
+    }
+
+    fn record(template_name: &str, tag: &str, duration_ms: u32) -> RenderRecord {
+        RenderRecord::success(
+            format!("{}:{}", template_name, tag),
+            template_name.to_string(),
+            tag.to_string(),
+            "sha256:manifest123".to_string(),
+            "sha256:data456".to_string(),
+            "sha256:pdf789".to_string(),
+            duration_ms,
+            1024,
+        )
+    }
+
+    #[test]
+    fn test_record_fields_round_trip() {
+        let mut original = record("invoice", "latest", 1000);
+        original.error = Some("Compilation failed".to_string());
+
+        let fields = record_fields(&original)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        let restored = record_from_fields(fields).unwrap();
+
+        assert_eq!(restored.render_id, original.render_id);
+        assert_eq!(restored.timestamp, original.timestamp);
+        assert_eq!(restored.error, original.error);
+        assert_eq!(restored.duration_ms, 1000);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_redis_render_storage_basic_operations() {
+        let storage = storage().await;
+
+        let record = record("invoice", "latest", 1000);
+        let render_id = record.render_id.clone();
+        storage.store_render(record).await.unwrap();
+
+        let retrieved = storage.get_render(&render_id).await.unwrap().unwrap();
+        assert_eq!(retrieved.render_id, render_id);
+        assert_eq!(retrieved.template_name, "invoice");
+        assert!(retrieved.success);
+        assert!(storage.get_render("missing").await.unwrap().is_none());
+
+        let recent = storage.list_recent_renders(10).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].render_id, render_id);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_redis_render_storage_pages_within_one_millisecond() {
+        let storage = storage().await;
+        let timestamp = OffsetDateTime::now_utc();
+        for _ in 0..7 {
+            let mut record = record("invoice", "latest", 1000);
+            record.timestamp = timestamp;
+            storage.store_render(record).await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut page = Page::new(3);
+        loop {
+            let records = storage.list_renders(page.clone()).await.unwrap();
+            let Some(last) = records.last() else {
+                break;
+            };
+            page = Page::new(3).after(last);
+            seen.extend(records.iter().map(|r| r.render_id.clone()));
+        }
+
+        let mut expected = seen.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.dedup();
+        assert_eq!(seen.len(), 7);
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_redis_render_storage_find_by_inputs() {
+        let storage = storage().await;
+
+        let record = record("invoice", "latest", 1000);
+        let render_id = record.render_id.clone();
+        storage.store_render(record).await.unwrap();
+
+        let found = storage
+            .find_by_inputs("invoice:latest", "sha256:data456")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.render_id, render_id);
+        assert!(
+            storage
+                .find_by_inputs("invoice:latest", "sha256:other")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_redis_render_storage_template_filtering() {
+        let storage = storage().await;
+
+        storage
+            .store_render(record("invoice", "latest", 1000))
+            .await
+            .unwrap();
+        storage
+            .store_render(record("letterhead", "v1", 1500))
+            .await
+            .unwrap();
+        storage
+            .store_render(record("invoice", "v2", 800))
+            .await
+            .unwrap();
+
+        let invoice_renders = storage.list_template_renders("invoice", 10).await.unwrap();
+        assert_eq!(invoice_renders.len(), 2);
+
+        let letterhead_renders = storage
+            .list_template_renders("letterhead", 10)
+            .await
+            .unwrap();
+        assert_eq!(letterhead_renders.len(), 1);
+        assert_eq!(letterhead_renders[0].template_name, "letterhead");
+
+        let totals = storage.total_renders_per_template().await.unwrap();
+        assert_eq!(totals[0].template_name, "invoice");
+        assert_eq!(totals[0].total_renders, 2);
+        assert_eq!(totals[1].total_renders, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_redis_render_storage_granularity() {
+        let storage = storage().await;
+        // Yesterday 01:00 UTC, so the renders below straddle a day boundary
+        let base = Granularity::Day.bucket_start(OffsetDateTime::now_utc()) - Duration::hours(23);
+
+        // Three renders in three distinct hours, spread over two days
+        for offset in [0, 1, 3] {
+            let mut record = record("invoice", "latest", 1000);
+            record.timestamp = base - Duration::hours(offset);
+            storage.store_render(record).await.unwrap();
+        }
+
+        let hourly = storage
+            .render_volume_over_time(3, Granularity::Hour)
+            .await
+            .unwrap();
+        assert_eq!(hourly.len(), 3);
+        assert!(hourly.iter().all(|p| p.renders == 1));
+        assert!(hourly.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        let daily = storage
+            .render_volume_over_time(3, Granularity::Day)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].renders, 1);
+        assert_eq!(daily[1].renders, 2);
+
+        let durations = storage
+            .average_duration_over_time(3, Granularity::Hour)
+            .await
+            .unwrap();
+        assert_eq!(durations.len(), 3);
+        assert!(durations.iter().all(|p| p.avg_duration_ms == 1000.0));
+
+        let percentiles = storage
+            .percentile_duration_over_time(3, Granularity::Day, &[0.9])
+            .await
+            .unwrap();
+        assert_eq!(percentiles.len(), 2);
+        assert_eq!(percentiles[1].percentiles[0].duration_ms, 1000.0);
+    }
+}
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
-use time::{Date, OffsetDateTime};
+use time::{Duration, OffsetDateTime, Time};
 use uuid::Uuid;
 
 /// Record of a template render operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RenderRecord {
     /// UUIDv7 for time-sortable, distributed-friendly IDs
     pub render_id: String,
@@ -34,6 +35,7 @@ pub struct RenderRecord {
 
 impl RenderRecord {
     /// Create a new successful render record
+    #[allow(clippy::too_many_arguments)]
     pub fn success(
         template_ref: String,
         template_name: String,
@@ -87,10 +89,92 @@ impl RenderRecord {
     }
 }
 
+/// One page of render history, newest first
+///
+/// Pages are chained by a cursor rather than an offset: the next page starts
+/// after the oldest record of the current one, so renders arriving while a
+/// client pages through history do not shift or repeat records. Records are
+/// ordered by timestamp and then by render ID, which breaks ties between
+/// renders stored in the same millisecond.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// Maximum number of records to return
+    pub limit: u32,
+    /// Only return records older than this, `None` for the newest records
+    pub before: Option<OffsetDateTime>,
+    /// Also return records at exactly `before` whose render ID sorts below this
+    pub before_id: Option<String>,
+}
+
+impl Page {
+    /// First page of at most `limit` records
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            before: None,
+            before_id: None,
+        }
+    }
+
+    /// Only return records older than `before`
+    pub fn with_before(mut self, before: OffsetDateTime) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    /// Continue after `record`, the last record of the previous page
+    pub fn after(mut self, record: &RenderRecord) -> Self {
+        self.before = Some(record.timestamp);
+        self.before_id = Some(record.render_id.clone());
+        self
+    }
+
+    /// Whether a record with `timestamp` and `render_id` belongs past the cursor
+    pub fn includes(&self, timestamp: OffsetDateTime, render_id: &str) -> bool {
+        match (self.before, &self.before_id) {
+            (None, _) => true,
+            (Some(before), None) => timestamp < before,
+            (Some(before), Some(before_id)) => {
+                timestamp < before || (timestamp == before && render_id < before_id.as_str())
+            }
+        }
+    }
+}
+
+/// Time bucket size for analytics series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Hour,
+    #[default]
+    Day,
+    /// Weeks start on Monday (ISO 8601)
+    Week,
+}
+
+impl Granularity {
+    /// Truncate a timestamp to the start of its bucket (in UTC)
+    pub fn bucket_start(&self, timestamp: OffsetDateTime) -> OffsetDateTime {
+        let timestamp = timestamp.to_offset(time::UtcOffset::UTC);
+        match self {
+            Granularity::Hour => {
+                timestamp.replace_time(Time::from_hms(timestamp.hour(), 0, 0).expect("valid hour"))
+            }
+            Granularity::Day => timestamp.replace_time(Time::MIDNIGHT),
+            Granularity::Week => {
+                let days_from_monday = timestamp.weekday().number_days_from_monday();
+                timestamp.replace_time(Time::MIDNIGHT) - Duration::days(days_from_monday as i64)
+            }
+        }
+    }
+}
+
 /// Analytics data point for render volume over time
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VolumePoint {
-    pub date: Date,
+    /// Start of the bucket this point aggregates
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
     pub renders: u64,
 }
 
@@ -101,19 +185,65 @@ pub struct TemplateStats {
     pub total_renders: u64,
 }
 
+/// Analytics data point for one template's renders on one day
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateVolumePoint {
+    pub template_name: String,
+    /// Start of the UTC day this point aggregates
+    #[serde(with = "time::serde::rfc3339")]
+    pub date: OffsetDateTime,
+    pub renders: u64,
+}
+
 /// Analytics data point for average render duration over time
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DurationPoint {
-    pub date: Date,
+    /// Start of the bucket this point aggregates
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
     pub avg_duration_ms: f64,
 }
 
+/// Render duration at one percentile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PercentileValue {
+    /// Requested percentile as a fraction, e.g. `0.9` for P90
+    pub percentile: f64,
+    pub duration_ms: f64,
+}
+
+/// Analytics data point for render duration percentiles over time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PercentilePoint {
+    /// Start of the bucket this point aggregates
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    /// One value per requested percentile, in request order
+    pub percentiles: Vec<PercentileValue>,
+}
+
 /// Query types for analytics
 #[derive(Debug, Clone)]
 pub enum AnalyticsQuery {
-    VolumeOverTime { days: u32 },
+    VolumeOverTime {
+        days: u32,
+        granularity: Granularity,
+    },
     TemplateStats,
-    DurationOverTime { days: u32 },
+    /// Renders per template and UTC day
+    TemplateVolumeOverTime {
+        days: u32,
+    },
+    DurationOverTime {
+        days: u32,
+        granularity: Granularity,
+    },
+    /// Duration percentiles of successful renders, given as fractions in `0.0..=1.0`
+    PercentileOverTime {
+        days: u32,
+        granularity: Granularity,
+        percentiles: Vec<f64>,
+    },
 }
 
 /// Result types for analytics queries
@@ -121,7 +251,31 @@ pub enum AnalyticsQuery {
 pub enum AnalyticsResult {
     Volume(Vec<VolumePoint>),
     Templates(Vec<TemplateStats>),
+    TemplateVolume(Vec<TemplateVolumePoint>),
     Duration(Vec<DurationPoint>),
+    Percentiles(Vec<PercentilePoint>),
+}
+
+/// Reject an empty list and percentiles outside `0.0..=1.0`
+pub(crate) fn validate_percentiles(percentiles: &[f64]) -> Result<(), RenderStorageError> {
+    if percentiles.is_empty() {
+        return Err(RenderStorageError::InvalidQuery(
+            "At least one percentile is required".to_string(),
+        ));
+    }
+    match percentiles.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+        Some(p) => Err(RenderStorageError::InvalidQuery(format!(
+            "Percentile {} is outside 0.0..=1.0",
+            p
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Nearest-rank percentile of ascending, non-empty `sorted_durations`
+pub(crate) fn nearest_rank(sorted_durations: &[u32], percentile: f64) -> f64 {
+    let rank = (percentile * sorted_durations.len() as f64).ceil() as usize;
+    sorted_durations[rank.clamp(1, sorted_durations.len()) - 1] as f64
 }
 
 /// Error types for render storage operations
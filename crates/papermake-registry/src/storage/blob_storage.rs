@@ -7,6 +7,8 @@ use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Mutex;
 
+use crate::address::HashedContent;
+
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
     #[error("Key not found: {0}")]
@@ -22,6 +24,15 @@ pub enum StorageError {
     InvalidKey(String),
 }
 
+/// Metadata of a stored value, see [`BlobStorage::head`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobMeta {
+    /// Size of the value in bytes
+    pub size: u64,
+    /// Entity tag reported by the backend, if it has one
+    pub etag: Option<String>,
+}
+
 /// Abstraction for blob storage backends
 #[async_trait]
 pub trait BlobStorage: Send + Sync {
@@ -32,13 +43,76 @@ pub trait BlobStorage: Send + Sync {
     async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
 
     /// Check if key exists
-    async fn exists(&self, key: &str) -> Result<bool, StorageError>;
+    ///
+    /// The default implementation fetches the value with [`BlobStorage::get`];
+    /// backends with a cheaper existence check should override it.
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        match self.get(key).await {
+            Ok(_) => Ok(true),
+            Err(StorageError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Metadata of the value at `key`, without downloading it
+    ///
+    /// Fails with [`StorageError::NotFound`] if the key does not exist. The
+    /// default implementation fetches the value with [`BlobStorage::get`];
+    /// backends that can stat a key should override it.
+    async fn head(&self, key: &str) -> Result<BlobMeta, StorageError> {
+        let data = self.get(key).await?;
+        Ok(BlobMeta {
+            size: data.len() as u64,
+            etag: None,
+        })
+    }
 
     /// Delete data by key
     async fn delete(&self, key: &str) -> Result<(), StorageError>;
 
     /// List all keys with the given prefix
     async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError>;
+
+    /// Store content produced by [`ContentAddress::hash_reader`](crate::address::ContentAddress::hash_reader)
+    ///
+    /// The default implementation loads spilled content back into memory
+    /// and calls [`BlobStorage::put`]; backends that can upload from a file
+    /// should override it to keep large blobs out of memory.
+    async fn put_stream(&self, key: &str, content: HashedContent) -> Result<(), StorageError> {
+        let data = content.into_bytes().map_err(|e| {
+            StorageError::Backend(format!("Failed to read content for '{}': {}", key, e))
+        })?;
+        self.put(key, data).await
+    }
+
+    /// Check that the backend is reachable
+    ///
+    /// The default implementation checks for a key that is never written,
+    /// which is one cheap round trip for remote backends.
+    async fn ping(&self) -> Result<(), StorageError> {
+        self.exists("health/ping").await.map(|_| ())
+    }
+
+    /// Provision the backend, e.g. create the bucket, if not done already
+    ///
+    /// Must be idempotent, as it runs on every startup. The default
+    /// implementation does nothing, for backends that need no setup.
+    async fn bootstrap(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+
+    /// Time-limited URL from which a client can download `key` directly
+    ///
+    /// Returns `None` for backends that cannot hand out such URLs, in which
+    /// case the content has to be served through the registry instead.
+    async fn presigned_url(
+        &self,
+        key: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>, StorageError> {
+        let _ = (key, ttl);
+        Ok(None)
+    }
 }
 
 /// In-memory storage implementation for testing
@@ -108,6 +182,21 @@ impl BlobStorage for MemoryStorage {
         Ok(storage.contains_key(key))
     }
 
+    async fn head(&self, key: &str) -> Result<BlobMeta, StorageError> {
+        let storage = self
+            .data
+            .lock()
+            .map_err(|_| StorageError::Backend("Lock poisoned".into()))?;
+
+        storage
+            .get(key)
+            .map(|data| BlobMeta {
+                size: data.len() as u64,
+                etag: None,
+            })
+            .ok_or_else(|| StorageError::NotFound(key.to_string()))
+    }
+
     async fn delete(&self, key: &str) -> Result<(), StorageError> {
         let mut storage = self
             .data
@@ -154,6 +243,13 @@ mod tests {
         assert!(storage.exists(key).await.unwrap());
         assert!(!storage.exists("nonexistent").await.unwrap());
 
+        // Test head
+        assert_eq!(storage.head(key).await.unwrap().size, data.len() as u64);
+        assert!(matches!(
+            storage.head("nonexistent").await,
+            Err(StorageError::NotFound(_))
+        ));
+
         // Test delete
         storage.delete(key).await.unwrap();
         assert!(!storage.exists(key).await.unwrap());
@@ -0,0 +1,263 @@
+//! Blob storage wrapper compressing values with zstd
+//!
+//! Typst sources, manifests and JSON render data compress very well. Wrap
+//! any [`BlobStorage`] in a [`CompressedStorage`] to store them zstd
+//! compressed: values are compressed on `put` and decompressed on `get`, so
+//! the registry never sees the difference. Keys are unaffected, as the
+//! registry hashes content before it reaches the storage.
+//!
+//! Compressed values start with a short header holding a magic number and
+//! the uncompressed size. Values without it are returned as stored, so a
+//! store can be wrapped after it already holds uncompressed values.
+
+use async_trait::async_trait;
+
+use crate::storage::blob_storage::{BlobMeta, BlobStorage, StorageError};
+
+/// Marks a value written compressed by [`CompressedStorage`]
+const MAGIC: &[u8; 4] = b"PMZ\x01";
+
+/// Magic number followed by the uncompressed size as little-endian `u64`
+const HEADER_LEN: usize = MAGIC.len() + 8;
+
+/// zstd's default level, a good trade-off for small text blobs
+pub const DEFAULT_LEVEL: i32 = 3;
+
+/// Storage compressing values before handing them to an inner store
+///
+/// Values that do not get smaller, e.g. PDFs with already compressed
+/// streams, are stored as they are. Large values passed to
+/// [`BlobStorage::put_stream`] are loaded into memory to be compressed.
+/// [`BlobStorage::presigned_url`] returns `None`, since a client
+/// downloading directly from the inner store would get compressed bytes.
+#[derive(Debug)]
+pub struct CompressedStorage<S: BlobStorage> {
+    inner: S,
+    level: i32,
+}
+
+impl<S: BlobStorage> CompressedStorage<S> {
+    /// Wrap `inner`, compressing at [`DEFAULT_LEVEL`]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            level: DEFAULT_LEVEL,
+        }
+    }
+
+    /// Compress at zstd `level`, from 1 (fastest) to 22 (smallest)
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The wrapped storage
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The value to store for `data`
+    fn encode(&self, key: &str, data: Vec<u8>) -> Result<Vec<u8>, StorageError> {
+        let compressed = zstd::bulk::compress(&data, self.level)
+            .map_err(|e| StorageError::Backend(format!("Failed to compress '{}': {}", key, e)))?;
+
+        // Uncompressed values must not be mistaken for compressed ones
+        if HEADER_LEN + compressed.len() >= data.len() && !data.starts_with(MAGIC) {
+            return Ok(data);
+        }
+
+        let mut stored = Vec::with_capacity(HEADER_LEN + compressed.len());
+        stored.extend_from_slice(MAGIC);
+        stored.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        stored.extend_from_slice(&compressed);
+        Ok(stored)
+    }
+
+    /// The original value of `stored`
+    fn decode(key: &str, stored: Vec<u8>) -> Result<Vec<u8>, StorageError> {
+        let Some(size) = uncompressed_size(&stored) else {
+            return Ok(stored);
+        };
+        zstd::bulk::decompress(&stored[HEADER_LEN..], size as usize)
+            .map_err(|e| StorageError::Backend(format!("Failed to decompress '{}': {}", key, e)))
+    }
+}
+
+/// Uncompressed size recorded in the header of `stored`, if it is compressed
+fn uncompressed_size(stored: &[u8]) -> Option<u64> {
+    let size = stored.strip_prefix(MAGIC)?.get(..8)?;
+    Some(u64::from_le_bytes(size.try_into().ok()?))
+}
+
+#[async_trait]
+impl<S: BlobStorage> BlobStorage for CompressedStorage<S> {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let stored = self.encode(key, data)?;
+        self.inner.put(key, stored).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let stored = self.inner.get(key).await?;
+        Self::decode(key, stored)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        self.inner.exists(key).await
+    }
+
+    /// Metadata with the uncompressed size
+    ///
+    /// The stored value is fetched to read its header, as the inner store
+    /// only knows the compressed size.
+    async fn head(&self, key: &str) -> Result<BlobMeta, StorageError> {
+        let stored = self.inner.get(key).await?;
+        Ok(BlobMeta {
+            size: uncompressed_size(&stored).unwrap_or(stored.len() as u64),
+            etag: None,
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.inner.delete(key).await
+    }
+
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.inner.list_keys(prefix).await
+    }
+
+    async fn ping(&self) -> Result<(), StorageError> {
+        self.inner.ping().await
+    }
+
+    async fn bootstrap(&self) -> Result<(), StorageError> {
+        self.inner.bootstrap().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Registry,
+        address::ContentAddress,
+        bundle::{TemplateBundle, TemplateMetadata},
+        storage::blob_storage::MemoryStorage,
+    };
+    use std::sync::Arc;
+
+    /// Memory storage shared with the test, to inspect what was stored
+    #[derive(Clone, Default)]
+    struct SharedStorage(Arc<MemoryStorage>);
+
+    #[async_trait]
+    impl BlobStorage for SharedStorage {
+        async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+            self.0.put(key, data).await
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            self.0.get(key).await
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            self.0.delete(key).await
+        }
+
+        async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+            self.0.list_keys(prefix).await
+        }
+    }
+
+    fn repetitive_template() -> Vec<u8> {
+        (0..200)
+            .map(|i| {
+                format!(
+                    "#table(columns: 3, [Item {}], [#data.qty], [#data.price])\n",
+                    i
+                )
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_compresses_repetitive_content() {
+        let inner = SharedStorage::default();
+        let storage = CompressedStorage::new(inner.clone());
+        let template = repetitive_template();
+
+        storage.put("blobs/main", template.clone()).await.unwrap();
+
+        let stored = inner.get("blobs/main").await.unwrap();
+        assert!(stored.starts_with(MAGIC));
+        assert!(
+            stored.len() < template.len() / 4,
+            "{} bytes stored for {}",
+            stored.len(),
+            template.len()
+        );
+        assert_eq!(storage.get("blobs/main").await.unwrap(), template);
+        assert_eq!(
+            storage.head("blobs/main").await.unwrap().size,
+            template.len() as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incompressible_and_legacy_values_are_stored_raw() {
+        let inner = SharedStorage::default();
+        let storage = CompressedStorage::new(inner.clone()).with_level(19);
+
+        // Too short to gain from compression
+        storage.put("short", b"abc".to_vec()).await.unwrap();
+        assert_eq!(inner.get("short").await.unwrap(), b"abc");
+        assert_eq!(storage.get("short").await.unwrap(), b"abc");
+
+        // Values written before wrapping are read back unchanged
+        inner.put("legacy", b"= Invoice".to_vec()).await.unwrap();
+        assert_eq!(storage.get("legacy").await.unwrap(), b"= Invoice");
+
+        // A raw value that looks like a header is compressed to stay unambiguous
+        let lookalike = [MAGIC.as_slice(), b"1"].concat();
+        storage.put("lookalike", lookalike.clone()).await.unwrap();
+        assert_ne!(inner.get("lookalike").await.unwrap(), lookalike);
+        assert_eq!(storage.get("lookalike").await.unwrap(), lookalike);
+
+        assert!(matches!(
+            storage.get("missing").await,
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_registry_keys_hash_uncompressed_content() {
+        let inner = SharedStorage::default();
+        let registry = Registry::new_storage_only(CompressedStorage::new(inner.clone()));
+        let template = repetitive_template();
+
+        registry
+            .publish(
+                TemplateBundle::new(
+                    template.clone(),
+                    TemplateMetadata::new("Invoice", "test@example.com"),
+                ),
+                "acme/invoice",
+                "latest",
+            )
+            .await
+            .unwrap();
+
+        let main_key = ContentAddress::blob_key(&ContentAddress::hash(&template));
+        let stored = inner.get(&main_key).await.unwrap();
+        assert!(stored.len() < template.len());
+
+        let pdf = registry
+            .render(
+                "acme/invoice:latest",
+                &serde_json::json!({"qty": 1, "price": 2}),
+            )
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
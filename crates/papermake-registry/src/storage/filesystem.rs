@@ -7,12 +7,14 @@ use crate::{
     address::ContentAddress,
     error::{RegistryError, StorageError},
     manifest::Manifest,
+    storage::blob_storage::StorageError as BlobStorageError,
 };
 
 pub struct RegistryFileSystem<S: BlobStorage> {
     storage: Arc<S>,
     manifest: Manifest,
     runtime: tokio::runtime::Handle,
+    verify: bool,
 }
 
 impl<S: BlobStorage> RegistryFileSystem<S> {
@@ -27,20 +29,44 @@ impl<S: BlobStorage> RegistryFileSystem<S> {
             storage,
             manifest,
             runtime,
+            verify: false,
         })
     }
 
-    fn normalize_path(&self, path: &str) -> String {
-        // Remove leading slash if present
-        let path = path.strip_prefix('/').unwrap_or(path);
+    /// Check every file read against its hash in the manifest
+    ///
+    /// A blob that does not match fails the read instead of being served.
+    pub fn with_verification(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+
+    /// Resolve a requested path to its manifest key
+    ///
+    /// Typst requests files by their path from the template root, e.g.
+    /// `/partials/table.typ`. `.` and `..` segments are resolved as well, and
+    /// a path climbing above the bundle root is denied rather than clamped.
+    fn normalize_path(path: &str) -> Result<String, FileError> {
+        let mut segments = Vec::new();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    if segments.pop().is_none() {
+                        return Err(FileError::AccessDenied);
+                    }
+                }
+                segment => segments.push(segment),
+            }
+        }
 
-        path.to_string()
+        Ok(segments.join("/"))
     }
 }
 
 impl<S: BlobStorage + 'static> RenderFileSystem for RegistryFileSystem<S> {
     fn get_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
-        let normalized_path = self.normalize_path(path);
+        let normalized_path = Self::normalize_path(path)?;
 
         let file_hash = self
             .manifest
@@ -54,9 +80,213 @@ impl<S: BlobStorage + 'static> RenderFileSystem for RegistryFileSystem<S> {
         let blob_key = blob_key.clone();
         let handle = self.runtime.clone();
 
-        std::thread::spawn(move || handle.block_on(storage.get(&blob_key)))
+        let content = std::thread::spawn(move || handle.block_on(storage.get(&blob_key)))
             .join()
-            .map_err(|_| FileError::NotFound(path.into()))?
-            .map_err(|_| FileError::NotFound(path.into()))
+            .map_err(|_| FileError::Other(Some("blob fetch panicked".into())))?
+            .map_err(|e| match e {
+                // Only a missing blob lets a layered file system fall through
+                BlobStorageError::NotFound(_) => FileError::NotFound(path.into()),
+                BlobStorageError::AccessDenied(_) => FileError::AccessDenied,
+                e => FileError::Other(Some(e.to_string().into())),
+            })?;
+
+        if self.verify && !ContentAddress::verify(&content, file_hash) {
+            return Err(FileError::Other(Some(
+                format!("{} does not match its hash {}", path, file_hash).into(),
+            )));
+        }
+
+        Ok(content)
+    }
+}
+
+/// File system consulting an ordered list of layers, first hit wins
+///
+/// Lets several bundles share common assets (fonts, logos) without copying
+/// them into each one: put the template's [`RegistryFileSystem`] first and a
+/// read-only base layer of org-wide assets behind it. A file present in an
+/// earlier layer shadows the same path in later ones.
+pub struct LayeredFileSystem {
+    layers: Vec<Arc<dyn RenderFileSystem>>,
+}
+
+impl LayeredFileSystem {
+    /// Create a file system resolving from `layers` in order
+    pub fn new(layers: Vec<Arc<dyn RenderFileSystem>>) -> Self {
+        Self { layers }
+    }
+
+    /// Add a layer consulted after all existing ones
+    pub fn with_layer(mut self, layer: Arc<dyn RenderFileSystem>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+}
+
+impl RenderFileSystem for LayeredFileSystem {
+    /// Return the file from the first layer that has it
+    ///
+    /// A `NotFound` from one layer moves on to the next; any other error is
+    /// returned immediately, since hiding it could silently serve a file
+    /// from a lower layer.
+    fn get_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        for layer in &self.layers {
+            match layer.get_file(path) {
+                Err(FileError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(FileError::NotFound(path.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bundle::TemplateMetadata, storage::blob_storage::MemoryStorage};
+    use papermake::InMemoryFileSystem;
+    use std::collections::BTreeMap;
+
+    /// Store `files` as blobs and build a file system over their manifest
+    async fn registry_file_system(files: &[(&str, &[u8])]) -> RegistryFileSystem<MemoryStorage> {
+        let storage = Arc::new(MemoryStorage::new());
+        let mut hashes = BTreeMap::new();
+        for (path, content) in files {
+            let hash = ContentAddress::hash(content);
+            storage
+                .put(&ContentAddress::blob_key(&hash), content.to_vec())
+                .await
+                .unwrap();
+            hashes.insert(path.to_string(), hash);
+        }
+        let manifest =
+            Manifest::new(hashes, TemplateMetadata::new("Invoice", "test@example.com")).unwrap();
+        RegistryFileSystem::new(storage, manifest).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_nested_relative_imports() {
+        let fs = registry_file_system(&[
+            (
+                "main.typ",
+                b"#import \"partials/table.typ\": table-title\n#table-title",
+            ),
+            (
+                "partials/table.typ",
+                b"#import \"../header.typ\": title\n#let table-title = [#title: items]",
+            ),
+            ("header.typ", b"#let title = \"Invoice\""),
+        ])
+        .await;
+
+        assert_eq!(
+            fs.get_file("/partials/./../header.typ").unwrap(),
+            b"#let title = \"Invoice\""
+        );
+        assert!(matches!(
+            fs.get_file("/partials/../../header.typ"),
+            Err(FileError::AccessDenied)
+        ));
+
+        let main_typ = String::from_utf8(fs.get_file("/main.typ").unwrap()).unwrap();
+        let result =
+            papermake::render_template(main_typ, Arc::new(fs), &serde_json::json!({})).unwrap();
+        assert!(result.success, "errors: {:?}", result.errors);
+    }
+
+    /// Storage whose backend is unreachable
+    struct UnavailableStorage;
+
+    #[async_trait::async_trait]
+    impl BlobStorage for UnavailableStorage {
+        async fn put(&self, _key: &str, _data: Vec<u8>) -> Result<(), BlobStorageError> {
+            Err(BlobStorageError::Backend("connection reset".to_string()))
+        }
+
+        async fn get(&self, _key: &str) -> Result<Vec<u8>, BlobStorageError> {
+            Err(BlobStorageError::Backend("connection reset".to_string()))
+        }
+
+        async fn delete(&self, _key: &str) -> Result<(), BlobStorageError> {
+            Err(BlobStorageError::Backend("connection reset".to_string()))
+        }
+
+        async fn list_keys(&self, _prefix: &str) -> Result<Vec<String>, BlobStorageError> {
+            Err(BlobStorageError::Backend("connection reset".to_string()))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_layered_file_system_surfaces_storage_failures() {
+        let manifest = Manifest::new(
+            BTreeMap::from([("main.typ".to_string(), ContentAddress::hash(b"= Invoice"))]),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        )
+        .unwrap();
+        let template_layer =
+            RegistryFileSystem::new(Arc::new(UnavailableStorage), manifest).unwrap();
+
+        let mut base_layer = InMemoryFileSystem::new();
+        base_layer.add_file("/main.typ", b"fallback".to_vec());
+
+        let fs =
+            LayeredFileSystem::new(vec![Arc::new(template_layer)]).with_layer(Arc::new(base_layer));
+
+        // A transient failure must not serve the lower layer's file
+        assert!(matches!(
+            fs.get_file("/main.typ"),
+            Err(FileError::Other(Some(message))) if message.contains("connection reset")
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_verification_rejects_tampered_blob() {
+        let fs = registry_file_system(&[
+            ("main.typ", b"#import \"header.typ\": title"),
+            ("header.typ", b"#let title = \"Invoice\""),
+        ])
+        .await
+        .with_verification();
+        let blob_key = ContentAddress::blob_key(&fs.manifest.files["header.typ"]);
+        fs.storage
+            .put(&blob_key, b"#let title = \"Mallory\"".to_vec())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            fs.get_file("/header.typ"),
+            Err(FileError::Other(Some(_)))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_layered_file_system() {
+        let storage = Arc::new(MemoryStorage::new());
+        let main_typ = b"#image(\"assets/logo.png\")".to_vec();
+        let main_hash = ContentAddress::hash(&main_typ);
+        storage
+            .put(&ContentAddress::blob_key(&main_hash), main_typ.clone())
+            .await
+            .unwrap();
+        let manifest = Manifest::new(
+            BTreeMap::from([("main.typ".to_string(), main_hash)]),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        )
+        .unwrap();
+        let template_layer = RegistryFileSystem::new(storage, manifest).unwrap();
+
+        let mut base_layer = InMemoryFileSystem::new();
+        base_layer.add_file("/assets/logo.png", b"org logo".to_vec());
+        base_layer.add_file("/main.typ", b"shadowed".to_vec());
+
+        let fs =
+            LayeredFileSystem::new(vec![Arc::new(template_layer)]).with_layer(Arc::new(base_layer));
+
+        assert_eq!(fs.get_file("/main.typ").unwrap(), main_typ);
+        assert_eq!(fs.get_file("/assets/logo.png").unwrap(), b"org logo");
+        assert!(matches!(
+            fs.get_file("/assets/missing.png"),
+            Err(FileError::NotFound(_))
+        ));
     }
 }
@@ -0,0 +1,200 @@
+//! Blob storage wrapper counting accesses per key
+//!
+//! Wrap any [`BlobStorage`] in an [`InstrumentedStorage`] to see how often
+//! the render pipeline touches each key, e.g. to check that a cache avoids
+//! repeated manifest reads or to find hot blobs when profiling throughput.
+//! The counters live behind an [`Arc`], so a handle obtained from
+//! [`InstrumentedStorage::stats`] keeps working after the storage is moved
+//! into a [`Registry`](crate::Registry).
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::address::HashedContent;
+use crate::storage::blob_storage::{BlobMeta, BlobStorage, StorageError};
+
+/// Access counts recorded by an [`InstrumentedStorage`]
+///
+/// Calls are counted whether or not they succeed, so a `get` of a missing
+/// key still shows up.
+#[derive(Debug, Default)]
+pub struct StorageStats {
+    gets: Mutex<HashMap<String, u64>>,
+    puts: Mutex<HashMap<String, u64>>,
+    list_keys: AtomicU64,
+}
+
+impl StorageStats {
+    /// Number of `get` calls for `key`
+    pub fn get_count(&self, key: &str) -> u64 {
+        self.gets.lock().unwrap().get(key).copied().unwrap_or(0)
+    }
+
+    /// Number of `get` calls across all keys
+    pub fn total_gets(&self) -> u64 {
+        self.gets.lock().unwrap().values().sum()
+    }
+
+    /// Number of `put` and `put_stream` calls for `key`
+    pub fn put_count(&self, key: &str) -> u64 {
+        self.puts.lock().unwrap().get(key).copied().unwrap_or(0)
+    }
+
+    /// Number of `put` and `put_stream` calls across all keys
+    pub fn total_puts(&self) -> u64 {
+        self.puts.lock().unwrap().values().sum()
+    }
+
+    /// Number of `list_keys` calls
+    pub fn list_keys_count(&self) -> u64 {
+        self.list_keys.load(Ordering::Relaxed)
+    }
+
+    /// Reset all counters to zero
+    pub fn reset(&self) {
+        self.gets.lock().unwrap().clear();
+        self.puts.lock().unwrap().clear();
+        self.list_keys.store(0, Ordering::Relaxed);
+    }
+
+    fn record(counts: &Mutex<HashMap<String, u64>>, key: &str) {
+        *counts.lock().unwrap().entry(key.to_string()).or_default() += 1;
+    }
+}
+
+/// Storage forwarding to an inner store while counting accesses
+#[derive(Debug)]
+pub struct InstrumentedStorage<S: BlobStorage> {
+    inner: S,
+    stats: Arc<StorageStats>,
+}
+
+impl<S: BlobStorage> InstrumentedStorage<S> {
+    /// Wrap `inner`, starting with all counters at zero
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            stats: Arc::new(StorageStats::default()),
+        }
+    }
+
+    /// Handle to the access counters
+    pub fn stats(&self) -> Arc<StorageStats> {
+        self.stats.clone()
+    }
+
+    /// The wrapped storage
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<S: BlobStorage> BlobStorage for InstrumentedStorage<S> {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        StorageStats::record(&self.stats.puts, key);
+        self.inner.put(key, data).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        StorageStats::record(&self.stats.gets, key);
+        self.inner.get(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        self.inner.exists(key).await
+    }
+
+    async fn head(&self, key: &str) -> Result<BlobMeta, StorageError> {
+        self.inner.head(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.inner.delete(key).await
+    }
+
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.stats.list_keys.fetch_add(1, Ordering::Relaxed);
+        self.inner.list_keys(prefix).await
+    }
+
+    async fn put_stream(&self, key: &str, content: HashedContent) -> Result<(), StorageError> {
+        // Forwarded so the inner store's streaming upload is kept
+        StorageStats::record(&self.stats.puts, key);
+        self.inner.put_stream(key, content).await
+    }
+
+    async fn ping(&self) -> Result<(), StorageError> {
+        self.inner.ping().await
+    }
+
+    async fn bootstrap(&self) -> Result<(), StorageError> {
+        self.inner.bootstrap().await
+    }
+
+    async fn presigned_url(
+        &self,
+        key: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>, StorageError> {
+        self.inner.presigned_url(key, ttl).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Registry,
+        address::ContentAddress,
+        bundle::{TemplateBundle, TemplateMetadata},
+        storage::blob_storage::MemoryStorage,
+    };
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_counts_publish_and_render() {
+        let storage = InstrumentedStorage::new(MemoryStorage::new());
+        let stats = storage.stats();
+        let registry = Registry::new_storage_only(storage);
+
+        let main_typ = b"= Invoice\nNumber: #data.number".to_vec();
+        let main_key = ContentAddress::blob_key(&ContentAddress::hash(&main_typ));
+        let manifest_hash = registry
+            .publish(
+                TemplateBundle::new(
+                    main_typ,
+                    TemplateMetadata::new("Invoice", "test@example.com"),
+                ),
+                "acme/invoice",
+                "latest",
+            )
+            .await
+            .unwrap();
+        let manifest_key = ContentAddress::manifest_key(&manifest_hash);
+        let ref_key = ContentAddress::ref_key("acme/invoice", "latest");
+
+        assert_eq!(stats.put_count(&main_key), 1);
+        assert_eq!(stats.put_count(&manifest_key), 1);
+        assert_eq!(stats.put_count(&ref_key), 1);
+        assert_eq!(stats.total_puts(), 3);
+        assert_eq!(stats.total_gets(), 0);
+
+        stats.reset();
+        let pdf = registry
+            .render("acme/invoice:latest", &serde_json::json!({"number": 42}))
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        assert_eq!(stats.get_count(&ref_key), 1);
+        assert_eq!(stats.get_count(&manifest_key), 1);
+        assert!(stats.get_count(&main_key) >= 1);
+        assert_eq!(stats.total_puts(), 0);
+        assert_eq!(stats.get_count("missing"), 0);
+
+        registry.list_templates().await.unwrap();
+        assert!(stats.list_keys_count() >= 1);
+    }
+}
@@ -0,0 +1,403 @@
+//! Local filesystem storage implementation
+//!
+//! Stores each key as a file under a root directory, e.g.
+//! `blobs/sha256/abc...` at `<root>/blobs/sha256/abc...`, for durable
+//! single-node deployments without an object store. Writes go to a
+//! temporary file under `<root>/.tmp` first and are renamed into place, so
+//! readers never see a partially written blob.
+
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    BlobStorage,
+    address::HashedContent,
+    storage::blob_storage::{BlobMeta, StorageError},
+};
+
+/// Directory under the root holding in-progress writes
+const TMP_DIR: &str = ".tmp";
+
+/// Blob storage backed by a directory on the local filesystem
+#[derive(Debug, Clone)]
+pub struct FilesystemBlobStorage {
+    root: PathBuf,
+}
+
+impl FilesystemBlobStorage {
+    /// Create storage rooted at `root`
+    ///
+    /// The directory is created on the first write if it does not exist.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file storing `key`
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(key
+            .split('/')
+            .fold(self.root.clone(), |path, segment| path.join(segment)))
+    }
+
+    /// Write `key` atomically by moving the file at `tmp_path` into place
+    async fn commit(&self, key: &str, tmp_path: &Path) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| backend_error("create directory for", key, e))?;
+        }
+        tokio::fs::rename(tmp_path, &path).await.map_err(|e| {
+            // Best effort: the temporary file is useless once the rename failed
+            let _ = std::fs::remove_file(tmp_path);
+            backend_error("move into place", key, e)
+        })
+    }
+
+    /// A fresh path under the temporary directory
+    async fn tmp_path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let tmp_dir = self.root.join(TMP_DIR);
+        tokio::fs::create_dir_all(&tmp_dir)
+            .await
+            .map_err(|e| backend_error("create temporary directory for", key, e))?;
+        Ok(tmp_dir.join(uuid::Uuid::new_v4().to_string()))
+    }
+}
+
+/// Reject keys that do not map to a file below the root
+fn validate_key(key: &str) -> Result<(), StorageError> {
+    if key.is_empty() {
+        return Err(StorageError::InvalidKey("Key cannot be empty".into()));
+    }
+
+    for segment in key.split('/') {
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\') {
+            return Err(StorageError::InvalidKey(format!(
+                "Key '{}' must be a relative path without empty, '.' or '..' segments",
+                key
+            )));
+        }
+    }
+
+    if key.split('/').next() == Some(TMP_DIR) {
+        return Err(StorageError::InvalidKey(format!(
+            "Key '{}' uses the reserved '{}' directory",
+            key, TMP_DIR
+        )));
+    }
+
+    Ok(())
+}
+
+fn backend_error(action: &str, key: &str, error: std::io::Error) -> StorageError {
+    StorageError::Backend(format!("Failed to {} '{}': {}", action, key, error))
+}
+
+/// Collect the keys of all files below `dir`, where `dir` holds `dir_key`
+fn collect_keys(dir: &Path, dir_key: &str, keys: &mut Vec<String>) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        // Keys are validated on write, so only foreign files are skipped here
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if dir_key.is_empty() && name == TMP_DIR {
+            continue;
+        }
+
+        let key = if dir_key.is_empty() {
+            name
+        } else {
+            format!("{}/{}", dir_key, name)
+        };
+        if entry.file_type()?.is_dir() {
+            collect_keys(&entry.path(), &key, keys)?;
+        } else {
+            keys.push(key);
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl BlobStorage for FilesystemBlobStorage {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        self.path(key)?;
+        let tmp_path = self.tmp_path(key).await?;
+
+        let write = async {
+            let mut file = tokio::fs::File::create(&tmp_path).await?;
+            file.write_all(&data).await?;
+            file.sync_all().await
+        };
+        if let Err(e) = write.await {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(backend_error("write", key, e));
+        }
+
+        self.commit(key, &tmp_path).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = self.path(key)?;
+        tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+            ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+            ErrorKind::PermissionDenied => StorageError::AccessDenied(key.to_string()),
+            _ => backend_error("read", key, e),
+        })
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        let path = self.path(key)?;
+        tokio::fs::try_exists(&path)
+            .await
+            .map_err(|e| backend_error("check", key, e))
+    }
+
+    async fn head(&self, key: &str) -> Result<BlobMeta, StorageError> {
+        let path = self.path(key)?;
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+                ErrorKind::PermissionDenied => StorageError::AccessDenied(key.to_string()),
+                _ => backend_error("stat", key, e),
+            })?;
+        Ok(BlobMeta {
+            size: metadata.len(),
+            etag: None,
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(backend_error("delete", key, e)),
+        }
+    }
+
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        // Only walk the deepest directory the prefix fully names
+        let dir_key = prefix.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let dir = if dir_key.is_empty() {
+            self.root.clone()
+        } else {
+            match self.path(dir_key) {
+                Ok(dir) => dir,
+                // No valid key starts with an invalid directory
+                Err(_) => return Ok(Vec::new()),
+            }
+        };
+
+        let root = self.root.clone();
+        let dir_key = dir_key.to_string();
+        let mut keys = tokio::task::spawn_blocking(move || {
+            let mut keys = Vec::new();
+            collect_keys(&dir, &dir_key, &mut keys).map(|_| keys)
+        })
+        .await
+        .map_err(|e| StorageError::Backend(format!("Failed to list keys: {}", e)))?
+        .map_err(|e| {
+            StorageError::Backend(format!(
+                "Failed to list keys with prefix '{}' under {}: {}",
+                prefix,
+                root.display(),
+                e
+            ))
+        })?;
+
+        keys.retain(|key| key.starts_with(prefix));
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Check that the root directory exists or can be created
+    async fn ping(&self) -> Result<(), StorageError> {
+        tokio::fs::create_dir_all(&self.root).await.map_err(|e| {
+            StorageError::Backend(format!(
+                "Storage root {} is not accessible: {}",
+                self.root.display(),
+                e
+            ))
+        })
+    }
+
+    /// Copy spilled content straight from its temporary file
+    async fn put_stream(&self, key: &str, content: HashedContent) -> Result<(), StorageError> {
+        match content {
+            HashedContent::Memory(data) => self.put(key, data).await,
+            HashedContent::File(file) => {
+                self.path(key)?;
+                // The spilled file may live on another filesystem, so it is
+                // copied next to the root rather than renamed
+                let tmp_path = self.tmp_path(key).await?;
+                if let Err(e) = tokio::fs::copy(file.path(), &tmp_path).await {
+                    let _ = tokio::fs::remove_file(&tmp_path).await;
+                    return Err(backend_error("write", key, e));
+                }
+                self.commit(key, &tmp_path).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Registry,
+        address::ContentAddress,
+        bundle::{TemplateBundle, TemplateMetadata},
+    };
+
+    #[tokio::test]
+    async fn test_filesystem_storage_basic_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemBlobStorage::new(dir.path());
+
+        storage
+            .put("blobs/sha256/abc", b"data".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(storage.get("blobs/sha256/abc").await.unwrap(), b"data");
+        assert!(dir.path().join("blobs/sha256/abc").is_file());
+        assert!(storage.exists("blobs/sha256/abc").await.unwrap());
+        assert_eq!(storage.head("blobs/sha256/abc").await.unwrap().size, 4);
+        assert!(!storage.exists("blobs/sha256/missing").await.unwrap());
+        assert!(matches!(
+            storage.head("blobs/sha256/missing").await,
+            Err(StorageError::NotFound(_))
+        ));
+
+        storage
+            .put("blobs/sha256/abc", b"new".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(storage.get("blobs/sha256/abc").await.unwrap(), b"new");
+
+        storage.delete("blobs/sha256/abc").await.unwrap();
+        assert!(!storage.exists("blobs/sha256/abc").await.unwrap());
+        assert!(matches!(
+            storage.get("blobs/sha256/abc").await,
+            Err(StorageError::NotFound(_))
+        ));
+        storage.delete("blobs/sha256/abc").await.unwrap();
+
+        // No temporary files are left behind
+        let leftovers = std::fs::read_dir(dir.path().join(TMP_DIR)).unwrap().count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_storage_rejects_keys_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemBlobStorage::new(dir.path().join("store"));
+
+        for key in [
+            "",
+            "../escape",
+            "refs/../../escape",
+            "/etc/passwd",
+            "a//b",
+            ".tmp/x",
+        ] {
+            assert!(
+                matches!(
+                    storage.put(key, b"x".to_vec()).await,
+                    Err(StorageError::InvalidKey(_))
+                ),
+                "key {:?} was accepted",
+                key
+            );
+        }
+        assert!(!dir.path().join("escape").exists());
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_storage_list_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemBlobStorage::new(dir.path());
+
+        for key in [
+            "refs/john/invoice/latest",
+            "refs/john/invoice/v1.0.0",
+            "refs/johnny/letter/latest",
+            "refs/alice/letter/latest",
+            "blobs/sha256/abc123",
+        ] {
+            storage.put(key, b"x".to_vec()).await.unwrap();
+        }
+
+        assert_eq!(
+            storage.list_keys("refs/john").await.unwrap(),
+            vec![
+                "refs/john/invoice/latest",
+                "refs/john/invoice/v1.0.0",
+                "refs/johnny/letter/latest",
+            ]
+        );
+        assert_eq!(
+            storage.list_keys("refs/john/").await.unwrap(),
+            vec!["refs/john/invoice/latest", "refs/john/invoice/v1.0.0"]
+        );
+        assert_eq!(storage.list_keys("").await.unwrap().len(), 5);
+        assert!(storage.list_keys("missing/").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_storage_put_stream_spilled_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FilesystemBlobStorage::new(dir.path());
+        let data = vec![7u8; 4096];
+
+        let (hash, content) = ContentAddress::hash_reader(&data[..], 1024).unwrap();
+        assert!(matches!(content, HashedContent::File(_)));
+        let key = ContentAddress::blob_key(&hash);
+        storage.put_stream(&key, content).await.unwrap();
+
+        assert_eq!(storage.get(&key).await.unwrap(), data);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_publish_and_render_from_filesystem_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = TemplateBundle::new(
+            b"= Invoice\nNumber: #data.number".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+
+        let registry = Registry::new_storage_only(FilesystemBlobStorage::new(dir.path()));
+        let manifest_hash = registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        // A fresh registry over the same directory sees the published template
+        let registry = Registry::new_storage_only(FilesystemBlobStorage::new(dir.path()));
+        let resolved = registry.resolve("acme/invoice:latest").await.unwrap();
+        assert_eq!(resolved, manifest_hash);
+
+        let pdf = registry
+            .render("acme/invoice:latest", &serde_json::json!({"number": 42}))
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
@@ -4,13 +4,24 @@ use async_trait::async_trait;
 
 pub mod blob_storage;
 pub mod filesystem;
+pub mod instrumented;
 
 // Re-export for convenience
 pub use blob_storage::BlobStorage;
 pub use papermake::FileError;
 
+// zstd compression wrapper
+#[cfg(feature = "zstd")]
+pub mod compressed;
+
+// Local filesystem implementation
+#[cfg(feature = "filesystem")]
+pub mod local_storage;
+
 // S3 implementation
 #[cfg(feature = "s3")]
+pub mod retry;
+#[cfg(feature = "s3")]
 pub mod s3_storage;
 
 /// File system abstraction for Typst rendering
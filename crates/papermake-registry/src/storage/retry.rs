@@ -0,0 +1,189 @@
+//! Retry with exponential backoff for transient storage failures
+//!
+//! Object stores shed load with throttling responses and occasionally fail
+//! requests with 5xx errors or timeouts that succeed when repeated. A
+//! [`RetryPolicy`] repeats such operations with jittered exponential backoff;
+//! which errors count as transient is decided by the caller, so definitive
+//! answers like "not found" or "access denied" are returned immediately.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How often and how quickly to retry a failing operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first; `1` disables retries
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled for each further retry
+    pub base_delay: Duration,
+    /// Upper bound for the backoff before any retry
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, backing off 100ms and then 200ms (before jitter)
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy making a single attempt
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Set the total number of attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set the backoff before the first retry
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the upper bound for the backoff
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Run `operation`, retrying errors for which `is_retryable` holds
+    ///
+    /// Returns the first success, the first non-retryable error, or the
+    /// last error once `max_attempts` attempts have failed.
+    pub async fn retry<T, E, F, Fut>(
+        &self,
+        is_retryable: impl Fn(&E) -> bool,
+        mut operation: F,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(error) if attempt < self.max_attempts && is_retryable(&error) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Delay after the failed `attempt`, counted from 1
+    ///
+    /// Half of the exponential delay is fixed and half is random, so clients
+    /// failing together spread their retries without retrying immediately.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        let jitter = random_fraction();
+        half + half.mul_f64(jitter)
+    }
+}
+
+/// Random number in `[0, 1)`, from the randomly seeded std hasher
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    enum MockError {
+        Throttled,
+        NotFound,
+    }
+
+    fn is_retryable(error: &MockError) -> bool {
+        *error == MockError::Throttled
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::default().with_base_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_retries_flaky_operation_until_success() {
+        let calls = AtomicU32::new(0);
+
+        // Backend that is throttled twice before answering
+        let result = fast_policy()
+            .retry(is_retryable, || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(MockError::Throttled),
+                    _ => Ok("content"),
+                }
+            })
+            .await;
+
+        assert_eq!(result, Ok("content"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), _> = fast_policy()
+            .with_max_attempts(2)
+            .retry(is_retryable, || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(MockError::Throttled)
+            })
+            .await;
+
+        assert_eq!(result, Err(MockError::Throttled));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_permanent_errors() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), _> = fast_policy()
+            .retry(is_retryable, || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(MockError::NotFound)
+            })
+            .await;
+
+        assert_eq!(result, Err(MockError::NotFound));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::default()
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(300));
+
+        for (attempt, full) in [(1, 100), (2, 200), (3, 300), (10, 300)] {
+            let delay = policy.backoff(attempt);
+            let full = Duration::from_millis(full);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+    }
+}
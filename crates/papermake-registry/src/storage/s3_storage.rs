@@ -7,31 +7,105 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::StreamExt;
 use minio::s3::{
+    builders::{ObjectContent, Size},
     client::Client,
     creds::StaticProvider,
+    error::{Error as S3Error, ErrorCode},
     http::BaseUrl,
     segmented_bytes::SegmentedBytes,
     types::{S3Api, ToStream},
 };
 use std::str::FromStr;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    BlobStorage,
+    address::HashedContent,
+    config,
+    storage::{
+        blob_storage::{BlobMeta, StorageError},
+        retry::RetryPolicy,
+    },
+};
+
+/// Read a required setting via [`config::env_var`]
+fn required_env(name: &str) -> Result<String, StorageError> {
+    config::env_var(name)
+        .map_err(|e| StorageError::Backend(e.to_string()))?
+        .ok_or_else(|| StorageError::Backend(format!("{} environment variable not set", name)))
+}
 
-use crate::{BlobStorage, storage::blob_storage::StorageError};
+/// Part size for multipart uploads (8 MiB)
+pub const MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Longest expiry S3 accepts for presigned URLs (7 days)
+pub const MAX_PRESIGNED_URL_TTL_SECONDS: u32 = 7 * 24 * 60 * 60;
+
+/// S3 error codes for throttling and temporary server-side failures
+const RETRYABLE_ERROR_CODES: &[&str] = &[
+    "slowdown",
+    "throttling",
+    "throttlingexception",
+    "toomanyrequests",
+    "requesttimeout",
+    "internalerror",
+    "serviceunavailable",
+];
+
+/// Whether `error` is transient, so repeating the request may succeed
+///
+/// Timeouts, connection failures, throttling (429) and server errors (5xx)
+/// are retryable; definitive answers such as missing keys or denied access
+/// are not.
+fn is_retryable(error: &S3Error) -> bool {
+    match error {
+        S3Error::HttpError(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status()
+                    .is_some_and(|status| status.as_u16() == 429 || status.is_server_error())
+        }
+        S3Error::ServerError(status) | S3Error::InvalidResponse(status, _) => {
+            *status == 429 || (500..600).contains(status)
+        }
+        S3Error::S3Error(response) => matches!(
+            &response.code,
+            ErrorCode::OtherError(code) if RETRYABLE_ERROR_CODES.contains(&code.as_str())
+        ),
+        S3Error::IOError(_) => true,
+        _ => false,
+    }
+}
 
 /// S3-compatible storage implementation using MinIO client
 pub struct S3Storage {
     client: Client,
     bucket: String,
+    retry: RetryPolicy,
 }
 
 impl S3Storage {
     /// Create a new S3 storage instance
+    ///
+    /// Failed requests are not retried; see [`S3Storage::with_retry`].
     pub fn new(client: Client, bucket: impl Into<String>) -> Self {
         Self {
             client,
             bucket: bucket.into(),
+            retry: RetryPolicy::none(),
         }
     }
 
+    /// Retry `get`, `put` and `list_keys` on transient failures
+    ///
+    /// Throttling, server errors and timeouts are retried per `policy`;
+    /// missing keys and denied access fail immediately.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
     /// Create S3 storage from environment variables
     ///
     /// Expects:
@@ -40,22 +114,14 @@ impl S3Storage {
     /// - S3_ENDPOINT_URL (for S3-compatible services like MinIO)
     /// - S3_BUCKET
     /// - S3_REGION (optional)
+    ///
+    /// Each can instead be read from a file named by the same variable with
+    /// a `_FILE` suffix, e.g. `S3_SECRET_ACCESS_KEY_FILE`.
     pub fn from_env() -> Result<Self, StorageError> {
-        let bucket = std::env::var("S3_BUCKET").map_err(|_| {
-            StorageError::Backend("S3_BUCKET environment variable not set".to_string())
-        })?;
-
-        let access_key = std::env::var("S3_ACCESS_KEY_ID").map_err(|_| {
-            StorageError::Backend("S3_ACCESS_KEY_ID environment variable not set".to_string())
-        })?;
-
-        let secret_key = std::env::var("S3_SECRET_ACCESS_KEY").map_err(|_| {
-            StorageError::Backend("S3_SECRET_ACCESS_KEY environment variable not set".to_string())
-        })?;
-
-        let endpoint_url = std::env::var("S3_ENDPOINT_URL").map_err(|_| {
-            StorageError::Backend("S3_ENDPOINT_URL environment variable not set".to_string())
-        })?;
+        let bucket = required_env("S3_BUCKET")?;
+        let access_key = required_env("S3_ACCESS_KEY_ID")?;
+        let secret_key = required_env("S3_SECRET_ACCESS_KEY")?;
+        let endpoint_url = required_env("S3_ENDPOINT_URL")?;
 
         // Create base URL for endpoint
         let base_url = BaseUrl::from_str(&endpoint_url)
@@ -101,6 +167,66 @@ impl S3Storage {
         }
     }
 
+    /// Upload everything read from `reader` to `key` as a multipart upload
+    ///
+    /// Parts of [`MULTIPART_PART_SIZE`] are sent as they are read, so only
+    /// about one part is held in memory at a time, e.g. when piping a
+    /// render straight to object storage. Content smaller than one part is
+    /// sent as a single `PutObject`.
+    pub async fn put_streaming<R>(&self, key: &str, reader: R) -> Result<(), StorageError>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        self.validate_key(key)?;
+
+        let content = ObjectContent::new_from_stream(ReaderStream::new(reader), Size::Unknown);
+        self.client
+            .put_object_content(&self.bucket, key, content)
+            .part_size(Size::Known(MULTIPART_PART_SIZE))
+            .send()
+            .await
+            .map_err(|e| StorageError::Backend(format!("Failed to put file '{}': {}", key, e)))?;
+
+        Ok(())
+    }
+
+    /// Presigned `GetObject` URL for `key`, valid for `ttl`
+    ///
+    /// The URL is signed locally with the configured credentials, so anyone
+    /// holding it can download the object without going through the
+    /// registry until it expires. S3 accepts expiry times between one second
+    /// and seven days.
+    pub async fn presigned_get_url(
+        &self,
+        key: &str,
+        ttl: std::time::Duration,
+    ) -> Result<String, StorageError> {
+        self.validate_key(key)?;
+
+        let expiry_seconds = u32::try_from(ttl.as_secs())
+            .ok()
+            .filter(|seconds| (1..=MAX_PRESIGNED_URL_TTL_SECONDS).contains(seconds))
+            .ok_or_else(|| {
+                StorageError::Backend(format!(
+                    "Presigned URL expiry must be between 1 and {} seconds, got {}",
+                    MAX_PRESIGNED_URL_TTL_SECONDS,
+                    ttl.as_secs()
+                ))
+            })?;
+
+        let response = self
+            .client
+            .get_presigned_object_url(&self.bucket, key, http::Method::GET)
+            .expiry_seconds(expiry_seconds)
+            .send()
+            .await
+            .map_err(|e| {
+                StorageError::Backend(format!("Failed to presign file '{}': {}", key, e))
+            })?;
+
+        Ok(response.url)
+    }
+
     /// Validate S3 key format
     fn validate_key(&self, key: &str) -> Result<(), StorageError> {
         if key.is_empty() || key.len() > 1024 {
@@ -119,34 +245,38 @@ impl S3Storage {
     }
 
     /// List files with a given prefix
+    ///
+    /// ListObjectsV2 returns at most 1000 keys per request; the stream
+    /// follows the continuation token until the listing is no longer
+    /// truncated, so all matching keys are returned.
     pub async fn list_files(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
-        let mut keys = Vec::new();
-        let mut stream = self
-            .client
-            .list_objects(&self.bucket)
-            .prefix(Some(prefix.to_string()))
-            .recursive(true)
-            .to_stream()
-            .await;
-
-        while let Some(result) = stream.next().await {
-            match result {
-                Ok(response) => {
+        // A listing interrupted between pages is restarted from the beginning
+        self.retry
+            .retry(is_retryable, || async {
+                let mut keys = Vec::new();
+                let mut stream = self
+                    .client
+                    .list_objects(&self.bucket)
+                    .prefix(Some(prefix.to_string()))
+                    .recursive(true)
+                    .to_stream()
+                    .await;
+
+                while let Some(result) = stream.next().await {
                     // Collect keys from the response
-                    for entry in response.contents {
+                    for entry in result?.contents {
                         keys.push(entry.name);
                     }
                 }
-                Err(e) => {
-                    return Err(StorageError::Backend(format!(
-                        "Failed to list files with prefix '{}': {}",
-                        prefix, e
-                    )));
-                }
-            }
-        }
-
-        Ok(keys)
+                Ok(keys)
+            })
+            .await
+            .map_err(|e| {
+                StorageError::Backend(format!(
+                    "Failed to list files with prefix '{}': {}",
+                    prefix, e
+                ))
+            })
     }
 }
 
@@ -155,24 +285,56 @@ impl BlobStorage for S3Storage {
     async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
         self.validate_key(key)?;
 
-        let bytes = SegmentedBytes::from(Bytes::from(data));
+        let bytes = Bytes::from(data);
 
-        self.client
-            .put_object(&self.bucket, key, bytes)
-            .send()
+        self.retry
+            .retry(is_retryable, || {
+                self.client
+                    .put_object(&self.bucket, key, SegmentedBytes::from(bytes.clone()))
+                    .send()
+            })
             .await
             .map_err(|e| StorageError::Backend(format!("Failed to put file '{}': {}", key, e)))?;
 
         Ok(())
     }
 
+    /// Upload content larger than one part with [`S3Storage::put_streaming`]
+    ///
+    /// Spilled content is streamed straight from its temporary file, so the
+    /// blob is never loaded into memory.
+    async fn put_stream(&self, key: &str, content: HashedContent) -> Result<(), StorageError> {
+        match content {
+            HashedContent::Memory(data) if data.len() as u64 <= MULTIPART_PART_SIZE => {
+                self.put(key, data).await
+            }
+            HashedContent::Memory(data) => {
+                self.put_streaming(key, std::io::Cursor::new(data)).await
+            }
+            HashedContent::File(file) => {
+                let reader = tokio::fs::File::open(file.path()).await.map_err(|e| {
+                    StorageError::Backend(format!("Failed to read content for '{}': {}", key, e))
+                })?;
+                // `file` is kept alive, and the temporary file with it, until the upload ends
+                self.put_streaming(key, reader).await
+            }
+        }
+    }
+
     async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
         self.validate_key(key)?;
 
-        let response = self
-            .client
-            .get_object(&self.bucket, key)
-            .send()
+        // A download interrupted while reading the body is requested again
+        let content = self
+            .retry
+            .retry(is_retryable, || async {
+                let response = self.client.get_object(&self.bucket, key).send().await?;
+                response
+                    .content
+                    .to_segmented_bytes()
+                    .await
+                    .map_err(S3Error::IOError)
+            })
             .await
             .map_err(|e| {
                 // Check if it's a not found error
@@ -183,10 +345,6 @@ impl BlobStorage for S3Storage {
                 }
             })?;
 
-        let content = response.content.to_segmented_bytes().await.map_err(|e| {
-            StorageError::Backend(format!("Failed to read file '{}' content: {}", key, e))
-        })?;
-
         Ok(content.to_bytes().to_vec())
     }
 
@@ -209,6 +367,30 @@ impl BlobStorage for S3Storage {
         }
     }
 
+    /// Stat the object with a `HeadObject` request
+    async fn head(&self, key: &str) -> Result<BlobMeta, StorageError> {
+        self.validate_key(key)?;
+
+        let response = self
+            .retry
+            .retry(is_retryable, || {
+                self.client.stat_object(&self.bucket, key).send()
+            })
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("NoSuchKey") || e.to_string().contains("404") {
+                    StorageError::NotFound(key.to_string())
+                } else {
+                    StorageError::Backend(format!("Failed to stat file '{}': {}", key, e))
+                }
+            })?;
+
+        Ok(BlobMeta {
+            size: response.size,
+            etag: Some(response.etag),
+        })
+    }
+
     async fn delete(&self, key: &str) -> Result<(), StorageError> {
         self.validate_key(key)?;
 
@@ -226,6 +408,37 @@ impl BlobStorage for S3Storage {
     async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
         self.list_files(prefix).await
     }
+
+    /// Check that the bucket exists with a `HeadBucket` request
+    async fn ping(&self) -> Result<(), StorageError> {
+        let response = self
+            .client
+            .bucket_exists(&self.bucket)
+            .send()
+            .await
+            .map_err(|e| {
+                StorageError::Backend(format!("Failed to check bucket '{}': {}", self.bucket, e))
+            })?;
+
+        if response.exists {
+            Ok(())
+        } else {
+            Err(StorageError::NotFound(format!("bucket '{}'", self.bucket)))
+        }
+    }
+
+    /// Create the bucket unless it exists, see [`S3Storage::ensure_bucket`]
+    async fn bootstrap(&self) -> Result<(), StorageError> {
+        self.ensure_bucket().await
+    }
+
+    async fn presigned_url(
+        &self,
+        key: &str,
+        ttl: std::time::Duration,
+    ) -> Result<Option<String>, StorageError> {
+        self.presigned_get_url(key, ttl).await.map(Some)
+    }
 }
 
 /// Utility functions for generating S3 keys for different types of content
@@ -322,6 +535,130 @@ mod tests {
         assert!(storage.validate_key(&"x".repeat(1025)).is_err());
     }
 
+    #[test]
+    fn test_retries_only_transient_errors() {
+        assert!(is_retryable(&S3Error::ServerError(503)));
+        assert!(is_retryable(&S3Error::InvalidResponse(
+            429,
+            "text/plain".to_string()
+        )));
+
+        let s3_error = |code: ErrorCode| {
+            S3Error::S3Error(minio::s3::error::ErrorResponse {
+                code,
+                ..Default::default()
+            })
+        };
+        assert!(is_retryable(&s3_error(ErrorCode::parse("SlowDown"))));
+        assert!(is_retryable(&s3_error(ErrorCode::parse("InternalError"))));
+        assert!(!is_retryable(&s3_error(ErrorCode::NoSuchKey)));
+        assert!(!is_retryable(&s3_error(ErrorCode::AccessDenied)));
+        assert!(!is_retryable(&S3Error::ServerError(404)));
+    }
+
+    // Presigning resolves the bucket region through a blocking call, which
+    // needs the multi-threaded runtime
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_presigned_get_url_contains_key_and_expiry() {
+        // With the region known up front, signing needs no request
+        let mut base_url = BaseUrl::from_str("http://localhost:9000").unwrap();
+        base_url.region = "us-east-1".to_string();
+        let client = Client::new(
+            base_url,
+            Some(Box::new(StaticProvider::new(
+                "minioadmin",
+                "minioadmin",
+                None,
+            ))),
+            None,
+            None,
+        )
+        .unwrap();
+        let storage = S3Storage::new(client, "papermake");
+
+        let url = storage
+            .presigned_get_url("pdfs/sha256/abc123", std::time::Duration::from_secs(900))
+            .await
+            .unwrap();
+        assert!(
+            url.starts_with("http://localhost:9000/papermake/pdfs/sha256/abc123?"),
+            "url: {}",
+            url
+        );
+        assert!(url.contains("X-Amz-Expires=900"), "url: {}", url);
+        assert!(url.contains("X-Amz-Signature="), "url: {}", url);
+
+        assert!(
+            storage
+                .presigned_get_url("pdfs/sha256/abc123", std::time::Duration::ZERO)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "requires a MinIO endpoint at localhost:9000"]
+    async fn test_list_keys_follows_pagination() {
+        let client = Client::new(
+            BaseUrl::from_str("http://localhost:9000").unwrap(),
+            Some(Box::new(StaticProvider::new(
+                "minioadmin",
+                "minioadmin",
+                None,
+            ))),
+            None,
+            None,
+        )
+        .unwrap();
+        let storage = S3Storage::new(client, "papermake-registry-test");
+        storage.ensure_bucket().await.unwrap();
+
+        // More keys than fit in a single ListObjectsV2 page of 1000
+        let prefix = format!("refs/pagination-{}/", uuid::Uuid::now_v7());
+        for i in 0..1500 {
+            storage
+                .put(&format!("{}tag-{:04}", prefix, i), b"sha256:test".to_vec())
+                .await
+                .unwrap();
+        }
+
+        let keys = storage.list_keys(&prefix).await.unwrap();
+        assert_eq!(keys.len(), 1500);
+
+        for key in keys {
+            storage.delete(&key).await.unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "requires a MinIO endpoint at localhost:9000"]
+    async fn test_put_streaming_multipart_roundtrip() {
+        let client = Client::new(
+            BaseUrl::from_str("http://localhost:9000").unwrap(),
+            Some(Box::new(StaticProvider::new(
+                "minioadmin",
+                "minioadmin",
+                None,
+            ))),
+            None,
+            None,
+        )
+        .unwrap();
+        let storage = S3Storage::new(client, "papermake-registry-test");
+        storage.ensure_bucket().await.unwrap();
+
+        // 20 MiB spans three parts, the last one partial
+        let data: Vec<u8> = (0..20 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let key = format!("pdfs/multipart-{}", uuid::Uuid::now_v7());
+        storage
+            .put_streaming(&key, std::io::Cursor::new(data.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.get(&key).await.unwrap(), data);
+        storage.delete(&key).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_s3_storage_from_env_missing_vars() {
         // Clear environment variables to test error handling
@@ -0,0 +1,146 @@
+//! End-to-end rendering harness for example template bundles
+//!
+//! Each subdirectory of a bundle directory is one template:
+//! - `main.typ` is the entrypoint
+//! - `data.json` is the sample data it is rendered with (not part of the bundle)
+//! - every other file, including nested ones, is added to the bundle by relative path
+//!
+//! Rendering every bundle through a real `Registry` catches breakage from
+//! Typst dependency bumps that unit tests on individual pieces would miss.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    Registry,
+    bundle::{TemplateBundle, TemplateMetadata},
+    storage::blob_storage::MemoryStorage,
+};
+
+/// Directory holding the bundled example templates
+pub(crate) fn examples_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/templates")
+}
+
+/// Load a template bundle and its sample data from a bundle directory
+pub(crate) fn load_example_bundle(dir: &Path) -> (TemplateBundle, serde_json::Value) {
+    let name = dir.file_name().unwrap().to_string_lossy().to_string();
+
+    let main_typ = std::fs::read(dir.join("main.typ"))
+        .unwrap_or_else(|e| panic!("{}: missing main.typ: {}", name, e));
+    let data_bytes = std::fs::read(dir.join("data.json"))
+        .unwrap_or_else(|e| panic!("{}: missing data.json: {}", name, e));
+    let data = serde_json::from_slice(&data_bytes)
+        .unwrap_or_else(|e| panic!("{}: invalid data.json: {}", name, e));
+
+    let mut bundle = TemplateBundle::new(main_typ, TemplateMetadata::new(&name, "examples"));
+    for path in collect_files(dir) {
+        let relative = path
+            .strip_prefix(dir)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+        if relative == "main.typ" || relative == "data.json" {
+            continue;
+        }
+        bundle = bundle.add_file(relative, std::fs::read(&path).unwrap());
+    }
+
+    (bundle, data)
+}
+
+/// Publish and render every bundle in `dir`, asserting each output is a valid PDF
+///
+/// Returns the rendered PDFs keyed by bundle name, sorted by name.
+pub(crate) async fn render_example_bundles(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let registry = Registry::new_storage_only(MemoryStorage::new());
+
+    let mut bundle_dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    bundle_dirs.sort();
+
+    let mut rendered = Vec::new();
+    for bundle_dir in bundle_dirs {
+        let name = bundle_dir
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let (bundle, data) = load_example_bundle(&bundle_dir);
+
+        let namespace = format!("examples/{}", name);
+        registry
+            .publish(bundle, &namespace, "latest")
+            .await
+            .unwrap_or_else(|e| panic!("{}: publish failed: {}", name, e));
+
+        let pdf = registry
+            .render(&format!("{}:latest", namespace), &data)
+            .await
+            .unwrap_or_else(|e| panic!("{}: render failed: {}", name, e));
+
+        assert_valid_pdf(&name, &pdf);
+        rendered.push((name, pdf));
+    }
+
+    rendered
+}
+
+/// Assert that `pdf` is a non-empty PDF with at least one page
+pub(crate) fn assert_valid_pdf(name: &str, pdf: &[u8]) {
+    assert!(!pdf.is_empty(), "{}: empty PDF", name);
+    assert!(pdf.starts_with(b"%PDF"), "{}: missing %PDF header", name);
+    assert!(page_count(pdf) >= 1, "{}: PDF has no pages", name);
+}
+
+/// Count page objects in an uncompressed-object PDF as written by typst-pdf
+fn page_count(pdf: &[u8]) -> usize {
+    const PAGE: &[u8] = b"/Type /Page";
+    (0..pdf.len())
+        .filter(|&i| pdf[i..].starts_with(PAGE) && pdf.get(i + PAGE.len()) != Some(&b's'))
+        .count()
+}
+
+fn collect_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(collect_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render_all_example_templates() {
+        let rendered = render_example_bundles(&examples_dir()).await;
+
+        let names: Vec<&str> = rendered.iter().map(|(name, _)| name.as_str()).collect();
+        assert!(names.contains(&"invoice"));
+        assert!(names.contains(&"letter"));
+    }
+
+    #[test]
+    fn test_load_example_bundle_collects_nested_files() {
+        let (bundle, data) = load_example_bundle(&examples_dir().join("letter"));
+
+        assert!(bundle.get_file("components/letterhead.typ").is_some());
+        assert!(bundle.get_file("data.json").is_none());
+        assert_eq!(data["sender"]["name"], "Erika Mustermann");
+    }
+
+    #[test]
+    fn test_page_count() {
+        let pdf = b"%PDF-1.7 /Type /Pages /Kids [] /Type /Page /Type /Page";
+        assert_eq!(page_count(pdf), 2);
+    }
+}
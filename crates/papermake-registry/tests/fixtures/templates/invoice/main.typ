@@ -0,0 +1,39 @@
+#set page(paper: "a4", margin: 2cm)
+#set text(size: 10pt)
+
+#grid(
+  columns: (1fr, 1fr),
+  [
+    *#data.seller.name* \
+    #data.seller.address
+  ],
+  align(right)[
+    = Invoice
+    No. #data.number \
+    Date: #data.date
+  ],
+)
+
+#v(1cm)
+
+*Bill to:* \
+#data.customer.name \
+#data.customer.address
+
+#v(0.5cm)
+
+#table(
+  columns: (1fr, auto, auto, auto),
+  align: (left, right, right, right),
+  table.header[*Description*][*Qty*][*Unit price*][*Amount*],
+  ..data.items.map(item => (
+    item.description,
+    str(item.quantity),
+    [#item.unit_price #data.currency],
+    [#(item.quantity * item.unit_price) #data.currency],
+  )).flatten(),
+)
+
+#let total = data.items.map(item => item.quantity * item.unit_price).sum()
+
+#align(right)[*Total: #total #data.currency*]
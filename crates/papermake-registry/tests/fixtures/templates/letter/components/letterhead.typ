@@ -0,0 +1,9 @@
+#let letterhead(sender) = [
+  #set text(size: 9pt)
+  #align(right)[
+    *#sender.name* \
+    #sender.street \
+    #sender.city
+  ]
+  #line(length: 100%, stroke: 0.5pt)
+]
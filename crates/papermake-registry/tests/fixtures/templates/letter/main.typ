@@ -0,0 +1,29 @@
+#import "components/letterhead.typ": letterhead
+
+#set page(paper: "a4", margin: (x: 2.5cm, y: 2cm))
+#set par(justify: true)
+
+#letterhead(data.sender)
+
+#v(1cm)
+
+#data.recipient.name \
+#data.recipient.street \
+#data.recipient.city
+
+#v(1cm)
+
+#align(right)[#data.city, #data.date]
+
+*#data.subject*
+
+#data.salutation
+
+#for paragraph in data.paragraphs [
+  #paragraph
+
+]
+
+#data.closing \
+#v(1cm)
+#data.sender.name
@@ -1,6 +1,8 @@
 //! Server configuration management
 
 use crate::error::{ApiError, Result};
+use crate::mime::MimeTypes;
+use papermake_registry::config;
 use serde::{Deserialize, Serialize};
 
 /// Server configuration
@@ -18,46 +20,126 @@ pub struct ServerConfig {
     /// Timeout for render jobs in seconds
     pub render_timeout_seconds: u64,
 
+    /// Maximum number of render jobs waiting for a worker; further jobs are
+    /// rejected until the queue drains
+    pub max_queued_jobs: usize,
+
+    /// Number of prepared templates kept for reuse across renders
+    pub template_cache_size: usize,
+
+    /// Maximum size of one NDJSON line in a render stream, in bytes
+    pub stream_max_line_bytes: usize,
+
+    /// Maximum size of a render stream request body, in bytes
+    pub stream_max_body_bytes: usize,
+
+    /// How long queued and in-flight render jobs may take to finish on
+    /// shutdown, in seconds
+    pub shutdown_timeout_seconds: u64,
+
+    /// How long presigned PDF download URLs stay valid, in seconds
+    pub presigned_url_ttl_seconds: u64,
+
+    /// How long finished render jobs stay available for status polling, in
+    /// seconds
+    pub job_retention_seconds: u64,
+
+    /// Maximum number of render jobs kept for status polling
+    pub max_stored_jobs: usize,
+
     /// CORS allowed origins
     pub cors_origins: Vec<String>,
 
     /// Whether to enable debug logging
     pub debug: bool,
+
+    /// Content types for served template files
+    pub mime_types: MimeTypes,
 }
 
 impl ServerConfig {
     /// Load configuration from environment variables
+    ///
+    /// Each variable can instead be read from a file named by the same
+    /// variable with a `_FILE` suffix, e.g. `PORT_FILE`.
     pub fn from_env() -> Result<Self> {
         Ok(Self {
-            host: std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
-            port: std::env::var("PORT")
-                .unwrap_or_else(|_| "3000".to_string())
+            host: env_var("HOST")?.unwrap_or_else(|| "0.0.0.0".to_string()),
+            port: env_var("PORT")?
+                .unwrap_or_else(|| "3000".to_string())
                 .parse()
                 .map_err(|_| ApiError::Config("Invalid PORT value".to_string()))?,
-            max_concurrent_renders: std::env::var("MAX_CONCURRENT_RENDERS")
-                .unwrap_or_else(|_| "10".to_string())
+            max_concurrent_renders: env_var("MAX_CONCURRENT_RENDERS")?
+                .unwrap_or_else(|| "10".to_string())
                 .parse()
                 .map_err(|_| {
                     ApiError::Config("Invalid MAX_CONCURRENT_RENDERS value".to_string())
                 })?,
-            render_timeout_seconds: std::env::var("RENDER_TIMEOUT_SECONDS")
-                .unwrap_or_else(|_| "300".to_string()) // 5 minutes default
+            render_timeout_seconds: env_var("RENDER_TIMEOUT_SECONDS")?
+                .unwrap_or_else(|| "300".to_string()) // 5 minutes default
                 .parse()
                 .map_err(|_| {
                     ApiError::Config("Invalid RENDER_TIMEOUT_SECONDS value".to_string())
                 })?,
-            cors_origins: std::env::var("CORS_ORIGINS")
-                .unwrap_or_else(|_| "*".to_string())
+            max_queued_jobs: env_var("MAX_QUEUED_JOBS")?
+                .unwrap_or_else(|| "1000".to_string())
+                .parse()
+                .map_err(|_| ApiError::Config("Invalid MAX_QUEUED_JOBS value".to_string()))?,
+            template_cache_size: env_var("TEMPLATE_CACHE_SIZE")?
+                .unwrap_or_else(|| "32".to_string())
+                .parse()
+                .map_err(|_| ApiError::Config("Invalid TEMPLATE_CACHE_SIZE value".to_string()))?,
+            stream_max_line_bytes: env_var("STREAM_MAX_LINE_BYTES")?
+                .unwrap_or_else(|| "1048576".to_string()) // 1 MiB default
+                .parse()
+                .map_err(|_| ApiError::Config("Invalid STREAM_MAX_LINE_BYTES value".to_string()))?,
+            stream_max_body_bytes: env_var("STREAM_MAX_BODY_BYTES")?
+                .unwrap_or_else(|| "1073741824".to_string()) // 1 GiB default
+                .parse()
+                .map_err(|_| ApiError::Config("Invalid STREAM_MAX_BODY_BYTES value".to_string()))?,
+            shutdown_timeout_seconds: env_var("SHUTDOWN_TIMEOUT_SECONDS")?
+                .unwrap_or_else(|| "30".to_string())
+                .parse()
+                .map_err(|_| {
+                    ApiError::Config("Invalid SHUTDOWN_TIMEOUT_SECONDS value".to_string())
+                })?,
+            presigned_url_ttl_seconds: env_var("PRESIGNED_URL_TTL_SECONDS")?
+                .unwrap_or_else(|| "900".to_string()) // 15 minutes default
+                .parse()
+                .map_err(|_| {
+                    ApiError::Config("Invalid PRESIGNED_URL_TTL_SECONDS value".to_string())
+                })?,
+            job_retention_seconds: env_var("JOB_RETENTION_SECONDS")?
+                .unwrap_or_else(|| "3600".to_string())
+                .parse()
+                .map_err(|_| ApiError::Config("Invalid JOB_RETENTION_SECONDS value".to_string()))?,
+            max_stored_jobs: env_var("MAX_STORED_JOBS")?
+                .unwrap_or_else(|| "10000".to_string())
+                .parse()
+                .map_err(|_| ApiError::Config("Invalid MAX_STORED_JOBS value".to_string()))?,
+            cors_origins: env_var("CORS_ORIGINS")?
+                .unwrap_or_else(|| "*".to_string())
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
-            debug: std::env::var("DEBUG")
+            debug: env_var("DEBUG")?
                 .map(|s| s.to_lowercase() == "true")
                 .unwrap_or(false),
+            mime_types: match env_var("MIME_TYPES")? {
+                Some(overrides) => MimeTypes::default()
+                    .with_overrides(&overrides)
+                    .map_err(|e| ApiError::Config(format!("Invalid MIME_TYPES value: {}", e)))?,
+                None => MimeTypes::default(),
+            },
         })
     }
 }
 
+/// Read a setting from `<name>_FILE` or `name`
+fn env_var(name: &str) -> Result<Option<String>> {
+    config::env_var(name).map_err(|e| ApiError::Config(e.to_string()))
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -65,8 +147,17 @@ impl Default for ServerConfig {
             port: 3000,
             max_concurrent_renders: 10,
             render_timeout_seconds: 300,
+            max_queued_jobs: 1000,
+            template_cache_size: 32,
+            stream_max_line_bytes: 1024 * 1024,
+            stream_max_body_bytes: 1024 * 1024 * 1024,
+            shutdown_timeout_seconds: 30,
+            presigned_url_ttl_seconds: 900,
+            job_retention_seconds: 3600,
+            max_stored_jobs: 10_000,
             cors_origins: vec!["*".to_string()],
             debug: false,
+            mime_types: MimeTypes::default(),
         }
     }
 }
@@ -5,10 +5,19 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use papermake_registry::RegistryError;
+use papermake::{
+    PapermakeError,
+    error::{CompilationError, ConfigError, DiagnosticInfo, FileSystemError},
+};
+use papermake_registry::{
+    RegistryError,
+    error::{ReferenceError, StorageError, TemplateError},
+};
 use serde_json::json;
 use thiserror::Error;
 
+use crate::models::{RenderErrorResponse, RenderStatus};
+
 /// Result type for API operations
 pub type Result<T> = std::result::Result<T, ApiError>;
 
@@ -39,6 +48,9 @@ pub enum ApiError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -50,10 +62,95 @@ pub enum ApiError {
 
     #[error("Timeout error: operation timed out")]
     Timeout,
+
+    #[error("Server is shutting down")]
+    ShuttingDown,
+
+    #[error("Render queue is full, try again later")]
+    QueueFull,
+
+    #[error("Invalid render job transition from {from} to {to}")]
+    InvalidTransition {
+        from: RenderStatus,
+        to: RenderStatus,
+    },
+}
+
+/// HTTP status for an error from the papermake core
+///
+/// Template and data problems are the client's to fix and map to `422`,
+/// render timeouts to `504`, files missing from the template to `404`, and
+/// other file system or setup failures to `500`.
+fn papermake_status(error: &PapermakeError) -> StatusCode {
+    match error {
+        PapermakeError::FileSystem(FileSystemError::NotFound { .. }) => StatusCode::NOT_FOUND,
+        PapermakeError::Compilation(CompilationError::Timeout { .. }) => {
+            StatusCode::GATEWAY_TIMEOUT
+        }
+        PapermakeError::Compilation(_) | PapermakeError::Data(_) | PapermakeError::Template(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        PapermakeError::Config(ConfigError::InvalidConfig { .. }) => StatusCode::BAD_REQUEST,
+        PapermakeError::FileSystem(_) | PapermakeError::Config(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// HTTP status for a registry error
+///
+/// Failures of the storage backend the registry talks to map to `502`, or
+/// `504` when it timed out, while a misconfigured backend is a `500`.
+fn registry_status(error: &RegistryError) -> StatusCode {
+    match error {
+        RegistryError::Template(TemplateError::NotFound { .. })
+        | RegistryError::Reference(ReferenceError::ResolutionFailed { .. })
+        | RegistryError::Storage(StorageError::NotFound { .. }) => StatusCode::NOT_FOUND,
+        RegistryError::Template(TemplateError::TooLarge { .. }) => StatusCode::PAYLOAD_TOO_LARGE,
+        RegistryError::Template(_) | RegistryError::Reference(_) => StatusCode::BAD_REQUEST,
+        RegistryError::AccessDenied(_)
+        | RegistryError::Storage(StorageError::AccessDenied { .. }) => StatusCode::FORBIDDEN,
+        RegistryError::Storage(StorageError::Network { .. } | StorageError::Backend { .. }) => {
+            StatusCode::BAD_GATEWAY
+        }
+        RegistryError::Storage(StorageError::Timeout { .. }) => StatusCode::GATEWAY_TIMEOUT,
+        RegistryError::Compilation(e) => papermake_status(e),
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Compiler diagnostics carried by a papermake error, if any
+fn papermake_diagnostics(error: &PapermakeError) -> Option<&[DiagnosticInfo]> {
+    match error {
+        PapermakeError::Compilation(CompilationError::TypstError { diagnostics, .. }) => {
+            Some(diagnostics)
+        }
+        _ => None,
+    }
+}
+
+/// Compiler diagnostics carried by a registry error, if any
+pub fn registry_diagnostics(error: &RegistryError) -> Option<&[DiagnosticInfo]> {
+    match error {
+        RegistryError::Compilation(e) => papermake_diagnostics(e),
+        _ => None,
+    }
+}
+
+impl ApiError {
+    /// Compiler diagnostics to include in the response body, if any
+    fn diagnostics(&self) -> Option<&[DiagnosticInfo]> {
+        match self {
+            ApiError::Registry(e) => registry_diagnostics(e),
+            ApiError::Papermake(e) => papermake_diagnostics(e),
+            _ => None,
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let diagnostics = self.diagnostics().map(<[DiagnosticInfo]>::to_vec);
         let (status, error_message) = match self {
             ApiError::TemplateNotFound(_) | ApiError::RenderNotFound(_) => {
                 (StatusCode::NOT_FOUND, self.to_string())
@@ -61,19 +158,30 @@ impl IntoResponse for ApiError {
             ApiError::Validation(_) | ApiError::BadRequest(_) => {
                 (StatusCode::BAD_REQUEST, self.to_string())
             }
+            ApiError::PayloadTooLarge(_) => (StatusCode::PAYLOAD_TOO_LARGE, self.to_string()),
             ApiError::Config(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Configuration error".to_string(),
             ),
-            ApiError::Registry(ref e) => match e {
-                RegistryError::Template(_) => (StatusCode::NOT_FOUND, self.to_string()),
-                _ => (
+            ApiError::Registry(ref e) => match registry_status(e) {
+                status @ (StatusCode::INTERNAL_SERVER_ERROR | StatusCode::BAD_GATEWAY) => {
+                    (status, "Registry error".to_string())
+                }
+                status => (status, self.to_string()),
+            },
+            ApiError::Papermake(ref e) => match papermake_status(e) {
+                StatusCode::INTERNAL_SERVER_ERROR => (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "Registry error".to_string(),
+                    "Internal server error".to_string(),
                 ),
+                status => (status, self.to_string()),
             },
             ApiError::RenderFailed(_) => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
-            ApiError::Timeout => (StatusCode::REQUEST_TIMEOUT, "Request timed out".to_string()),
+            ApiError::Timeout => (StatusCode::GATEWAY_TIMEOUT, "Request timed out".to_string()),
+            ApiError::ShuttingDown | ApiError::QueueFull => {
+                (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
+            }
+            ApiError::InvalidTransition { .. } => (StatusCode::CONFLICT, self.to_string()),
             ApiError::Serialization(_) => {
                 (StatusCode::BAD_REQUEST, "Invalid JSON format".to_string())
             }
@@ -83,12 +191,25 @@ impl IntoResponse for ApiError {
             ),
         };
 
-        let body = Json(json!({
-            "error": error_message,
-            "status": status.as_u16()
-        }));
-
-        (status, body).into_response()
+        match diagnostics {
+            Some(diagnostics) => (
+                status,
+                Json(RenderErrorResponse {
+                    error: error_message,
+                    status: status.as_u16(),
+                    diagnostics,
+                }),
+            )
+                .into_response(),
+            None => (
+                status,
+                Json(json!({
+                    "error": error_message,
+                    "status": status.as_u16()
+                })),
+            )
+                .into_response(),
+        }
     }
 }
 
@@ -114,3 +235,182 @@ impl ApiError {
         Self::Validation(msg.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use papermake::error::{DataError, DiagnosticSeverity, SourceLocation};
+    use papermake_registry::storage::blob_storage;
+
+    async fn response_parts(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_compilation_error_is_422_with_diagnostics() {
+        let error = ApiError::Registry(RegistryError::Compilation(
+            CompilationError::TypstError {
+                error_count: 1,
+                diagnostics: vec![DiagnosticInfo {
+                    message: "unknown variable: totl".to_string(),
+                    severity: DiagnosticSeverity::Error,
+                    location: Some(SourceLocation {
+                        file: "main.typ".to_string(),
+                        line: 3,
+                        column: 2,
+                        range: Some((20, 24)),
+                    }),
+                    hints: vec!["did you mean `total`?".to_string()],
+                }],
+            }
+            .into(),
+        ));
+
+        let (status, body) = response_parts(error).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["status"], 422);
+        let diagnostic = &body["diagnostics"][0];
+        assert_eq!(diagnostic["message"], "unknown variable: totl");
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["location"]["file"], "main.typ");
+        assert_eq!(diagnostic["location"]["line"], 3);
+        assert_eq!(diagnostic["location"]["column"], 2);
+        assert_eq!(diagnostic["hints"][0], "did you mean `total`?");
+    }
+
+    #[tokio::test]
+    async fn test_error_categories() {
+        let cases = [
+            (
+                ApiError::Registry(RegistryError::Compilation(
+                    DataError::SchemaValidation {
+                        path: "/total".to_string(),
+                        message: "expected number".to_string(),
+                    }
+                    .into(),
+                )),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ApiError::Papermake(CompilationError::Timeout { timeout_ms: 500 }.into()),
+                StatusCode::GATEWAY_TIMEOUT,
+            ),
+            (ApiError::ShuttingDown, StatusCode::SERVICE_UNAVAILABLE),
+            (
+                ApiError::Registry(TemplateError::not_found("acme/invoice").into()),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                ApiError::Registry(RegistryError::AccessDenied(
+                    "bob may not read alice/invoice".to_string(),
+                )),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                ApiError::Registry(StorageError::backend("connection reset").into()),
+                StatusCode::BAD_GATEWAY,
+            ),
+        ];
+
+        for (error, expected) in cases {
+            let message = error.to_string();
+            let (status, body) = response_parts(error).await;
+            assert_eq!(status, expected, "{}", message);
+            assert_eq!(body["status"], expected.as_u16());
+            assert!(body.get("diagnostics").is_none());
+        }
+
+        // Internal details are not leaked
+        let (_, body) =
+            response_parts(ApiError::Registry(StorageError::backend("secret").into())).await;
+        assert_eq!(body["error"], "Registry error");
+    }
+
+    #[test]
+    fn test_registry_status() {
+        let cases: Vec<(RegistryError, StatusCode)> = vec![
+            (
+                TemplateError::not_found("acme/invoice").into(),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                StorageError::not_found("blobs/sha256:abc").into(),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                StorageError::from(blob_storage::StorageError::NotFound(
+                    "blobs/sha256:abc".to_string(),
+                ))
+                .into(),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                RegistryError::Compilation(
+                    FileSystemError::NotFound {
+                        path: "/lib/helpers.typ".to_string(),
+                    }
+                    .into(),
+                ),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                ReferenceError::invalid_format("acme/", "missing name").into(),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                ReferenceError::invalid_tag("v 1", "contains whitespace").into(),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                TemplateError::missing_file("main.typ").into(),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                TemplateError::too_large(4096, 1024).into(),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                RegistryError::AccessDenied("bob may not publish to acme".to_string()),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                StorageError::access_denied("blobs/sha256:abc").into(),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                StorageError::backend("connection reset").into(),
+                StatusCode::BAD_GATEWAY,
+            ),
+            (
+                StorageError::network("dns lookup failed").into(),
+                StatusCode::BAD_GATEWAY,
+            ),
+            (
+                StorageError::timeout(30).into(),
+                StatusCode::GATEWAY_TIMEOUT,
+            ),
+            (
+                StorageError::configuration("missing bucket").into(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                RegistryError::Compilation(
+                    CompilationError::TypstError {
+                        error_count: 1,
+                        diagnostics: Vec::new(),
+                    }
+                    .into(),
+                ),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+        ];
+
+        for (error, expected) in cases {
+            assert_eq!(registry_status(&error), expected, "{}", error);
+        }
+    }
+}
@@ -3,30 +3,47 @@
 //! Provides REST API endpoints for template management, PDF rendering,
 //! and analytics for the Papermake PDF generation system.
 
-use axum::{Router, extract::DefaultBodyLimit, response::Json, routing::get};
-use papermake_registry::{ClickHouseStorage, Registry, S3Storage};
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, FromRef, State},
+    http::header,
+    response::{IntoResponse, Json},
+    routing::get,
+};
+use papermake_registry::{ClickHouseStorage, Registry, RetryPolicy, S3Storage};
 use serde_json::{Value, json};
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 mod config;
 mod error;
+mod mime;
 mod models;
+mod openapi;
+mod queue;
 mod routes;
+mod worker;
 
 use config::ServerConfig;
 use error::Result;
 
-use crate::models::RenderJob;
+use crate::queue::{JobQueue, JobStore};
 
 /// Main application state
 #[derive(Clone)]
 pub struct AppState {
     pub registry: Arc<Registry<S3Storage, ClickHouseStorage>>,
     pub config: ServerConfig,
-    pub job_sender: tokio::sync::mpsc::UnboundedSender<RenderJob>,
+    pub job_queue: JobQueue,
+    pub jobs: JobStore,
+}
+
+impl FromRef<AppState> for JobStore {
+    fn from_ref(state: &AppState) -> Self {
+        state.jobs.clone()
+    }
 }
 
 #[tokio::main]
@@ -49,34 +66,41 @@ async fn main() -> Result<()> {
         config.host, config.port
     );
 
-    let s3_storage = S3Storage::from_env().unwrap(); // TODO: improve error handling
+    let s3_storage = S3Storage::from_env()
+        .unwrap() // TODO: improve error handling
+        .with_retry(RetryPolicy::default());
+    let clickhouse = ClickHouseStorage::from_env().unwrap();
 
-    // Ensure S3 bucket exists
-    if let Err(e) = s3_storage.ensure_bucket().await {
-        error!("Failed to ensure S3 bucket exists: {}", e);
-    }
+    // Create registry, keeping prepared templates so repeated renders of
+    // the same manifest reuse its Typst world and memoized layout
+    let registry = Arc::new(
+        Registry::new(s3_storage, clickhouse).with_template_cache(config.template_cache_size),
+    );
 
-    let clickhouse = ClickHouseStorage::from_env().unwrap();
-    if let Err(e) = clickhouse.init_schema().await {
-        error!("Failed to initialize ClickHouse schema: {}", e);
+    // Create the S3 bucket and ClickHouse tables if they don't exist yet
+    if let Err(e) = registry.bootstrap().await {
+        error!("Failed to bootstrap storage: {}", e);
+        return Err(e.into());
     }
 
-    // Create registry
-    let registry = Arc::new(Registry::new(s3_storage, clickhouse));
-
-    // Create job channel for event-driven processing
-    let (job_sender, _job_receiver) = tokio::sync::mpsc::unbounded_channel();
+    // Create job queue for event-driven processing
+    let (job_queue, job_receiver) = JobQueue::new(config.max_queued_jobs);
 
     // Create application state
+    let jobs = JobStore::new(
+        Duration::from_secs(config.job_retention_seconds),
+        config.max_stored_jobs,
+    );
     let state = AppState {
         registry,
         config: config.clone(),
-        job_sender,
+        job_queue: job_queue.clone(),
+        jobs: jobs.clone(),
     };
 
     // Start background render worker
-    // worker::spawn_render_worker(state.clone(), job_receiver); TODO: enable
-    // info!("🔧 Background render worker started");
+    let render_worker = worker::spawn_render_worker(state.clone(), job_receiver);
+    info!("🔧 Background render worker started");
 
     // Build router
     let app = create_router(state);
@@ -87,16 +111,68 @@ async fn main() -> Result<()> {
 
     info!("🚀 Server listening on http://{}", addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            info!("Shutting down, no longer accepting render jobs");
+            job_queue.close();
+        })
+        .await?;
+
+    // The router and its queue handles are gone, so the worker stops once
+    // it has rendered the jobs accepted before shutdown
+    let timeout = Duration::from_secs(config.shutdown_timeout_seconds);
+    let cancelled = worker::drain_render_worker(render_worker, &jobs, timeout).await;
+    if !cancelled.is_empty() {
+        warn!(
+            "Cancelled {} render jobs still running after {}s",
+            cancelled.len(),
+            config.shutdown_timeout_seconds
+        );
+    }
 
     Ok(())
 }
 
+/// Resolve on Ctrl+C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
 /// Create the main application router
 fn create_router(state: AppState) -> Router {
     Router::new()
         // Health check
         .route("/health", get(health_check))
+        .route("/health/ready", get(routes::health::dependency_readiness))
+        .route("/readyz", get(readiness_check))
+        // Prometheus metrics
+        .route("/metrics", get(metrics))
         // API routes
         .nest("/api", api_routes())
         // Middleware
@@ -116,9 +192,10 @@ fn api_routes() -> Router<AppState> {
         .nest("/render", routes::render::router())
         .nest("/renders", routes::renders::router())
         .nest("/analytics", routes::analytics::router())
+        .route("/openapi.json", get(openapi::openapi_json))
 }
 
-/// Health check endpoint
+/// Liveness endpoint, not probing any dependency
 async fn health_check() -> Result<Json<Value>> {
     Ok(Json(json!({
         "status": "healthy",
@@ -127,3 +204,20 @@ async fn health_check() -> Result<Json<Value>> {
         "timestamp": time::OffsetDateTime::now_utc()
     })))
 }
+
+/// Readiness endpoint including render queue saturation
+async fn readiness_check(State(state): State<AppState>) -> Result<Json<Value>> {
+    Ok(Json(json!({
+        "status": "ready",
+        "queue": state.job_queue.metrics().snapshot(),
+        "timestamp": time::OffsetDateTime::now_utc()
+    })))
+}
+
+/// Prometheus metrics endpoint
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.job_queue.metrics().to_prometheus(),
+    )
+}
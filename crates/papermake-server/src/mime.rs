@@ -0,0 +1,118 @@
+//! Content types for served template files
+//!
+//! Maps file extensions to MIME types. The built-in table covers Typst
+//! sources, common image formats and fonts; entries can be added or
+//! overridden through `ServerConfig`, e.g. for team-specific extensions.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Content type for files whose extension is not in the table
+pub const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+/// Built-in extension → MIME type mappings
+const BUILTIN_MIME_TYPES: &[(&str, &str)] = &[
+    // Typst and data
+    ("typ", "text/x-typst"),
+    ("json", "application/json"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("csv", "text/csv"),
+    ("xml", "application/xml"),
+    ("txt", "text/plain"),
+    ("bib", "application/x-bibtex"),
+    // Images
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+    // Fonts
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("ttc", "font/collection"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    // Documents
+    ("pdf", "application/pdf"),
+];
+
+/// Extension → MIME type table used when serving template files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MimeTypes {
+    types: HashMap<String, String>,
+}
+
+impl Default for MimeTypes {
+    fn default() -> Self {
+        Self {
+            types: BUILTIN_MIME_TYPES
+                .iter()
+                .map(|(ext, mime)| (ext.to_string(), mime.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl MimeTypes {
+    /// Add or override the MIME type for `extension` (without the dot)
+    pub fn with_type(mut self, extension: &str, mime: &str) -> Self {
+        self.types
+            .insert(extension.to_ascii_lowercase(), mime.to_string());
+        self
+    }
+
+    /// Parse overrides in the form `typ=text/x-typst,woff2=font/woff2`
+    pub fn with_overrides(mut self, overrides: &str) -> Result<Self, String> {
+        for entry in overrides
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (extension, mime) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected extension=type, got '{}'", entry))?;
+            self = self.with_type(extension.trim().trim_start_matches('.'), mime.trim());
+        }
+        Ok(self)
+    }
+
+    /// MIME type for `path`, falling back to [`DEFAULT_MIME_TYPE`]
+    pub fn for_path(&self, path: &str) -> &str {
+        path.rsplit_once('.')
+            .filter(|(stem, _)| !stem.is_empty() && !stem.ends_with('/'))
+            .and_then(|(_, ext)| self.types.get(&ext.to_ascii_lowercase()))
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_MIME_TYPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_types() {
+        let types = MimeTypes::default();
+
+        assert_eq!(types.for_path("main.typ"), "text/x-typst");
+        assert_eq!(types.for_path("fonts/Inter.WOFF2"), "font/woff2");
+        assert_eq!(types.for_path("assets/logo.png"), "image/png");
+        assert_eq!(types.for_path("data.unknown"), DEFAULT_MIME_TYPE);
+        assert_eq!(types.for_path("Makefile"), DEFAULT_MIME_TYPE);
+        assert_eq!(types.for_path("assets/.hidden"), DEFAULT_MIME_TYPE);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let types = MimeTypes::default()
+            .with_overrides("typ=text/plain, .tpl = text/x-template")
+            .unwrap();
+
+        assert_eq!(types.for_path("main.typ"), "text/plain");
+        assert_eq!(types.for_path("page.tpl"), "text/x-template");
+        assert!(MimeTypes::default().with_overrides("typ").is_err());
+    }
+}
@@ -1,34 +1,47 @@
 //! Common API types and utilities
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 /// Standard pagination parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationQuery {
+    /// Maximum number of items to return (default: 50)
     #[serde(default = "default_limit")]
     pub limit: u32,
 
+    /// Number of items to skip
     #[serde(default)]
     pub offset: u32,
 }
 
-fn default_limit() -> u32 {
+pub(crate) fn default_limit() -> u32 {
     50
 }
 
 /// Standard pagination response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
     pub pagination: PaginationInfo,
 }
 
 /// Pagination metadata
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginationInfo {
     pub limit: u32,
     pub offset: u32,
     pub total: Option<u32>,
     pub has_more: bool,
+    /// Cursor for the next page of a timestamp-paged list, if there is one
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    pub next_before: Option<time::OffsetDateTime>,
+    /// Render ID to pass along with `next_before` as `before_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_before_id: Option<String>,
 }
 
 impl<T> PaginatedResponse<T> {
@@ -45,13 +58,15 @@ impl<T> PaginatedResponse<T> {
                 offset,
                 total,
                 has_more,
+                next_before: None,
+                next_before_id: None,
             },
         }
     }
 }
 
 /// Standard API response wrapper
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiResponse<T> {
     pub data: T,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -75,9 +90,10 @@ impl<T> ApiResponse<T> {
 }
 
 /// Common query parameters for filtering
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct SearchQuery {
     #[serde(flatten)]
+    #[param(ignore)]
     pub pagination: PaginationQuery,
 
     /// Search term for name/content filtering
@@ -93,15 +109,11 @@ pub struct SearchQuery {
     pub sort_order: Option<SortOrder>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
+#[derive(Default)]
 pub enum SortOrder {
     Asc,
+    #[default]
     Desc,
 }
-
-impl Default for SortOrder {
-    fn default() -> Self {
-        Self::Desc
-    }
-}
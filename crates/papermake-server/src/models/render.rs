@@ -1,4 +1,393 @@
+//! Render job model and its status lifecycle
+
+use papermake::error::DiagnosticInfo;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+
+/// Lifecycle status of a render job
+///
+/// Legal transitions are `Pending → InProgress → Completed/Failed`; a pending
+/// job may also fail directly (e.g. when its template cannot be resolved).
+/// `Completed` and `Failed` are terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+impl RenderStatus {
+    /// Canonical string form, used for storage and the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderStatus::Pending => "pending",
+            RenderStatus::InProgress => "in_progress",
+            RenderStatus::Completed => "completed",
+            RenderStatus::Failed => "failed",
+        }
+    }
+
+    /// Whether no further transitions are allowed
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, RenderStatus::Completed | RenderStatus::Failed)
+    }
+
+    /// Whether a job in this status may move to `to`
+    pub fn can_transition_to(&self, to: RenderStatus) -> bool {
+        matches!(
+            (self, to),
+            (RenderStatus::Pending, RenderStatus::InProgress)
+                | (RenderStatus::Pending, RenderStatus::Failed)
+                | (RenderStatus::InProgress, RenderStatus::Completed)
+                | (RenderStatus::InProgress, RenderStatus::Failed)
+        )
+    }
+}
+
+impl fmt::Display for RenderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RenderStatus {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(RenderStatus::Pending),
+            "in_progress" => Ok(RenderStatus::InProgress),
+            "completed" => Ok(RenderStatus::Completed),
+            "failed" => Ok(RenderStatus::Failed),
+            _ => Err(ApiError::validation(&format!(
+                "Unknown render status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// A render job queued for background processing
+#[derive(Debug, Clone, Serialize)]
 pub struct RenderJob {
+    pub id: String,
     pub reference: String,
     pub data: serde_json::Value,
+    status: RenderStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    completed_at: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    render_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pdf_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<DiagnosticInfo>,
+}
+
+impl RenderJob {
+    /// Start building a job for the given template reference
+    pub fn builder(reference: impl Into<String>) -> RenderJobBuilder {
+        RenderJobBuilder::new(reference)
+    }
+
+    /// Current status
+    pub fn status(&self) -> RenderStatus {
+        self.status
+    }
+
+    /// When the job reached a terminal status
+    pub fn completed_at(&self) -> Option<OffsetDateTime> {
+        self.completed_at
+    }
+
+    /// Render id of the stored render, once completed
+    pub fn render_id(&self) -> Option<&str> {
+        self.render_id.as_deref()
+    }
+
+    /// Hash of the rendered PDF, once completed
+    pub fn pdf_hash(&self) -> Option<&str> {
+        self.pdf_hash.as_deref()
+    }
+
+    /// How long rendering took, once completed
+    pub fn duration_ms(&self) -> Option<u32> {
+        self.duration_ms
+    }
+
+    /// Why the job failed, if it did
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Compiler diagnostics explaining the failure, if the template did not compile
+    pub fn diagnostics(&self) -> &[DiagnosticInfo] {
+        &self.diagnostics
+    }
+
+    /// Complete the job with the render it produced
+    pub fn complete(
+        &mut self,
+        render_id: impl Into<String>,
+        pdf_hash: impl Into<String>,
+        duration_ms: u32,
+    ) -> Result<(), ApiError> {
+        self.transition(RenderStatus::Completed)?;
+        self.render_id = Some(render_id.into());
+        self.pdf_hash = Some(pdf_hash.into());
+        self.duration_ms = Some(duration_ms);
+        Ok(())
+    }
+
+    /// Fail the job with an error message
+    pub fn fail(&mut self, error: impl Into<String>) -> Result<(), ApiError> {
+        self.fail_with_diagnostics(error, Vec::new())
+    }
+
+    /// Fail the job with an error message and the compiler diagnostics behind it
+    pub fn fail_with_diagnostics(
+        &mut self,
+        error: impl Into<String>,
+        diagnostics: Vec<DiagnosticInfo>,
+    ) -> Result<(), ApiError> {
+        self.transition(RenderStatus::Failed)?;
+        self.error = Some(error.into());
+        self.diagnostics = diagnostics;
+        Ok(())
+    }
+
+    /// Move the job to a new status
+    ///
+    /// Rejects illegal transitions, leaving the job unchanged. Entering a
+    /// terminal status records `completed_at`.
+    pub fn transition(&mut self, to: RenderStatus) -> Result<(), ApiError> {
+        if !self.status.can_transition_to(to) {
+            return Err(ApiError::InvalidTransition {
+                from: self.status,
+                to,
+            });
+        }
+
+        self.status = to;
+        if to.is_terminal() {
+            self.completed_at = Some(OffsetDateTime::now_utc());
+        }
+
+        Ok(())
+    }
+}
+
+/// Client-facing status of a render job, served by `/api/renders/{job_id}`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RenderJobStatus {
+    pub job_id: String,
+    pub status: RenderStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub completed_at: Option<OffsetDateTime>,
+    /// Render duration in milliseconds, excluding time spent queued
+    pub rendering_latency: Option<u32>,
+    pub error_message: Option<String>,
+    /// Compiler diagnostics, if the job failed because the template did not compile
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<DiagnosticInfo>,
+    /// Where to download the PDF, once completed
+    pub pdf_url: Option<String>,
+}
+
+/// Error body for a render whose template failed to compile
+///
+/// Returned with `422 Unprocessable Entity`. Each diagnostic carries the
+/// message, severity, source location and hints, so editors can point at
+/// the offending line.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RenderErrorResponse {
+    pub error: String,
+    pub status: u16,
+    pub diagnostics: Vec<DiagnosticInfo>,
+}
+
+impl From<&RenderJob> for RenderJobStatus {
+    fn from(job: &RenderJob) -> Self {
+        Self {
+            job_id: job.id.clone(),
+            status: job.status,
+            created_at: job.created_at,
+            completed_at: job.completed_at,
+            rendering_latency: job.duration_ms,
+            error_message: job.error.clone(),
+            diagnostics: job.diagnostics.clone(),
+            pdf_url: job
+                .render_id
+                .as_ref()
+                .map(|render_id| format!("/api/renders/{}/pdf", render_id)),
+        }
+    }
+}
+
+/// Builder for [`RenderJob`]
+#[derive(Debug)]
+pub struct RenderJobBuilder {
+    id: Option<String>,
+    reference: String,
+    data: serde_json::Value,
+}
+
+impl RenderJobBuilder {
+    pub fn new(reference: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            reference: reference.into(),
+            data: serde_json::Value::Object(Default::default()),
+        }
+    }
+
+    /// Use a specific job id instead of a generated one
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set the data the template is rendered with
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Build a pending job
+    pub fn build(self) -> RenderJob {
+        RenderJob {
+            id: self.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            reference: self.reference,
+            data: self.data,
+            status: RenderStatus::Pending,
+            created_at: OffsetDateTime::now_utc(),
+            completed_at: None,
+            render_id: None,
+            pdf_hash: None,
+            duration_ms: None,
+            error: None,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_creates_pending_job() {
+        let job = RenderJob::builder("acme/invoice:latest")
+            .with_id("job-1")
+            .with_data(serde_json::json!({"number": 1}))
+            .build();
+
+        assert_eq!(job.id, "job-1");
+        assert_eq!(job.reference, "acme/invoice:latest");
+        assert_eq!(job.data["number"], 1);
+        assert_eq!(job.status(), RenderStatus::Pending);
+        assert!(job.completed_at().is_none());
+
+        let generated = RenderJob::builder("acme/invoice:latest").build();
+        assert!(!generated.id.is_empty());
+    }
+
+    #[test]
+    fn test_transitions_set_completed_at_on_terminal_states() {
+        let mut job = RenderJob::builder("invoice").build();
+
+        job.transition(RenderStatus::InProgress).unwrap();
+        assert!(job.completed_at().is_none());
+
+        job.transition(RenderStatus::Completed).unwrap();
+        assert_eq!(job.status(), RenderStatus::Completed);
+        let completed_at = job.completed_at().unwrap();
+        assert!(completed_at >= job.created_at);
+
+        let mut failed = RenderJob::builder("invoice").build();
+        failed.transition(RenderStatus::Failed).unwrap();
+        assert!(failed.completed_at().is_some());
+    }
+
+    #[test]
+    fn test_complete_and_fail_record_outcome() {
+        let mut job = RenderJob::builder("invoice").build();
+        assert!(job.complete("render-1", "sha256:pdf", 12).is_err());
+        assert!(job.render_id().is_none());
+
+        job.transition(RenderStatus::InProgress).unwrap();
+        job.complete("render-1", "sha256:pdf", 12).unwrap();
+        assert_eq!(job.status(), RenderStatus::Completed);
+        assert_eq!(job.render_id(), Some("render-1"));
+        assert_eq!(job.pdf_hash(), Some("sha256:pdf"));
+        assert_eq!(job.duration_ms(), Some(12));
+
+        let mut failed = RenderJob::builder("invoice").build();
+        failed.fail("Template not found").unwrap();
+        assert_eq!(failed.error(), Some("Template not found"));
+        assert!(failed.fail("again").is_err());
+        assert_eq!(failed.error(), Some("Template not found"));
+    }
+
+    #[test]
+    fn test_illegal_transitions_are_rejected() {
+        let mut job = RenderJob::builder("invoice").build();
+
+        // Cannot complete without starting
+        assert!(matches!(
+            job.transition(RenderStatus::Completed),
+            Err(ApiError::InvalidTransition {
+                from: RenderStatus::Pending,
+                to: RenderStatus::Completed
+            })
+        ));
+        assert!(job.transition(RenderStatus::Pending).is_err());
+
+        job.transition(RenderStatus::InProgress).unwrap();
+        job.transition(RenderStatus::Completed).unwrap();
+        let completed_at = job.completed_at();
+
+        // Terminal states are final
+        for to in [
+            RenderStatus::Pending,
+            RenderStatus::InProgress,
+            RenderStatus::Completed,
+            RenderStatus::Failed,
+        ] {
+            assert!(job.transition(to).is_err());
+        }
+        assert_eq!(job.status(), RenderStatus::Completed);
+        assert_eq!(job.completed_at(), completed_at);
+    }
+
+    #[test]
+    fn test_status_string_round_trip() {
+        for status in [
+            RenderStatus::Pending,
+            RenderStatus::InProgress,
+            RenderStatus::Completed,
+            RenderStatus::Failed,
+        ] {
+            assert_eq!(status.as_str().parse::<RenderStatus>().unwrap(), status);
+            assert_eq!(
+                serde_json::to_value(status).unwrap(),
+                serde_json::json!(status.as_str())
+            );
+        }
+        assert!("running".parse::<RenderStatus>().is_err());
+    }
 }
@@ -0,0 +1,61 @@
+//! OpenAPI description of the HTTP API, served at `/api/openapi.json`
+
+use axum::Json;
+use utoipa::OpenApi;
+
+use crate::routes::{render, renders, templates};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Papermake API",
+        description = "Template management and PDF rendering"
+    ),
+    paths(
+        templates::list_templates,
+        templates::publish_template,
+        templates::publish_template_simple,
+        templates::list_template_tags,
+        templates::get_template_metadata,
+        templates::get_template_file,
+        render::render_template,
+        render::render_preview,
+        render::render_stream,
+        renders::list_renders,
+        renders::get_render_job,
+        renders::get_render_pdf,
+    ),
+    tags(
+        (name = "templates", description = "Publish and inspect templates"),
+        (name = "render", description = "Render templates to PDF"),
+        (name = "renders", description = "Render jobs and stored renders"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Handler for GET /api/openapi.json
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_openapi_json_describes_routes() {
+        let Json(spec) = openapi_json().await;
+        let spec: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&spec).unwrap()).unwrap();
+
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        assert!(spec["paths"]["/api/render/{reference}"]["post"].is_object());
+        assert!(spec["paths"]["/api/renders/{job_id}"]["get"].is_object());
+        assert!(spec["paths"]["/api/templates"]["get"].is_object());
+
+        let schemas = &spec["components"]["schemas"];
+        assert!(schemas["RenderJobStatus"]["properties"]["pdf_url"].is_object());
+        assert!(schemas["RenderRecord"].is_object());
+        assert!(schemas["DiagnosticInfo"].is_object());
+    }
+}
@@ -0,0 +1,410 @@
+//! Render job queue with saturation metrics
+//!
+//! Wraps the job channel so that every enqueue, start and finish updates the
+//! gauges used for capacity planning: queue depth, busy workers and average
+//! queue wait time. These are exposed on `/metrics` and `/readyz`.
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    RwLock,
+    mpsc::{self, error::TrySendError},
+};
+
+use crate::{
+    error::{ApiError, Result},
+    models::RenderJob,
+};
+
+/// Gauges describing render queue saturation
+#[derive(Debug, Default)]
+pub struct QueueMetrics {
+    queue_depth: AtomicU64,
+    busy_workers: AtomicU64,
+    started_jobs: AtomicU64,
+    total_wait_micros: AtomicU64,
+}
+
+/// Point-in-time view of [`QueueMetrics`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct QueueMetricsSnapshot {
+    pub queue_depth: u64,
+    pub busy_workers: u64,
+    pub avg_queue_wait_ms: f64,
+}
+
+impl QueueMetrics {
+    fn job_enqueued(&self) {
+        self.queue_depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn job_started(&self, waited: Duration) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        self.busy_workers.fetch_add(1, Ordering::Relaxed);
+        self.started_jobs.fetch_add(1, Ordering::Relaxed);
+        self.total_wait_micros
+            .fetch_add(waited.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn job_finished(&self) {
+        self.busy_workers.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Current gauge values
+    pub fn snapshot(&self) -> QueueMetricsSnapshot {
+        let started = self.started_jobs.load(Ordering::Relaxed);
+        let total_wait_micros = self.total_wait_micros.load(Ordering::Relaxed);
+
+        QueueMetricsSnapshot {
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            busy_workers: self.busy_workers.load(Ordering::Relaxed),
+            avg_queue_wait_ms: if started == 0 {
+                0.0
+            } else {
+                total_wait_micros as f64 / started as f64 / 1000.0
+            },
+        }
+    }
+
+    /// Render the gauges in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let snapshot = self.snapshot();
+        let gauges = [
+            (
+                "papermake_render_queue_depth",
+                "Render jobs waiting in the queue",
+                snapshot.queue_depth as f64,
+            ),
+            (
+                "papermake_render_workers_busy",
+                "Render workers currently processing a job",
+                snapshot.busy_workers as f64,
+            ),
+            (
+                "papermake_render_queue_wait_seconds_avg",
+                "Average time jobs spent queued before a worker picked them up",
+                snapshot.avg_queue_wait_ms / 1000.0,
+            ),
+        ];
+
+        let mut output = String::new();
+        for (name, help, value) in gauges {
+            let _ = writeln!(output, "# HELP {} {}", name, help);
+            let _ = writeln!(output, "# TYPE {} gauge", name);
+            let _ = writeln!(output, "{} {}", name, value);
+        }
+        output
+    }
+}
+
+/// A job together with the time it entered the queue
+struct QueuedJob {
+    job: RenderJob,
+    enqueued_at: Instant,
+}
+
+/// Sending half of the render job queue
+#[derive(Clone)]
+pub struct JobQueue {
+    sender: mpsc::Sender<QueuedJob>,
+    metrics: Arc<QueueMetrics>,
+    closed: Arc<AtomicBool>,
+}
+
+/// Receiving half of the render job queue, owned by the worker pool
+pub struct JobReceiver {
+    receiver: mpsc::Receiver<QueuedJob>,
+    metrics: Arc<QueueMetrics>,
+}
+
+/// A job a worker is processing; marks the worker idle again when dropped
+pub struct ActiveJob {
+    pub job: RenderJob,
+    metrics: Arc<QueueMetrics>,
+}
+
+impl JobQueue {
+    /// Create a new queue holding up to `capacity` waiting jobs, and its
+    /// receiving half
+    ///
+    /// A `capacity` of zero is treated as one.
+    pub fn new(capacity: usize) -> (Self, JobReceiver) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let metrics = Arc::new(QueueMetrics::default());
+
+        (
+            Self {
+                sender,
+                metrics: metrics.clone(),
+                closed: Arc::default(),
+            },
+            JobReceiver { receiver, metrics },
+        )
+    }
+
+    /// Add a job to the queue
+    ///
+    /// Fails with [`ApiError::ShuttingDown`] once the queue is closed and
+    /// with [`ApiError::QueueFull`] while it holds `capacity` waiting jobs.
+    pub fn enqueue(&self, job: RenderJob) -> Result<()> {
+        if self.closed.load(Ordering::Relaxed) {
+            return Err(ApiError::ShuttingDown);
+        }
+        self.metrics.job_enqueued();
+        self.sender
+            .try_send(QueuedJob {
+                job,
+                enqueued_at: Instant::now(),
+            })
+            .map_err(|e| {
+                self.metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                match e {
+                    TrySendError::Full(_) => ApiError::QueueFull,
+                    TrySendError::Closed(_) => {
+                        ApiError::Internal("Render job queue is closed".to_string())
+                    }
+                }
+            })
+    }
+
+    /// Stop accepting jobs, for every clone of this queue
+    ///
+    /// Jobs already queued stay in the queue for the worker to finish.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    /// Saturation metrics for this queue
+    pub fn metrics(&self) -> &QueueMetrics {
+        &self.metrics
+    }
+}
+
+impl JobReceiver {
+    /// Wait for the next job, marking a worker busy until the returned guard drops
+    pub async fn recv(&mut self) -> Option<ActiveJob> {
+        let queued = self.receiver.recv().await?;
+        self.metrics.job_started(queued.enqueued_at.elapsed());
+
+        Some(ActiveJob {
+            job: queued.job,
+            metrics: self.metrics.clone(),
+        })
+    }
+}
+
+impl Drop for ActiveJob {
+    fn drop(&mut self) {
+        self.metrics.job_finished();
+    }
+}
+
+/// Latest state of submitted jobs, for status polling
+///
+/// The store is in memory only: job states are lost on restart and are not
+/// shared between server instances. Finished jobs are evicted once they are
+/// older than the retention period, or oldest first when the store holds more
+/// than `capacity` jobs. Unfinished jobs are never evicted.
+#[derive(Clone)]
+pub struct JobStore {
+    jobs: Arc<RwLock<HashMap<String, RenderJob>>>,
+    retention: Duration,
+    capacity: usize,
+}
+
+impl Default for JobStore {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(3600), 10_000)
+    }
+}
+
+impl JobStore {
+    /// Create a store keeping finished jobs for `retention`, holding at most
+    /// `capacity` jobs
+    pub fn new(retention: Duration, capacity: usize) -> Self {
+        Self {
+            jobs: Arc::default(),
+            retention,
+            capacity,
+        }
+    }
+
+    /// Record the current state of `job`, replacing any earlier one
+    pub async fn insert(&self, job: RenderJob) {
+        let mut jobs = self.jobs.write().await;
+        jobs.insert(job.id.clone(), job);
+        self.evict(&mut jobs);
+    }
+
+    /// Forget the job with `id`
+    pub async fn remove(&self, id: &str) -> Option<RenderJob> {
+        self.jobs.write().await.remove(id)
+    }
+
+    /// Drop expired finished jobs, then the oldest finished ones over capacity
+    fn evict(&self, jobs: &mut HashMap<String, RenderJob>) {
+        let cutoff = time::OffsetDateTime::now_utc() - self.retention;
+        jobs.retain(|_, job| job.completed_at().is_none_or(|at| at > cutoff));
+
+        let excess = jobs.len().saturating_sub(self.capacity);
+        if excess == 0 {
+            return;
+        }
+        let mut finished: Vec<(time::OffsetDateTime, String)> = jobs
+            .values()
+            .filter_map(|job| Some((job.completed_at()?, job.id.clone())))
+            .collect();
+        finished.sort();
+        for (_, id) in finished.into_iter().take(excess) {
+            jobs.remove(&id);
+        }
+    }
+
+    /// Latest recorded state of the job with `id`
+    pub async fn get(&self, id: &str) -> Option<RenderJob> {
+        self.jobs.read().await.get(id).cloned()
+    }
+
+    /// Fail every job that is still pending or in progress, returning their ids
+    pub async fn fail_unfinished(&self, error: &str) -> Vec<String> {
+        let mut jobs = self.jobs.write().await;
+        let mut failed: Vec<String> = jobs
+            .values_mut()
+            .filter(|job| !job.status().is_terminal())
+            .filter_map(|job| job.fail(error).ok().map(|()| job.id.clone()))
+            .collect();
+        failed.sort();
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> RenderJob {
+        RenderJob::builder("invoice:latest").build()
+    }
+
+    #[test]
+    fn test_enqueue_without_workers_increases_depth() {
+        let (queue, _receiver) = JobQueue::new(16);
+
+        queue.enqueue(job()).unwrap();
+        queue.enqueue(job()).unwrap();
+
+        let snapshot = queue.metrics().snapshot();
+        assert_eq!(snapshot.queue_depth, 2);
+        assert_eq!(snapshot.busy_workers, 0);
+        assert_eq!(snapshot.avg_queue_wait_ms, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_job_lifecycle_updates_gauges() {
+        let (queue, mut receiver) = JobQueue::new(16);
+        queue.enqueue(job()).unwrap();
+
+        let active = receiver.recv().await.unwrap();
+        let snapshot = queue.metrics().snapshot();
+        assert_eq!(snapshot.queue_depth, 0);
+        assert_eq!(snapshot.busy_workers, 1);
+        assert_eq!(active.job.reference, "invoice:latest");
+
+        drop(active);
+        assert_eq!(queue.metrics().snapshot().busy_workers, 0);
+    }
+
+    #[test]
+    fn test_enqueue_on_closed_queue_keeps_depth() {
+        let (queue, receiver) = JobQueue::new(16);
+        drop(receiver);
+
+        assert!(queue.enqueue(job()).is_err());
+        assert_eq!(queue.metrics().snapshot().queue_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_closed_queue_rejects_new_jobs() {
+        let (queue, mut receiver) = JobQueue::new(16);
+        queue.enqueue(job()).unwrap();
+
+        queue.clone().close();
+        assert!(matches!(queue.enqueue(job()), Err(ApiError::ShuttingDown)));
+        assert_eq!(queue.metrics().snapshot().queue_depth, 1);
+
+        // Jobs queued before closing are still handed out
+        assert!(receiver.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_rejects_new_jobs() {
+        let (queue, mut receiver) = JobQueue::new(2);
+        queue.enqueue(job()).unwrap();
+        queue.enqueue(job()).unwrap();
+
+        assert!(matches!(queue.enqueue(job()), Err(ApiError::QueueFull)));
+        assert_eq!(queue.metrics().snapshot().queue_depth, 2);
+
+        // Room frees up as soon as a worker takes a job
+        let _active = receiver.recv().await.unwrap();
+        queue.enqueue(job()).unwrap();
+    }
+
+    async fn finished_job(store: &JobStore) -> String {
+        let mut job = job();
+        job.fail("boom").unwrap();
+        let id = job.id.clone();
+        store.insert(job).await;
+        id
+    }
+
+    #[tokio::test]
+    async fn test_job_store_evicts_expired_finished_jobs() {
+        let store = JobStore::new(Duration::ZERO, 100);
+        let pending = job();
+        let pending_id = pending.id.clone();
+        store.insert(pending).await;
+
+        let finished = finished_job(&store).await;
+        // Evicted on the next insert once past retention
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        store.insert(job()).await;
+
+        assert!(store.get(&finished).await.is_none());
+        assert!(store.get(&pending_id).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_job_store_evicts_oldest_finished_over_capacity() {
+        let store = JobStore::new(Duration::from_secs(3600), 2);
+        let oldest = finished_job(&store).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let newer = finished_job(&store).await;
+        let pending = job();
+        let pending_id = pending.id.clone();
+        store.insert(pending).await;
+
+        assert!(store.get(&oldest).await.is_none());
+        assert!(store.get(&newer).await.is_some());
+        assert!(store.get(&pending_id).await.is_some());
+    }
+
+    #[test]
+    fn test_prometheus_format() {
+        let (queue, _receiver) = JobQueue::new(16);
+        queue.enqueue(job()).unwrap();
+
+        let output = queue.metrics().to_prometheus();
+        assert!(output.contains("# TYPE papermake_render_queue_depth gauge"));
+        assert!(output.contains("papermake_render_queue_depth 1\n"));
+        assert!(output.contains("papermake_render_workers_busy 0\n"));
+    }
+}
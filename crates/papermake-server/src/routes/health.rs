@@ -0,0 +1,144 @@
+//! Dependency readiness checks
+//!
+//! `/health` only reports that the process is up. `/health/ready` probes
+//! blob storage and render storage, so load balancers stop routing to an
+//! instance that cannot reach them.
+
+use axum::{Json, extract::State, http::StatusCode};
+use papermake_registry::{BlobStorage, Registry, RenderStorage};
+use serde::Serialize;
+
+use crate::AppState;
+
+/// Outcome of probing one dependency
+#[derive(Debug, Serialize)]
+pub struct DependencyStatus {
+    /// Dependency name, `blob_storage` or `render_storage`
+    pub name: &'static str,
+    /// Whether the probe succeeded
+    pub healthy: bool,
+    /// Why the probe failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of the dependency readiness check
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `ready` if every dependency is healthy, `unavailable` otherwise
+    pub status: &'static str,
+    pub dependencies: Vec<DependencyStatus>,
+    pub timestamp: time::OffsetDateTime,
+}
+
+/// Readiness endpoint probing blob and render storage
+pub async fn dependency_readiness(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    probe_dependencies(&state.registry).await
+}
+
+/// Probe the registry's storages, returning 503 unless all are reachable
+async fn probe_dependencies<S, R>(
+    registry: &Registry<S, R>,
+) -> (StatusCode, Json<ReadinessResponse>)
+where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+{
+    let (blob_storage, render_storage) =
+        tokio::join!(registry.ping_storage(), registry.ping_render_storage());
+    let dependencies = vec![
+        DependencyStatus::new("blob_storage", blob_storage),
+        DependencyStatus::new("render_storage", render_storage),
+    ];
+
+    let ready = dependencies.iter().all(|dependency| dependency.healthy);
+    let response = ReadinessResponse {
+        status: if ready { "ready" } else { "unavailable" },
+        dependencies,
+        timestamp: time::OffsetDateTime::now_utc(),
+    };
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(response))
+}
+
+impl DependencyStatus {
+    fn new<E: std::fmt::Display>(name: &'static str, result: Result<(), E>) -> Self {
+        Self {
+            name,
+            healthy: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use papermake_registry::{
+        render_storage::MemoryRenderStorage,
+        storage::blob_storage::{MemoryStorage, StorageError},
+    };
+
+    /// Blob storage whose backend is unreachable
+    struct UnreachableStorage;
+
+    #[async_trait]
+    impl BlobStorage for UnreachableStorage {
+        async fn put(&self, _key: &str, _data: Vec<u8>) -> Result<(), StorageError> {
+            Err(StorageError::Backend("connection refused".into()))
+        }
+
+        async fn get(&self, _key: &str) -> Result<Vec<u8>, StorageError> {
+            Err(StorageError::Backend("connection refused".into()))
+        }
+
+        async fn exists(&self, _key: &str) -> Result<bool, StorageError> {
+            Err(StorageError::Backend("connection refused".into()))
+        }
+
+        async fn delete(&self, _key: &str) -> Result<(), StorageError> {
+            Err(StorageError::Backend("connection refused".into()))
+        }
+
+        async fn list_keys(&self, _prefix: &str) -> Result<Vec<String>, StorageError> {
+            Err(StorageError::Backend("connection refused".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ready_when_dependencies_reachable() {
+        let registry = Registry::new(MemoryStorage::new(), MemoryRenderStorage::new());
+
+        let (status, Json(response)) = probe_dependencies(&registry).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.status, "ready");
+        assert!(response.dependencies.iter().all(|d| d.healthy));
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_names_failing_dependency() {
+        let registry = Registry::new(UnreachableStorage, MemoryRenderStorage::new());
+
+        let (status, Json(response)) = probe_dependencies(&registry).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.status, "unavailable");
+
+        let failing: Vec<&str> = response
+            .dependencies
+            .iter()
+            .filter(|d| !d.healthy)
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(failing, vec!["blob_storage"]);
+        let error = response.dependencies[0].error.as_deref().unwrap();
+        assert!(error.contains("connection refused"), "error: {}", error);
+    }
+}
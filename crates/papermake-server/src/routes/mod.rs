@@ -1,6 +1,7 @@
 //! HTTP route handlers
 
 pub mod analytics;
+pub mod health;
 // Render
 pub mod render;
 // Retrieve renders
@@ -1,50 +1,736 @@
+use std::{collections::HashMap, convert::Infallible, fmt::Display, sync::Arc, time::Duration};
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{
+        HeaderMap, StatusCode,
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+    },
+    response::Response,
     routing::post,
 };
-
+use base64::Engine;
+use futures::{Stream, StreamExt};
+use http_body_util::Limited;
+use papermake::{
+    InMemoryFileSystem, PapermakeError, RenderOptions, error::CompilationError,
+    render_template_with_options,
+};
+use papermake_registry::{BlobStorage, PreparedTemplate, Registry, RenderStorage};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     AppState,
     error::{ApiError, Result as ApiResult},
-    models::ApiResponse,
+    models::{ApiResponse, RenderErrorResponse, RenderJob, RenderStatus},
+    worker::render_blocking,
 };
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/{reference}", post(render_template))
+    Router::new()
+        .route("/preview", post(render_preview))
+        .route("/{reference}", post(render_template))
+        .route("/{reference}/stream", post(render_stream))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RenderRequest {
     pub data: serde_json::Value,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RenderResponse {
-    pub render_id: String,
-    pub pdf_hash: String,
-    pub duration_ms: u32,
+    pub job_id: String,
+    pub status: RenderStatus,
 }
 
+/// Handler for POST /api/render/{reference} - Submit a render job
+///
+/// The job is queued for the background worker and its id returned right
+/// away; poll `/api/renders/{job_id}` for the outcome.
+#[utoipa::path(
+    post,
+    path = "/api/render/{reference}",
+    tag = "render",
+    params(
+        (
+            "reference" = String,
+            Path,
+            description = "Template reference, e.g. `acme/invoice:latest`",
+        ),
+    ),
+    request_body = RenderRequest,
+    responses(
+        (status = 202, description = "Render job queued", body = ApiResponse<RenderResponse>),
+        (status = 503, description = "Server is shutting down or the render queue is full"),
+    )
+)]
 #[axum::debug_handler]
 pub async fn render_template(
     State(state): State<AppState>,
     Path(reference): Path<String>,
     Json(request): Json<RenderRequest>,
-) -> ApiResult<Json<ApiResponse<RenderResponse>>> {
-    let result = state
-        .registry
-        .render_and_store(&reference, &request.data)
-        .await
-        .map_err(|e| ApiError::RenderFailed(e.to_string()))?;
-
+) -> ApiResult<(StatusCode, Json<ApiResponse<RenderResponse>>)> {
+    let job = RenderJob::builder(reference)
+        .with_data(request.data)
+        .build();
     let response = RenderResponse {
-        render_id: result.render_id,
-        pdf_hash: result.pdf_hash,
-        duration_ms: result.duration_ms,
+        job_id: job.id.clone(),
+        status: job.status(),
+    };
+
+    // Record the job before the worker can pick it up, forgetting it again
+    // if it was never queued
+    let job_id = job.id.clone();
+    state.jobs.insert(job.clone()).await;
+    if let Err(e) = state.job_queue.enqueue(job) {
+        state.jobs.remove(&job_id).await;
+        return Err(e);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::new(response))))
+}
+
+/// Unpublished template to render once, e.g. from an editor playground
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PreviewRequest {
+    /// Source of `main.typ`
+    pub main: String,
+    /// Additional files by path, base64 encoded
+    #[serde(default)]
+    pub files: HashMap<String, String>,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// Handler for POST /api/render/preview - Render an unpublished template
+///
+/// Renders synchronously and returns the PDF without touching the registry
+/// or storing the result. The request is subject to the server's body size
+/// limit and the render to the configured render timeout; compilation
+/// errors are returned as `422` with structured diagnostics.
+#[utoipa::path(
+    post,
+    path = "/api/render/preview",
+    tag = "render",
+    request_body = PreviewRequest,
+    responses(
+        (status = 200, description = "Rendered PDF", content_type = "application/pdf"),
+        (status = 422, description = "Template failed to compile", body = RenderErrorResponse),
+        (status = 504, description = "Render timed out"),
+    )
+)]
+#[axum::debug_handler]
+pub async fn render_preview(
+    State(state): State<AppState>,
+    Json(request): Json<PreviewRequest>,
+) -> ApiResult<Response<Body>> {
+    let timeout = Duration::from_secs(state.config.render_timeout_seconds);
+    let pdf = preview_pdf(request, timeout).await?;
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/pdf")
+        .body(Body::from(pdf))
+        .unwrap())
+}
+
+/// Render a preview request to PDF, giving up after `timeout`
+async fn preview_pdf(request: PreviewRequest, timeout: Duration) -> ApiResult<Vec<u8>> {
+    let mut file_system = InMemoryFileSystem::new();
+    for (path, content) in request.files {
+        let content = base64::engine::general_purpose::STANDARD
+            .decode(content)
+            .map_err(|e| ApiError::bad_request(&format!("Invalid base64 in '{}': {}", path, e)))?;
+        // Typst resolves files relative to the template root, e.g. "/logo.png"
+        let path = format!("/{}", path.trim_start_matches('/'));
+        file_system.add_file(path, content);
+    }
+
+    let options = RenderOptions::default().with_timeout(timeout);
+    let result = tokio::task::spawn_blocking(move || {
+        render_template_with_options(request.main, Arc::new(file_system), &request.data, &options)
+    })
+    .await
+    .map_err(|e| ApiError::internal(&format!("Render task failed: {}", e)))??;
+
+    match result.pdf {
+        Some(pdf) if result.success => Ok(pdf),
+        _ => {
+            let diagnostics: Vec<_> = result.errors.into_iter().map(Into::into).collect();
+            Err(ApiError::Papermake(PapermakeError::Compilation(
+                CompilationError::TypstError {
+                    error_count: diagnostics.len(),
+                    diagnostics,
+                },
+            )))
+        }
+    }
+}
+
+/// What each result line of the streaming endpoint carries
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamOutput {
+    /// Store each PDF and return its render_id
+    #[default]
+    RenderId,
+    /// Return each PDF inline, base64 encoded, without storing it
+    Pdf,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StreamParams {
+    /// What each result line carries
+    #[serde(default)]
+    pub output: StreamOutput,
+}
+
+/// One NDJSON result line, matching the input line at `index`
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct StreamRenderLine {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub render_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl StreamRenderLine {
+    fn error(index: usize, error: impl Display) -> Self {
+        Self {
+            index,
+            error: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Handler for POST /api/render/{reference}/stream - Render many data sets with one template
+///
+/// The body is NDJSON with one data object per line. The reference is resolved
+/// once and every line is rendered with the same compiled template; one result
+/// line is streamed back per non-empty input line, in order. A line that fails
+/// to parse or render yields an `error` line and the stream continues, as
+/// does a line longer than `STREAM_MAX_LINE_BYTES`. The body is capped at
+/// `STREAM_MAX_BODY_BYTES`; reading past it ends the stream with an error line.
+#[utoipa::path(
+    post,
+    path = "/api/render/{reference}/stream",
+    tag = "render",
+    params(
+        (
+            "reference" = String,
+            Path,
+            description = "Template reference, e.g. `acme/invoice:latest`",
+        ),
+        StreamParams,
+    ),
+    request_body(
+        content = String,
+        content_type = "application/x-ndjson",
+        description = "One JSON data object per line"
+    ),
+    responses(
+        (
+            status = 200,
+            description = "One result line per input line",
+            body = StreamRenderLine,
+            content_type = "application/x-ndjson",
+        ),
+        (status = 413, description = "Declared body exceeds the stream body limit"),
+    )
+)]
+#[axum::debug_handler]
+pub async fn render_stream(
+    State(state): State<AppState>,
+    Path(reference): Path<String>,
+    Query(params): Query<StreamParams>,
+    headers: HeaderMap,
+    body: Body,
+) -> ApiResult<Response<Body>> {
+    let body = limit_body(&headers, body, state.config.stream_max_body_bytes)?;
+    let prepared = state.registry.prepare(&reference).await?;
+
+    let results = render_ndjson_stream(
+        state.registry.clone(),
+        prepared,
+        body.into_data_stream(),
+        params.output,
+        Duration::from_secs(state.config.render_timeout_seconds),
+        state.config.stream_max_line_bytes,
+    );
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(results))
+        .unwrap())
+}
+
+/// Cap a request body at `max_bytes`
+///
+/// A declared `Content-Length` above the limit is rejected up front with
+/// `413`. A body growing past it while streaming fails with a read error.
+fn limit_body(headers: &HeaderMap, body: Body, max_bytes: usize) -> ApiResult<Body> {
+    let declared = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > max_bytes as u64) {
+        return Err(ApiError::PayloadTooLarge(format!(
+            "Request body exceeds {} bytes",
+            max_bytes
+        )));
+    }
+
+    Ok(Body::new(Limited::new(body, max_bytes)))
+}
+
+/// State threaded through the NDJSON render stream
+struct NdjsonRender<S: BlobStorage, R: RenderStorage, B> {
+    registry: Arc<Registry<S, R>>,
+    prepared: PreparedTemplate,
+    body: B,
+    buffer: Vec<u8>,
+    index: usize,
+    output: StreamOutput,
+    timeout: Duration,
+    max_line: usize,
+    /// Discarding the rest of a line that exceeded `max_line`
+    skipping: bool,
+    body_done: bool,
+}
+
+/// Render every NDJSON line of `body` with `prepared`, yielding one result line each
+///
+/// The stream is pull-based: the body is only read when the consumer polls for
+/// the next result, so a slow client pauses reading the request body instead of
+/// buffering rendered PDFs in memory. Each line compiles on the blocking
+/// thread pool and fails with a timeout error after `timeout`. A line longer
+/// than `max_line` bytes is answered with an error and skipped without being
+/// buffered in full.
+fn render_ndjson_stream<S, R, B, E>(
+    registry: Arc<Registry<S, R>>,
+    prepared: PreparedTemplate,
+    body: B,
+    output: StreamOutput,
+    timeout: Duration,
+    max_line: usize,
+) -> impl Stream<Item = Result<Bytes, Infallible>>
+where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+    B: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    let state = NdjsonRender {
+        registry,
+        prepared,
+        body,
+        buffer: Vec::new(),
+        index: 0,
+        output,
+        timeout,
+        max_line,
+        skipping: false,
+        body_done: false,
     };
 
-    Ok(Json(ApiResponse::new(response)))
+    futures::stream::unfold(state, |mut state| async move {
+        let line = match state.next_line().await? {
+            Ok(line) => state.render_line(&line).await,
+            Err(e) => StreamRenderLine::error(state.index, e),
+        };
+        state.index += 1;
+
+        let mut bytes = serde_json::to_vec(&line).unwrap_or_default();
+        bytes.push(b'\n');
+        Some((Ok(Bytes::from(bytes)), state))
+    })
+}
+
+impl<S, R, B, E> NdjsonRender<S, R, B>
+where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+    B: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    /// Read the next non-empty line, pulling more of the body only when needed
+    ///
+    /// Never buffers much more than `max_line` bytes: an oversized line is
+    /// reported once and its remainder dropped as it arrives.
+    async fn next_line(&mut self) -> Option<Result<Vec<u8>, String>> {
+        loop {
+            if let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                if std::mem::take(&mut self.skipping) || line.trim_ascii().is_empty() {
+                    continue;
+                }
+                if line.len() > self.max_line {
+                    return Some(Err(self.line_too_long()));
+                }
+                return Some(Ok(line));
+            }
+
+            if self.buffer.len() > self.max_line {
+                self.buffer.clear();
+                if !std::mem::replace(&mut self.skipping, true) {
+                    return Some(Err(self.line_too_long()));
+                }
+            }
+
+            if self.body_done {
+                let rest = std::mem::take(&mut self.buffer);
+                if std::mem::take(&mut self.skipping) {
+                    return None;
+                }
+                return (!rest.trim_ascii().is_empty()).then_some(Ok(rest));
+            }
+
+            match self.body.next().await {
+                Some(Ok(chunk)) => self.buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    self.body_done = true;
+                    self.buffer.clear();
+                    return Some(Err(format!("Failed to read body: {}", e)));
+                }
+                None => self.body_done = true,
+            }
+        }
+    }
+
+    fn line_too_long(&self) -> String {
+        format!("Line exceeds {} bytes", self.max_line)
+    }
+
+    async fn render_line(&mut self, line: &[u8]) -> StreamRenderLine {
+        let data: serde_json::Value = match serde_json::from_slice(line) {
+            Ok(data) => data,
+            Err(e) => return StreamRenderLine::error(self.index, format!("Invalid JSON: {}", e)),
+        };
+
+        match self.output {
+            StreamOutput::RenderId => {
+                let start_time = std::time::Instant::now();
+                let outcome = render_blocking(&self.prepared, &data, self.timeout)
+                    .await
+                    .map(|pdf_bytes| (self.prepared.manifest_hash().to_string(), pdf_bytes));
+                let duration_ms = start_time.elapsed().as_millis() as u32;
+
+                match self
+                    .registry
+                    .store_rendered(self.prepared.reference(), &data, outcome, duration_ms)
+                    .await
+                {
+                    Ok(result) => StreamRenderLine {
+                        index: self.index,
+                        render_id: Some(result.render_id),
+                        pdf_hash: Some(result.pdf_hash),
+                        duration_ms: Some(result.duration_ms),
+                        ..Default::default()
+                    },
+                    Err(e) => StreamRenderLine::error(self.index, e),
+                }
+            }
+            StreamOutput::Pdf => {
+                let start_time = std::time::Instant::now();
+                match render_blocking(&self.prepared, &data, self.timeout).await {
+                    Ok(pdf_bytes) => StreamRenderLine {
+                        index: self.index,
+                        pdf: Some(base64::engine::general_purpose::STANDARD.encode(pdf_bytes)),
+                        duration_ms: Some(start_time.elapsed().as_millis() as u32),
+                        ..Default::default()
+                    },
+                    Err(e) => StreamRenderLine::error(self.index, e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use papermake_registry::{
+        bundle::{TemplateBundle, TemplateMetadata},
+        render_storage::MemoryRenderStorage,
+        storage::blob_storage::{MemoryStorage, StorageError},
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Memory storage counting reference lookups
+    #[derive(Default)]
+    struct CountingStorage {
+        inner: MemoryStorage,
+        ref_lookups: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl BlobStorage for CountingStorage {
+        async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+            self.inner.put(key, data).await
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            if key.starts_with("refs/") {
+                self.ref_lookups.fetch_add(1, Ordering::Relaxed);
+            }
+            self.inner.get(key).await
+        }
+
+        async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+            self.inner.exists(key).await
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            self.inner.delete(key).await
+        }
+
+        async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+            self.inner.list_keys(prefix).await
+        }
+    }
+
+    async fn setup() -> (
+        Arc<Registry<CountingStorage, MemoryRenderStorage>>,
+        PreparedTemplate,
+        Arc<AtomicUsize>,
+    ) {
+        let storage = CountingStorage::default();
+        let ref_lookups = storage.ref_lookups.clone();
+        let registry = Arc::new(Registry::new(storage, MemoryRenderStorage::new()));
+        let bundle = TemplateBundle::new(
+            b"= Invoice\nNumber: #data.number".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+        let prepared = registry.prepare("acme/invoice:latest").await.unwrap();
+
+        (registry, prepared, ref_lookups)
+    }
+
+    async fn collect_lines(
+        stream: impl Stream<Item = Result<Bytes, Infallible>>,
+    ) -> Vec<serde_json::Value> {
+        let output: Vec<u8> = stream.map(|chunk| chunk.unwrap().to_vec()).concat().await;
+        output
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_renders_every_line_with_one_resolution() {
+        let (registry, prepared, ref_lookups) = setup().await;
+
+        let body: String = (0..1000)
+            .map(|i| format!("{{\"number\": {}}}\n", i))
+            .collect();
+        // Split the body into chunks that do not align with line boundaries
+        let chunks: Vec<Result<Bytes, Infallible>> = body
+            .as_bytes()
+            .chunks(37)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+
+        let lines = collect_lines(render_ndjson_stream(
+            registry.clone(),
+            prepared,
+            futures::stream::iter(chunks),
+            StreamOutput::RenderId,
+            Duration::from_secs(30),
+            1 << 20,
+        ))
+        .await;
+
+        assert_eq!(lines.len(), 1000);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line["index"], i);
+            assert!(line["render_id"].is_string(), "line {}: {}", i, line);
+        }
+        assert_eq!(
+            registry.list_recent_renders(2000).await.unwrap().len(),
+            1000
+        );
+        assert_eq!(ref_lookups.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_preview_renders_unpublished_template() {
+        let request = PreviewRequest {
+            main: "#image(\"logo.svg\", width: 1cm)\n= Invoice #data.number".to_string(),
+            files: HashMap::from([(
+                "logo.svg".to_string(),
+                base64::engine::general_purpose::STANDARD
+                    .encode(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"/>"#),
+            )]),
+            data: serde_json::json!({"number": 42}),
+        };
+
+        let pdf = preview_pdf(request, Duration::from_secs(30)).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_reports_diagnostics() {
+        let request = PreviewRequest {
+            main: "= Invoice\n#totl".to_string(),
+            files: HashMap::new(),
+            data: serde_json::Value::Null,
+        };
+
+        let error = preview_pdf(request, Duration::from_secs(30))
+            .await
+            .unwrap_err();
+        let ApiError::Papermake(PapermakeError::Compilation(CompilationError::TypstError {
+            diagnostics,
+            ..
+        })) = error
+        else {
+            panic!("expected compilation error, got {:?}", error);
+        };
+        assert!(diagnostics[0].message.contains("totl"));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().line, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_reports_errors_per_line() {
+        let (registry, prepared, _) = setup().await;
+
+        let body = "{\"number\": 1}\n\nnot json\n{}\n{\"number\": 2}";
+        let chunks = vec![Ok::<_, Infallible>(Bytes::from(body))];
+
+        let lines = collect_lines(render_ndjson_stream(
+            registry,
+            prepared,
+            futures::stream::iter(chunks),
+            StreamOutput::Pdf,
+            Duration::from_secs(30),
+            1 << 20,
+        ))
+        .await;
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0]["pdf"].is_string());
+        assert!(
+            lines[1]["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid JSON")
+        );
+        assert!(lines[2]["error"].is_string());
+        assert_eq!(lines[3]["index"], 3);
+        assert!(lines[3]["pdf"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_stream_rejects_oversized_lines() {
+        let (registry, prepared, _) = setup().await;
+
+        // The oversized line arrives in small chunks without a newline for a while
+        let long_line = format!("{{\"number\": \"{}\"}}\n", "9".repeat(4096));
+        let body = format!("{}{{\"number\": 2}}\n", long_line);
+        let chunks: Vec<Result<Bytes, Infallible>> = body
+            .as_bytes()
+            .chunks(100)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+
+        let lines = collect_lines(render_ndjson_stream(
+            registry,
+            prepared,
+            futures::stream::iter(chunks),
+            StreamOutput::Pdf,
+            Duration::from_secs(30),
+            1024,
+        ))
+        .await;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["error"], "Line exceeds 1024 bytes");
+        assert_eq!(lines[1]["index"], 1);
+        assert!(lines[1]["pdf"].is_string());
+    }
+
+    #[test]
+    fn test_limit_body_rejects_declared_oversized_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_LENGTH, "2048".parse().unwrap());
+
+        assert!(matches!(
+            limit_body(&headers, Body::from("{}"), 1024),
+            Err(ApiError::PayloadTooLarge(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_fails_once_body_exceeds_limit() {
+        let (registry, prepared, _) = setup().await;
+        let body = limit_body(
+            &HeaderMap::new(),
+            Body::from("{\"number\": 1}\n".repeat(100)),
+            64,
+        )
+        .unwrap();
+
+        let lines = collect_lines(render_ndjson_stream(
+            registry,
+            prepared,
+            body.into_data_stream(),
+            StreamOutput::Pdf,
+            Duration::from_secs(30),
+            1 << 20,
+        ))
+        .await;
+
+        assert_eq!(lines.len(), 1);
+        assert!(
+            lines[0]["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Failed to read body"),
+            "{}",
+            lines[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_applies_render_timeout() {
+        let (registry, prepared, _) = setup().await;
+        let chunks = vec![Ok::<_, Infallible>(Bytes::from("{\"number\": 1}\n"))];
+
+        let lines = collect_lines(render_ndjson_stream(
+            registry.clone(),
+            prepared,
+            futures::stream::iter(chunks),
+            StreamOutput::RenderId,
+            Duration::ZERO,
+            1 << 20,
+        ))
+        .await;
+
+        assert!(
+            lines[0]["error"].as_str().unwrap().contains("timed out"),
+            "{}",
+            lines[0]
+        );
+        // The timed out render is still recorded, as a failure
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].success);
+    }
 }
@@ -3,79 +3,224 @@ use axum::{
     body::Body,
     extract::{Path, Query, State},
     http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
-    response::Response,
+    response::{IntoResponse, Response},
     routing::get,
 };
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     AppState,
     error::{ApiError, Result as ApiResult},
-    models::api::{PaginatedResponse, PaginationQuery},
+    models::{
+        ApiResponse, RenderJobStatus,
+        api::{PaginatedResponse, PaginationInfo, default_limit},
+    },
+    queue::JobStore,
 };
 
-use papermake_registry::render_storage::types::RenderRecord;
+use papermake_registry::{
+    BlobStorage, Page, Registry, RegistryError, RenderStorage, render_storage::types::RenderRecord,
+};
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(list_renders))
+        .route("/{job_id}", get(get_render_job))
         .route("/{render_id}/pdf", get(get_render_pdf))
 }
 
-/// Handler for GET /api/renders - List recent renders with pagination
+/// Query parameters for GET /api/renders
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RenderListQuery {
+    /// Maximum number of renders to return (default: 50)
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    /// Only list renders older than this RFC 3339 timestamp
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub before: Option<time::OffsetDateTime>,
+    /// Render ID of the last render seen, to also list older renders
+    /// sharing the `before` timestamp
+    pub before_id: Option<String>,
+}
+
+/// Handler for GET /api/renders - List renders, newest first
+///
+/// Pages are chained by a cursor: pass `pagination.next_before` and
+/// `pagination.next_before_id` of one response as `before` and `before_id`
+/// to get the next page.
+#[utoipa::path(
+    get,
+    path = "/api/renders",
+    tag = "renders",
+    params(RenderListQuery),
+    responses(
+        (
+            status = 200,
+            description = "Renders, newest first",
+            body = PaginatedResponse<RenderRecord>,
+        ),
+    )
+)]
 #[axum::debug_handler]
 pub async fn list_renders(
     State(state): State<AppState>,
-    Query(pagination): Query<PaginationQuery>,
+    Query(query): Query<RenderListQuery>,
 ) -> ApiResult<Json<PaginatedResponse<RenderRecord>>> {
-    let renders = state
-        .registry
-        .list_recent_renders(pagination.limit + 1) // Get one extra to check if there are more
-        .await
-        .map_err(|e| match e {
-            papermake_registry::RegistryError::RenderStorage(_) => {
-                ApiError::Internal("Failed to fetch render records".to_string())
-            }
-            _ => ApiError::Internal(e.to_string()),
-        })?;
-
-    // Apply offset manually since the registry method doesn't support it yet
-    let mut data: Vec<RenderRecord> = renders
-        .into_iter()
-        .skip(pagination.offset as usize)
-        .collect();
+    Ok(Json(render_page(&state.registry, &query).await?))
+}
+
+/// Fetch the page of render records selected by `query`
+async fn render_page<S, R>(
+    registry: &Registry<S, R>,
+    query: &RenderListQuery,
+) -> ApiResult<PaginatedResponse<RenderRecord>>
+where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+{
+    // Get one extra to check if there are more
+    let mut page = Page::new(query.limit.saturating_add(1));
+    page.before = query.before;
+    page.before_id = query.before_id.clone();
+    let mut data = registry.list_renders(page).await.map_err(|e| match e {
+        papermake_registry::RegistryError::RenderStorage(_) => {
+            ApiError::Internal("Failed to fetch render records".to_string())
+        }
+        _ => ApiError::Internal(e.to_string()),
+    })?;
 
     // Check if there are more records and trim to requested limit
-    let has_more = data.len() > pagination.limit as usize;
-    data.truncate(pagination.limit as usize);
+    let has_more = data.len() > query.limit as usize;
+    data.truncate(query.limit as usize);
+    let cursor = data.last().filter(|_| has_more);
+    let next_before = cursor.map(|record| record.timestamp);
+    let next_before_id = cursor.map(|record| record.render_id.clone());
 
-    let response = PaginatedResponse {
+    Ok(PaginatedResponse {
         data,
-        pagination: crate::models::api::PaginationInfo {
-            limit: pagination.limit,
-            offset: pagination.offset,
+        pagination: PaginationInfo {
+            limit: query.limit,
+            offset: 0,
             total: None, // We don't have total count yet
             has_more,
+            next_before,
+            next_before_id,
         },
-    };
+    })
+}
+
+/// Handler for GET /api/renders/{job_id} - Status of a submitted render job
+///
+/// Completed jobs carry a `pdf_url` pointing at the rendered PDF.
+#[utoipa::path(
+    get,
+    path = "/api/renders/{job_id}",
+    tag = "renders",
+    params(("job_id" = String, Path, description = "Id returned when submitting the job")),
+    responses(
+        (status = 200, description = "Job status", body = ApiResponse<RenderJobStatus>),
+        (status = 404, description = "No such job"),
+    )
+)]
+#[axum::debug_handler(state = AppState)]
+pub async fn get_render_job(
+    State(jobs): State<JobStore>,
+    Path(job_id): Path<String>,
+) -> ApiResult<Json<ApiResponse<RenderJobStatus>>> {
+    let job = jobs
+        .get(&job_id)
+        .await
+        .ok_or_else(|| ApiError::render_not_found(&job_id))?;
 
-    Ok(Json(response))
+    Ok(Json(ApiResponse::new(RenderJobStatus::from(&job))))
 }
 
+/// Query parameters for GET /api/renders/{render_id}/pdf
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct RenderPdfQuery {
+    /// Return a presigned `pdf_url` instead of the PDF itself
+    #[serde(default)]
+    pub presign: bool,
+}
+
+/// Presigned download location of a rendered PDF
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RenderPdfUrl {
+    pub pdf_url: String,
+    /// Seconds until `pdf_url` expires
+    pub expires_in: u64,
+}
+
+/// Handler for GET /api/renders/{render_id}/pdf
+///
+/// With `?presign=true`, responds with a [`RenderPdfUrl`] from which the
+/// client downloads the PDF straight from blob storage. Blob storage that
+/// cannot presign URLs falls back to returning the PDF bytes.
+#[utoipa::path(
+    get,
+    path = "/api/renders/{render_id}/pdf",
+    tag = "renders",
+    params(
+        ("render_id" = String, Path, description = "Id of a stored render"),
+        RenderPdfQuery,
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The PDF, or where to download it with `presign=true`",
+            content(
+                (ApiResponse<RenderPdfUrl> = "application/json"),
+                ("application/pdf"),
+            ),
+        ),
+        (status = 404, description = "No such render"),
+    )
+)]
 #[axum::debug_handler]
 pub async fn get_render_pdf(
     State(state): State<AppState>,
     Path(render_id): Path<String>,
+    Query(query): Query<RenderPdfQuery>,
 ) -> ApiResult<Response<Body>> {
-    let pdf_bytes = state
-        .registry
-        .get_render_pdf(&render_id)
+    let presign_ttl = query
+        .presign
+        .then(|| Duration::from_secs(state.config.presigned_url_ttl_seconds));
+    render_pdf_response(&state.registry, &render_id, presign_ttl).await
+}
+
+async fn render_pdf_response<S, R>(
+    registry: &Registry<S, R>,
+    render_id: &str,
+    presign_ttl: Option<Duration>,
+) -> ApiResult<Response<Body>>
+where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+{
+    let not_found = |e: RegistryError| match e {
+        RegistryError::RenderStorage(_) => ApiError::render_not_found(render_id),
+        _ => ApiError::Internal(e.to_string()),
+    };
+
+    if let Some(ttl) = presign_ttl
+        && let Some(pdf_url) = registry
+            .get_render_pdf_url(render_id, ttl)
+            .await
+            .map_err(not_found)?
+    {
+        let url = RenderPdfUrl {
+            pdf_url,
+            expires_in: ttl.as_secs(),
+        };
+        return Ok(Json(ApiResponse::new(url)).into_response());
+    }
+
+    let pdf_bytes = registry
+        .get_render_pdf(render_id)
         .await
-        .map_err(|e| match e {
-            papermake_registry::RegistryError::RenderStorage(_) => {
-                ApiError::render_not_found(&render_id)
-            }
-            _ => ApiError::Internal(e.to_string()),
-        })?;
+        .map_err(not_found)?;
 
     let filename = format!("render-{}.pdf", render_id);
 
@@ -88,3 +233,141 @@ pub async fn get_render_pdf(
         .body(Body::from(pdf_bytes))
         .unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RenderJob, RenderStatus};
+    use axum::{body::to_bytes, http::StatusCode};
+    use papermake_registry::{
+        bundle::{TemplateBundle, TemplateMetadata},
+        render_storage::MemoryRenderStorage,
+        storage::blob_storage::MemoryStorage,
+    };
+
+    async fn job_status(jobs: &JobStore, job_id: &str) -> ApiResult<RenderJobStatus> {
+        let Json(response) = get_render_job(State(jobs.clone()), Path(job_id.to_string())).await?;
+        Ok(response.data)
+    }
+
+    #[tokio::test]
+    async fn test_render_job_status_follows_job() {
+        let jobs = JobStore::default();
+        let mut job = RenderJob::builder("acme/invoice:latest")
+            .with_id("job-1")
+            .build();
+        jobs.insert(job.clone()).await;
+
+        let status = job_status(&jobs, "job-1").await.unwrap();
+        assert_eq!(status.status, RenderStatus::Pending);
+        assert!(status.completed_at.is_none());
+        assert!(status.pdf_url.is_none());
+
+        job.transition(RenderStatus::InProgress).unwrap();
+        job.complete("render-1", "sha256:pdf", 42).unwrap();
+        jobs.insert(job).await;
+
+        let status = job_status(&jobs, "job-1").await.unwrap();
+        assert_eq!(status.status, RenderStatus::Completed);
+        assert!(status.completed_at.is_some());
+        assert_eq!(status.rendering_latency, Some(42));
+        assert!(status.error_message.is_none());
+        assert_eq!(status.pdf_url.as_deref(), Some("/api/renders/render-1/pdf"));
+
+        assert!(matches!(
+            job_status(&jobs, "unknown").await,
+            Err(ApiError::RenderNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_render_pages_chain_without_gaps() {
+        let render_storage = MemoryRenderStorage::new();
+        let start = time::OffsetDateTime::now_utc();
+        for i in 0..25 {
+            let mut record = RenderRecord::success(
+                "acme/invoice:latest".to_string(),
+                "acme/invoice".to_string(),
+                "latest".to_string(),
+                "sha256:manifest".to_string(),
+                format!("sha256:data{}", i),
+                "sha256:pdf".to_string(),
+                10,
+                1024,
+            );
+            // Groups of three share a timestamp, one of them across the page boundary
+            record.timestamp = start - time::Duration::seconds(i / 3);
+            render_storage.store_render(record).await.unwrap();
+        }
+        let registry = Registry::new(MemoryStorage::new(), render_storage);
+
+        let first = render_page(
+            &registry,
+            &RenderListQuery {
+                limit: 20,
+                before: None,
+                before_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.data.len(), 20);
+        assert!(first.pagination.has_more);
+
+        let second = render_page(
+            &registry,
+            &RenderListQuery {
+                limit: 20,
+                before: first.pagination.next_before,
+                before_id: first.pagination.next_before_id.clone(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(second.data.len(), 5);
+        assert!(!second.pagination.has_more);
+        assert!(second.pagination.next_before.is_none());
+
+        // Every record exactly once
+        let mut data_hashes: Vec<&str> = first
+            .data
+            .iter()
+            .chain(&second.data)
+            .map(|record| record.data_hash.as_str())
+            .collect();
+        data_hashes.sort();
+        data_hashes.dedup();
+        assert_eq!(data_hashes.len(), 25);
+    }
+
+    #[tokio::test]
+    async fn test_presign_falls_back_to_inline_pdf() {
+        let registry = Registry::new(MemoryStorage::new(), MemoryRenderStorage::new());
+        let bundle = TemplateBundle::new(
+            b"= Invoice".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+        let render = registry
+            .render_and_store("acme/invoice:latest", &serde_json::json!({}))
+            .await
+            .unwrap();
+
+        // Memory storage cannot presign, so the PDF is returned inline
+        let response =
+            render_pdf_response(&registry, &render.render_id, Some(Duration::from_secs(60)))
+                .await
+                .unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/pdf");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.starts_with(b"%PDF"));
+
+        let error = render_pdf_response(&registry, "unknown", Some(Duration::from_secs(60)))
+            .await
+            .unwrap_err();
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -3,23 +3,28 @@
 use crate::{
     AppState,
     error::{ApiError, Result},
-    models::api::{ApiResponse, PaginatedResponse, SearchQuery},
+    mime::MimeTypes,
+    models::api::{ApiResponse, PaginatedResponse, PaginationQuery, SearchQuery},
 };
 use axum::{
     Json, Router,
+    body::Body,
     extract::{Multipart, Path, Query, State},
+    http::header::CONTENT_TYPE,
+    response::Response,
     routing::{get, post},
 };
 use papermake_registry::{
-    TemplateInfo,
+    BlobStorage, Registry, RenderStorage, TemplateInfo,
     bundle::{TemplateBundle, TemplateMetadata},
     reference::Reference,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
 /// Query parameters for publishing a template
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PublishParams {
     /// Tag for the template (defaults to "latest")
     #[serde(default = "default_tag")]
@@ -31,7 +36,7 @@ fn default_tag() -> String {
 }
 
 /// Response after successfully publishing a template
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PublishResponse {
     /// Success message
     pub message: String,
@@ -42,7 +47,7 @@ pub struct PublishResponse {
 }
 
 /// Simplified request for publishing a template with JSON payload
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct PublishSimpleRequest {
     /// Main template file content (UTF-8 string)
     pub main_typ: String,
@@ -53,7 +58,7 @@ pub struct PublishSimpleRequest {
 }
 
 /// Template metadata response for API
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TemplateMetadataResponse {
     /// Template name
     pub name: String,
@@ -79,6 +84,7 @@ pub fn router() -> Router<AppState> {
         .route("/{name}/publish-simple", post(publish_template_simple))
         .route("/{name}/tags", get(list_template_tags))
         .route("/{reference}", get(get_template_metadata))
+        .route("/{reference}/files/{*path}", get(get_template_file))
 }
 
 /// List all templates in the registry
@@ -88,6 +94,19 @@ pub fn router() -> Router<AppState> {
 /// - limit: Maximum number of templates to return (default: 50)
 /// - offset: Number of templates to skip (default: 0)
 /// - search: Search term to filter templates by name
+#[utoipa::path(
+    get,
+    path = "/api/templates",
+    tag = "templates",
+    params(PaginationQuery, SearchQuery),
+    responses(
+        (
+            status = 200,
+            description = "Templates in the registry",
+            body = PaginatedResponse<TemplateInfo>,
+        ),
+    )
+)]
 pub async fn list_templates(
     State(state): State<AppState>,
     Query(query): Query<SearchQuery>,
@@ -138,6 +157,25 @@ pub async fn list_templates(
 /// - metadata: JSON metadata with name and author (required)
 /// - schema: Optional JSON schema file
 /// - files[]: Additional template files (optional, multiple)
+#[utoipa::path(
+    post,
+    path = "/api/templates/{name}/publish",
+    tag = "templates",
+    params(
+        ("name" = String, Path, description = "Template name, optionally namespaced"),
+        PublishParams,
+    ),
+    request_body(
+        content = String,
+        content_type = "multipart/form-data",
+        description = "Fields `main_typ`, `metadata`, optional `schema` and `files[<path>]`"
+    ),
+    responses(
+        (status = 200, description = "Template published", body = ApiResponse<PublishResponse>),
+        (status = 400, description = "Invalid bundle"),
+        (status = 413, description = "Bundle exceeds the registry's limits"),
+    )
+)]
 pub async fn publish_template(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -176,7 +214,7 @@ pub async fn publish_template(
             }
             field_name if field_name.starts_with("files[") => {
                 // Extract filename from field name like "files[components/header.typ]"
-                if let Some(filename) = extract_filename_from_field(&field_name) {
+                if let Some(filename) = extract_filename_from_field(field_name) {
                     files.insert(filename, data.to_vec());
                 }
             }
@@ -238,6 +276,21 @@ pub async fn publish_template(
 ///   "schema": { "optional": "json schema object" },
 ///   "metadata": { "name": "template name", "author": "author email" }
 /// }
+#[utoipa::path(
+    post,
+    path = "/api/templates/{name}/publish-simple",
+    tag = "templates",
+    params(
+        ("name" = String, Path, description = "Template name, optionally namespaced"),
+        PublishParams,
+    ),
+    request_body = PublishSimpleRequest,
+    responses(
+        (status = 200, description = "Template published", body = ApiResponse<PublishResponse>),
+        (status = 400, description = "Invalid bundle"),
+        (status = 413, description = "Bundle exceeds the registry's limits"),
+    )
+)]
 pub async fn publish_template_simple(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -281,6 +334,16 @@ pub async fn publish_template_simple(
 /// List all tags for a specific template
 ///
 /// GET /api/templates/{name}/tags
+#[utoipa::path(
+    get,
+    path = "/api/templates/{name}/tags",
+    tag = "templates",
+    params(("name" = String, Path, description = "Template name, optionally namespaced")),
+    responses(
+        (status = 200, description = "Tags of the template", body = ApiResponse<Vec<String>>),
+        (status = 404, description = "No such template"),
+    )
+)]
 pub async fn list_template_tags(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -305,6 +368,26 @@ pub async fn list_template_tags(
 /// - name:tag
 /// - namespace/name
 /// - namespace/name:tag
+#[utoipa::path(
+    get,
+    path = "/api/templates/{reference}",
+    tag = "templates",
+    params(
+        (
+            "reference" = String,
+            Path,
+            description = "Template reference, e.g. `acme/invoice:latest`",
+        ),
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Template metadata",
+            body = ApiResponse<TemplateMetadataResponse>,
+        ),
+        (status = 404, description = "No such template"),
+    )
+)]
 pub async fn get_template_metadata(
     State(state): State<AppState>,
     Path(reference): Path<String>,
@@ -324,10 +407,7 @@ pub async fn get_template_metadata(
     let templates = state.registry.list_templates().await?;
     let template = templates
         .iter()
-        .find(|t| {
-            let template_matches = t.name == parsed_ref.name && t.namespace == parsed_ref.namespace;
-            template_matches
-        })
+        .find(|t| t.name == parsed_ref.name && t.namespace == parsed_ref.namespace)
         .ok_or_else(|| ApiError::template_not_found(&reference))?;
 
     let tag = parsed_ref.tag_or_default();
@@ -344,6 +424,54 @@ pub async fn get_template_metadata(
     Ok(Json(ApiResponse::new(response_data)))
 }
 
+/// Serve a single file from a template bundle
+///
+/// GET /api/templates/{reference}/files/{path}
+///
+/// The `Content-Type` is looked up from the file extension in the
+/// configured [`MimeTypes`], falling back to `application/octet-stream`.
+#[utoipa::path(
+    get,
+    path = "/api/templates/{reference}/files/{path}",
+    tag = "templates",
+    params(
+        (
+            "reference" = String,
+            Path,
+            description = "Template reference, e.g. `acme/invoice:latest`",
+        ),
+        ("path" = String, Path, description = "Path of the file within the bundle"),
+    ),
+    responses(
+        (status = 200, description = "File content, typed by extension"),
+        (status = 404, description = "No such template or file"),
+    )
+)]
+pub async fn get_template_file(
+    State(state): State<AppState>,
+    Path((reference, path)): Path<(String, String)>,
+) -> Result<Response> {
+    serve_template_file(&state.registry, &state.config.mime_types, &reference, &path).await
+}
+
+async fn serve_template_file<S, R>(
+    registry: &Registry<S, R>,
+    mime_types: &MimeTypes,
+    reference: &str,
+    path: &str,
+) -> Result<Response>
+where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+{
+    let content = registry.get_template_file(reference, path).await?;
+
+    Response::builder()
+        .header(CONTENT_TYPE, mime_types.for_path(path))
+        .body(Body::from(content))
+        .map_err(|e| ApiError::internal(&format!("Failed to build response: {}", e)))
+}
+
 /// Extract filename from multipart field name like "files[components/header.typ]"
 fn extract_filename_from_field(field_name: &str) -> Option<String> {
     if field_name.starts_with("files[") && field_name.ends_with(']') {
@@ -358,6 +486,51 @@ fn extract_filename_from_field(field_name: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+    use papermake_registry::{
+        render_storage::MemoryRenderStorage, storage::blob_storage::MemoryStorage,
+    };
+
+    #[tokio::test]
+    async fn test_serve_template_file_content_types() {
+        let registry = Registry::new(MemoryStorage::new(), MemoryRenderStorage::new());
+        let bundle = TemplateBundle::new(
+            b"#include \"parts/header.typ\"".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        )
+        .add_file("parts/header.typ", b"= Invoice".to_vec())
+        .add_file("assets/blob.xyz", vec![0, 1, 2]);
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        let mime_types = MimeTypes::default()
+            .with_overrides("typ=text/x-typst; charset=utf-8")
+            .unwrap();
+
+        let response =
+            serve_template_file(&registry, &mime_types, "acme/invoice", "parts/header.typ")
+                .await
+                .unwrap();
+        assert_eq!(
+            response.headers()[CONTENT_TYPE],
+            "text/x-typst; charset=utf-8"
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"= Invoice");
+
+        let response =
+            serve_template_file(&registry, &mime_types, "acme/invoice", "assets/blob.xyz")
+                .await
+                .unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/octet-stream");
+
+        let error = serve_template_file(&registry, &mime_types, "acme/invoice", "missing.typ")
+            .await
+            .unwrap_err();
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+    }
 
     #[test]
     fn test_extract_filename_from_field() {
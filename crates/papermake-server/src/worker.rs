@@ -1,181 +1,441 @@
 //! Background worker for processing render jobs
+//!
+//! Jobs submitted through `/api/render` are taken from the [`JobReceiver`]
+//! one at a time. Each is compiled on the blocking thread pool under the
+//! render timeout, see [`render_blocking`], and stored with
+//! `Registry::store_rendered`, which uploads the PDF and records the render. Every status change is written
+//! to the [`JobStore`] so clients can poll `/api/renders/{job_id}`.
+//!
+//! On shutdown the queue is closed and [`drain_render_worker`] gives the
+//! worker a bounded time to finish the jobs it already accepted.
 
-/*
- * Legacy, needs to be updated
- */
+use std::{sync::Arc, time::Duration};
 
-use crate::{AppState, error::Result};
-use tokio::time::Instant;
-use tracing::{debug, error, info};
+use papermake::error::CompilationError;
+use papermake_registry::{BlobStorage, PreparedTemplate, Registry, RegistryError, RenderStorage};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
-/// Helper function to parse version string to u64 for legacy compatibility
-fn parse_version_to_u64(version: &str) -> u64 {
-    if let Some(v) = version.strip_prefix('v') {
-        v.parse().unwrap_or(1)
-    } else {
-        version.parse().unwrap_or(1)
+use crate::{
+    AppState,
+    error::registry_diagnostics,
+    models::{RenderJob, RenderStatus},
+    queue::{JobReceiver, JobStore},
+};
+
+/// Spawn the render worker in the background
+pub fn spawn_render_worker(state: AppState, job_receiver: JobReceiver) -> JoinHandle<()> {
+    let render_timeout = Duration::from_secs(state.config.render_timeout_seconds);
+    tokio::spawn(run_render_worker(
+        state.registry,
+        state.jobs,
+        job_receiver,
+        render_timeout,
+    ))
+}
+
+/// Process jobs until the queue is closed, failing any that render longer than `render_timeout`
+pub async fn run_render_worker<S, R>(
+    registry: Arc<Registry<S, R>>,
+    jobs: JobStore,
+    mut job_receiver: JobReceiver,
+    render_timeout: Duration,
+) where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+{
+    info!("Starting render worker");
+
+    while let Some(mut active) = job_receiver.recv().await {
+        process_render_job(&registry, &jobs, &mut active.job, render_timeout).await;
     }
+
+    info!("Job queue closed, shutting down render worker");
 }
 
-/// Background worker that processes pending render jobs
-pub struct RenderWorker {
-    state: AppState,
-    job_receiver: tokio::sync::mpsc::UnboundedReceiver<_>,
+/// Render `data` with `prepared` on the blocking thread pool, giving up after `timeout`
+///
+/// Typst compiles synchronously, so compiling on the async runtime would
+/// stall every other task scheduled on the same thread. The render uses a
+/// clone of `prepared`, which shares its cache of loaded files. A render
+/// that times out is abandoned and finishes in the background.
+pub async fn render_blocking(
+    prepared: &PreparedTemplate,
+    data: &serde_json::Value,
+    timeout: Duration,
+) -> Result<Vec<u8>, RegistryError> {
+    let mut template = prepared.clone();
+    let data = data.clone();
+    let render = tokio::task::spawn_blocking(move || template.render(&data));
+
+    match tokio::time::timeout(timeout, render).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(RegistryError::Compilation(
+            CompilationError::TemplateCompilation {
+                message: format!("Render task failed: {}", e),
+            }
+            .into(),
+        )),
+        Err(_) => Err(RegistryError::Compilation(
+            CompilationError::Timeout {
+                timeout_ms: timeout.as_millis() as u64,
+            }
+            .into(),
+        )),
+    }
 }
 
-impl RenderWorker {
-    /// Create a new render worker
-    pub fn new(state: AppState, job_receiver: tokio::sync::mpsc::UnboundedReceiver<_>) -> Self {
-        Self {
-            state,
-            job_receiver,
+/// Wait up to `timeout` for the worker to finish its remaining jobs
+///
+/// Called once no more jobs can be enqueued. If the worker is still busy
+/// when the timeout expires it is aborted, and every job it did not finish
+/// is failed as cancelled so it does not vanish. Returns the ids of the
+/// cancelled jobs.
+pub async fn drain_render_worker(
+    mut worker: JoinHandle<()>,
+    jobs: &JobStore,
+    timeout: Duration,
+) -> Vec<String> {
+    if tokio::time::timeout(timeout, &mut worker).await.is_ok() {
+        return Vec::new();
+    }
+
+    worker.abort();
+    let cancelled = jobs
+        .fail_unfinished("Cancelled: the server shut down before the job finished")
+        .await;
+    for id in &cancelled {
+        warn!("Cancelled render job {} on shutdown", id);
+    }
+    cancelled
+}
+
+/// Render a single job, recording each status change in `jobs`
+async fn process_render_job<S, R>(
+    registry: &Registry<S, R>,
+    jobs: &JobStore,
+    job: &mut RenderJob,
+    render_timeout: Duration,
+) where
+    S: BlobStorage + 'static,
+    R: RenderStorage + 'static,
+{
+    if let Err(e) = job.transition(RenderStatus::InProgress) {
+        error!("Skipping render job {}: {}", job.id, e);
+        return;
+    }
+    jobs.insert(job.clone()).await;
+
+    info!(
+        "Processing render job {} for template {}",
+        job.id, job.reference
+    );
+    let start_time = std::time::Instant::now();
+    let rendered = match registry.prepare(&job.reference).await {
+        Ok(prepared) => render_blocking(&prepared, &job.data, render_timeout)
+            .await
+            .map(|pdf_bytes| (prepared.manifest_hash().to_string(), pdf_bytes)),
+        Err(e) => Err(e),
+    };
+    let duration_ms = start_time.elapsed().as_millis() as u32;
+
+    let stored = registry
+        .store_rendered(&job.reference, &job.data, rendered, duration_ms)
+        .await;
+    let outcome = match stored {
+        Ok(result) => {
+            info!(
+                "Completed render job {} as render {} in {}ms",
+                job.id, result.render_id, result.duration_ms
+            );
+            job.complete(result.render_id, result.pdf_hash, result.duration_ms)
         }
+        Err(e) => {
+            error!("Render job {} failed: {}", job.id, e);
+            let diagnostics = registry_diagnostics(&e).map(<[_]>::to_vec);
+            job.fail_with_diagnostics(e.to_string(), diagnostics.unwrap_or_default())
+        }
+    };
+
+    if let Err(e) = outcome {
+        error!("Failed to record outcome of render job {}: {}", job.id, e);
     }
+    jobs.insert(job.clone()).await;
+}
 
-    /// Start the worker loop
-    pub async fn start(mut self) {
-        info!("Starting event-driven render worker");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use crate::{models::RenderJobStatus, queue::JobQueue};
+    use papermake_registry::{
+        bundle::{TemplateBundle, TemplateMetadata},
+        render_storage::MemoryRenderStorage,
+        storage::blob_storage::{MemoryStorage, StorageError},
+    };
 
-        loop {
-            // Wait for job from channel
-            match self.job_receiver.recv().await {
-                Some(job) => {
-                    if let Err(e) = self.process_render_job(job).await {
-                        error!("Error processing render job: {}", e);
-                    }
-                }
-                None => {
-                    info!("Job channel closed, shutting down worker");
-                    break;
-                }
+    /// Memory storage taking `delay` to store each rendered PDF
+    struct SlowPdfStorage {
+        inner: MemoryStorage,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl BlobStorage for SlowPdfStorage {
+        async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+            if key.starts_with("pdfs/") {
+                tokio::time::sleep(self.delay).await;
             }
+            self.inner.put(key, data).await
         }
-    }
 
-    /// Process a single render job
-    async fn process_render_job(&self, mut job: RenderJob) -> Result<()> {
-        let start_time = Instant::now();
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            self.inner.get(key).await
+        }
 
-        // Mark job as in progress
-        job.start();
-        self.state.registry.update_render_job(&job).await?;
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            self.inner.delete(key).await
+        }
 
-        // Get the template for this job
-        info!(
-            "Processing render job {} for template {}",
-            job.id, job.template_ref
+        async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+            self.inner.list_keys(prefix).await
+        }
+    }
+
+    /// A registry whose renders take `delay`, with a published `acme/invoice`
+    async fn slow_registry(delay: Duration) -> Arc<Registry<SlowPdfStorage, MemoryRenderStorage>> {
+        let storage = SlowPdfStorage {
+            inner: MemoryStorage::new(),
+            delay,
+        };
+        let registry = Arc::new(Registry::new(storage, MemoryRenderStorage::new()));
+        let bundle = TemplateBundle::new(
+            b"= Invoice\nNumber: #data.number".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
         );
-        let template = match self
-            .state
-            .registry
-            .get_template(&job.template_ref.to_string())
+        registry
+            .publish(bundle, "acme/invoice", "latest")
             .await
-        {
-            Ok(template_entry) => template_entry.template,
-            Err(e) => {
-                let error_msg = format!("Failed to get template {} {}", job.template_ref, e);
-                error!("Render job {} failed: {}", job.id, error_msg);
-                job.fail(error_msg);
-                // Save failed job status
-                if let Err(save_err) = self.state.registry.update_render_job(&job).await {
-                    error!(
-                        "Failed to save job failure status for {}: {}",
-                        job.id, save_err
-                    );
-                }
-                return Ok(());
+            .unwrap();
+        registry
+    }
+
+    /// Enqueue a render of `acme/invoice` as the render route does
+    async fn submit(queue: &JobQueue, jobs: &JobStore) -> String {
+        let job = RenderJob::builder("acme/invoice:latest")
+            .with_data(serde_json::json!({"number": 42}))
+            .build();
+        let id = job.id.clone();
+        jobs.insert(job.clone()).await;
+        queue.enqueue(job).unwrap();
+        id
+    }
+
+    /// Poll until the job with `id` is picked up by the worker
+    async fn wait_in_progress(jobs: &JobStore, id: &str) {
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while jobs.get(id).await.unwrap().status() != RenderStatus::InProgress {
+                tokio::time::sleep(Duration::from_millis(5)).await;
             }
-        };
+        })
+        .await
+        .expect("render job was not started");
+    }
+
+    /// Submit `job` as the render route does and poll until it is terminal
+    async fn submit_and_wait(queue: &JobQueue, jobs: &JobStore, job: RenderJob) -> RenderJob {
+        let id = job.id.clone();
+        jobs.insert(job.clone()).await;
+        queue.enqueue(job).unwrap();
 
-        // Render the PDF
-        debug!("Rendering PDF for job {} with data: {:?}", job.id, job.data);
-        match self.render_pdf(&template, &job.data).await {
-            Ok(pdf_data) => {
-                info!(
-                    "Successfully rendered PDF for job {} ({} bytes)",
-                    job.id,
-                    pdf_data.len()
-                );
-                // Generate S3 key for the PDF
-                let s3_key = format!("renders/{}/{}.pdf", job.template_ref, job.id);
-
-                // Store PDF in file storage
-                match self.store_pdf(&s3_key, pdf_data).await {
-                    Ok(_) => {
-                        let latency_ms = start_time.elapsed().as_millis() as u64;
-                        job.complete(s3_key, latency_ms);
-                        info!(
-                            "Successfully completed render job {} in {}ms",
-                            job.id, latency_ms
-                        );
-                    }
-                    Err(e) => {
-                        let error_msg = format!("Failed to store PDF: {}", e);
-                        error!(
-                            "Render job {} failed during S3 upload: {}",
-                            job.id, error_msg
-                        );
-                        job.fail(error_msg);
-                    }
+        tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                let job = jobs.get(&id).await.unwrap();
+                if job.status().is_terminal() {
+                    return job;
                 }
+                tokio::time::sleep(Duration::from_millis(10)).await;
             }
-            Err(e) => {
-                let error_msg = format!("PDF rendering failed: {}", e);
-                error!("Render job {} failed: {}", job.id, error_msg);
-                job.fail(error_msg);
-            }
-        }
+        })
+        .await
+        .expect("render job did not finish")
+    }
 
-        // Save final job status
-        if let Err(e) = self.state.registry.update_render_job(&job).await {
-            error!("Failed to save final job status: {}", e);
-        }
+    #[tokio::test]
+    async fn test_worker_completes_submitted_jobs() {
+        let registry = Arc::new(Registry::new(
+            MemoryStorage::new(),
+            MemoryRenderStorage::new(),
+        ));
+        let bundle = TemplateBundle::new(
+            b"= Invoice\nNumber: #data.number".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
 
-        Ok(())
-    }
-
-    /// Render PDF using papermake
-    async fn render_pdf(
-        &self,
-        template: &papermake::Template,
-        data: &serde_json::Value,
-    ) -> Result<Vec<u8>> {
-        // Validate data against template schema
-        template.validate_data(data)?;
-
-        // Render the PDF
-        let render_result = template.render(data)?;
-
-        // Extract PDF bytes
-        match render_result.pdf {
-            Some(pdf_bytes) => Ok(pdf_bytes),
-            None => {
-                let error_details = render_result
-                    .errors
-                    .into_iter()
-                    .map(|e| e.to_string())
-                    .collect::<Vec<_>>()
-                    .join("; ");
-
-                Err(crate::error::ApiError::RenderFailed(format!(
-                    "PDF generation failed: {}",
-                    error_details
-                ))
-                .into())
-            }
-        }
+        let (queue, receiver) = JobQueue::new(16);
+        let jobs = JobStore::default();
+        let worker = tokio::spawn(run_render_worker(
+            registry.clone(),
+            jobs.clone(),
+            receiver,
+            Duration::from_secs(30),
+        ));
+
+        let job = RenderJob::builder("acme/invoice:latest")
+            .with_data(serde_json::json!({"number": 42}))
+            .build();
+        let job = submit_and_wait(&queue, &jobs, job).await;
+
+        assert_eq!(job.status(), RenderStatus::Completed);
+        let render_id = job.render_id().unwrap();
+        let pdf = registry.get_render_pdf(render_id).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // A failing job is recorded and does not stop the worker
+        let missing = RenderJob::builder("acme/missing:latest").build();
+        let missing = submit_and_wait(&queue, &jobs, missing).await;
+        assert_eq!(missing.status(), RenderStatus::Failed);
+        assert!(missing.error().is_some());
+
+        drop(queue);
+        worker.await.unwrap();
+    }
+    #[tokio::test]
+    async fn test_failed_job_reports_structured_diagnostics() {
+        let registry = Arc::new(Registry::new(
+            MemoryStorage::new(),
+            MemoryRenderStorage::new(),
+        ));
+        let bundle = TemplateBundle::new(
+            b"= Invoice\n#let total = 1\nTotal: #total-amount".to_vec(),
+            TemplateMetadata::new("Invoice", "test@example.com"),
+        );
+        registry
+            .publish(bundle, "acme/invoice", "latest")
+            .await
+            .unwrap();
+
+        let (queue, receiver) = JobQueue::new(16);
+        let jobs = JobStore::default();
+        let worker = tokio::spawn(run_render_worker(
+            registry,
+            jobs.clone(),
+            receiver,
+            Duration::from_secs(30),
+        ));
+
+        let job = RenderJob::builder("acme/invoice:latest").build();
+        let job = submit_and_wait(&queue, &jobs, job).await;
+        assert_eq!(job.status(), RenderStatus::Failed);
+
+        // As served by /api/renders/{job_id}
+        let status = serde_json::to_value(RenderJobStatus::from(&job)).unwrap();
+        let diagnostic = &status["diagnostics"][0];
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["location"]["line"], 3);
+        assert!(
+            diagnostic["hints"][0]
+                .as_str()
+                .unwrap()
+                .contains("subtraction")
+        );
+
+        drop(queue);
+        worker.await.unwrap();
     }
 
-    /// Store PDF in file storage
-    async fn store_pdf(&self, s3_key: &str, pdf_data: Vec<u8>) -> Result<()> {
-        todo!("Implement with new registry")
+    #[tokio::test]
+    async fn test_job_exceeding_render_timeout_fails() {
+        let registry = slow_registry(Duration::ZERO).await;
+        let (queue, receiver) = JobQueue::new(16);
+        let jobs = JobStore::default();
+        let worker = tokio::spawn(run_render_worker(
+            registry.clone(),
+            jobs.clone(),
+            receiver,
+            Duration::ZERO,
+        ));
+
+        let job = RenderJob::builder("acme/invoice:latest")
+            .with_data(serde_json::json!({"number": 42}))
+            .build();
+        let job = submit_and_wait(&queue, &jobs, job).await;
+        assert_eq!(job.status(), RenderStatus::Failed);
+        assert!(job.error().unwrap().contains("timed out"));
+
+        let records = registry.list_recent_renders(10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].success);
+
+        drop(queue);
+        worker.await.unwrap();
     }
-}
 
-/// Spawn the render worker in the background
-pub fn spawn_render_worker(state: AppState, job_receiver: tokio::sync::mpsc::UnboundedReceiver<_>) {
-    tokio::spawn(async move {
-        let worker = RenderWorker::new(state, job_receiver);
-        worker.start().await;
-    });
+    #[tokio::test]
+    async fn test_shutdown_finishes_in_flight_render() {
+        let registry = slow_registry(Duration::from_millis(300)).await;
+        let (queue, receiver) = JobQueue::new(16);
+        let jobs = JobStore::default();
+        let worker = tokio::spawn(run_render_worker(
+            registry.clone(),
+            jobs.clone(),
+            receiver,
+            Duration::from_secs(30),
+        ));
+
+        let in_flight = submit(&queue, &jobs).await;
+        let queued = submit(&queue, &jobs).await;
+        wait_in_progress(&jobs, &in_flight).await;
+
+        // Shutdown starts: no new jobs, and the server drops its queue
+        queue.close();
+        let rejected = RenderJob::builder("acme/invoice:latest").build();
+        assert!(matches!(
+            queue.enqueue(rejected),
+            Err(ApiError::ShuttingDown)
+        ));
+        drop(queue);
+
+        let cancelled = drain_render_worker(worker, &jobs, Duration::from_secs(30)).await;
+        assert!(cancelled.is_empty());
+        for id in [in_flight, queued] {
+            let job = jobs.get(&id).await.unwrap();
+            assert_eq!(job.status(), RenderStatus::Completed);
+            let pdf = registry.get_render_pdf(job.render_id().unwrap()).await;
+            assert!(pdf.unwrap().starts_with(b"%PDF"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout_cancels_unfinished_jobs() {
+        let registry = slow_registry(Duration::from_secs(30)).await;
+        let (queue, receiver) = JobQueue::new(16);
+        let jobs = JobStore::default();
+        let worker = tokio::spawn(run_render_worker(
+            registry,
+            jobs.clone(),
+            receiver,
+            Duration::from_secs(30),
+        ));
+
+        let in_flight = submit(&queue, &jobs).await;
+        let queued = submit(&queue, &jobs).await;
+        wait_in_progress(&jobs, &in_flight).await;
+        queue.close();
+        drop(queue);
+
+        let cancelled = drain_render_worker(worker, &jobs, Duration::from_millis(50)).await;
+        let mut expected = vec![in_flight, queued];
+        expected.sort();
+        assert_eq!(cancelled, expected);
+        for id in &expected {
+            let job = jobs.get(id).await.unwrap();
+            assert_eq!(job.status(), RenderStatus::Failed);
+            assert!(job.error().unwrap().starts_with("Cancelled"));
+        }
+    }
 }
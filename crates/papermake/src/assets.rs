@@ -0,0 +1,146 @@
+//! Placeholder fallback for missing image assets
+//!
+//! Templates often compute asset paths from data (e.g. `flags/#data.country.png`).
+//! When such an asset is missing, Typst fails the whole render. Wrapping the
+//! file system in a [`FallbackFileSystem`] serves a placeholder image instead
+//! and records the missing path so it can be reported as a warning.
+
+use std::sync::{Arc, Mutex};
+
+use typst::diag::FileError;
+
+use crate::typst::RenderFileSystem;
+
+/// 1x1 light grey PNG used when no custom placeholder is configured
+pub const DEFAULT_PLACEHOLDER_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90, 0x77, 0x53,
+    0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x38, 0x73, 0xe6, 0x0c,
+    0x00, 0x04, 0xcc, 0x02, 0x65, 0x39, 0x9a, 0x65, 0x2c, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e,
+    0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+/// File extensions treated as images eligible for the placeholder fallback
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp"];
+
+/// Configuration for replacing missing image assets with a placeholder
+#[derive(Debug, Clone)]
+pub struct AssetFallback {
+    /// Directory (relative to the template root) whose missing images fall back
+    pub directory: String,
+    /// Image bytes served in place of a missing asset; format is detected from the data
+    pub placeholder: Vec<u8>,
+}
+
+impl AssetFallback {
+    /// Fall back to the default placeholder for missing images under `directory`
+    pub fn new(directory: impl Into<String>) -> Self {
+        Self {
+            directory: directory.into(),
+            placeholder: DEFAULT_PLACEHOLDER_PNG.to_vec(),
+        }
+    }
+
+    /// Use custom placeholder image bytes
+    pub fn with_placeholder(mut self, placeholder: Vec<u8>) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Whether a missing `path` should be served the placeholder
+    fn applies_to(&self, path: &str) -> bool {
+        let path = path.trim_start_matches('/');
+        let directory = self.directory.trim_matches('/');
+
+        let in_directory = directory.is_empty()
+            || path
+                .strip_prefix(directory)
+                .is_some_and(|rest| rest.starts_with('/'));
+
+        let is_image = path
+            .rsplit_once('.')
+            .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+
+        in_directory && is_image
+    }
+}
+
+/// File system wrapper serving a placeholder for missing image assets
+///
+/// All other lookups, including missing non-image files and images outside
+/// the configured directory, behave exactly like the inner file system.
+pub struct FallbackFileSystem {
+    inner: Arc<dyn RenderFileSystem>,
+    fallback: AssetFallback,
+    missing: Mutex<Vec<String>>,
+}
+
+impl FallbackFileSystem {
+    /// Wrap `inner`, applying the given fallback configuration
+    pub fn new(inner: Arc<dyn RenderFileSystem>, fallback: AssetFallback) -> Self {
+        Self {
+            inner,
+            fallback,
+            missing: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Paths that were missing and served the placeholder, in request order
+    pub fn missing_assets(&self) -> Vec<String> {
+        self.missing.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+}
+
+impl RenderFileSystem for FallbackFileSystem {
+    fn get_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        match self.inner.get_file(path) {
+            Err(FileError::NotFound(_)) if self.fallback.applies_to(path) => {
+                if let Ok(mut missing) = self.missing.lock()
+                    && !missing.iter().any(|p| p == path)
+                {
+                    missing.push(path.to_string());
+                }
+                Ok(self.fallback.placeholder.clone())
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typst::InMemoryFileSystem;
+
+    #[test]
+    fn test_fallback_applies_only_to_images_in_directory() {
+        let fallback = AssetFallback::new("flags");
+
+        assert!(fallback.applies_to("/flags/de.png"));
+        assert!(fallback.applies_to("flags/nested/fr.SVG"));
+        assert!(!fallback.applies_to("/flags.png"));
+        assert!(!fallback.applies_to("/flagsx/de.png"));
+        assert!(!fallback.applies_to("/logos/acme.png"));
+        assert!(!fallback.applies_to("/flags/readme.txt"));
+    }
+
+    #[test]
+    fn test_fallback_file_system_records_missing() {
+        let mut inner = InMemoryFileSystem::new();
+        inner.add_file("/flags/de.png", b"real".to_vec());
+        let fs = FallbackFileSystem::new(Arc::new(inner), AssetFallback::new("flags"));
+
+        assert_eq!(fs.get_file("/flags/de.png").unwrap(), b"real");
+        assert_eq!(
+            fs.get_file("/flags/xx.png").unwrap(),
+            DEFAULT_PLACEHOLDER_PNG
+        );
+        assert_eq!(
+            fs.get_file("/flags/xx.png").unwrap(),
+            DEFAULT_PLACEHOLDER_PNG
+        );
+        assert!(fs.get_file("/other.typ").is_err());
+
+        assert_eq!(fs.missing_assets(), vec!["/flags/xx.png".to_string()]);
+    }
+}
@@ -0,0 +1,92 @@
+//! Bar and line charts for templates
+//!
+//! Part of the `pm` module injected with [`RenderOptions::inject_prelude`],
+//! drawn with plain Typst shapes so no package has to be downloaded:
+//!
+//! - `pm.bar-chart(values, labels: none, ..)`: one bar per value, e.g.
+//!   `pm.bar-chart((10, 20, 30), labels: ("Q1", "Q2", "Q3"))`
+//! - `pm.line-chart(series, labels: none, ..)`: one line per array in
+//!   `series`, e.g. `pm.line-chart(((1, 3, 2), (2, 2, 4)))`
+//!
+//! Both take `width` and `height` (default `8cm` by `5cm`) for the plot
+//! area, which is drawn with an axis along its left and bottom edge. Labels
+//! are set in a row below the plot area, so they add to the chart's height.
+//! Bars start at zero and negative values are drawn as empty bars; lines
+//! are scaled to the range of all series, including zero.
+//!
+//! [`RenderOptions::inject_prelude`]: crate::RenderOptions::inject_prelude
+
+/// Typst source of the chart helpers, appended to the `pm` module
+pub(crate) const MODULE: &str = r##"
+#let chart-colors = (rgb("#4e79a7"), rgb("#f28e2b"), rgb("#e15759"), rgb("#76b7b2"))
+
+#let chart-axes(width, height) = {
+  place(bottom + left, line(length: width, stroke: 0.5pt))
+  place(bottom + left, line(angle: -90deg, length: height, stroke: 0.5pt))
+}
+
+#let chart-label(label) = text(size: 8pt, [#label])
+
+#let with-labels(plot, labels) = {
+  if labels == none { plot } else { stack(dir: ttb, spacing: 4pt, plot, labels) }
+}
+
+#let bar-labels(labels, slot) = {
+  grid(columns: (slot,) * labels.len(), ..labels.map(label => align(center, chart-label(label))))
+}
+
+#let line-labels(labels, width, count) = {
+  if count == 1 { return box(width: width, align(center, chart-label(labels.at(0)))) }
+  // Labels sit under the points, the outer ones flush with the plot edges
+  let slot = width / (count - 1)
+  grid(
+    columns: (slot / 2,) + (slot,) * (count - 2) + (slot / 2,),
+    ..labels.enumerate().map(((i, label)) => {
+      let side = if i == 0 { left } else if i == count - 1 { right } else { center }
+      align(side, chart-label(label))
+    }),
+  )
+}
+
+#let bar-chart(values, labels: none, width: 8cm, height: 5cm, fill: chart-colors.at(0), gap: 20%) = {
+  let top = calc.max(0, ..values)
+  if top == 0 { top = 1 }
+  let slot = width / calc.max(values.len(), 1)
+  let bar-width = slot * ((100% - gap) / 100%)
+  let plot = box(width: width, height: height, {
+    for (i, value) in values.enumerate() {
+      place(
+        bottom + left,
+        dx: slot * i + (slot - bar-width) / 2,
+        rect(width: bar-width, height: height * calc.max(value, 0) / top, fill: fill, stroke: none),
+      )
+    }
+    chart-axes(width, height)
+  })
+  with-labels(plot, if labels != none { bar-labels(labels, slot) })
+}
+
+#let line-chart(series, labels: none, width: 8cm, height: 5cm, colors: chart-colors, stroke: 1.5pt) = {
+  let all = series.flatten()
+  let low = calc.min(0, ..all)
+  let high = calc.max(0, ..all)
+  if high == low { high = low + 1 }
+  let count = calc.max(..series.map(values => values.len()), 1)
+  let x(i) = if count == 1 { width / 2 } else { width * i / (count - 1) }
+  let y(value) = height * (high - value) / (high - low)
+  let plot = box(width: width, height: height, {
+    for (n, values) in series.enumerate() {
+      let color = colors.at(calc.rem(n, colors.len()))
+      for i in range(1, values.len()) {
+        place(top + left, line(
+          start: (x(i - 1), y(values.at(i - 1))),
+          end: (x(i), y(values.at(i))),
+          stroke: color + stroke,
+        ))
+      }
+    }
+    chart-axes(width, height)
+  })
+  with-labels(plot, if labels != none { line-labels(labels, width, count) })
+}
+"##;
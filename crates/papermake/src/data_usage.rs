@@ -0,0 +1,140 @@
+//! Detection of data keys the template never reads
+//!
+//! The inverse of schema validation: instead of checking the data against
+//! what the template expects, the main template source is scanned for the
+//! top-level fields it reads from `data`, and every data key outside that
+//! set is reported. This catches sending `customerName` to a template that
+//! reads `customer_name`.
+//!
+//! The scan is purely syntactic. It understands `data.field` and
+//! `data.at("field")`; any other use of `data` (passing it to a function,
+//! iterating over it, a computed key) could read arbitrary keys, so no
+//! warnings are produced for such templates.
+
+use std::collections::BTreeSet;
+
+use typst::syntax::SyntaxNode;
+use typst::syntax::ast::{self, AstNode};
+
+use crate::error::{DiagnosticInfo, DiagnosticSeverity};
+
+/// Name of the binding holding the template data
+const DATA_BINDING: &str = "data";
+
+/// Top-level data fields read by `main_typ`
+///
+/// Returns `None` if `data` is used in a way whose accessed fields cannot be
+/// determined statically.
+pub fn read_data_fields(main_typ: &str) -> Option<BTreeSet<String>> {
+    let root = typst::syntax::parse(main_typ);
+    let mut fields = BTreeSet::new();
+    collect_fields(&root, &mut fields).then_some(fields)
+}
+
+/// Warnings for top-level keys in `data` that `main_typ` never reads
+///
+/// Each warning hints at the fields the template reads that are missing
+/// from `data`, which usually contain the intended name.
+pub fn unused_data_keys(main_typ: &str, data: &serde_json::Value) -> Vec<DiagnosticInfo> {
+    let (Some(fields), Some(object)) = (read_data_fields(main_typ), data.as_object()) else {
+        return Vec::new();
+    };
+
+    let missing: Vec<&str> = fields
+        .iter()
+        .filter(|field| !object.contains_key(field.as_str()))
+        .map(String::as_str)
+        .collect();
+
+    object
+        .keys()
+        .filter(|key| !fields.contains(key.as_str()))
+        .map(|key| DiagnosticInfo {
+            message: format!("data key '{}' is never read by the template", key),
+            severity: DiagnosticSeverity::Warning,
+            location: None,
+            hints: missing
+                .iter()
+                .map(|field| format!("the template reads '{}', which is not in the data", field))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Record fields read from `data` below `node`, returning `false` on any
+/// use of `data` that is not a static field access
+fn collect_fields(node: &SyntaxNode, fields: &mut BTreeSet<String>) -> bool {
+    if let Some(call) = node.cast::<ast::FuncCall>()
+        && let ast::Expr::FieldAccess(access) = call.callee()
+        && is_data(access.target())
+        && access.field().get() == "at"
+    {
+        let key = call.args().items().next().and_then(|arg| match arg {
+            ast::Arg::Pos(ast::Expr::Str(key)) => Some(key.get().to_string()),
+            _ => None,
+        });
+        let Some(key) = key else {
+            return false;
+        };
+        fields.insert(key);
+        return call
+            .args()
+            .to_untyped()
+            .children()
+            .all(|child| collect_fields(child, fields));
+    }
+
+    if let Some(access) = node.cast::<ast::FieldAccess>()
+        && is_data(access.target())
+    {
+        fields.insert(access.field().get().to_string());
+        return true;
+    }
+
+    if node.cast::<ast::Expr>().is_some_and(is_data) {
+        return false;
+    }
+
+    node.children().all(|child| collect_fields(child, fields))
+}
+
+fn is_data(expr: ast::Expr) -> bool {
+    matches!(expr, ast::Expr::Ident(ident) if ident.get() == DATA_BINDING)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_read_data_fields() {
+        let fields = read_data_fields(
+            "= #data.title\n#for item in data.items [#item.name]\n#data.at(\"total\", default: 0)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            fields.into_iter().collect::<Vec<_>>(),
+            vec!["items", "title", "total"]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_use_disables_detection() {
+        assert!(read_data_fields("#let show-all(d) = d\n#show-all(data)").is_none());
+        assert!(read_data_fields("#for (k, v) in data [#k]").is_none());
+        assert!(read_data_fields("#let key = \"a\"\n#data.at(key)").is_none());
+        assert!(unused_data_keys("#repr(data)", &json!({ "anything": 1 })).is_empty());
+    }
+
+    #[test]
+    fn test_unused_data_keys() {
+        let warnings = unused_data_keys("Hello #data.name", &json!({ "nam": "x" }));
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, DiagnosticSeverity::Warning);
+        assert!(warnings[0].message.contains("'nam'"));
+        assert!(warnings[0].hints[0].contains("'name'"));
+    }
+}
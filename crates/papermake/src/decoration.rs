@@ -0,0 +1,241 @@
+//! Page headers and footers applied to every template
+//!
+//! Organizations often want the same letterhead, e.g. a logo in the header
+//! and the company address in the footer, on every document without editing
+//! each template. A [`PageDecoration`] set with
+//! [`RenderOptions::with_page_decoration`] is applied as a
+//! `#set page(header: .., footer: ..)` rule ahead of the template, so a
+//! template setting its own header or footer replaces that part, while the
+//! other one stays.
+//!
+//! With [`PageDecoration::force`], the rule is repeated after each top-level
+//! `set page` and `show: ..` rule of the main template, so it also wins over
+//! the template's own header and footer. Rules in imported files or nested
+//! blocks are not seen.
+//!
+//! [`RenderOptions::with_page_decoration`]: crate::RenderOptions::with_page_decoration
+
+use typst::syntax::{LinkedNode, ast};
+
+use crate::error::{ConfigError, PapermakeError, Result};
+use crate::render::typst_string;
+
+/// Binding the prelude stores the header content in
+const HEADER_BINDING: &str = "papermake-header";
+
+/// Binding the prelude stores the footer content in
+const FOOTER_BINDING: &str = "papermake-footer";
+
+/// Content of a page header or footer
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecorationContent {
+    /// Typst markup, e.g. `Acme Corp #h(1fr) #context counter(page).display()`
+    Markup(String),
+    /// Image from the render file system, scaled to `height_pt` points
+    Image { path: String, height_pt: f64 },
+}
+
+impl DecorationContent {
+    /// Typst markup content
+    pub fn markup(markup: impl Into<String>) -> Self {
+        Self::Markup(markup.into())
+    }
+
+    /// Image at `path`, relative to the template root, `height_pt` points high
+    pub fn image(path: impl Into<String>, height_pt: f64) -> Self {
+        Self::Image {
+            path: path.into(),
+            height_pt,
+        }
+    }
+
+    /// Typst expression for this content
+    fn expression(&self) -> String {
+        match self {
+            Self::Markup(markup) => format!("[{}]", markup),
+            Self::Image { path, height_pt } => format!(
+                "image({}, height: {}pt)",
+                typst_string(&format!("/{}", path.trim_start_matches('/'))),
+                height_pt
+            ),
+        }
+    }
+
+    /// Reject content that would fail with an error pointing into the prelude
+    fn validate(&self, setting: &str) -> Result<()> {
+        let reason = match self {
+            Self::Markup(_) => typst::syntax::parse_code(&self.expression())
+                .errors()
+                .first()
+                .map(|error| format!("invalid markup: {}", error.message)),
+            Self::Image { height_pt, .. } if !(height_pt.is_finite() && *height_pt > 0.0) => {
+                Some(format!("image height {} is not positive", height_pt))
+            }
+            Self::Image { .. } => None,
+        };
+
+        match reason {
+            Some(reason) => Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: format!("page_decoration.{}", setting),
+                reason,
+            })),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Header and footer added to every page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageDecoration {
+    /// Content of the page header, if any
+    pub header: Option<DecorationContent>,
+    /// Content of the page footer, if any
+    pub footer: Option<DecorationContent>,
+    /// Override the template's own header and footer, off by default
+    pub force: bool,
+}
+
+impl PageDecoration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the page header
+    pub fn with_header(mut self, header: DecorationContent) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Set the page footer
+    pub fn with_footer(mut self, footer: DecorationContent) -> Self {
+        self.footer = Some(footer);
+        self
+    }
+
+    /// Override the template's own header and footer
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(header) = &self.header {
+            header.validate("header")?;
+        }
+        if let Some(footer) = &self.footer {
+            footer.validate("footer")?;
+        }
+        Ok(())
+    }
+
+    /// Prelude binding the content and applying it as page defaults
+    pub(crate) fn prelude(&self) -> String {
+        let mut prelude = String::new();
+        for (binding, content) in [
+            (HEADER_BINDING, &self.header),
+            (FOOTER_BINDING, &self.footer),
+        ] {
+            if let Some(content) = content {
+                prelude.push_str(&format!("#let {} = {}\n", binding, content.expression()));
+            }
+        }
+        if let Some(rule) = self.page_rule() {
+            prelude.push_str(&rule);
+            prelude.push('\n');
+        }
+        prelude
+    }
+
+    /// Single-line `set page` rule referring to the prelude bindings
+    fn page_rule(&self) -> Option<String> {
+        let mut fields = Vec::new();
+        if self.header.is_some() {
+            fields.push(format!("header: {}", HEADER_BINDING));
+        }
+        if self.footer.is_some() {
+            fields.push(format!("footer: {}", FOOTER_BINDING));
+        }
+        (!fields.is_empty()).then(|| format!("#set page({})", fields.join(", ")))
+    }
+
+    /// Repeat the page rule after the template's own page rules, if forced
+    ///
+    /// The rule is inserted on the same line, so line numbers in
+    /// diagnostics are unaffected.
+    pub(crate) fn apply_to_template(&self, main_typ: String) -> String {
+        let Some(rule) = self.page_rule().filter(|_| self.force) else {
+            return main_typ;
+        };
+
+        let root = typst::syntax::parse(&main_typ);
+        let ends: Vec<usize> = LinkedNode::new(&root)
+            .children()
+            .filter(|node| overrides_page(node))
+            .map(|node| node.range().end)
+            .collect();
+        if ends.is_empty() {
+            return main_typ;
+        }
+
+        // Embedded statements end with a semicolon or line break
+        let rule = format!(";{}", rule);
+        let mut forced = String::with_capacity(main_typ.len() + ends.len() * rule.len());
+        let mut last = 0;
+        for end in ends {
+            forced.push_str(&main_typ[last..end]);
+            forced.push_str(&rule);
+            last = end;
+        }
+        forced.push_str(&main_typ[last..]);
+        forced
+    }
+}
+
+/// Whether a top-level node may replace the page header or footer
+///
+/// That is a `set page(..)` rule or a `show: ..` rule applying a template
+/// function, which usually sets up the page itself.
+fn overrides_page(node: &LinkedNode) -> bool {
+    if let Some(rule) = node.cast::<ast::SetRule>() {
+        return matches!(rule.target(), ast::Expr::Ident(ident) if ident.as_str() == "page");
+    }
+    node.cast::<ast::ShowRule>()
+        .is_some_and(|rule| rule.selector().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn footer() -> PageDecoration {
+        PageDecoration::new().with_footer(DecorationContent::markup("Acme Corp"))
+    }
+
+    #[test]
+    fn test_forced_rule_follows_template_page_rules() {
+        let template = "#set page(footer: [Own])\n#show: body => body\n#set text(red)\nHello";
+        let forced = footer()
+            .with_force(true)
+            .apply_to_template(template.to_string());
+        assert_eq!(
+            forced,
+            "#set page(footer: [Own]);#set page(footer: papermake-footer)\n\
+             #show: body => body;#set page(footer: papermake-footer)\n\
+             #set text(red)\nHello"
+        );
+
+        // Not forced, the template is left alone
+        assert_eq!(footer().apply_to_template(template.to_string()), template);
+    }
+
+    #[test]
+    fn test_invalid_markup_is_rejected() {
+        let decoration = PageDecoration::new().with_header(DecorationContent::markup("Unclosed ]"));
+        assert!(matches!(
+            decoration.validate(),
+            Err(PapermakeError::Config(ConfigError::InvalidConfig { setting, .. }))
+                if setting == "page_decoration.header"
+        ));
+        assert!(footer().validate().is_ok());
+    }
+}
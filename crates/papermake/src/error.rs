@@ -3,9 +3,12 @@
 //! This module provides a comprehensive error hierarchy for all papermake operations.
 //! Errors are organized by domain to provide clear context and actionable information.
 
+use serde::Serialize;
 use std::fmt;
 use thiserror::Error;
-use typst::diag::{FileError, SourceDiagnostic};
+use typst::diag::{FileError, Severity, SourceDiagnostic};
+
+use crate::typst::PapermakeWorld;
 
 /// Main error type for the papermake library
 ///
@@ -78,6 +81,9 @@ pub enum CompilationError {
 
     #[error("Import resolution failed: {import_path} - {reason}")]
     ImportResolution { import_path: String, reason: String },
+
+    #[error("Render timed out after {timeout_ms} ms")]
+    Timeout { timeout_ms: u64 },
 }
 
 /// File system related errors
@@ -117,8 +123,9 @@ pub enum DataError {
     #[error("JSON deserialization failed: {reason}")]
     Deserialization { reason: String },
 
-    #[error("Schema validation failed: {message}")]
-    SchemaValidation { message: String },
+    /// `path` is the JSON pointer of the offending value, `""` for the root
+    #[error("Schema validation failed at '{path}': {message}")]
+    SchemaValidation { path: String, message: String },
 
     #[error("Invalid data format: {expected}, got {actual}")]
     InvalidFormat { expected: String, actual: String },
@@ -156,7 +163,8 @@ pub enum ConfigError {
 ///
 /// This struct captures detailed information about compilation errors
 /// including source location, severity, and helpful hints.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct DiagnosticInfo {
     /// The error message
     pub message: String,
@@ -169,7 +177,9 @@ pub struct DiagnosticInfo {
 }
 
 /// Diagnostic severity levels
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
 pub enum DiagnosticSeverity {
     Error,
     Warning,
@@ -177,7 +187,8 @@ pub enum DiagnosticSeverity {
 }
 
 /// Source location information for diagnostics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SourceLocation {
     /// File path or identifier
     pub file: String,
@@ -214,11 +225,20 @@ impl fmt::Display for DiagnosticSeverity {
 pub type Result<T> = std::result::Result<T, PapermakeError>;
 
 /// Utility function to convert Typst diagnostics to our diagnostic format
-pub fn convert_typst_diagnostic(diagnostic: SourceDiagnostic) -> DiagnosticInfo {
+///
+/// The location is resolved against `world`, see
+/// [`PapermakeWorld::source_location`].
+pub fn convert_typst_diagnostic(
+    world: &PapermakeWorld,
+    diagnostic: SourceDiagnostic,
+) -> DiagnosticInfo {
     DiagnosticInfo {
         message: diagnostic.message.to_string(),
-        severity: DiagnosticSeverity::Error, // Typst diagnostics are typically errors
-        location: None, // Will be filled in by the caller with file context
+        severity: match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::Error,
+            Severity::Warning => DiagnosticSeverity::Warning,
+        },
+        location: world.source_location(diagnostic.span),
         hints: diagnostic.hints.into_iter().map(|h| h.to_string()).collect(),
     }
 }
@@ -229,10 +249,13 @@ pub fn template_missing_file<S: Into<String>>(file: S) -> PapermakeError {
 }
 
 /// Create a compilation error from Typst diagnostics
-pub fn compilation_error_from_diagnostics(diagnostics: Vec<SourceDiagnostic>) -> PapermakeError {
+pub fn compilation_error_from_diagnostics(
+    world: &PapermakeWorld,
+    diagnostics: Vec<SourceDiagnostic>,
+) -> PapermakeError {
     let diagnostic_infos: Vec<DiagnosticInfo> = diagnostics
         .into_iter()
-        .map(convert_typst_diagnostic)
+        .map(|diagnostic| convert_typst_diagnostic(world, diagnostic))
         .collect();
     
     let error_count = diagnostic_infos.len();
@@ -389,13 +412,13 @@ impl PapermakeError {
 
     /// Check if this error is recoverable
     pub fn is_recoverable(&self) -> bool {
-        match self {
-            PapermakeError::Template(TemplateError::NotFound { .. }) => false,
-            PapermakeError::FileSystem(FileSystemError::NotFound { .. }) => false,
-            PapermakeError::FileSystem(FileSystemError::PermissionDenied { .. }) => false,
-            PapermakeError::Config(_) => false,
-            _ => true,
-        }
+        !matches!(
+            self,
+            PapermakeError::Template(TemplateError::NotFound { .. })
+                | PapermakeError::FileSystem(FileSystemError::NotFound { .. })
+                | PapermakeError::FileSystem(FileSystemError::PermissionDenied { .. })
+                | PapermakeError::Config(_)
+        )
     }
 
     /// Get error suggestions for common problems
@@ -0,0 +1,722 @@
+//! Rendering HTML content without authoring a Typst template
+//!
+//! The HTML is converted into Typst markup and rendered like any other
+//! template. Only a structural subset is understood, which is enough for
+//! paginating content written elsewhere (a CMS, a rich text editor):
+//!
+//! | HTML | Typst |
+//! |------|-------|
+//! | `<h1>` – `<h6>` | `heading` |
+//! | `<p>`, `<div>`, `<section>`, … | paragraphs |
+//! | `<ul>`, `<ol start="..">`, `<li>` | `list`, `enum` |
+//! | `<strong>`, `<b>`, `<em>`, `<i>`, `<u>`, `<s>`, `<del>` | `strong`, `emph`, `underline`, `strike` |
+//! | `<code>`, `<pre>` | `raw` |
+//! | `<a href="..">` | `link` |
+//! | `<img src=".." alt="..">` | `image` |
+//! | `<table>`, `<tr>`, `<th>`, `<td>` | `table` |
+//! | `<blockquote>`, `<br>`, `<hr>` | `quote`, `linebreak`, `line` |
+//!
+//! CSS, `style` and `class` attributes, scripts and the `<head>` are
+//! ignored. Unknown tags are dropped while their content is kept. Image
+//! sources are resolved against [`HtmlRenderOptions::file_system`], so
+//! remote URLs and data URIs are not supported.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::render::{RenderOptions, RenderResult, render_template_with_options};
+use crate::typst::{InMemoryFileSystem, RenderFileSystem};
+
+/// Elements that never have content or a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is never rendered
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "template", "title"];
+
+/// Elements rendered as their own paragraph
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "main",
+    "nav",
+    "aside",
+    "figure",
+    "figcaption",
+    "address",
+    "body",
+    "html",
+];
+
+/// Characters with a meaning in Typst markup, escaped in text
+pub(crate) const MARKUP_CHARS: &[char] = &[
+    '\\', '/', '*', '_', '#', '$', '@', '<', '>', '[', ']', '~', '`', '=', '-', '+', '.',
+];
+
+/// Page setup for [`render_html`]
+#[derive(Clone, Default)]
+pub struct HtmlRenderOptions {
+    /// Paper size, e.g. `"a4"` or `"us-letter"` (Typst's default is A4)
+    pub paper: Option<String>,
+    /// Page margin applied to all sides, e.g. `"2cm"`
+    pub margin: Option<String>,
+    /// Files referenced by `<img src="..">`; empty if unset
+    pub file_system: Option<Arc<dyn RenderFileSystem>>,
+}
+
+impl fmt::Debug for HtmlRenderOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HtmlRenderOptions")
+            .field("paper", &self.paper)
+            .field("margin", &self.margin)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HtmlRenderOptions {
+    /// Set the paper size
+    pub fn with_paper(mut self, paper: impl Into<String>) -> Self {
+        self.paper = Some(paper.into());
+        self
+    }
+
+    /// Set the page margin
+    pub fn with_margin(mut self, margin: impl Into<String>) -> Self {
+        self.margin = Some(margin.into());
+        self
+    }
+
+    /// Resolve image sources against `file_system`
+    pub fn with_file_system(mut self, file_system: Arc<dyn RenderFileSystem>) -> Self {
+        self.file_system = Some(file_system);
+        self
+    }
+}
+
+/// Render HTML content to PDF
+///
+/// See the [module documentation](self) for the supported HTML subset.
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` for a malformed paper size or
+/// margin. Problems within the content, such as a missing image, are
+/// reported in `RenderResult::errors`.
+pub fn render_html(html: &str, options: HtmlRenderOptions) -> Result<RenderResult> {
+    render_nodes(&parse(html), options)
+}
+
+/// Render a parsed element tree to PDF
+pub(crate) fn render_nodes(nodes: &[Node], options: HtmlRenderOptions) -> Result<RenderResult> {
+    let render_options = RenderOptions {
+        paper: options.paper,
+        margin: options.margin,
+        ..Default::default()
+    };
+    let file_system = options
+        .file_system
+        .unwrap_or_else(|| Arc::new(InMemoryFileSystem::new()));
+
+    render_template_with_options(
+        nodes_to_typst(nodes),
+        file_system,
+        &serde_json::Value::Object(Default::default()),
+        &render_options,
+    )
+}
+
+/// Convert HTML into equivalent Typst markup
+pub fn html_to_typst(html: &str) -> String {
+    nodes_to_typst(&parse(html))
+}
+
+/// Typst markup for a parsed element tree
+pub(crate) fn nodes_to_typst(nodes: &[Node]) -> String {
+    let mut output = String::new();
+    emit_nodes(nodes, &mut output);
+    collapse_breaks(&output) + "\n"
+}
+
+/// A parsed HTML node
+///
+/// Also the target of other markup front ends such as
+/// [`crate::markdown`], so every input format shares one Typst emitter.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Node {
+    Text(String),
+    Element {
+        tag: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+}
+
+impl Node {
+    /// Element with no attributes
+    pub(crate) fn element(tag: &str, children: Vec<Node>) -> Self {
+        Node::Element {
+            tag: tag.to_string(),
+            attrs: Vec::new(),
+            children,
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+
+    fn is_element(&self, names: &[&str]) -> bool {
+        matches!(self, Node::Element { tag, .. } if names.contains(&tag.as_str()))
+    }
+
+    fn children(&self) -> &[Node] {
+        match self {
+            Node::Element { children, .. } => children,
+            Node::Text(_) => &[],
+        }
+    }
+
+    /// Concatenated text of this node and its descendants
+    pub(crate) fn text_content(&self) -> String {
+        match self {
+            Node::Text(text) => text.clone(),
+            Node::Element { tag, children, .. } if tag == "br" && children.is_empty() => {
+                "\n".to_string()
+            }
+            Node::Element { children, .. } => children.iter().map(Node::text_content).collect(),
+        }
+    }
+}
+
+/// An element whose closing tag has not been seen yet
+struct OpenElement {
+    tag: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+/// Lenient HTML parser producing a node tree
+///
+/// Unclosed elements are closed with their parent, stray closing tags are
+/// ignored, and an open `<p>`, `<li>`, `<tr>` or table cell is implicitly
+/// closed by a sibling of the same kind, as browsers do.
+fn parse(html: &str) -> Vec<Node> {
+    let mut stack = vec![OpenElement {
+        tag: String::new(),
+        attrs: Vec::new(),
+        children: Vec::new(),
+    }];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(closing) = rest
+            .strip_prefix("</")
+            .filter(|s| s.starts_with(|c: char| c.is_ascii_alphabetic()))
+        {
+            let end = closing.find('>').unwrap_or(closing.len());
+            let tag = closing[..end].trim().to_ascii_lowercase();
+            rest = closing.get(end + 1..).unwrap_or("");
+            if stack.iter().skip(1).any(|open| open.tag == tag) {
+                close_until(&mut stack, |open| open == tag);
+            }
+        } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            let (tag, attrs, self_closing, remaining) = parse_start_tag(&rest[1..]);
+            rest = remaining;
+
+            if SKIPPED_ELEMENTS.contains(&tag.as_str()) {
+                if !self_closing {
+                    rest = skip_past_closing_tag(rest, &tag);
+                }
+                continue;
+            }
+
+            close_implicitly(&mut stack, &tag);
+            if self_closing || VOID_ELEMENTS.contains(&tag.as_str()) {
+                let node = Node::Element {
+                    tag,
+                    attrs,
+                    children: Vec::new(),
+                };
+                stack.last_mut().unwrap().children.push(node);
+            } else {
+                stack.push(OpenElement {
+                    tag,
+                    attrs,
+                    children: Vec::new(),
+                });
+            }
+        } else {
+            let end = rest[1..].find('<').map_or(rest.len(), |i| i + 1);
+            let text = decode_entities(&rest[..end]);
+            stack.last_mut().unwrap().children.push(Node::Text(text));
+            rest = &rest[end..];
+        }
+    }
+
+    close_until(&mut stack, |_| false);
+    stack.pop().unwrap().children
+}
+
+/// Close open elements until one matching `is_target` has been closed
+///
+/// The root is never closed.
+fn close_until(stack: &mut Vec<OpenElement>, is_target: impl Fn(&str) -> bool) {
+    while stack.len() > 1 {
+        let open = stack.pop().unwrap();
+        let is_match = is_target(&open.tag);
+        stack.last_mut().unwrap().children.push(Node::Element {
+            tag: open.tag,
+            attrs: open.attrs,
+            children: open.children,
+        });
+        if is_match {
+            break;
+        }
+    }
+}
+
+/// Close elements that an opening `tag` ends without a closing tag
+fn close_implicitly(stack: &mut Vec<OpenElement>, tag: &str) {
+    let (closes, boundary): (&[&str], &[&str]) = match tag {
+        "p" => (&["p"], &["div", "li", "td", "th", "blockquote"]),
+        "li" => (&["li"], &["ul", "ol"]),
+        "td" | "th" => (&["td", "th"], &["tr", "table"]),
+        "tr" => (&["tr"], &["table"]),
+        _ => return,
+    };
+
+    let open = stack
+        .iter()
+        .skip(1)
+        .rev()
+        .take_while(|open| !boundary.contains(&open.tag.as_str()))
+        .position(|open| closes.contains(&open.tag.as_str()));
+    if let Some(depth) = open {
+        let target = stack.len() - 1 - depth;
+        while stack.len() > target {
+            close_until(stack, |_| true);
+        }
+    }
+}
+
+/// Parse a start tag after its `<`, returning the lowercase tag name,
+/// attributes, whether it is self-closing and the input after the tag
+fn parse_start_tag(input: &str) -> (String, Vec<(String, String)>, bool, &str) {
+    let name_end = input
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(input.len());
+    let tag = input[..name_end].to_ascii_lowercase();
+    let mut rest = &input[name_end..];
+    let mut attrs = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return (tag, attrs, true, after);
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return (tag, attrs, false, after);
+        }
+        if rest.is_empty() {
+            return (tag, attrs, false, rest);
+        }
+        if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            continue;
+        }
+
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+                    (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        attrs.push((key, value));
+    }
+}
+
+/// Skip input up to and including the closing tag of `tag`
+fn skip_past_closing_tag<'a>(input: &'a str, tag: &str) -> &'a str {
+    let closing = format!("</{}", tag);
+    let lower = input.to_ascii_lowercase();
+    match lower.find(&closing) {
+        Some(start) => {
+            let rest = &input[start..];
+            rest.find('>').map_or("", |end| &rest[end + 1..])
+        }
+        None => "",
+    }
+}
+
+/// Decode character references in text and attribute values
+///
+/// Numeric references and the named references common in editor output
+/// are decoded; anything else is kept verbatim.
+fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|c| (c, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "copy" => '©',
+        "reg" => '®',
+        "euro" => '€',
+        _ => return None,
+    })
+}
+
+fn emit_nodes(nodes: &[Node], output: &mut String) {
+    for node in nodes {
+        emit_node(node, output);
+    }
+}
+
+/// Markup for `nodes`, trimmed for use inside a content block
+fn inline(nodes: &[Node]) -> String {
+    let mut output = String::new();
+    emit_nodes(nodes, &mut output);
+    collapse_breaks(&output)
+}
+
+/// Trim `markup` and reduce whitespace around paragraph breaks to one blank line
+fn collapse_breaks(markup: &str) -> String {
+    let mut output = String::with_capacity(markup.len());
+    let mut rest = markup.trim();
+
+    while let Some(start) = rest.find(char::is_whitespace) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        let run = &rest[..end];
+        if run.matches('\n').count() >= 2 {
+            output.push_str("\n\n");
+        } else {
+            output.push_str(run);
+        }
+        rest = &rest[end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn emit_node(node: &Node, output: &mut String) {
+    let (tag, children) = match node {
+        Node::Text(text) => {
+            emit_text(text, output);
+            return;
+        }
+        Node::Element { tag, children, .. } => (tag.as_str(), children.as_slice()),
+    };
+
+    match tag {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = &tag[1..];
+            let body = inline(children);
+            emit_block(output, &format!("#heading(level: {})[{}];", level, body));
+        }
+        "ul" | "ol" => {
+            let items: Vec<String> = children
+                .iter()
+                .filter(|child| child.is_element(&["li"]))
+                .map(|item| format!("[{}]", inline(item.children())))
+                .collect();
+            let mut args = items.join(", ");
+            if tag == "ol"
+                && let Some(start) = node
+                    .attr("start")
+                    .and_then(|s| s.trim().parse::<u64>().ok())
+            {
+                args = format!("start: {}, {}", start, args);
+            }
+            let function = if tag == "ul" { "list" } else { "enum" };
+            emit_block(output, &format!("#{}({});", function, args));
+        }
+        "table" => emit_table(node, output),
+        "blockquote" => emit_block(
+            output,
+            &format!("#quote(block: true)[{}];", inline(children)),
+        ),
+        "pre" => {
+            let lang = node
+                .attr("lang")
+                .filter(|lang| !lang.is_empty())
+                .map(|lang| format!("lang: {}, ", string_literal(lang)))
+                .unwrap_or_default();
+            emit_block(
+                output,
+                &format!(
+                    "#raw(block: true, {}{});",
+                    lang,
+                    string_literal(node.text_content().trim_matches('\n'))
+                ),
+            )
+        }
+        "hr" => emit_block(output, "#line(length: 100%);"),
+        "strong" | "b" => emit_call(output, "strong", children),
+        "em" | "i" => emit_call(output, "emph", children),
+        "u" | "ins" => emit_call(output, "underline", children),
+        "s" | "del" | "strike" => emit_call(output, "strike", children),
+        "code" | "kbd" | "samp" => {
+            output.push_str(&format!("#raw({});", string_literal(&node.text_content())));
+        }
+        "a" => match node.attr("href").filter(|href| !href.is_empty()) {
+            Some(href) => {
+                output.push_str(&format!(
+                    "#link({})[{}];",
+                    string_literal(href),
+                    inline(children)
+                ));
+            }
+            None => emit_nodes(children, output),
+        },
+        "img" => {
+            if let Some(src) = node.attr("src").filter(|src| !src.is_empty()) {
+                output.push_str(&format!("#image({}", string_literal(src)));
+                if let Some(alt) = node.attr("alt") {
+                    output.push_str(&format!(", alt: {}", string_literal(alt)));
+                }
+                output.push_str(");");
+            }
+        }
+        "br" => output.push_str("#linebreak();"),
+        _ if BLOCK_ELEMENTS.contains(&tag) => {
+            output.push_str("\n\n");
+            emit_nodes(children, output);
+            output.push_str("\n\n");
+        }
+        _ => emit_nodes(children, output),
+    }
+}
+
+/// Emit `markup` as its own paragraph
+fn emit_block(output: &mut String, markup: &str) {
+    output.push_str("\n\n");
+    output.push_str(markup);
+    output.push_str("\n\n");
+}
+
+/// Emit `children` as the content argument of `function`
+fn emit_call(output: &mut String, function: &str, children: &[Node]) {
+    output.push_str(&format!("#{}[{}];", function, inline(children)));
+}
+
+fn emit_table(table: &Node, output: &mut String) {
+    // Rows may sit directly in the table or in a thead/tbody/tfoot section
+    let rows: Vec<&Node> = table
+        .children()
+        .iter()
+        .flat_map(|child| {
+            if child.is_element(&["thead", "tbody", "tfoot"]) {
+                child.children().iter().collect()
+            } else {
+                vec![child]
+            }
+        })
+        .filter(|child| child.is_element(&["tr"]))
+        .collect();
+
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            row.children()
+                .iter()
+                .filter(|cell| cell.is_element(&["td", "th"]))
+                .map(|cell| {
+                    let body = inline(cell.children());
+                    if cell.is_element(&["th"]) {
+                        format!("[#strong[{}];]", body)
+                    } else {
+                        format!("[{}]", body)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return;
+    }
+
+    let cells: Vec<String> = rows
+        .into_iter()
+        .flat_map(|mut row| {
+            row.resize(columns, "[]".to_string());
+            row
+        })
+        .collect();
+    emit_block(
+        output,
+        &format!("#table(columns: {}, {});", columns, cells.join(", ")),
+    );
+}
+
+/// Emit text with whitespace collapsed as HTML does and markup escaped
+fn emit_text(text: &str, output: &mut String) {
+    let mut last_was_space = output.ends_with([' ', '\n']);
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !last_was_space {
+                output.push(' ');
+                last_was_space = true;
+            }
+            continue;
+        }
+
+        if MARKUP_CHARS.contains(&c) {
+            output.push('\\');
+        }
+        output.push(c);
+        last_was_space = false;
+    }
+}
+
+/// Typst string literal for `value`
+fn string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converts_structure() {
+        let markup = html_to_typst(
+            "<h1>Report</h1>\n<p>Total: <b>42</b> <i>units</i></p>\n\
+             <ul><li>One<li>Two</ul><ol start=\"3\"><li>Three</li></ol>",
+        );
+
+        assert_eq!(
+            markup,
+            "#heading(level: 1)[Report];\n\nTotal: #strong[42]; #emph[units];\n\n\
+             #list([One], [Two]);\n\n#enum(start: 3, [Three]);\n"
+        );
+    }
+
+    #[test]
+    fn test_escapes_markup_in_text() {
+        let markup = html_to_typst("<p>1. #pay $5 *now* // &lt;b&gt; a&amp;b</p>");
+
+        assert_eq!(markup, "1\\. \\#pay \\$5 \\*now\\* \\/\\/ \\<b\\> a&b\n");
+    }
+
+    #[test]
+    fn test_converts_tables_links_and_images() {
+        let markup = html_to_typst(
+            "<table><thead><tr><th>Item</th><th>Qty</th></tr></thead>\
+             <tbody><tr><td>Pen<td>2<tr><td>Ink</td></tr></tbody></table>\
+             <p><a href=\"https://example.com\">Site</a><img src='logo.png' alt=\"Logo\"></p>",
+        );
+
+        assert!(markup.contains(
+            "#table(columns: 2, [#strong[Item];], [#strong[Qty];], [Pen], [2], [Ink], []);"
+        ));
+        assert!(markup.contains("#link(\"https://example.com\")[Site];"));
+        assert!(markup.contains("#image(\"logo.png\", alt: \"Logo\");"));
+    }
+
+    #[test]
+    fn test_skips_head_scripts_and_comments() {
+        let markup = html_to_typst(
+            "<!DOCTYPE html><html><head><title>T</title><style>p { color: red }</style></head>\
+             <body><!-- note --><script>alert('<p>')</script><p>Body</p></body></html>",
+        );
+
+        assert_eq!(markup, "Body\n");
+    }
+}
@@ -0,0 +1,139 @@
+//! Import depth limit
+//!
+//! Typst rejects cyclic imports itself, but a long non-cyclic chain of
+//! imports is resolved file by file without any bound. Before compiling,
+//! the import graph reachable from the main source is walked and the render
+//! is rejected if any chain is longer than the configured limit.
+
+use std::collections::HashMap;
+
+use typst::World;
+use typst::syntax::{FileId, SyntaxNode, ast};
+
+use crate::error::{CompilationError, Result};
+use crate::typst::PapermakeWorld;
+
+/// Default maximum number of nested imports from the main template
+pub const DEFAULT_MAX_IMPORT_DEPTH: usize = 64;
+
+/// Fail with `CompilationError::ImportResolution` if an import chain
+/// starting at the world's main source is deeper than `max_depth`
+///
+/// Package imports, missing files and cycles are skipped here; Typst
+/// reports those with better diagnostics during compilation.
+pub(crate) fn check_import_depth(world: &PapermakeWorld, max_depth: usize) -> Result<()> {
+    let mut walker = ImportWalker {
+        world,
+        max_depth,
+        chain: Vec::new(),
+        heights: HashMap::new(),
+    };
+    walker.height(world.main()).map(|_| ())
+}
+
+struct ImportWalker<'a> {
+    world: &'a PapermakeWorld,
+    max_depth: usize,
+    /// Files on the current import path, starting at the main source
+    chain: Vec<FileId>,
+    /// Fully explored files: length of their longest import chain and the
+    /// import it goes through
+    heights: HashMap<FileId, (usize, Option<FileId>)>,
+}
+
+impl ImportWalker<'_> {
+    /// Length of the longest import chain below `id`
+    fn height(&mut self, id: FileId) -> Result<usize> {
+        if let Some(&(height, _)) = self.heights.get(&id) {
+            if self.chain.len() + height > self.max_depth {
+                return Err(self.depth_error(Some(id)));
+            }
+            return Ok(height);
+        }
+        if self.chain.contains(&id) {
+            return Ok(0);
+        }
+        if self.chain.len() > self.max_depth {
+            return Err(self.depth_error(Some(id)));
+        }
+
+        let Ok(source) = self.world.source(id) else {
+            return Ok(0);
+        };
+
+        let mut imports = Vec::new();
+        collect_imports(source.root(), &mut imports);
+
+        self.chain.push(id);
+        let mut deepest = (0, None);
+        for path in imports {
+            let child = id.join(&path);
+            let height = self.height(child)? + 1;
+            if height > deepest.0 {
+                deepest = (height, Some(child));
+            }
+        }
+        self.chain.pop();
+
+        self.heights.insert(id, deepest);
+        Ok(deepest.0)
+    }
+
+    /// Error naming the current chain, extended through the deepest imports of `tail`
+    fn depth_error(&self, tail: Option<FileId>) -> crate::error::PapermakeError {
+        let mut chain = self.chain.clone();
+        let mut next = tail;
+        while let Some(id) = next {
+            chain.push(id);
+            next = self.heights.get(&id).and_then(|&(_, child)| child);
+        }
+
+        let names: Vec<String> = chain
+            .iter()
+            .map(|id| id.vpath().as_rootless_path().display().to_string())
+            .collect();
+
+        CompilationError::ImportResolution {
+            import_path: names.last().cloned().unwrap_or_default(),
+            reason: format!(
+                "import depth exceeds the limit of {}: {}",
+                self.max_depth,
+                names.join(" -> ")
+            ),
+        }
+        .into()
+    }
+}
+
+/// Literal paths of all file imports and includes in Typst `source`
+///
+/// Paths are returned as written, relative to the importing file unless
+/// they start with `/`. Package imports (`@preview/..`) and imports of
+/// computed paths are left out.
+pub fn file_imports(source: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    collect_imports(&typst::syntax::parse(source), &mut imports);
+    imports
+}
+
+/// Collect the literal paths of all file imports and includes below `node`
+fn collect_imports(node: &SyntaxNode, imports: &mut Vec<String>) {
+    let source = node
+        .cast::<ast::ModuleImport>()
+        .map(|import| import.source())
+        .or_else(|| {
+            node.cast::<ast::ModuleInclude>()
+                .map(|include| include.source())
+        });
+
+    if let Some(ast::Expr::Str(path)) = source {
+        let path = path.get();
+        if !path.starts_with('@') {
+            imports.push(path.to_string());
+        }
+    }
+
+    for child in node.children() {
+        collect_imports(child, imports);
+    }
+}
@@ -0,0 +1,277 @@
+//! Input fields a template consumes
+//!
+//! Integrators building data-entry forms need to know which fields to ask
+//! for. [`describe_inputs`] combines the template's JSON Schema, which says
+//! what the data may contain, with a scan of the main template for the
+//! fields it actually reads, like [`crate::lint`] does for its checks.
+//! [`used_inputs`] does the same one level up, for the `sys.inputs` keys.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+use typst::syntax::ast::{self, AstNode};
+use typst::syntax::{Source, SyntaxNode};
+
+use crate::lint::{collect_accesses, is_sys_inputs};
+
+/// One field of the template data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InputField {
+    /// Dotted path below `data`, e.g. `customer.name`
+    pub path: String,
+    /// The schema's `type` for the field, if it has one
+    pub type_hint: Option<String>,
+    /// Whether the schema requires the field, and every object containing it
+    pub required: bool,
+    /// Whether the main template reads the field, or a field nested in it
+    pub used_in_source: bool,
+}
+
+/// Describe the fields of the data consumed by `main_typ`
+///
+/// Every property of `schema` is listed, following nested `properties` of
+/// objects but not array `items`. Fields the template reads that the schema
+/// does not define are listed too, without type and not required. Reads
+/// below a field the schema types as something other than an object, e.g.
+/// `data.items.len()`, count as reads of that field.
+///
+/// Fields are sorted by path.
+pub fn describe_inputs(main_typ: &str, schema: Option<&Value>) -> Vec<InputField> {
+    let mut fields = BTreeMap::new();
+    if let Some(schema) = schema {
+        collect_schema_fields(schema, "", true, &mut fields);
+    }
+
+    let source = Source::detached(main_typ);
+    let mut accesses = Vec::new();
+    collect_accesses(source.root(), &mut accesses);
+
+    let mut used = BTreeSet::new();
+    for access in &accesses {
+        let mut path = String::new();
+        for (field, _) in &access.fields {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(field);
+            used.insert(path.clone());
+
+            let leaf = fields
+                .get(&path)
+                .is_some_and(|field: &InputField| field.type_hint.as_deref() != Some("object"));
+            if leaf {
+                break;
+            }
+        }
+    }
+
+    for path in used {
+        fields
+            .entry(path.clone())
+            .or_insert_with(|| InputField {
+                path,
+                type_hint: None,
+                required: false,
+                used_in_source: false,
+            })
+            .used_in_source = true;
+    }
+
+    fields.into_values().collect()
+}
+
+/// The `sys.inputs` keys `main_typ` reads, sorted
+///
+/// Counts `sys.inputs.key` and `sys.inputs.at("key")` with a literal key,
+/// and `data_key` if the template reads the `data` binding papermake
+/// decodes from it. Keys computed at runtime cannot be seen.
+pub fn used_inputs(main_typ: &str, data_key: &str) -> Vec<String> {
+    let source = Source::detached(main_typ);
+    let mut keys = BTreeSet::new();
+    collect_input_keys(source.root(), data_key, &mut keys);
+    keys.into_iter().collect()
+}
+
+fn collect_input_keys(node: &SyntaxNode, data_key: &str, keys: &mut BTreeSet<String>) {
+    if let Some(key) = input_key(node) {
+        keys.insert(key);
+    } else if let Some(access) = node.cast::<ast::FieldAccess>() {
+        // The field of `x.data` is not the `data` binding
+        collect_input_keys(access.target().to_untyped(), data_key, keys);
+        return;
+    } else if matches!(node.cast::<ast::Expr>(), Some(ast::Expr::Ident(ident)) if ident.get() == "data")
+    {
+        keys.insert(data_key.to_string());
+    }
+
+    for child in node.children() {
+        collect_input_keys(child, data_key, keys);
+    }
+}
+
+/// The literal key read by `node`, if it is `sys.inputs.key` or `sys.inputs.at("key")`
+fn input_key(node: &SyntaxNode) -> Option<String> {
+    if let Some(call) = node.cast::<ast::FuncCall>() {
+        let ast::Expr::FieldAccess(callee) = call.callee() else {
+            return None;
+        };
+        if !reads_sys_inputs(callee.to_untyped()) {
+            return None;
+        }
+        return match call.args().items().next() {
+            Some(ast::Arg::Pos(ast::Expr::Str(key))) => Some(key.get().to_string()),
+            _ => None,
+        };
+    }
+
+    let access = node.cast::<ast::FieldAccess>()?;
+    (reads_sys_inputs(node) && access.field().get() != "at")
+        .then(|| access.field().get().to_string())
+}
+
+/// Whether `node` is a field access or method call on `sys.inputs`
+pub(crate) fn reads_sys_inputs(node: &SyntaxNode) -> bool {
+    let access = match node.cast::<ast::FuncCall>() {
+        Some(call) => match call.callee() {
+            ast::Expr::FieldAccess(callee) => callee,
+            _ => return false,
+        },
+        None => match node.cast::<ast::FieldAccess>() {
+            Some(access) => access,
+            None => return false,
+        },
+    };
+    matches!(access.target(), ast::Expr::FieldAccess(target) if is_sys_inputs(target))
+}
+
+/// Record the properties of the object `schema` at `prefix`
+fn collect_schema_fields(
+    schema: &Value,
+    prefix: &str,
+    required: bool,
+    fields: &mut BTreeMap<String, InputField>,
+) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    let required_keys: BTreeSet<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+
+    for (key, property) in properties {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        let required = required && required_keys.contains(key.as_str());
+        fields.insert(
+            path.clone(),
+            InputField {
+                path: path.clone(),
+                type_hint: type_hint(property),
+                required,
+                used_in_source: false,
+            },
+        );
+        collect_schema_fields(property, &path, required, fields);
+    }
+}
+
+/// The property's type, the first one other than `null` for a list of types
+fn type_hint(property: &Value) -> Option<String> {
+    match property.get("type") {
+        Some(Value::String(name)) => Some(name.clone()),
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|name| *name != "null")
+            .map(str::to_string),
+        _ if property.get("properties").is_some() => Some("object".to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_and_source_fields() {
+        let schema = json!({
+            "type": "object",
+            "required": ["customer"],
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "email": { "type": ["null", "string"] }
+                    }
+                },
+                "items": { "type": "array", "items": { "type": "object" } },
+                "notes": { "type": "string" }
+            }
+        });
+        let fields = describe_inputs(
+            "#data.customer.name\n#data.items.len()\n#data.at(\"discount\")",
+            Some(&schema),
+        );
+
+        let summary: Vec<(&str, Option<&str>, bool, bool)> = fields
+            .iter()
+            .map(|field| {
+                (
+                    field.path.as_str(),
+                    field.type_hint.as_deref(),
+                    field.required,
+                    field.used_in_source,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("customer", Some("object"), true, true),
+                ("customer.email", Some("string"), false, false),
+                ("customer.name", Some("string"), true, true),
+                ("discount", None, false, true),
+                ("items", Some("array"), false, true),
+                ("notes", Some("string"), false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_used_inputs() {
+        let main_typ = "#let rows = json(bytes(sys.inputs.rows))\n\
+                        #sys.inputs.at(\"title\", default: none)\n\
+                        #data.name #page.data";
+        assert_eq!(
+            used_inputs(main_typ, "payload"),
+            ["payload", "rows", "title"]
+        );
+        assert!(used_inputs("= Static", "data").is_empty());
+    }
+
+    #[test]
+    fn test_without_schema() {
+        let fields = describe_inputs("#data.title", None);
+        assert_eq!(
+            fields,
+            vec![InputField {
+                path: "title".to_string(),
+                type_hint: None,
+                required: false,
+                used_in_source: true,
+            }]
+        );
+    }
+}
@@ -1,16 +1,55 @@
 //! Papermake is a PDF generation library that uses Typst templates
 //! with associated schemas to render PDFs from structured data.
 
+pub mod assets;
+pub mod charts;
+pub mod data_usage;
+pub mod decoration;
 pub mod error;
+pub mod html;
+pub mod imports;
+pub mod inputs;
+pub mod lint;
+pub mod locale;
+pub mod markdown;
+pub mod outline;
+pub mod pdf;
+pub mod preprocess;
 pub mod render;
+pub mod sample;
+pub mod tabular;
 pub mod typst;
+pub mod validation;
 // Re-export core types
+pub use assets::{AssetFallback, FallbackFileSystem};
+pub use decoration::{DecorationContent, PageDecoration};
 pub use error::{
     PapermakeError, Result, SourceLocation, TemplateError, compilation_error_from_diagnostics,
     convert_typst_diagnostic, template_missing_file,
 };
-pub use render::{RenderError, RenderResult, render_template, render_template_with_cache};
-pub use typst::{InMemoryFileSystem, PapermakeWorld, RenderFileSystem};
+pub use html::{HtmlRenderOptions, html_to_typst, render_html};
+pub use imports::{DEFAULT_MAX_IMPORT_DEPTH, file_imports};
+pub use inputs::{InputField, describe_inputs, used_inputs};
+pub use lint::{LintFinding, LintRule, lint_template};
+pub use locale::Locale;
+pub use markdown::{markdown_to_typst, render_markdown};
+pub use outline::OutlineEntry;
+pub use pdf::merge_pdfs;
+pub use preprocess::Preprocessor;
+pub use render::{
+    OutputFormat, PageInfo, PdfOptions, RenderError, RenderOptions, RenderOutput, RenderResult,
+    RenderedPage, Watermark, check_template, evict_cache, render_template,
+    render_template_from_reader, render_template_output, render_template_to,
+    render_template_to_writer, render_template_with_cache, render_template_with_inputs,
+    render_template_with_options, render_with_outline, render_with_thumbnail,
+};
+pub use sample::generate_sample_data;
+pub use tabular::{CsvOptions, parse_csv, render_template_with_csv};
+pub use typst::{
+    DATA_FILE_PATH, DEFAULT_DATA_KEY, FontSource, InMemoryFileSystem, PapermakeWorld,
+    RenderFileSystem,
+};
+pub use validation::validate_data;
 
 // Re-export typst types needed by papermake-registry
 pub use ::typst::diag::FileError;
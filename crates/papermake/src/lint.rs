@@ -0,0 +1,270 @@
+//! Static checks for template mistakes the compiler does not catch
+//!
+//! A template that reads `data.Customer` when the schema calls the field
+//! `customer` compiles fine and only fails at render time, for whichever
+//! payload first exercises it. [`lint_template`] scans the main template
+//! source and reports:
+//!
+//! - reads of fields the JSON Schema does not define, following nested
+//!   `properties` for chains like `data.customer.name` and suggesting the
+//!   intended field when only the case differs
+//! - templates that never read their data at all, which usually means the
+//!   data is read under a different name
+//!
+//! Like [`crate::data_usage`], the scan is purely syntactic and only sees
+//! static accesses such as `data.field` and `data.at("field")`.
+
+use serde::Serialize;
+use typst::syntax::ast::{self, AstNode};
+use typst::syntax::{Source, Span, SyntaxNode};
+
+use crate::error::SourceLocation;
+
+/// Name of the binding holding the template data
+const DATA_BINDING: &str = "data";
+
+/// What a [`LintFinding`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    /// A field read from `data` that the schema does not define
+    UnknownField,
+    /// The template never reads `data` or `sys.inputs`
+    DataNeverRead,
+}
+
+/// A likely mistake found by [`lint_template`]
+#[derive(Debug, Clone, Serialize)]
+pub struct LintFinding {
+    /// Which check produced the finding
+    pub rule: LintRule,
+    /// Human-readable description
+    pub message: String,
+    /// Where in the main template the problem is, if it has a location
+    pub location: Option<SourceLocation>,
+    /// Suggestions for fixing it
+    pub hints: Vec<String>,
+}
+
+/// Check the main template `content` for likely mistakes
+///
+/// Field accesses are checked against `schema` only where it lists
+/// `properties`; without a schema, only the data-never-read check runs.
+pub fn lint_template(content: &str, schema: Option<&serde_json::Value>) -> Vec<LintFinding> {
+    let source = Source::detached(content);
+    let mut accesses = Vec::new();
+    let reads_data = collect_accesses(source.root(), &mut accesses);
+
+    let mut findings = Vec::new();
+    if !reads_data {
+        findings.push(LintFinding {
+            rule: LintRule::DataNeverRead,
+            message: "the template never reads its data".to_string(),
+            location: None,
+            hints: vec![format!(
+                "data is bound as `{}`, e.g. `#{}.customer.name`",
+                DATA_BINDING, DATA_BINDING
+            )],
+        });
+    }
+
+    if let Some(schema) = schema {
+        for access in &accesses {
+            if let Some(finding) = check_access(&source, schema, access) {
+                findings.push(finding);
+            }
+        }
+    }
+
+    findings
+}
+
+/// A static chain of field reads starting at `data`
+pub(crate) struct FieldChain {
+    pub(crate) fields: Vec<(String, Span)>,
+}
+
+/// Check `access` against the nested `properties` of `schema`
+fn check_access(
+    source: &Source,
+    schema: &serde_json::Value,
+    access: &FieldChain,
+) -> Option<LintFinding> {
+    let mut node = schema;
+    let mut path = DATA_BINDING.to_string();
+
+    for (field, span) in &access.fields {
+        // Schemas without properties, e.g. arrays or free-form objects,
+        // say nothing about which fields exist
+        let properties = node.get("properties")?.as_object()?;
+        if let Some(property) = properties.get(field) {
+            node = property;
+            path = format!("{}.{}", path, field);
+            continue;
+        }
+
+        let hints = match properties
+            .keys()
+            .find(|key| key.eq_ignore_ascii_case(field))
+        {
+            Some(key) => vec![format!("did you mean `{}.{}`?", path, key)],
+            None => vec![format!(
+                "the schema defines {}",
+                properties
+                    .keys()
+                    .map(|key| format!("`{}.{}`", path, key))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )],
+        };
+        return Some(LintFinding {
+            rule: LintRule::UnknownField,
+            message: format!("`{}.{}` is not defined in the schema", path, field),
+            location: location(source, *span),
+            hints,
+        });
+    }
+
+    None
+}
+
+/// Location of `span` within `source`
+fn location(source: &Source, span: Span) -> Option<SourceLocation> {
+    let range = source.range(span)?;
+    Some(SourceLocation {
+        file: "main.typ".to_string(),
+        line: source.byte_to_line(range.start)? + 1,
+        column: source.byte_to_column(range.start)? + 1,
+        range: Some((range.start, range.end)),
+    })
+}
+
+/// Record static field chains on `data` below `node`
+///
+/// Returns whether `data` or `sys.inputs` is used anywhere below `node`.
+pub(crate) fn collect_accesses(node: &SyntaxNode, accesses: &mut Vec<FieldChain>) -> bool {
+    if let Some(call) = node.cast::<ast::FuncCall>()
+        && let ast::Expr::FieldAccess(access) = call.callee()
+        && is_data(access.target())
+        && access.field().get() == "at"
+    {
+        if let Some(ast::Arg::Pos(ast::Expr::Str(key))) = call.args().items().next() {
+            accesses.push(FieldChain {
+                fields: vec![(key.get().to_string(), key.span())],
+            });
+        }
+        call.args().to_untyped().children().for_each(|child| {
+            collect_accesses(child, accesses);
+        });
+        return true;
+    }
+
+    if let Some(access) = node.cast::<ast::FieldAccess>() {
+        if let Some(fields) = data_chain(access) {
+            accesses.push(FieldChain { fields });
+            return true;
+        }
+        if is_sys_inputs(access) {
+            return true;
+        }
+    }
+
+    let mut reads_data = node.cast::<ast::Expr>().is_some_and(is_data);
+    for child in node.children() {
+        reads_data |= collect_accesses(child, accesses);
+    }
+    reads_data
+}
+
+/// Fields of a chain like `data.a.b`, outermost last, if it starts at `data`
+fn data_chain(access: ast::FieldAccess) -> Option<Vec<(String, Span)>> {
+    let field = (
+        access.field().get().to_string(),
+        access.field().to_untyped().span(),
+    );
+    let mut fields = match access.target() {
+        ast::Expr::FieldAccess(inner) => data_chain(inner)?,
+        target if is_data(target) => Vec::new(),
+        _ => return None,
+    };
+    fields.push(field);
+    Some(fields)
+}
+
+pub(crate) fn is_sys_inputs(access: ast::FieldAccess) -> bool {
+    access.field().get() == "inputs"
+        && matches!(access.target(), ast::Expr::Ident(ident) if ident.get() == "sys")
+}
+
+fn is_data(expr: ast::Expr) -> bool {
+    matches!(expr, ast::Expr::Ident(ident) if ident.get() == DATA_BINDING)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}}
+                },
+                "items": {"type": "array"}
+            }
+        })
+    }
+
+    #[test]
+    fn test_reports_misspelled_field() {
+        let findings = lint_template(
+            "= Invoice\nFor #data.Customer.name\n#data.customer.nmae",
+            Some(&schema()),
+        );
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].rule, LintRule::UnknownField);
+        assert_eq!(
+            findings[0].message,
+            "`data.Customer` is not defined in the schema"
+        );
+        assert_eq!(findings[0].hints, vec!["did you mean `data.customer`?"]);
+        let location = findings[0].location.as_ref().unwrap();
+        assert_eq!((location.line, location.column), (2, 11));
+
+        assert_eq!(
+            findings[1].message,
+            "`data.customer.nmae` is not defined in the schema"
+        );
+        assert_eq!(
+            findings[1].hints,
+            vec!["the schema defines `data.customer.name`"]
+        );
+        assert_eq!(findings[1].location.as_ref().unwrap().line, 3);
+    }
+
+    #[test]
+    fn test_known_fields_and_untyped_subtrees_pass() {
+        let findings = lint_template(
+            "#data.customer.name\n#data.items.len()\n#data.at(\"customer\")",
+            Some(&schema()),
+        );
+
+        assert!(findings.is_empty(), "{:?}", findings);
+    }
+
+    #[test]
+    fn test_reports_template_never_reading_data() {
+        let findings = lint_template("= Invoice\n#let total = 42\n#total", None);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, LintRule::DataNeverRead);
+
+        let decoded = "#let payload = json(bytes(sys.inputs.payload))\n#payload.total";
+        assert!(lint_template(decoded, None).is_empty());
+        assert!(lint_template("#repr(data)", None).is_empty());
+    }
+}
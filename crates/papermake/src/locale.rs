@@ -0,0 +1,219 @@
+//! Locale-aware formatting helpers for templates
+//!
+//! Typst formats numbers as `1234567.5` and has no notion of grouping
+//! separators or currency placement, so invoices end up reimplementing them
+//! per template. With [`RenderOptions::inject_prelude`] set, the template
+//! prelude imports a module of helpers configured for a [`Locale`] as `pm`:
+//!
+//! - `pm.locale`: the locale code, e.g. `"de-DE"`
+//! - `pm.format-number(value, decimals: 2)`: `1.234.567,50`
+//! - `pm.format-currency(value, decimals: 2)`: `1.234.567,50 €`
+//! - `pm.format-date(date, pattern: ..)`: `01.03.2025`, where `date` is a
+//!   `datetime` or an ISO 8601 `YYYY-MM-DD` string as found in JSON data
+//! - `pm.bar-chart` and `pm.line-chart`, see [`crate::charts`]
+//!
+//! Dates are formatted with Typst's `datetime.display` patterns, so month
+//! names are not localized.
+//!
+//! [`RenderOptions::inject_prelude`]: crate::RenderOptions::inject_prelude
+
+use crate::charts;
+use crate::error::{ConfigError, PapermakeError, Result};
+use crate::render::typst_string;
+
+/// Virtual path of the module the prelude imports as `pm`
+pub(crate) const MODULE_PATH: &str = "/.papermake/pm.typ";
+
+/// Placeholder for the formatted amount in [`Locale::currency_pattern`]
+pub const AMOUNT_PLACEHOLDER: &str = "{amount}";
+
+/// Number, currency and date conventions for the `pm` template helpers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    /// Locale code exposed to templates as `pm.locale`
+    pub code: String,
+    /// Separator between the integer and fractional digits
+    pub decimal_separator: String,
+    /// Separator between groups of three integer digits
+    pub group_separator: String,
+    /// Currency format, with [`AMOUNT_PLACEHOLDER`] for the formatted number
+    pub currency_pattern: String,
+    /// Default `datetime.display` pattern for `pm.format-date`
+    pub date_pattern: String,
+}
+
+/// Built-in locales: code, decimal and group separator, currency and date pattern
+const LOCALES: &[(&str, &str, &str, &str, &str)] = &[
+    ("en-US", ".", ",", "${amount}", "[month]/[day]/[year]"),
+    ("en-GB", ".", ",", "£{amount}", "[day]/[month]/[year]"),
+    ("de-DE", ",", ".", "{amount} €", "[day].[month].[year]"),
+    ("de-AT", ",", ".", "€ {amount}", "[day].[month].[year]"),
+    ("de-CH", ".", "’", "CHF {amount}", "[day].[month].[year]"),
+    (
+        "fr-FR",
+        ",",
+        "\u{202f}",
+        "{amount} €",
+        "[day]/[month]/[year]",
+    ),
+    ("es-ES", ",", ".", "{amount} €", "[day]/[month]/[year]"),
+    ("it-IT", ",", ".", "{amount} €", "[day]/[month]/[year]"),
+    ("nl-NL", ",", ".", "€ {amount}", "[day]-[month]-[year]"),
+    ("ja-JP", ".", ",", "¥{amount}", "[year]/[month]/[day]"),
+];
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::from_code("en-US").expect("en-US is a built-in locale")
+    }
+}
+
+impl Locale {
+    /// Built-in locale for `code`, e.g. `"en-US"` or `"de_DE"`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::InvalidConfig` if there is no built-in locale
+    /// for `code`. Other locales can be built by adjusting a built-in one
+    /// with the `with_*` methods.
+    pub fn from_code(code: &str) -> Result<Self> {
+        let normalized = code.replace('_', "-");
+        LOCALES
+            .iter()
+            .find(|(known, ..)| known.eq_ignore_ascii_case(&normalized))
+            .map(|&(code, decimal, group, currency, date)| Self {
+                code: code.to_string(),
+                decimal_separator: decimal.to_string(),
+                group_separator: group.to_string(),
+                currency_pattern: currency.to_string(),
+                date_pattern: date.to_string(),
+            })
+            .ok_or_else(|| {
+                PapermakeError::Config(ConfigError::InvalidConfig {
+                    setting: "locale".to_string(),
+                    reason: format!(
+                        "'{}' is not a built-in locale; expected one of {}",
+                        code,
+                        LOCALES
+                            .iter()
+                            .map(|(code, ..)| *code)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                })
+            })
+    }
+
+    /// Set the decimal separator
+    pub fn with_decimal_separator(mut self, separator: impl Into<String>) -> Self {
+        self.decimal_separator = separator.into();
+        self
+    }
+
+    /// Set the digit group separator
+    pub fn with_group_separator(mut self, separator: impl Into<String>) -> Self {
+        self.group_separator = separator.into();
+        self
+    }
+
+    /// Set the currency format, e.g. `"{amount} EUR"`
+    pub fn with_currency_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.currency_pattern = pattern.into();
+        self
+    }
+
+    /// Set the default date pattern, e.g. `"[year]-[month]-[day]"`
+    pub fn with_date_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.date_pattern = pattern.into();
+        self
+    }
+
+    /// Reject a currency pattern without an amount placeholder
+    pub(crate) fn validate(&self) -> Result<()> {
+        if !self.currency_pattern.contains(AMOUNT_PLACEHOLDER) {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "currency_pattern".to_string(),
+                reason: format!(
+                    "'{}' must contain {}",
+                    self.currency_pattern, AMOUNT_PLACEHOLDER
+                ),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Prelude line importing the helpers module as `pm`
+    pub(crate) fn prelude(&self) -> String {
+        format!("#import \"{}\" as pm\n", MODULE_PATH)
+    }
+
+    /// Source of the helpers module served at [`MODULE_PATH`]
+    pub(crate) fn module(&self) -> String {
+        let formatting = format!(
+            r#"#let locale = {code}
+
+#let format-number(value, decimals: 2) = {{
+  let digits = str(int(calc.round(calc.abs(value) * calc.pow(10, decimals))))
+  while digits.len() <= decimals {{ digits = "0" + digits }}
+  let integer = digits.slice(0, digits.len() - decimals)
+  let grouped = ""
+  for (i, digit) in integer.clusters().enumerate() {{
+    if i > 0 and calc.rem(integer.len() - i, 3) == 0 {{ grouped += {group} }}
+    grouped += digit
+  }}
+  if value < 0 {{ grouped = "-" + grouped }}
+  if decimals > 0 {{ grouped += {decimal} + digits.slice(integer.len()) }}
+  grouped
+}}
+
+#let format-currency(value, decimals: 2) = {{
+  {currency}.replace({placeholder}, format-number(value, decimals: decimals))
+}}
+
+#let format-date(date, pattern: {date}) = {{
+  if type(date) == str {{
+    let (year, month, day) = date.slice(0, 10).split("-").map(int)
+    date = datetime(year: year, month: month, day: day)
+  }}
+  date.display(pattern)
+}}
+"#,
+            code = typst_string(&self.code),
+            group = typst_string(&self.group_separator),
+            decimal = typst_string(&self.decimal_separator),
+            currency = typst_string(&self.currency_pattern),
+            placeholder = typst_string(AMOUNT_PLACEHOLDER),
+            date = typst_string(&self.date_pattern),
+        );
+        formatting + charts::MODULE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code_normalizes_and_rejects_unknown() {
+        let locale = Locale::from_code("de_de").unwrap();
+        assert_eq!(locale.code, "de-DE");
+        assert_eq!(locale.decimal_separator, ",");
+        assert_eq!(locale.group_separator, ".");
+
+        assert!(matches!(
+            Locale::from_code("xx-YY"),
+            Err(PapermakeError::Config(ConfigError::InvalidConfig { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_currency_pattern_requires_placeholder() {
+        assert!(Locale::default().validate().is_ok());
+        assert!(
+            Locale::default()
+                .with_currency_pattern("USD")
+                .validate()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Rendering Markdown content without authoring a Typst template
+//!
+//! CommonMark, plus GitHub-style tables and strikethrough, is parsed into
+//! the element tree used by [`crate::html`] and rendered through the same
+//! Typst conversion. Fenced code blocks become monospace `raw` blocks,
+//! highlighted when the fence names a language Typst knows.
+//!
+//! Raw HTML embedded in the Markdown is skipped, as are footnotes and task
+//! list markers. Image paths are resolved against
+//! [`HtmlRenderOptions::file_system`].
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+use crate::error::Result;
+use crate::html::{HtmlRenderOptions, Node, nodes_to_typst, render_nodes};
+use crate::render::RenderResult;
+
+/// Render Markdown content to PDF
+///
+/// Accepts the same page options as [`crate::render_html`].
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` for a malformed paper size or
+/// margin. Problems within the content, such as a missing image, are
+/// reported in `RenderResult::errors`.
+pub fn render_markdown(markdown: &str, options: HtmlRenderOptions) -> Result<RenderResult> {
+    render_nodes(&parse(markdown), options)
+}
+
+/// Convert Markdown into equivalent Typst markup
+pub fn markdown_to_typst(markdown: &str) -> String {
+    nodes_to_typst(&parse(markdown))
+}
+
+/// An element whose end event has not been seen yet
+struct OpenElement {
+    tag: &'static str,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl OpenElement {
+    fn new(tag: &'static str) -> Self {
+        Self {
+            tag,
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn with_attr(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.attrs.push((key.to_string(), value.into()));
+        self
+    }
+}
+
+/// Parse Markdown into the HTML element tree
+fn parse(markdown: &str) -> Vec<Node> {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    );
+    let mut stack = vec![OpenElement::new("")];
+    let mut in_table_head = false;
+
+    for event in parser {
+        match event {
+            Event::Start(tag) => {
+                let open = match tag {
+                    Tag::Paragraph => OpenElement::new("p"),
+                    Tag::Heading { level, .. } => OpenElement::new(match level as usize {
+                        1 => "h1",
+                        2 => "h2",
+                        3 => "h3",
+                        4 => "h4",
+                        5 => "h5",
+                        _ => "h6",
+                    }),
+                    Tag::BlockQuote(_) => OpenElement::new("blockquote"),
+                    Tag::CodeBlock(CodeBlockKind::Fenced(info)) => {
+                        // The info string may carry more than the language
+                        let lang = info.split_whitespace().next().unwrap_or_default();
+                        OpenElement::new("pre").with_attr("lang", lang)
+                    }
+                    Tag::CodeBlock(CodeBlockKind::Indented) => OpenElement::new("pre"),
+                    Tag::List(Some(start)) => {
+                        OpenElement::new("ol").with_attr("start", start.to_string())
+                    }
+                    Tag::List(None) => OpenElement::new("ul"),
+                    Tag::Item => OpenElement::new("li"),
+                    Tag::Table(_) => OpenElement::new("table"),
+                    // The header row's cells are not wrapped in a TableRow
+                    Tag::TableHead => {
+                        in_table_head = true;
+                        OpenElement::new("tr")
+                    }
+                    Tag::TableRow => OpenElement::new("tr"),
+                    Tag::TableCell if in_table_head => OpenElement::new("th"),
+                    Tag::TableCell => OpenElement::new("td"),
+                    Tag::Emphasis => OpenElement::new("em"),
+                    Tag::Strong => OpenElement::new("strong"),
+                    Tag::Strikethrough => OpenElement::new("del"),
+                    Tag::Link { dest_url, .. } => {
+                        OpenElement::new("a").with_attr("href", dest_url.to_string())
+                    }
+                    Tag::Image { dest_url, .. } => {
+                        OpenElement::new("img").with_attr("src", dest_url.to_string())
+                    }
+                    // Kept as a transparent container so its end event pairs up
+                    _ => OpenElement::new("span"),
+                };
+                stack.push(open);
+            }
+            Event::End(tag) => {
+                if tag == TagEnd::TableHead {
+                    in_table_head = false;
+                }
+                // The root is never closed
+                if stack.len() < 2 {
+                    continue;
+                }
+                let mut open = stack.pop().unwrap();
+                // The image description is its alt text, not content
+                if open.tag == "img" {
+                    let alt = std::mem::take(&mut open.children)
+                        .iter()
+                        .map(Node::text_content)
+                        .collect::<String>();
+                    open = open.with_attr("alt", alt);
+                }
+                let node = Node::Element {
+                    tag: open.tag.to_string(),
+                    attrs: open.attrs,
+                    children: open.children,
+                };
+                push(&mut stack, node);
+            }
+            Event::Text(text) => push(&mut stack, Node::Text(text.to_string())),
+            Event::Code(code) => push(
+                &mut stack,
+                Node::element("code", vec![Node::Text(code.to_string())]),
+            ),
+            Event::SoftBreak => push(&mut stack, Node::Text(" ".to_string())),
+            Event::HardBreak => push(&mut stack, Node::element("br", Vec::new())),
+            Event::Rule => push(&mut stack, Node::element("hr", Vec::new())),
+            _ => {}
+        }
+    }
+
+    stack
+        .into_iter()
+        .next()
+        .map(|root| root.children)
+        .unwrap_or_default()
+}
+
+fn push(stack: &mut [OpenElement], node: Node) {
+    if let Some(open) = stack.last_mut() {
+        open.children.push(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converts_headings_lists_and_inline_formatting() {
+        let markup = markdown_to_typst(
+            "# Invoice\n\nDue **now**, _please_.\n\n- One\n- Two\n\n3. Three\n4. Four\n",
+        );
+
+        assert_eq!(
+            markup,
+            "#heading(level: 1)[Invoice];\n\nDue #strong[now];, #emph[please];\\.\n\n\
+             #list([One], [Two]);\n\n#enum(start: 3, [Three], [Four]);\n"
+        );
+    }
+
+    #[test]
+    fn test_converts_tables_and_code_blocks() {
+        let markup = markdown_to_typst(
+            "| Item | Qty |\n|------|----:|\n| Pen  | 2   |\n\n\
+             ```rust title=\"x\"\nfn main() {\n    println!(\"hi\");\n}\n```\n",
+        );
+
+        assert!(
+            markup.contains("#table(columns: 2, [#strong[Item];], [#strong[Qty];], [Pen], [2]);")
+        );
+        assert!(markup.contains(
+            "#raw(block: true, lang: \"rust\", \"fn main() {\\n    println!(\\\"hi\\\");\\n}\");"
+        ));
+    }
+
+    #[test]
+    fn test_image_description_becomes_alt_text() {
+        let markup = markdown_to_typst("![Company *logo*](logo.png)");
+
+        assert_eq!(markup, "#image(\"logo.png\", alt: \"Company logo\");\n");
+    }
+}
@@ -0,0 +1,47 @@
+//! Heading outline extraction
+//!
+//! Reads the heading hierarchy from a compiled document's introspection data,
+//! e.g. for building a table of contents or a navigation sidebar.
+
+use serde::Serialize;
+use typst::foundations::{NativeElement, StyleChain};
+use typst::layout::PagedDocument;
+use typst::model::HeadingElem;
+
+/// A single heading in a rendered document's outline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineEntry {
+    /// Heading level, starting at 1 for top-level headings
+    pub level: usize,
+    /// Plain-text heading title with markup stripped
+    pub title: String,
+    /// 1-based page number the heading appears on
+    pub page: usize,
+}
+
+/// Extract all outlined headings from a compiled document, in document order
+///
+/// Headings with `outlined: false` are skipped, matching what Typst's own
+/// `outline()` and the PDF bookmarks show.
+pub(crate) fn extract_outline(document: &PagedDocument) -> Vec<OutlineEntry> {
+    let introspector = &document.introspector;
+
+    introspector
+        .query(&HeadingElem::elem().select())
+        .iter()
+        .filter_map(|content| {
+            let heading = content.to_packed::<HeadingElem>()?;
+            if !heading.outlined(StyleChain::default()) {
+                return None;
+            }
+
+            let page = introspector.page(content.location()?).get();
+
+            Some(OutlineEntry {
+                level: heading.resolve_level(StyleChain::default()).get(),
+                title: heading.body.plain_text().trim().to_string(),
+                page,
+            })
+        })
+        .collect()
+}
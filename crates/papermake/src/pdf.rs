@@ -0,0 +1,196 @@
+//! Combining rendered PDFs into one document
+//!
+//! Batch renders produce one PDF per data set, e.g. one per invoice, while
+//! printing wants a single file. [`merge_pdfs`] concatenates the page trees
+//! of several PDFs and stores resources they share, such as a logo or an
+//! embedded font subset, only once.
+
+use std::collections::{HashMap, HashSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId, dictionary};
+
+use crate::error::{DataError, FileSystemError, PapermakeError, Result};
+
+/// Page attributes a page may inherit from its ancestors in the page tree
+const INHERITABLE_ATTRIBUTES: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Concatenate `pdfs` into a single PDF, pages in input order
+///
+/// Only the pages and what they reference are carried over: outlines,
+/// named destinations, page labels and document metadata of the inputs are
+/// dropped. Objects that are byte-for-byte identical across inputs are
+/// merged, which deduplicates images and fonts shared between renders of
+/// the same template.
+///
+/// # Errors
+///
+/// Fails with [`DataError::InvalidFieldValue`] for `pdfs[i]` if an input
+/// cannot be parsed, or for `pdfs` if there are no pages to merge.
+pub fn merge_pdfs(pdfs: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let mut merged = Document::with_version("1.7");
+    let pages_id = merged.new_object_id();
+    let mut kids = Vec::new();
+
+    for (index, pdf) in pdfs.iter().enumerate() {
+        let field = format!("pdfs[{}]", index);
+        let mut document = Document::load_mem(pdf).map_err(|e| invalid_input(&field, e))?;
+        document.renumber_objects_with(merged.max_id + 1);
+        merged.max_id = merged.max_id.max(document.max_id);
+
+        let page_ids: Vec<ObjectId> = document.page_iter().collect();
+        for &page_id in &page_ids {
+            let mut page =
+                flattened_page(&document, page_id).map_err(|e| invalid_input(&field, e))?;
+            page.set("Parent", pages_id);
+            document.objects.insert(page_id, Object::Dictionary(page));
+        }
+        kids.extend(page_ids.into_iter().map(Object::Reference));
+        merged.objects.extend(document.objects);
+    }
+
+    if kids.is_empty() {
+        return Err(invalid_input("pdfs", "no pages to merge"));
+    }
+
+    let count = kids.len() as i64;
+    merged.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+        }),
+    );
+    let catalog_id = merged.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    merged.trailer.set("Root", catalog_id);
+
+    // The inputs' catalogs and page trees are no longer referenced
+    merged.prune_objects();
+    deduplicate(&mut merged);
+    merged.renumber_objects();
+
+    let mut bytes = Vec::new();
+    merged.save_to(&mut bytes).map_err(|e| {
+        PapermakeError::FileSystem(FileSystemError::WriteError {
+            path: "merged.pdf".to_string(),
+            reason: e.to_string(),
+        })
+    })?;
+    Ok(bytes)
+}
+
+/// The page's dictionary with the attributes it inherits copied into it
+fn flattened_page(document: &Document, page_id: ObjectId) -> lopdf::Result<Dictionary> {
+    let mut page = document.get_dictionary(page_id)?.clone();
+    let mut visited = HashSet::from([page_id]);
+
+    let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+    while let Some(parent_id) = parent.filter(|id| visited.insert(*id)) {
+        let node = document.get_dictionary(parent_id)?;
+        for key in INHERITABLE_ATTRIBUTES {
+            if !page.has(key)
+                && let Ok(value) = node.get(key)
+            {
+                page.set(key, value.clone());
+            }
+        }
+        parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+
+    Ok(page)
+}
+
+/// Replace identical objects by one of them until none are left
+///
+/// Merging leaf objects like font files can make the objects referencing
+/// them identical in turn, hence the repetition.
+fn deduplicate(document: &mut Document) {
+    loop {
+        let mut first: HashMap<Vec<u8>, ObjectId> = HashMap::new();
+        let mut replace: HashMap<ObjectId, ObjectId> = HashMap::new();
+        for (&id, object) in &document.objects {
+            if let Some(key) = dedup_key(object) {
+                let original = *first.entry(key).or_insert(id);
+                if original != id {
+                    replace.insert(id, original);
+                }
+            }
+        }
+        if replace.is_empty() {
+            return;
+        }
+
+        for id in replace.keys() {
+            document.objects.remove(id);
+        }
+        for object in document.objects.values_mut() {
+            replace_references(object, &replace);
+        }
+        for (_, value) in document.trailer.iter_mut() {
+            replace_references(value, &replace);
+        }
+    }
+}
+
+/// Content identifying `object` for deduplication, if it may be shared
+///
+/// Pages and annotations belong to exactly one page, so identical ones are
+/// kept apart.
+fn dedup_key(object: &Object) -> Option<Vec<u8>> {
+    let (kind, dict, content) = match object {
+        Object::Stream(stream) => (b'S', &stream.dict, stream.content.as_slice()),
+        Object::Dictionary(dict) => (b'D', dict, &[][..]),
+        _ => return None,
+    };
+    let per_page = dict.has(b"Rect")
+        || matches!(
+            dict.get(b"Type").and_then(Object::as_name),
+            Ok(b"Page" | b"Pages" | b"Catalog" | b"Annot")
+        );
+    if per_page {
+        return None;
+    }
+
+    let mut key = vec![kind];
+    key.extend_from_slice(format!("{:?}", dict).as_bytes());
+    key.push(0);
+    key.extend_from_slice(content);
+    Some(key)
+}
+
+/// Point references to replaced objects at their replacement
+fn replace_references(object: &mut Object, replace: &HashMap<ObjectId, ObjectId>) {
+    match object {
+        Object::Reference(id) => {
+            if let Some(replacement) = replace.get(id) {
+                *id = *replacement;
+            }
+        }
+        Object::Array(items) => {
+            for item in items {
+                replace_references(item, replace);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (_, value) in dict.iter_mut() {
+                replace_references(value, replace);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, value) in stream.dict.iter_mut() {
+                replace_references(value, replace);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn invalid_input(field: &str, reason: impl ToString) -> PapermakeError {
+    PapermakeError::Data(DataError::InvalidFieldValue {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    })
+}
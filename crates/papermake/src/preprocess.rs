@@ -0,0 +1,142 @@
+//! Placeholder substitution for templates not written against `data`
+//!
+//! Lets existing `{{placeholder}}` templates render without rewriting them
+//! in Typst's data-binding syntax. Before the main template is handed to
+//! Typst, every `{{ path }}` is replaced by the value at `path` in the data,
+//! where `path` is a dot-separated list of object keys and array indices,
+//! e.g. `{{ customer.name }}` or `{{ items.0.price }}`. Strings are inserted
+//! as-is, numbers and booleans in their JSON form, and objects and arrays as
+//! JSON text. Missing paths and `null` produce nothing, as in Mustache.
+//!
+//! # Escaping
+//!
+//! Substitution is textual, so values become part of the Typst source. A
+//! value containing markup like `#` or `[` would otherwise be interpreted,
+//! which lets whoever controls the data inject arbitrary Typst code (read
+//! template files, change the layout, or hide content). `{{ path }}`
+//! therefore escapes every Typst markup character with a backslash, the
+//! Typst equivalent of Mustache's HTML escaping. The escaping is only
+//! correct in markup: a placeholder inside a Typst string literal or code
+//! expression needs `data` instead. `{{{ path }}}` inserts the value
+//! unescaped and must only be used with trusted data.
+//!
+//! Only the main template is preprocessed; imported files are not.
+
+use crate::html::MARKUP_CHARS;
+
+/// Source transformation applied to the main template before compilation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preprocessor {
+    /// Substitute Mustache-style `{{ path }}` placeholders from the data
+    Mustache,
+}
+
+impl Preprocessor {
+    /// Apply the preprocessor to `source`
+    pub fn apply(&self, source: &str, data: &serde_json::Value) -> String {
+        match self {
+            Preprocessor::Mustache => substitute(source, data),
+        }
+    }
+}
+
+/// Replace `{{ path }}` and `{{{ path }}}` placeholders in `source`
+///
+/// An opening brace pair without a matching close is kept verbatim.
+fn substitute(source: &str, data: &serde_json::Value) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start..];
+
+        let (open, close, escape) = if after.starts_with("{{{") {
+            ("{{{", "}}}", false)
+        } else {
+            ("{{", "}}", true)
+        };
+        let Some(end) = after[open.len()..].find(close) else {
+            output.push_str(after);
+            return output;
+        };
+
+        let path = after[open.len()..open.len() + end].trim();
+        let value = lookup(data, path).map(value_text).unwrap_or_default();
+        if escape {
+            escape_markup(&value, &mut output);
+        } else {
+            output.push_str(&value);
+        }
+        rest = &after[open.len() + end + close.len()..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Value at the dot-separated `path`, if present
+fn lookup<'a>(data: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.')
+        .try_fold(data, |value, segment| match value {
+            serde_json::Value::Object(object) => object.get(segment),
+            serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// Text inserted for `value`
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Append `text` with Typst markup characters escaped
+fn escape_markup(text: &str, output: &mut String) {
+    for c in text.chars() {
+        if MARKUP_CHARS.contains(&c) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_substitutes_nested_paths() {
+        let data = json!({
+            "customer": {"name": "Acme"},
+            "items": [{"qty": 2}],
+            "paid": true,
+            "note": null
+        });
+
+        assert_eq!(
+            substitute(
+                "{{customer.name}}: {{ items.0.qty }} {{paid}}{{note}}{{missing.key}}",
+                &data
+            ),
+            "Acme: 2 true"
+        );
+    }
+
+    #[test]
+    fn test_escapes_markup_unless_triple_braced() {
+        let data = json!({"name": "#panic() [x]"});
+
+        assert_eq!(substitute("{{name}}", &data), "\\#panic() \\[x\\]");
+        assert_eq!(substitute("{{{name}}}", &data), "#panic() [x]");
+    }
+
+    #[test]
+    fn test_keeps_unclosed_placeholder() {
+        assert_eq!(substitute("a {{name", &json!({"name": "b"})), "a {{name");
+    }
+}
@@ -3,16 +3,36 @@
 //! This module provides the main template rendering functionality,
 //! converting Typst templates with JSON data into PDF documents.
 
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{Read, Write};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
 
 use serde::Serialize;
 use typst::World;
 use typst::WorldExt;
-use typst_pdf::PdfOptions;
+use typst::diag::SourceDiagnostic;
+use typst::foundations::Datetime;
+use typst::layout::{Frame, FrameItem, Page, PageRanges, PagedDocument};
+use typst::syntax::Span;
+use typst_pdf::{PdfOptions as TypstPdfOptions, PdfStandard, PdfStandards, Timestamp};
 
 use crate::RenderFileSystem;
-use crate::error::{CompilationError, PapermakeError, Result};
-use crate::typst::PapermakeWorld;
+use crate::assets::{AssetFallback, FallbackFileSystem};
+use crate::data_usage::unused_data_keys;
+use crate::decoration::PageDecoration;
+use crate::error::{
+    CompilationError, ConfigError, DiagnosticInfo, DiagnosticSeverity, PapermakeError, Result,
+    SourceLocation, convert_typst_diagnostic,
+};
+use crate::imports::{DEFAULT_MAX_IMPORT_DEPTH, check_import_depth};
+use crate::inputs::{reads_sys_inputs, used_inputs};
+use crate::locale::{self, Locale};
+use crate::outline::{OutlineEntry, extract_outline};
+use crate::preprocess::Preprocessor;
+use crate::typst::{DEFAULT_DATA_KEY, FontSource, PapermakeWorld};
 
 /// Individual rendering error with location information
 ///
@@ -28,6 +48,35 @@ pub struct RenderError {
     pub end: usize,
     /// Optional file path where the error occurred
     pub file: Option<String>,
+    /// Logical file, line and column as the template author sees them
+    pub location: Option<SourceLocation>,
+    /// Hints for fixing the error
+    pub hints: Vec<String>,
+}
+
+impl RenderError {
+    /// Create an error that is not tied to a source location
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            start: 0,
+            end: 0,
+            file: None,
+            location: None,
+            hints: Vec::new(),
+        }
+    }
+}
+
+impl From<RenderError> for DiagnosticInfo {
+    fn from(error: RenderError) -> Self {
+        DiagnosticInfo {
+            message: error.message,
+            severity: DiagnosticSeverity::Error,
+            location: error.location,
+            hints: error.hints,
+        }
+    }
 }
 
 impl std::fmt::Display for RenderError {
@@ -42,15 +91,997 @@ impl std::fmt::Display for RenderError {
 /// Result of template rendering operation
 ///
 /// Contains either the successfully generated PDF bytes or detailed error information.
-/// Even when PDF generation succeeds, there may be warnings in the errors vector.
+/// Even when PDF generation succeeds, there may be entries in `warnings`.
 #[derive(Debug, Serialize)]
 pub struct RenderResult {
     /// The generated PDF bytes (None if compilation failed)
     pub pdf: Option<Vec<u8>>,
-    /// List of compilation errors and warnings
+    /// List of compilation errors
     pub errors: Vec<RenderError>,
+    /// Warnings from compilation and papermake's own checks
+    ///
+    /// Warnings never fail a render unless [`RenderOptions::strict`] is set,
+    /// in which case they are reported in `errors` instead.
+    pub warnings: Vec<DiagnosticInfo>,
     /// Whether the rendering was successful (PDF was generated)
     pub success: bool,
+    /// Dimensions of every page of the compiled document (empty if compilation failed)
+    pub pages: Vec<PageInfo>,
+    /// Characters no loaded font could render, drawn as empty boxes ("tofu")
+    ///
+    /// Sorted and deduplicated. Usually means a font for the script is
+    /// missing, e.g. a CJK font for Chinese text.
+    pub missing_glyphs: Vec<char>,
+    /// `sys.inputs` keys the main template reads, see [`used_inputs`]
+    pub used_inputs: Vec<String>,
+}
+
+/// Size of one page of the compiled document
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PageInfo {
+    /// Zero-based page index
+    pub index: usize,
+    /// Page width in points
+    pub width_pt: f64,
+    /// Page height in points
+    pub height_pt: f64,
+}
+
+/// Collect the page dimensions of a compiled document
+fn page_info(document: &PagedDocument) -> Vec<PageInfo> {
+    document
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| PageInfo {
+            index,
+            width_pt: page.frame.width().to_pt(),
+            height_pt: page.frame.height().to_pt(),
+        })
+        .collect()
+}
+
+/// Characters rendered with a font's `.notdef` glyph anywhere in `document`
+fn missing_glyphs(document: &PagedDocument) -> Vec<char> {
+    fn collect(frame: &Frame, missing: &mut BTreeSet<char>) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => collect(&group.frame, missing),
+                FrameItem::Text(text) => missing.extend(
+                    text.glyphs
+                        .iter()
+                        .filter(|glyph| glyph.id == 0)
+                        .filter_map(|glyph| text.text.get(glyph.range()))
+                        .flat_map(str::chars)
+                        .filter(|c| !c.is_whitespace()),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let mut missing = BTreeSet::new();
+    for page in &document.pages {
+        collect(&page.frame, &mut missing);
+    }
+    missing.into_iter().collect()
+}
+
+/// Output format for [`render_template_to`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A single PDF holding the whole document
+    Pdf,
+    /// One PNG image per page, rasterized at `dpi` dots per inch
+    Png { dpi: u32 },
+    /// One SVG image per page
+    Svg,
+}
+
+/// Exported bytes for one page
+///
+/// For [`OutputFormat::Pdf`] there is a single entry with index 0 that holds
+/// the whole document.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedPage {
+    /// Zero-based page index
+    pub index: usize,
+    /// Format-specific bytes: PDF, PNG or UTF-8 encoded SVG
+    pub bytes: Vec<u8>,
+}
+
+/// Result of [`render_template_to`]
+///
+/// Like [`RenderResult`], but carries the exported pages instead of PDF bytes.
+#[derive(Debug, Serialize)]
+pub struct RenderOutput {
+    /// Exported pages (empty if compilation failed)
+    pub pages: Vec<RenderedPage>,
+    /// List of compilation errors
+    pub errors: Vec<RenderError>,
+    /// Warnings from compilation and papermake's own checks
+    pub warnings: Vec<DiagnosticInfo>,
+    /// Whether the rendering was successful (output was generated)
+    pub success: bool,
+    /// Dimensions of every page of the compiled document (empty if compilation failed)
+    pub page_info: Vec<PageInfo>,
+    /// Characters no loaded font could render, see [`RenderResult::missing_glyphs`]
+    pub missing_glyphs: Vec<char>,
+    /// `sys.inputs` keys the main template reads, see [`used_inputs`]
+    pub used_inputs: Vec<String>,
+}
+
+impl RenderOutput {
+    /// Convert PDF output into a [`RenderResult`]
+    fn into_pdf_result(self) -> RenderResult {
+        RenderResult {
+            pdf: self.pages.into_iter().next().map(|page| page.bytes),
+            errors: self.errors,
+            warnings: self.warnings,
+            success: self.success,
+            pages: self.page_info,
+            missing_glyphs: self.missing_glyphs,
+            used_inputs: self.used_inputs,
+        }
+    }
+}
+
+/// Settings that only affect PDF export
+///
+/// Ignored for image formats.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PdfOptions {
+    /// Title written to the PDF document info, overriding the template's own
+    pub title: Option<String>,
+
+    /// Author written to the PDF document info
+    pub author: Option<String>,
+
+    /// Keywords written to the PDF document info
+    pub keywords: Vec<String>,
+
+    /// Export conforming to PDF/A-2b for long-term archiving
+    ///
+    /// Typst rejects documents that cannot conform, e.g. ones using
+    /// transparency in embedded images, and the render fails.
+    pub pdf_a: bool,
+
+    /// Export only these pages (zero-based, end exclusive, like [`PageInfo::index`])
+    ///
+    /// Pages past the end of the document are ignored. `page_info` still
+    /// describes the whole document.
+    pub page_range: Option<Range<usize>>,
+
+    /// Produce a byte-identical PDF for identical inputs, e.g. for deduplication
+    ///
+    /// Pins the clock as [`RenderOptions::deterministic`] does and writes
+    /// that date, `RenderOptions::today` or 1970-01-01, as the creation date
+    /// unless the template sets `document.date` itself. The document ID is
+    /// always derived from the title and author, never random.
+    ///
+    /// Without this flag Typst writes no creation date, so only templates
+    /// reading `datetime.today()` vary between renders.
+    pub deterministic: bool,
+}
+
+impl PdfOptions {
+    /// Set the document title
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the document author
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Set the document keywords
+    pub fn with_keywords<I>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.keywords = keywords.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Enable or disable PDF/A-2b export
+    pub fn with_pdf_a(mut self, pdf_a: bool) -> Self {
+        self.pdf_a = pdf_a;
+        self
+    }
+
+    /// Export only the pages in `range`
+    pub fn with_page_range(mut self, range: Range<usize>) -> Self {
+        self.page_range = Some(range);
+        self
+    }
+
+    /// Enable or disable deterministic PDF export
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// `set document` rule carrying the metadata, if any is set
+    fn document_rule(&self) -> Option<String> {
+        let mut fields = Vec::new();
+        if let Some(title) = &self.title {
+            fields.push(format!("title: {}", typst_string(title)));
+        }
+        if let Some(author) = &self.author {
+            fields.push(format!("author: {}", typst_string(author)));
+        }
+        if !self.keywords.is_empty() {
+            let keywords: Vec<String> = self.keywords.iter().map(|k| typst_string(k)).collect();
+            // The trailing comma keeps a single keyword an array
+            fields.push(format!("keywords: ({},)", keywords.join(", ")));
+        }
+
+        (!fields.is_empty()).then(|| format!("#set document({})\n", fields.join(", ")))
+    }
+
+    /// Equivalent Typst export options, given the render's pinned date
+    fn to_typst(&self, today: Option<time::Date>) -> TypstPdfOptions<'static> {
+        let standards = if self.pdf_a {
+            PdfStandards::new(&[PdfStandard::A_2b]).expect("PDF/A-2b is a valid standard")
+        } else {
+            PdfStandards::default()
+        };
+        let page_ranges = self.page_range.as_ref().map(|range| {
+            // Typst page ranges are one-based and inclusive
+            let first = NonZeroUsize::new(range.start + 1);
+            let last = NonZeroUsize::new(range.end);
+            PageRanges::new(vec![first..=last])
+        });
+
+        let timestamp = today
+            .filter(|_| self.deterministic)
+            .and_then(|date| Datetime::from_ymd(date.year(), date.month().into(), date.day()))
+            .map(Timestamp::new_utc);
+
+        TypstPdfOptions {
+            page_ranges,
+            standards,
+            timestamp,
+            ..TypstPdfOptions::default()
+        }
+    }
+}
+
+/// Text overlaid across every page, e.g. "DRAFT" or "CONFIDENTIAL"
+///
+/// Drawn as the page foreground, centered and rotated, so it shows on top
+/// of filled tables and images without moving any content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    /// The text to draw
+    pub text: String,
+
+    /// Opacity from `0.0` (invisible) to `1.0` (opaque), `0.25` by default
+    pub opacity: f64,
+
+    /// Rotation in degrees, clockwise as for Typst's `rotate`
+    ///
+    /// `-45.0` by default, so the text rises from bottom left to top right.
+    pub angle: f64,
+
+    /// Text color as a hex code like `"#c00"` or `"#808080"`, grey by default
+    pub color: String,
+}
+
+impl Watermark {
+    /// Grey watermark at 25% opacity, rising at 45 degrees
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            opacity: 0.25,
+            angle: -45.0,
+            color: "#808080".to_string(),
+        }
+    }
+
+    /// Set the opacity, from `0.0` to `1.0`
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Set the rotation in degrees
+    pub fn with_angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    /// Set the text color as a hex code
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// Reject values Typst would fail on with an error pointing into the prelude
+    fn validate(&self) -> Result<()> {
+        let invalid = |setting: &str, reason: String| {
+            Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: format!("watermark.{}", setting),
+                reason,
+            }))
+        };
+
+        if !(0.0..=1.0).contains(&self.opacity) {
+            return invalid(
+                "opacity",
+                format!("{} is not between 0.0 and 1.0", self.opacity),
+            );
+        }
+        if !self.angle.is_finite() {
+            return invalid("angle", format!("{} is not a finite number", self.angle));
+        }
+        let is_hex = self.color.strip_prefix('#').is_some_and(|digits| {
+            matches!(digits.len(), 3 | 4 | 6 | 8) && digits.chars().all(|c| c.is_ascii_hexdigit())
+        });
+        if !is_hex {
+            return invalid(
+                "color",
+                format!("'{}' is not a hex color like #808080", self.color),
+            );
+        }
+        Ok(())
+    }
+
+    /// `set page` rule drawing the watermark in the page foreground
+    fn page_rule(&self) -> String {
+        format!(
+            "#set page(foreground: place(center + horizon, rotate({}deg, text(64pt, fill: rgb({}).transparentize({}%), {}))))\n",
+            self.angle,
+            typst_string(&self.color),
+            (1.0 - self.opacity) * 100.0,
+            typst_string(&self.text)
+        )
+    }
+}
+
+impl From<&str> for Watermark {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Watermark {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Render-time configuration that is not part of the template data
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Key under which the data is injected into `sys.inputs` (default `"data"`)
+    ///
+    /// Lets third-party templates that read e.g. `json.decode(sys.inputs.payload)`
+    /// render unmodified. The decoded value is bound to `data` regardless.
+    /// Has no effect for [`render_template_from_reader`], which does not use
+    /// `sys.inputs`.
+    pub data_key: String,
+
+    /// Default document language as a BCP 47 tag, e.g. `"en-GB"` or `"de"`
+    ///
+    /// Applied as a `#set text(lang: .., region: ..)` rule ahead of the template
+    /// body, so it drives hyphenation and smart quotes for templates that don't
+    /// pick a language themselves. Precedence, from strongest to weakest:
+    /// 1. `set text(lang: ..)` rules inside the template
+    /// 2. this option
+    /// 3. Typst's built-in default (`en`)
+    pub document_lang: Option<String>,
+
+    /// Linearize the exported PDF for fast web view
+    ///
+    /// Lets browsers display the first page before the whole file has
+    /// downloaded. Runs an extra qpdf pass over the output, so it costs
+    /// additional render time; requires the `linearize` feature.
+    pub linearize: bool,
+
+    /// Serve a placeholder for missing image assets instead of failing
+    ///
+    /// Disabled by default, so a missing asset is a hard compilation error.
+    /// When enabled, the substituted paths are reported in
+    /// `RenderResult::warnings` while the render still succeeds.
+    pub asset_fallback: Option<AssetFallback>,
+
+    /// Fonts to render with instead of the cached system fonts
+    ///
+    /// Loaded on every render, so prefer a small `InMemory` or `SystemDir`
+    /// set when rendering at high volume.
+    pub font_source: Option<FontSource>,
+
+    /// Font families to try, in order, for characters the default font lacks
+    ///
+    /// E.g. `["Noto Sans CJK SC"]` for Chinese text in otherwise Latin
+    /// documents. See [`PapermakeWorld::set_font_fallbacks`] for how this
+    /// interacts with fonts set by the template. Characters no loaded font
+    /// provides are listed in `RenderResult::missing_glyphs`.
+    pub font_fallbacks: Vec<String>,
+
+    /// Maximum number of nested imports below the main template
+    ///
+    /// Guards against absurdly deep (but non-cyclic) import chains, which
+    /// would otherwise be resolved file by file without bound. Exceeding it
+    /// fails the render with `CompilationError::ImportResolution`.
+    pub max_import_depth: usize,
+
+    /// Paper size applied as a `#set page(paper: ..)` default, e.g. `"a4"` or `"us-letter"`
+    pub paper: Option<String>,
+
+    /// Page margin applied to all sides as a `#set page(margin: ..)` default, e.g. `"2cm"`
+    pub margin: Option<String>,
+
+    /// Text overlaid diagonally across every page, see [`Watermark`]
+    ///
+    /// A page foreground set by the template replaces the watermark.
+    pub watermark: Option<Watermark>,
+
+    /// Header and footer added to every page, see [`PageDecoration`]
+    ///
+    /// A header or footer set by the template replaces the decoration's
+    /// unless [`PageDecoration::force`] is set.
+    pub page_decoration: Option<PageDecoration>,
+
+    /// Seed exposed to the template as `sys.inputs.seed`
+    ///
+    /// Typst itself has no randomness; this lets templates that derive
+    /// pseudo-random values (e.g. sample IDs or generated patterns) do so
+    /// reproducibly.
+    pub seed: Option<u64>,
+
+    /// Date returned by `datetime.today()` instead of the current date
+    pub today: Option<time::Date>,
+
+    /// Produce byte-identical output for identical inputs
+    ///
+    /// Fixes `datetime.today()` to 1970-01-01 unless `today` is set. Typst
+    /// already writes PDFs without a creation timestamp, so the clock is the
+    /// only source of variation. [`PdfOptions::deterministic`] additionally
+    /// pins the PDF creation date.
+    pub deterministic: bool,
+
+    /// Treat compiler warnings as errors
+    ///
+    /// A render with warnings fails and reports them in `errors` instead of
+    /// producing output.
+    pub strict: bool,
+
+    /// Fail the render if the document has more pages than this
+    ///
+    /// Checked after layout and before export, so a template that
+    /// unexpectedly spills to many pages does not pay the export cost.
+    pub max_pages: Option<usize>,
+
+    /// Fail the render if the exported output is larger than this many bytes
+    ///
+    /// Together with `max_pages`, bounds what a template looping over
+    /// untrusted data can produce. For PNG and SVG the limit applies to the
+    /// sum of all pages.
+    pub max_output_bytes: Option<usize>,
+
+    /// Output format for [`render_template_output`]
+    ///
+    /// Functions returning a [`RenderResult`] always produce PDF and reject
+    /// other formats.
+    pub format: OutputFormat,
+
+    /// Metadata, PDF/A conformance and page selection for PDF export
+    pub pdf: PdfOptions,
+
+    /// Abort the render if compilation and export take longer than this
+    ///
+    /// The default is no timeout, matching the behaviour before this option
+    /// existed. Typst offers no way to interrupt a running compilation, so on
+    /// timeout the render thread is abandoned: the caller gets an error
+    /// immediately, while the thread runs to completion in the background
+    /// and its result is discarded. Targets without threads, such as
+    /// `wasm32-unknown-unknown`, fail such renders with an I/O error.
+    ///
+    /// A wall-clock limit depends on machine speed and load. A deterministic
+    /// work budget would be preferable, but Typst 0.13 exposes no step or
+    /// instruction counter to build one on. The compiler does stop some
+    /// runaway templates deterministically by itself: a `while` loop fails
+    /// after 10 000 iterations ("loop seems to be infinite") and recursion
+    /// fails beyond 80 nested calls ("maximum function call depth
+    /// exceeded"). Anything else, such as huge `for` ranges or expensive
+    /// layout, is only bounded by this timeout.
+    pub timeout: Option<Duration>,
+
+    /// Skip validating data against the template's `schema.json`
+    ///
+    /// Only applies where a schema is known, such as renders through the
+    /// registry; plain template strings carry no schema.
+    pub skip_validation: bool,
+
+    /// Warn about top-level data keys the template never reads
+    ///
+    /// Off by default. Each unused key is reported in `warnings`; see
+    /// [`crate::data_usage`] for what is detected.
+    /// Ignored by [`render_template_from_reader`], which never parses the data.
+    pub warn_unused_data: bool,
+
+    /// Transform the main template with the data before compiling it
+    ///
+    /// Off by default. See [`crate::preprocess`] for the placeholder syntax
+    /// and its escaping rules. Ignored by [`render_template_from_reader`],
+    /// which never parses the data.
+    pub preprocess: Option<Preprocessor>,
+
+    /// Bind `pm` to number, currency and date formatting helpers for a locale
+    ///
+    /// Off by default. See [`crate::locale`] for the helpers.
+    pub inject_prelude: Option<Locale>,
+
+    /// Files for this render only, by path, e.g. an uploaded signature
+    ///
+    /// Served ahead of the template's file system, so a template can use
+    /// `#image("runtime/signature.png")` for a file that is not part of its
+    /// bundle. Paths are relative to the template root; a leading `/` is
+    /// optional. A runtime file shadows a bundle file at the same path.
+    /// The files live only in the render's world and are never written to
+    /// any storage.
+    pub runtime_files: HashMap<String, Vec<u8>>,
+
+    /// Reuse an earlier successful render of the same reference and data
+    ///
+    /// Off by default. Honoured by the registry's tracked renders, which
+    /// return the stored PDF when the reference still resolves to the
+    /// manifest that render used, even if it was rendered with other
+    /// options. The core renderer ignores it.
+    pub use_cache: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            data_key: DEFAULT_DATA_KEY.to_string(),
+            document_lang: None,
+            linearize: false,
+            asset_fallback: None,
+            font_source: None,
+            font_fallbacks: Vec::new(),
+            max_import_depth: DEFAULT_MAX_IMPORT_DEPTH,
+            paper: None,
+            margin: None,
+            watermark: None,
+            page_decoration: None,
+            seed: None,
+            today: None,
+            deterministic: false,
+            strict: false,
+            max_pages: None,
+            max_output_bytes: None,
+            format: OutputFormat::Pdf,
+            pdf: PdfOptions::default(),
+            timeout: None,
+            skip_validation: false,
+            warn_unused_data: false,
+            preprocess: None,
+            inject_prelude: None,
+            runtime_files: HashMap::new(),
+            use_cache: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Set the `sys.inputs` key the data is injected under
+    pub fn with_data_key(mut self, data_key: impl Into<String>) -> Self {
+        self.data_key = data_key.into();
+        self
+    }
+
+    /// Set the default document language
+    pub fn with_document_lang(mut self, lang: impl Into<String>) -> Self {
+        self.document_lang = Some(lang.into());
+        self
+    }
+
+    /// Enable or disable PDF linearization
+    pub fn with_linearize(mut self, linearize: bool) -> Self {
+        self.linearize = linearize;
+        self
+    }
+
+    /// Replace missing image assets with a placeholder
+    pub fn with_asset_fallback(mut self, fallback: AssetFallback) -> Self {
+        self.asset_fallback = Some(fallback);
+        self
+    }
+
+    /// Render with fonts from `font_source`
+    pub fn with_font_source(mut self, font_source: FontSource) -> Self {
+        self.font_source = Some(font_source);
+        self
+    }
+
+    /// Try `families`, in order, for characters the default font lacks
+    pub fn with_font_fallbacks<I>(mut self, families: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.font_fallbacks = families.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Limit how deeply templates may nest imports
+    pub fn with_max_import_depth(mut self, max_import_depth: usize) -> Self {
+        self.max_import_depth = max_import_depth;
+        self
+    }
+
+    /// Set the default paper size
+    pub fn with_paper(mut self, paper: impl Into<String>) -> Self {
+        self.paper = Some(paper.into());
+        self
+    }
+
+    /// Set the default page margin
+    pub fn with_margin(mut self, margin: impl Into<String>) -> Self {
+        self.margin = Some(margin.into());
+        self
+    }
+
+    /// Overlay a watermark on every page, e.g. `with_watermark("DRAFT")`
+    pub fn with_watermark(mut self, watermark: impl Into<Watermark>) -> Self {
+        self.watermark = Some(watermark.into());
+        self
+    }
+
+    /// Add a header and footer to every page, e.g. a letterhead
+    pub fn with_page_decoration(mut self, decoration: PageDecoration) -> Self {
+        self.page_decoration = Some(decoration);
+        self
+    }
+
+    /// Expose a seed to the template as `sys.inputs.seed`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Fix the date returned by `datetime.today()`
+    pub fn with_today(mut self, today: time::Date) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    /// Enable or disable deterministic output
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Enable or disable treating warnings as errors
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Limit the number of pages a render may produce
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Limit the size of the exported output in bytes
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = Some(max_output_bytes);
+        self
+    }
+
+    /// Set the output format
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the PDF export options
+    pub fn with_pdf_options(mut self, pdf: PdfOptions) -> Self {
+        self.pdf = pdf;
+        self
+    }
+
+    /// Abort renders that take longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Enable or disable skipping schema validation
+    pub fn with_skip_validation(mut self, skip_validation: bool) -> Self {
+        self.skip_validation = skip_validation;
+        self
+    }
+
+    /// Enable or disable warnings for unused data keys
+    pub fn with_warn_unused_data(mut self, warn_unused_data: bool) -> Self {
+        self.warn_unused_data = warn_unused_data;
+        self
+    }
+
+    /// Preprocess the main template with `preprocessor`
+    pub fn with_preprocessor(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocess = Some(preprocessor);
+        self
+    }
+
+    /// Inject the `pm` formatting helpers for `locale`
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.inject_prelude = Some(locale);
+        self
+    }
+
+    /// Serve `content` at `path` for this render only
+    pub fn with_runtime_file(mut self, path: impl Into<String>, content: Vec<u8>) -> Self {
+        self.runtime_files.insert(path.into(), content);
+        self
+    }
+
+    /// Enable or disable reusing an earlier render of the same inputs
+    pub fn with_use_cache(mut self, use_cache: bool) -> Self {
+        self.use_cache = use_cache;
+        self
+    }
+
+    /// Apply the configured preprocessor, if any, to `main_typ`
+    fn preprocess(&self, main_typ: String, data: &serde_json::Value) -> String {
+        match &self.preprocess {
+            Some(preprocessor) => preprocessor.apply(&main_typ, data),
+            None => main_typ,
+        }
+    }
+
+    /// Date the clock is fixed to, if any
+    fn pinned_today(&self) -> Option<time::Date> {
+        match self.today {
+            Some(today) => Some(today),
+            None if self.deterministic || self.pdf.deterministic => {
+                Some(time::Date::from_calendar_date(1970, time::Month::January, 1).unwrap())
+            }
+            None => None,
+        }
+    }
+
+    /// Apply the world-level options: fonts, clock and extra inputs
+    fn apply_to_world(&self, world: &mut PapermakeWorld) -> Result<()> {
+        if let Some(font_source) = &self.font_source {
+            world.set_font_source(font_source)?;
+        }
+        if !self.font_fallbacks.is_empty() {
+            world.set_font_fallbacks(self.font_fallbacks.clone());
+        }
+
+        if let Some(today) = self.pinned_today() {
+            world.set_time(today.midnight().assume_utc());
+        }
+
+        if let Some(seed) = self.seed {
+            world.set_input("seed", &seed.to_string());
+        }
+
+        if let Some(locale) = &self.inject_prelude {
+            world.add_virtual_file(locale::MODULE_PATH, locale.module().into_bytes());
+        }
+
+        for (path, content) in &self.runtime_files {
+            let path = format!("/{}", path.trim_start_matches('/'));
+            world.add_virtual_file(&path, content.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Reject formats other than PDF for functions returning a [`RenderResult`]
+    fn require_pdf(&self) -> Result<()> {
+        if self.format != OutputFormat::Pdf {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "format".to_string(),
+                reason:
+                    "this function only produces PDF; use render_template_output for other formats"
+                        .to_string(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Wrap `file_system` as configured, keeping a handle to the fallback layer
+    fn wrap_file_system(
+        &self,
+        file_system: Arc<dyn RenderFileSystem>,
+    ) -> (Arc<dyn RenderFileSystem>, Option<Arc<FallbackFileSystem>>) {
+        match &self.asset_fallback {
+            Some(fallback) => {
+                let fallback_fs = Arc::new(FallbackFileSystem::new(file_system, fallback.clone()));
+                (fallback_fs.clone(), Some(fallback_fs))
+            }
+            None => (file_system, None),
+        }
+    }
+
+    /// Reject option combinations this build cannot honour
+    fn validate(&self) -> Result<()> {
+        if self.data_key.is_empty()
+            || !self
+                .data_key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "data_key".to_string(),
+                reason: format!(
+                    "'{}' must be non-empty and contain only ASCII letters, digits, '_' or '-'",
+                    self.data_key
+                ),
+            }));
+        }
+
+        if let Some(paper) = &self.paper
+            && (paper.is_empty() || !paper.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "paper".to_string(),
+                reason: format!("'{}' is not a Typst paper name", paper),
+            }));
+        }
+
+        if let Some(margin) = &self.margin
+            && !is_length(margin)
+        {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "margin".to_string(),
+                reason: format!(
+                    "'{}' must be a number followed by pt, mm, cm, in or em",
+                    margin
+                ),
+            }));
+        }
+
+        if self.format == (OutputFormat::Png { dpi: 0 }) {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "dpi".to_string(),
+                reason: "PNG resolution must be greater than 0".to_string(),
+            }));
+        }
+
+        if let Some(range) = &self.pdf.page_range
+            && range.start >= range.end
+        {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "page_range".to_string(),
+                reason: format!("{}..{} selects no pages", range.start, range.end),
+            }));
+        }
+
+        if let Some(locale) = &self.inject_prelude {
+            locale.validate()?;
+        }
+
+        if let Some(watermark) = &self.watermark {
+            watermark.validate()?;
+        }
+
+        if let Some(decoration) = &self.page_decoration {
+            decoration.validate()?;
+        }
+
+        if self.linearize && !cfg!(feature = "linearize") {
+            return Err(PapermakeError::Config(ConfigError::InvalidConfig {
+                setting: "linearize".to_string(),
+                reason: "papermake was built without the `linearize` feature".to_string(),
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Build the Typst prelude applying these options as document defaults
+    fn prelude(&self) -> Result<String> {
+        let mut prelude = String::new();
+
+        if let Some(paper) = &self.paper {
+            prelude.push_str(&format!("#set page(paper: \"{}\")\n", paper));
+        }
+        if let Some(margin) = &self.margin {
+            prelude.push_str(&format!("#set page(margin: {})\n", margin));
+        }
+        if let Some(watermark) = &self.watermark {
+            prelude.push_str(&watermark.page_rule());
+        }
+        if let Some(decoration) = &self.page_decoration {
+            prelude.push_str(&decoration.prelude());
+        }
+
+        if let Some(tag) = &self.document_lang {
+            let (lang, region) = parse_lang_tag(tag)?;
+            match region {
+                Some(region) => prelude.push_str(&format!(
+                    "#set text(lang: \"{}\", region: \"{}\")\n",
+                    lang, region
+                )),
+                None => prelude.push_str(&format!("#set text(lang: \"{}\")\n", lang)),
+            }
+        }
+
+        if let Some(rule) = self.pdf.document_rule() {
+            prelude.push_str(&rule);
+        }
+
+        if let Some(locale) = &self.inject_prelude {
+            prelude.push_str(&locale.prelude());
+        }
+
+        Ok(prelude)
+    }
+}
+
+/// Whether `value` is a simple Typst length literal like `2cm` or `12.5pt`
+fn is_length(value: &str) -> bool {
+    let Some(number) = ["pt", "mm", "cm", "in", "em"]
+        .iter()
+        .find_map(|unit| value.strip_suffix(unit))
+    else {
+        return false;
+    };
+
+    !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && number.parse::<f64>().is_ok()
+}
+
+/// Quote `value` as a Typst string literal
+pub(crate) fn typst_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Split a language tag like `en-GB` into Typst's `lang` and `region` values
+fn parse_lang_tag(tag: &str) -> Result<(String, Option<String>)> {
+    let invalid = |reason: &str| {
+        PapermakeError::Config(ConfigError::InvalidConfig {
+            setting: "document_lang".to_string(),
+            reason: format!("'{}': {}", tag, reason),
+        })
+    };
+
+    let mut parts = tag.split(['-', '_']);
+    let lang = parts.next().unwrap_or_default();
+    if !(2..=3).contains(&lang.len()) || !lang.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(invalid("language must be a 2 or 3 letter ISO 639 code"));
+    }
+
+    let region = match parts.next() {
+        Some(region) if region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some(region.to_ascii_uppercase())
+        }
+        Some(_) => return Err(invalid("region must be a 2 letter ISO 3166-1 code")),
+        None => None,
+    };
+
+    if parts.next().is_some() {
+        return Err(invalid("only language and region subtags are supported"));
+    }
+
+    Ok((lang.to_ascii_lowercase(), region))
 }
 
 /// Render a Typst template to PDF
@@ -100,68 +1131,624 @@ pub fn render_template(
     file_system: Arc<dyn RenderFileSystem>,
     data: &serde_json::Value,
 ) -> Result<RenderResult> {
+    render_template_with_options(main_typ, file_system, data, &RenderOptions::default())
+}
+
+/// Render a Typst template to PDF, PNG or SVG
+///
+/// PNG and SVG produce one [`RenderedPage`] per document page, e.g. for page
+/// previews in a web UI without a PDF viewer. PDF produces a single entry
+/// holding the whole document.
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` for a PNG resolution of 0 DPI.
+pub fn render_template_to(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    data: &serde_json::Value,
+    format: OutputFormat,
+) -> Result<RenderOutput> {
+    let options = RenderOptions::default().with_format(format);
+    render_template_output(main_typ, file_system, data, &options)
+}
+
+/// Render a Typst template to PDF with explicit render options
+///
+/// This is the single entry point all other PDF render functions delegate
+/// to. Template data stays in `data`; everything about *how* the document is
+/// rendered (page defaults, language, determinism, limits) lives in
+/// `options`, so the same data can be rendered with different settings.
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` if an option value is malformed,
+/// e.g. an unparseable `document_lang`, or if `options.format` is not PDF.
+pub fn render_template_with_options(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    data: &serde_json::Value,
+    options: &RenderOptions,
+) -> Result<RenderResult> {
+    options.require_pdf()?;
+    render_template_output(main_typ, file_system, data, options).map(RenderOutput::into_pdf_result)
+}
+
+/// Render a Typst template in `options.format` with explicit render options
+///
+/// Like [`render_template_with_options`], but returns a [`RenderOutput`] so
+/// PNG and SVG output can be combined with the other options.
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` if an option value is malformed.
+pub fn render_template_output(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    data: &serde_json::Value,
+    options: &RenderOptions,
+) -> Result<RenderOutput> {
+    let main_typ = options.preprocess(main_typ, data);
+    let inputs = BTreeMap::from([(options.data_key.clone(), serde_json::to_string(&data)?)]);
+    let unused_data = if options.warn_unused_data {
+        unused_data_keys(&main_typ, data)
+    } else {
+        Vec::new()
+    };
+
+    render_serialized_inputs(main_typ, file_system, inputs, unused_data, options)
+}
+
+/// Render a Typst template to PDF with several named JSON inputs
+///
+/// Each entry in `inputs` is injected as its own `sys.inputs` key, so a
+/// template combining datasets can decode them independently, e.g.
+/// `json(bytes(sys.inputs.branding))`. The entry under `options.data_key`
+/// (`"data"` by default) is also bound to `data` as in [`render_template`];
+/// without such an entry `data` is `none`.
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` if an option value is malformed or
+/// `options.format` is not PDF.
+pub fn render_template_with_inputs(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    inputs: &BTreeMap<String, serde_json::Value>,
+    options: &RenderOptions,
+) -> Result<RenderResult> {
+    options.require_pdf()?;
+    let main_typ = match inputs.get(&options.data_key) {
+        Some(data) => options.preprocess(main_typ, data),
+        None => options.preprocess(main_typ, &serde_json::Value::Null),
+    };
+    let serialized = inputs
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), serde_json::to_string(value)?)))
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    let unused_data = match inputs.get(&options.data_key) {
+        Some(data) if options.warn_unused_data => unused_data_keys(&main_typ, data),
+        _ => Vec::new(),
+    };
+
+    render_serialized_inputs(main_typ, file_system, serialized, unused_data, options)
+        .map(RenderOutput::into_pdf_result)
+}
+
+/// Render with already serialized `sys.inputs`, appending `unused_data`
+/// as warnings
+fn render_serialized_inputs(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    inputs: BTreeMap<String, String>,
+    unused_data: Vec<DiagnosticInfo>,
+    options: &RenderOptions,
+) -> Result<RenderOutput> {
+    let (mut output, _) = render_with(
+        main_typ,
+        file_system,
+        options,
+        |main_typ, file_system| {
+            let mut world = PapermakeWorld::with_inputs(main_typ, inputs, &options.data_key);
+            world.set_file_system(file_system);
+            world
+        },
+        |_| (),
+    )?;
+
+    output.warnings.extend(unused_data);
+    Ok(output)
+}
+
+/// Render a Typst template to PDF, writing the PDF bytes into `writer`
+///
+/// Useful for sending a document straight to a file or socket. Note that
+/// Typst's PDF exporter always produces the complete document in memory, so
+/// this does not avoid that internal buffer; it only avoids the caller
+/// holding a second copy. The buffer is written out and dropped before this
+/// function returns.
+///
+/// On success the returned `RenderResult` has `success` set but `pdf` is
+/// `None`, since the bytes went to `writer`. On compilation failure nothing
+/// is written.
+///
+/// # Errors
+///
+/// Returns a `FileSystemError` if writing to `writer` fails.
+pub fn render_template_to_writer<W: Write>(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    data: &serde_json::Value,
+    mut writer: W,
+) -> Result<RenderResult> {
+    let mut result = render_template(main_typ, file_system, data)?;
+
+    if let Some(pdf_bytes) = result.pdf.take() {
+        writer.write_all(&pdf_bytes)?;
+        writer.flush()?;
+    }
+
+    Ok(result)
+}
+
+/// Render a Typst template to PDF, reading raw JSON data from `data`
+///
+/// Intended for very large datasets. The regular entry points hold the data
+/// twice: once as the serialized JSON string and once more as the Typst
+/// string in `sys.inputs.data`. Here the bytes are read straight from
+/// `data` into a single buffer that the world serves as a virtual file
+/// (see [`PapermakeWorld::with_data_file`]), and no `serde_json::Value` is
+/// ever built on the Rust side. Typst still parses the whole document when
+/// the template's `data` binding is evaluated.
+///
+/// The template sees the data through the usual `data` binding, but
+/// `sys.inputs.data` is not available. Invalid JSON surfaces as a Typst
+/// compilation error in the returned `RenderResult`.
+///
+/// # Errors
+///
+/// Returns a `FileSystemError` if reading from `data` fails, and
+/// `ConfigError::InvalidConfig` for malformed options.
+pub fn render_template_from_reader<R: Read>(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    mut data: R,
+    options: &RenderOptions,
+) -> Result<RenderResult> {
+    options.require_pdf()?;
+    let mut data_bytes = Vec::new();
+    data.read_to_end(&mut data_bytes)?;
+
+    let (output, _) = render_with(
+        main_typ,
+        file_system,
+        options,
+        |main_typ, file_system| PapermakeWorld::with_data_file(main_typ, data_bytes, file_system),
+        |_| (),
+    )?;
+    Ok(output.into_pdf_result())
+}
+
+/// Compile a template without data and return its diagnostics
+///
+/// A dry run for catching syntax errors and unresolved imports before the
+/// first real render: the template is compiled with `data` bound to an
+/// empty object and nothing is exported. Errors are listed before warnings;
+/// an empty result means the template compiles cleanly.
+///
+/// Since the data is empty, templates that read data fields directly
+/// report a missing key here. Templates meant to pass the check should read
+/// fields with a default, e.g. `data.at("name", default: none)`.
+///
+/// # Errors
+///
+/// Returns `CompilationError::ImportResolution` if imports nest deeper than
+/// [`DEFAULT_MAX_IMPORT_DEPTH`].
+pub fn check_template(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+) -> Result<Vec<DiagnosticInfo>> {
+    let inputs = BTreeMap::from([(DEFAULT_DATA_KEY.to_string(), "{}".to_string())]);
+    let mut world = PapermakeWorld::with_inputs(main_typ, inputs, DEFAULT_DATA_KEY);
+    world.set_file_system(file_system);
+    check_import_depth(&world, DEFAULT_MAX_IMPORT_DEPTH)?;
+
+    let compiled = typst::compile::<PagedDocument>(&world);
+    Ok(compiled
+        .output
+        .err()
+        .into_iter()
+        .flatten()
+        .chain(compiled.warnings)
+        .map(|diagnostic| convert_typst_diagnostic(&world, diagnostic))
+        .collect())
+}
+
+/// Render a Typst template to PDF and extract its heading outline
+///
+/// Returns the usual [`RenderResult`] together with one [`OutlineEntry`] per
+/// outlined heading, in document order. Suitable for building a table of
+/// contents or a navigation sidebar for a preview. The outline is empty if
+/// compilation fails.
+pub fn render_with_outline(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    data: &serde_json::Value,
+) -> Result<(RenderResult, Vec<OutlineEntry>)> {
+    let data_str = serde_json::to_string(&data)?;
+
+    let (output, outline) = render_with(
+        main_typ,
+        file_system,
+        &RenderOptions::default(),
+        |main_typ, file_system| PapermakeWorld::with_file_system(main_typ, data_str, file_system),
+        extract_outline,
+    )?;
+    Ok((output.into_pdf_result(), outline))
+}
+
+/// Render a Typst template to PDF together with a PNG thumbnail of its first page
+///
+/// Both outputs come from a single compilation, which makes this cheaper
+/// than calling [`render_template`] and [`render_template_to`] with
+/// [`OutputFormat::Png`] separately, e.g. when generating a gallery. The
+/// thumbnail is `None` if compilation fails or the document has no pages.
+///
+/// # Errors
+///
+/// Returns `ConfigError::InvalidConfig` for a thumbnail resolution of 0 DPI.
+pub fn render_with_thumbnail(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    data: &serde_json::Value,
+    thumb_dpi: u32,
+) -> Result<(RenderResult, Option<Vec<u8>>)> {
+    if thumb_dpi == 0 {
+        return Err(ConfigError::InvalidConfig {
+            setting: "thumb_dpi".to_string(),
+            reason: "thumbnail resolution must be greater than 0".to_string(),
+        }
+        .into());
+    }
     let data_str = serde_json::to_string(&data)?;
 
-    let world = PapermakeWorld::with_file_system(main_typ, data_str, file_system);
+    let (output, thumbnail) = render_with(
+        main_typ,
+        file_system,
+        &RenderOptions::default(),
+        |main_typ, file_system| PapermakeWorld::with_file_system(main_typ, data_str, file_system),
+        move |document| {
+            document
+                .pages
+                .first()
+                .and_then(|page| render_png(page, thumb_dpi).ok())
+        },
+    )?;
+    Ok((output.into_pdf_result(), thumbnail))
+}
+
+/// Shared render pipeline behind all option-aware entry points
+///
+/// Validates `options`, prepends the prelude, wraps the file system, builds
+/// the world with `make_world`, applies the world-level options and compiles
+/// in `options.format`. `inspect` runs on the compiled document as in
+/// [`compile_world_to`].
+fn render_with<T: Default + Send + 'static>(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    options: &RenderOptions,
+    make_world: impl FnOnce(String, Arc<dyn RenderFileSystem>) -> PapermakeWorld,
+    inspect: impl FnOnce(&PagedDocument) -> T + Send + 'static,
+) -> Result<(RenderOutput, T)> {
+    options.validate()?;
+    let used_inputs = used_inputs(&main_typ, &options.data_key);
+    let prelude = options.prelude()?;
+    let main_typ = match &options.page_decoration {
+        Some(decoration) => decoration.apply_to_template(main_typ),
+        None => main_typ,
+    };
+    let main_typ = format!("{}{}", prelude, main_typ);
+
+    let (file_system, fallback) = options.wrap_file_system(file_system);
+    let mut world = make_world(main_typ, file_system);
+    world.extend_prelude(prelude.len());
+    options.apply_to_world(&mut world)?;
+    check_import_depth(&world, options.max_import_depth)?;
+
+    let (mut output, inspected) = match options.timeout {
+        Some(timeout) => compile_with_timeout(world, options.clone(), timeout, inspect)?,
+        None => compile_world_to(&world, options, options.format, inspect),
+    };
+    report_missing_assets(&mut output.warnings, fallback.as_deref());
+    output.used_inputs = used_inputs;
+    Ok((output, inspected))
+}
+
+/// Run [`compile_world_to`] on a separate thread, giving up after `timeout`
+///
+/// # Errors
+///
+/// Returns `CompilationError::Timeout` if the render does not finish in
+/// time and `CompilationError::TemplateCompilation` if the render thread
+/// panics.
+fn compile_with_timeout<T: Default + Send + 'static>(
+    world: PapermakeWorld,
+    options: RenderOptions,
+    timeout: Duration,
+    inspect: impl FnOnce(&PagedDocument) -> T + Send + 'static,
+) -> Result<(RenderOutput, T)> {
+    let (sender, receiver) = mpsc::channel();
+    // Keep the compile and export spans under the caller's span
+    let span = tracing::Span::current();
+    std::thread::Builder::new()
+        .name("papermake-render".to_string())
+        .spawn(move || {
+            let _entered = span.enter();
+            let result = compile_world_to(&world, &options, options.format, inspect);
+            // The receiver is gone if the render timed out
+            let _ = sender.send(result);
+        })?;
+
+    receiver.recv_timeout(timeout).map_err(|e| {
+        match e {
+            mpsc::RecvTimeoutError::Timeout => CompilationError::Timeout {
+                timeout_ms: timeout.as_millis() as u64,
+            },
+            mpsc::RecvTimeoutError::Disconnected => CompilationError::TemplateCompilation {
+                message: "render thread panicked".to_string(),
+            },
+        }
+        .into()
+    })
+}
+
+/// Append a warning listing assets that were replaced by the placeholder
+fn report_missing_assets(
+    warnings: &mut Vec<DiagnosticInfo>,
+    fallback: Option<&FallbackFileSystem>,
+) {
+    let missing = fallback.map(|fs| fs.missing_assets()).unwrap_or_default();
+    if !missing.is_empty() {
+        warnings.push(DiagnosticInfo {
+            message: format!(
+                "missing assets replaced by placeholder: {}",
+                missing.join(", ")
+            ),
+            severity: DiagnosticSeverity::Warning,
+            location: None,
+            hints: Vec::new(),
+        });
+    }
+}
 
-    let compile_result = typst::compile(&world);
+/// Compile a prepared world and export it in `format`, collecting diagnostics
+///
+/// `inspect` runs on the compiled document before export and yields
+/// `T::default()` if compilation fails.
+fn compile_world_to<T: Default>(
+    world: &PapermakeWorld,
+    options: &RenderOptions,
+    format: OutputFormat,
+    inspect: impl FnOnce(&PagedDocument) -> T,
+) -> (RenderOutput, T) {
+    let compile_result =
+        tracing::info_span!("compile").in_scope(|| typst::compile::<PagedDocument>(world));
 
     let mut errors = Vec::new();
-    let mut pdf = None;
+    let mut warnings = Vec::new();
+    let mut pages = Vec::new();
+    let mut page_infos = Vec::new();
+    let mut missing = Vec::new();
     let mut success = false;
+    let mut inspected = T::default();
 
     match compile_result.output {
+        Ok(_) if options.strict && !compile_result.warnings.is_empty() => {
+            // Strict mode: warnings fail the render like errors
+            for warning in compile_result.warnings {
+                errors.push(diagnostic_error(world, warning));
+            }
+        }
         Ok(document) => {
-            // Compilation succeeded, generate PDF
-            match typst_pdf::pdf(&document, &PdfOptions::default()) {
-                Ok(pdf_bytes) => {
-                    pdf = Some(pdf_bytes);
+            warnings = compile_result
+                .warnings
+                .into_iter()
+                .map(|warning| convert_typst_diagnostic(world, warning))
+                .collect();
+            inspected = inspect(&document);
+            page_infos = page_info(&document);
+            missing = missing_glyphs(&document);
+
+            let page_count = document.pages.len();
+            let export = match options.max_pages {
+                Some(max_pages) if page_count > max_pages => Err(format!(
+                    "document has {} pages, exceeding the limit of {}",
+                    page_count, max_pages
+                )),
+                // Compilation succeeded, export the document
+                _ => export_document(&document, format, options).and_then(|exported| {
+                    check_output_size(&exported, options.max_output_bytes).map(|_| exported)
+                }),
+            };
+
+            match export {
+                Ok(exported) => {
+                    pages = exported;
                     success = true;
                 }
-                Err(pdf_error) => {
-                    errors.push(RenderError {
-                        message: format!("PDF generation failed: {:?}", pdf_error),
-                        start: 0,
-                        end: 0,
-                        file: None,
-                    });
-                }
+                Err(message) => errors.push(RenderError::new(message)),
             }
         }
         Err(diagnostics) => {
             // Compilation failed, collect diagnostic information
             for diagnostic in diagnostics {
-                let span = diagnostic.span;
-                let mut render_error = RenderError {
-                    message: diagnostic.message.to_string(),
-                    start: 0,
-                    end: 0,
-                    file: None,
-                };
-
-                // Try to get source location information
-                if let Some(id) = span.id() {
-                    if let Ok(_source) = world.source(id) {
-                        render_error.file = Some(format!("{:?}", id));
-                        if let Some(range) = world.range(span) {
-                            render_error.start = range.start;
-                            render_error.end = range.end;
-                        }
-                    }
-                }
-
-                errors.push(render_error);
+                errors.push(diagnostic_error(world, diagnostic));
             }
         }
     }
 
-    Ok(RenderResult {
-        pdf,
-        errors,
-        success,
+    (
+        RenderOutput {
+            pages,
+            errors,
+            warnings,
+            success,
+            page_info: page_infos,
+            missing_glyphs: missing,
+            used_inputs: Vec::new(),
+        },
+        inspected,
+    )
+}
+
+/// Reject exported output larger than `max_output_bytes`
+fn check_output_size(
+    pages: &[RenderedPage],
+    max_output_bytes: Option<usize>,
+) -> std::result::Result<(), String> {
+    let size: usize = pages.iter().map(|page| page.bytes.len()).sum();
+    match max_output_bytes {
+        Some(max_output_bytes) if size > max_output_bytes => Err(format!(
+            "output is {} bytes, exceeding the limit of {} bytes",
+            size, max_output_bytes
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Convert a Typst diagnostic into a `RenderError` with source location
+fn diagnostic_error(world: &PapermakeWorld, diagnostic: SourceDiagnostic) -> RenderError {
+    let span = diagnostic.span;
+    let mut render_error = RenderError::new(diagnostic.message.as_str());
+    render_error.location = world.source_location(span);
+    render_error.hints = diagnostic.hints.iter().map(|h| h.to_string()).collect();
+    if let Some(hint) = available_inputs_hint(world, span) {
+        render_error.hints.push(hint);
+    }
+
+    // Try to get source location information
+    if let Some(id) = span.id()
+        && let Ok(_source) = world.source(id)
+    {
+        render_error.file = Some(format!("{:?}", id));
+        if let Some(range) = world.range(span) {
+            render_error.start = range.start;
+            render_error.end = range.end;
+        }
+    }
+
+    render_error
+}
+
+/// Hint listing the provided `sys.inputs` keys for an error reading one
+///
+/// Typst reports a missing key as a plain dictionary error, which does not
+/// tell the author which inputs the caller actually passed.
+fn available_inputs_hint(world: &PapermakeWorld, span: Span) -> Option<String> {
+    let source = world.source(span.id()?).ok()?;
+    let node = source.find(span)?;
+    // The error points at the field or the arguments of `sys.inputs.at(..)`
+    let reads_inputs = std::iter::successors(Some(node), |node| node.parent().cloned())
+        .take(3)
+        .any(|node| reads_sys_inputs(node.get()));
+    if !reads_inputs {
+        return None;
+    }
+
+    let keys = world.input_keys();
+    Some(if keys.is_empty() {
+        "no `sys.inputs` keys were provided".to_string()
+    } else {
+        format!("available `sys.inputs` keys: {}", keys.join(", "))
     })
 }
 
+/// Span covering the export of a compiled document
+///
+/// The exported size is recorded as `bytes` once the export finishes.
+fn export_span(format: OutputFormat) -> tracing::Span {
+    tracing::info_span!("export", format = ?format, bytes = tracing::field::Empty)
+}
+
+/// Export a compiled document in the requested format, within an export span
+fn export_document(
+    document: &PagedDocument,
+    format: OutputFormat,
+    options: &RenderOptions,
+) -> std::result::Result<Vec<RenderedPage>, String> {
+    let span = export_span(format);
+    let _entered = span.enter();
+    let pages = export_pages(document, format, options)?;
+    span.record(
+        "bytes",
+        pages.iter().map(|page| page.bytes.len()).sum::<usize>(),
+    );
+    Ok(pages)
+}
+
+/// Export a compiled document in the requested format
+fn export_pages(
+    document: &PagedDocument,
+    format: OutputFormat,
+    options: &RenderOptions,
+) -> std::result::Result<Vec<RenderedPage>, String> {
+    match format {
+        OutputFormat::Pdf => {
+            let pdf_bytes = typst_pdf::pdf(document, &options.pdf.to_typst(options.pinned_today()))
+                .map_err(|pdf_error| format!("PDF generation failed: {:?}", pdf_error))?;
+            let pdf_bytes = if options.linearize {
+                linearize_pdf(&pdf_bytes)?
+            } else {
+                pdf_bytes
+            };
+
+            Ok(vec![RenderedPage {
+                index: 0,
+                bytes: pdf_bytes,
+            }])
+        }
+        OutputFormat::Png { dpi } => document
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| render_png(page, dpi).map(|bytes| RenderedPage { index, bytes }))
+            .collect(),
+        OutputFormat::Svg => Ok(document
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| RenderedPage {
+                index,
+                bytes: typst_svg::svg(page).into_bytes(),
+            })
+            .collect()),
+    }
+}
+
+/// Rasterize one page to PNG at `dpi`
+fn render_png(page: &Page, dpi: u32) -> std::result::Result<Vec<u8>, String> {
+    typst_render::render(page, dpi as f32 / 72.0)
+        .encode_png()
+        .map_err(|e| format!("PNG encoding failed: {}", e))
+}
+
+/// Rewrite a PDF in linearized ("fast web view") form
+#[cfg(feature = "linearize")]
+fn linearize_pdf(pdf_bytes: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    let document = qpdf::QPdf::read_from_memory(pdf_bytes)
+        .map_err(|e| format!("PDF linearization failed: {}", e))?;
+    document
+        .writer()
+        .linearize(true)
+        .write_to_memory()
+        .map_err(|e| format!("PDF linearization failed: {}", e))
+}
+
+#[cfg(not(feature = "linearize"))]
+fn linearize_pdf(_pdf_bytes: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    Err("PDF linearization requires the `linearize` feature".to_string())
+}
+
 /// Render a template with caching support
 ///
 /// This function allows reusing a compiled world for multiple renders with different data,
@@ -207,48 +1794,41 @@ pub fn render_template_with_cache(
     };
 
     // Compile with the updated world
-    let compile_result = typst::compile(world as &dyn World);
+    let compile_result =
+        tracing::info_span!("compile").in_scope(|| typst::compile(world as &dyn World));
 
     let mut errors = Vec::new();
+    let mut warnings = Vec::new();
     let mut pdf = None;
     let mut success = false;
+    let mut pages = Vec::new();
+    let mut missing = Vec::new();
 
     match compile_result.output {
-        Ok(document) => match typst_pdf::pdf(&document, &PdfOptions::default()) {
-            Ok(pdf_bytes) => {
-                pdf = Some(pdf_bytes);
-                success = true;
-            }
-            Err(pdf_error) => {
-                errors.push(RenderError {
-                    message: format!("PDF generation failed: {:?}", pdf_error),
-                    start: 0,
-                    end: 0,
-                    file: None,
-                });
+        Ok(document) => {
+            warnings = compile_result
+                .warnings
+                .into_iter()
+                .map(|warning| convert_typst_diagnostic(world, warning))
+                .collect();
+            pages = page_info(&document);
+            missing = missing_glyphs(&document);
+            let export = export_span(OutputFormat::Pdf);
+            match export.in_scope(|| typst_pdf::pdf(&document, &TypstPdfOptions::default())) {
+                Ok(pdf_bytes) => {
+                    export.record("bytes", pdf_bytes.len());
+                    pdf = Some(pdf_bytes);
+                    success = true;
+                }
+                Err(pdf_error) => errors.push(RenderError::new(format!(
+                    "PDF generation failed: {:?}",
+                    pdf_error
+                ))),
             }
-        },
+        }
         Err(diagnostics) => {
             for diagnostic in diagnostics {
-                let span = diagnostic.span;
-                let mut render_error = RenderError {
-                    message: diagnostic.message.to_string(),
-                    start: 0,
-                    end: 0,
-                    file: None,
-                };
-
-                if let Some(id) = span.id() {
-                    if let Ok(_source) = world.source(id) {
-                        render_error.file = Some(format!("{:?}", id));
-                        if let Some(range) = world.range(span) {
-                            render_error.start = range.start;
-                            render_error.end = range.end;
-                        }
-                    }
-                }
-
-                errors.push(render_error);
+                errors.push(diagnostic_error(world, diagnostic));
             }
         }
     }
@@ -256,6 +1836,23 @@ pub fn render_template_with_cache(
     Ok(RenderResult {
         pdf,
         errors,
+        warnings,
         success,
+        pages,
+        missing_glyphs: missing,
+        used_inputs: used_inputs(&main_typ, world.data_key()),
     })
 }
+
+/// Drop memoized compilation results unused in the last `max_age` evictions
+///
+/// Typst memoizes evaluation and layout in a process-wide cache, so
+/// renders of a template whose world is kept (see
+/// [`render_template_with_cache`]) only redo the work affected by the new
+/// data. The cache is never pruned on its own; long-running processes
+/// should call this after each render to bound its memory. Each call ages
+/// every entry by one, so with `max_age` of 10, results not reused within
+/// the last ten calls are dropped.
+pub fn evict_cache(max_age: usize) {
+    typst::comemo::evict(max_age);
+}
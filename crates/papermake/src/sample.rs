@@ -0,0 +1,327 @@
+//! Sample data generated from a JSON Schema
+//!
+//! Template editors offer a "fill with sample data" action for previews,
+//! which needs plausible values of the right shape. [`generate_sample_data`]
+//! walks a template's `schema.json` and builds one example value per schema
+//! node, preferring the values the schema itself suggests.
+
+use serde_json::{Map, Value, json};
+
+/// Nesting depth after which recursive `$ref`s produce `null`
+const MAX_DEPTH: usize = 32;
+
+/// Build example data matching the JSON Schema `schema`
+///
+/// Per schema node, the first of `examples`, `default`, `const` and `enum`
+/// is used as is. Otherwise the value is derived from the `type`:
+///
+/// - strings are the property name, e.g. `{"name": "name"}`, or a fixed
+///   example for the `date`, `date-time`, `email`, `uri` and `uuid` formats
+/// - numbers and integers are `0`, moved into `minimum`/`maximum` bounds
+/// - booleans are `false`
+/// - arrays hold one element, or `minItems` elements
+/// - objects hold every property listed in `properties`
+///
+/// Local `$ref`s like `#/$defs/address` are followed, `oneOf` and `anyOf`
+/// use their first alternative and `allOf` merges its objects. String
+/// `pattern`s are not taken into account, so a schema with patterns may
+/// reject the generated data.
+pub fn generate_sample_data(schema: &Value) -> Value {
+    sample(schema, None, schema, 0)
+}
+
+/// Sample for `schema`, the value of property `name` if any
+fn sample(schema: &Value, name: Option<&str>, root: &Value, depth: usize) -> Value {
+    let Some(schema) = schema.as_object() else {
+        return Value::Null;
+    };
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+
+    if let Some(example) = schema
+        .get("examples")
+        .and_then(Value::as_array)
+        .and_then(|examples| examples.first())
+    {
+        return example.clone();
+    }
+    if let Some(value) = schema.get("default").or_else(|| schema.get("const")) {
+        return value.clone();
+    }
+    if let Some(value) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+    {
+        return value.clone();
+    }
+
+    if let Some(target) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+    {
+        return sample(target, name, root, depth + 1);
+    }
+    if let Some(first) = ["oneOf", "anyOf"]
+        .iter()
+        .find_map(|keyword| schema.get(*keyword)?.as_array()?.first())
+    {
+        return sample(first, name, root, depth + 1);
+    }
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        return sample_all_of(parts, name, root, depth);
+    }
+
+    match schema_type(schema) {
+        Some("string") => sample_string(schema, name),
+        Some("integer") => sample_number(schema, true),
+        Some("number") => sample_number(schema, false),
+        Some("boolean") => Value::Bool(false),
+        Some("array") => sample_array(schema, name, root, depth),
+        Some("object") => sample_object(schema, root, depth),
+        _ => Value::Null,
+    }
+}
+
+/// The schema's type, inferred from its keywords if not given
+///
+/// For a list of types the first one other than `null` is used.
+fn schema_type(schema: &Map<String, Value>) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(name)) => Some(name),
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|name| *name != "null"),
+        _ if schema.contains_key("properties") => Some("object"),
+        _ if schema.contains_key("items") || schema.contains_key("prefixItems") => Some("array"),
+        _ => None,
+    }
+}
+
+fn sample_string(schema: &Map<String, Value>, name: Option<&str>) -> Value {
+    let example = match schema.get("format").and_then(Value::as_str) {
+        Some("date") => "2024-01-31".to_string(),
+        Some("date-time") => "2024-01-31T12:00:00Z".to_string(),
+        Some("time") => "12:00:00Z".to_string(),
+        Some("email") => format!("{}@example.com", name.unwrap_or("name")),
+        Some("uri") | Some("url") => "https://example.com".to_string(),
+        Some("uuid") => "00000000-0000-4000-8000-000000000000".to_string(),
+        _ => name.unwrap_or("string").to_string(),
+    };
+
+    let min_length = length(schema, "minLength").unwrap_or(0);
+    let max_length = length(schema, "maxLength").unwrap_or(usize::MAX);
+    let mut example: String = example.chars().take(max_length).collect();
+    while example.chars().count() < min_length {
+        example.push('x');
+    }
+    Value::String(example)
+}
+
+fn sample_number(schema: &Map<String, Value>, integer: bool) -> Value {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    // Step past exclusive bounds by one, or by a tenth for non-integers
+    let step = if integer { 1.0 } else { 0.1 };
+
+    let mut value: f64 = 0.0;
+    if let Some(minimum) = bound("minimum") {
+        value = value.max(minimum);
+    }
+    if let Some(minimum) = bound("exclusiveMinimum") {
+        value = value.max(minimum + step);
+    }
+    if let Some(maximum) = bound("maximum") {
+        value = value.min(maximum);
+    }
+    if let Some(maximum) = bound("exclusiveMaximum") {
+        value = value.min(maximum - step);
+    }
+
+    if integer {
+        json!(value.ceil() as i64)
+    } else {
+        json!(value)
+    }
+}
+
+fn sample_array(
+    schema: &Map<String, Value>,
+    name: Option<&str>,
+    root: &Value,
+    depth: usize,
+) -> Value {
+    if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+        return Value::Array(
+            items
+                .iter()
+                .map(|item| sample(item, name, root, depth + 1))
+                .collect(),
+        );
+    }
+
+    let count = length(schema, "minItems")
+        .unwrap_or(1)
+        .max(1)
+        .min(length(schema, "maxItems").unwrap_or(usize::MAX));
+    let item = match schema.get("items") {
+        Some(items) => sample(items, name, root, depth + 1),
+        None => Value::Null,
+    };
+    Value::Array(vec![item; count])
+}
+
+fn sample_object(schema: &Map<String, Value>, root: &Value, depth: usize) -> Value {
+    let properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(key, property)| (key.clone(), sample(property, Some(key), root, depth + 1)))
+        .collect();
+    Value::Object(properties)
+}
+
+/// Merge the samples of all `allOf` parts, keeping the first non-object one
+fn sample_all_of(parts: &[Value], name: Option<&str>, root: &Value, depth: usize) -> Value {
+    let mut merged: Option<Value> = None;
+    for part in parts {
+        match (&mut merged, sample(part, name, root, depth + 1)) {
+            (Some(Value::Object(merged)), Value::Object(part)) => merged.extend(part),
+            (None, part) => merged = Some(part),
+            _ => {}
+        }
+    }
+    merged.unwrap_or(Value::Null)
+}
+
+/// A non-negative integer keyword like `minLength`
+fn length(schema: &Map<String, Value>, keyword: &str) -> Option<usize> {
+    schema
+        .get(keyword)
+        .and_then(Value::as_u64)
+        .map(|length| length as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate_data;
+
+    fn assert_valid(schema: &Value) -> Value {
+        let sample = generate_sample_data(schema);
+        if let Err(e) = validate_data(&sample, schema) {
+            panic!("sample {} does not match schema: {}", sample, e);
+        }
+        sample
+    }
+
+    #[test]
+    fn test_nested_objects() {
+        let schema = json!({
+            "type": "object",
+            "required": ["customer", "total"],
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "required": ["name", "email"],
+                    "properties": {
+                        "name": { "type": "string", "minLength": 6 },
+                        "email": { "type": "string", "format": "email" },
+                        "vip": { "type": "boolean" }
+                    }
+                },
+                "total": { "type": "number", "exclusiveMinimum": 0, "examples": [99.5] },
+                "count": { "type": "integer", "minimum": 3 }
+            }
+        });
+
+        let sample = assert_valid(&schema);
+        assert_eq!(
+            sample,
+            json!({
+                "customer": { "name": "namexx", "email": "email@example.com", "vip": false },
+                "total": 99.5,
+                "count": 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "description": { "type": "string" },
+                            "amount": { "type": "number" }
+                        }
+                    }
+                },
+                "tags": { "type": "array", "items": { "type": "string" }, "minItems": 2 },
+                "point": { "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "string" }] }
+            }
+        });
+
+        let sample = assert_valid(&schema);
+        assert_eq!(
+            sample["items"],
+            json!([{ "description": "description", "amount": 0.0 }])
+        );
+        assert_eq!(sample["tags"], json!(["tags", "tags"]));
+        assert_eq!(sample["point"], json!([0, "point"]));
+    }
+
+    #[test]
+    fn test_enum_default_and_refs() {
+        let schema = json!({
+            "type": "object",
+            "$defs": {
+                "currency": { "type": "string", "enum": ["EUR", "USD"] }
+            },
+            "properties": {
+                "currency": { "$ref": "#/$defs/currency" },
+                "status": { "enum": ["draft", "sent"] },
+                "language": { "type": "string", "default": "de" },
+                "due": { "type": ["null", "string"], "format": "date" },
+                "note": { "oneOf": [{ "type": "string", "maxLength": 2 }, { "type": "null" }] }
+            }
+        });
+
+        let sample = assert_valid(&schema);
+        assert_eq!(
+            sample,
+            json!({
+                "currency": "EUR",
+                "status": "draft",
+                "language": "de",
+                "due": "2024-01-31",
+                "note": "no"
+            })
+        );
+    }
+
+    #[test]
+    fn test_recursive_ref_terminates() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "child": { "$ref": "#" }
+            }
+        });
+
+        // The innermost `child` is null, which the schema rejects, so only
+        // check that generation stops
+        let sample = generate_sample_data(&schema);
+        assert_eq!(sample["name"], "name");
+        assert!(sample["child"]["child"].is_object());
+    }
+}
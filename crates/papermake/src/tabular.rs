@@ -0,0 +1,165 @@
+//! Rendering templates from CSV data
+//!
+//! Tabular sources such as exports from spreadsheets or accounting systems
+//! are parsed into JSON and injected as `sys.inputs.rows`, so a template
+//! reads them with `json(bytes(sys.inputs.rows))` instead of requiring a
+//! separate CSV to JSON conversion. Every field is a string; templates
+//! convert numbers themselves, e.g. `float(row.amount)`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::RenderFileSystem;
+use crate::error::{DataError, PapermakeError, Result};
+use crate::render::{RenderOptions, RenderResult, render_template_with_inputs};
+
+/// `sys.inputs` key holding the parsed rows
+pub const CSV_ROWS_KEY: &str = "rows";
+
+/// How CSV input is parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field delimiter, `,` by default
+    pub delimiter: u8,
+    /// Whether the first row names the columns, `true` by default
+    ///
+    /// With a header row, each row becomes an object keyed by column name.
+    /// Without one, each row becomes an array of its fields.
+    pub has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Set the field delimiter, e.g. `b';'` or `b'\t'`
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set whether the first row names the columns
+    pub fn with_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+}
+
+/// Parse CSV into a JSON array with one entry per row
+///
+/// # Errors
+///
+/// Returns `DataError::InvalidFormat` if a row has a different number of
+/// fields than the first row, or the CSV is otherwise malformed.
+pub fn parse_csv(csv: &str, options: &CsvOptions) -> Result<serde_json::Value> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .from_reader(csv.as_bytes());
+
+    let headers = if options.has_headers {
+        Some(reader.headers().map_err(csv_error)?.clone())
+    } else {
+        None
+    };
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let row = match &headers {
+            Some(headers) => serde_json::Value::Object(
+                headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(column, field)| (column.to_string(), field.into()))
+                    .collect(),
+            ),
+            None => record.iter().map(serde_json::Value::from).collect(),
+        };
+        rows.push(row);
+    }
+
+    Ok(serde_json::Value::Array(rows))
+}
+
+/// Convert a CSV parse error, naming the line it occurred on
+fn csv_error(error: csv::Error) -> PapermakeError {
+    let line = error.position().map(|position| position.line());
+    let (expected, actual) = match error.kind() {
+        csv::ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => (
+            format!("rows with {} fields", expected_len),
+            format!("{} fields", len),
+        ),
+        _ => ("well-formed CSV".to_string(), error.to_string()),
+    };
+
+    DataError::InvalidFormat {
+        expected,
+        actual: match line {
+            Some(line) => format!("{} on line {}", actual, line),
+            None => actual,
+        },
+    }
+    .into()
+}
+
+/// Render a template with CSV data as `sys.inputs.rows`
+///
+/// The CSV is parsed with [`parse_csv`] and rendered as with
+/// [`render_template_with_inputs`]. No input is given under the data key,
+/// so `data` is `none`.
+///
+/// # Errors
+///
+/// Returns `DataError::InvalidFormat` for malformed CSV, including ragged
+/// rows, and `ConfigError::InvalidConfig` if an option value is malformed.
+pub fn render_template_with_csv(
+    main_typ: String,
+    file_system: Arc<dyn RenderFileSystem>,
+    csv: &str,
+    csv_options: &CsvOptions,
+    options: &RenderOptions,
+) -> Result<RenderResult> {
+    let rows = parse_csv(csv, csv_options)?;
+    let inputs = BTreeMap::from([(CSV_ROWS_KEY.to_string(), rows)]);
+
+    render_template_with_inputs(main_typ, file_system, &inputs, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parses_rows_keyed_by_header() {
+        let rows = parse_csv(
+            "item;qty\nPen;2\n\"Ink; blue\";1\n",
+            &CsvOptions::default().with_delimiter(b';'),
+        )
+        .unwrap();
+
+        assert_eq!(
+            rows,
+            json!([
+                {"item": "Pen", "qty": "2"},
+                {"item": "Ink; blue", "qty": "1"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parses_rows_without_header_as_arrays() {
+        let rows = parse_csv("Pen,2\nInk,1", &CsvOptions::default().with_headers(false)).unwrap();
+
+        assert_eq!(rows, json!([["Pen", "2"], ["Ink", "1"]]));
+    }
+}
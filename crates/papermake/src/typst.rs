@@ -1,18 +1,28 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
-use typst::Library;
 use typst::diag::{FileError, FileResult};
 use typst::foundations::{Bytes, Datetime, Dict, IntoValue};
-use typst::syntax::{FileId, Source};
-use typst::text::{Font, FontBook};
+use typst::syntax::{FileId, Source, Span, VirtualPath};
+use typst::text::{Font, FontBook, FontFamily, FontList, TextElem};
 use typst::utils::LazyHash;
+use typst::{Library, World, WorldExt};
+#[cfg(any(feature = "fs", feature = "embed-fonts"))]
 use typst_kit::fonts::{FontSearcher, FontSlot};
 
+use crate::error::SourceLocation;
+
+/// Typst's default text font, kept first when fallbacks are configured
+const DEFAULT_FONT_FAMILY: &str = "Libertinus Serif";
+
+/// Font book and the fonts it indexes
+type Fonts = (LazyHash<FontBook>, Vec<Font>);
+
 // Define a static lazy variable to hold the cached fonts
-static CACHED_FONTS: Lazy<(FontBook, Vec<Font>)> = Lazy::new(|| {
+#[cfg(feature = "fs")]
+static CACHED_FONTS: Lazy<Fonts> = Lazy::new(|| {
     let mut font_searcher = FontSearcher::new();
     let font_searcher = font_searcher.include_system_fonts(true);
 
@@ -28,13 +38,115 @@ static CACHED_FONTS: Lazy<(FontBook, Vec<Font>)> = Lazy::new(|| {
     let fonts = fonts
         .fonts
         .iter()
-        .map(FontSlot::get)
-        .filter_map(|f| f)
+        .filter_map(FontSlot::get)
         .collect::<Vec<_>>();
 
-    (book, fonts)
+    (LazyHash::new(book), fonts)
 });
 
+// Without the `fs` feature there is no ambient font source to search
+#[cfg(not(feature = "fs"))]
+static CACHED_FONTS: Lazy<Fonts> = Lazy::new(|| (LazyHash::new(FontBook::new()), Vec::new()));
+
+/// Where a world loads its fonts from
+///
+/// Worlds created without a font source use the system fonts plus the
+/// fonts in `FONTS_DIR`, searched once per process and cached. Without the
+/// `fs` feature, e.g. for WebAssembly, they have no fonts at all, so text
+/// needs an `InMemory` (or `Embedded`) source.
+#[derive(Debug, Clone)]
+pub enum FontSource {
+    /// Only the fonts found in the given directory (searched recursively);
+    /// requires the `fs` feature
+    SystemDir(PathBuf),
+    /// The default font set bundled with Typst; requires the `embed-fonts` feature
+    Embedded,
+    /// Raw font files (TTF, OTF or collections), e.g. loaded from a database
+    InMemory(Vec<Vec<u8>>),
+}
+
+impl FontSource {
+    /// Load the font book and fonts for this source
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::FontLoading` if the directory is unreadable,
+    /// no usable font is found, or `SystemDir` or `Embedded` is used without
+    /// the `fs` or `embed-fonts` feature.
+    pub fn load(&self) -> Result<(FontBook, Vec<Font>), crate::error::PapermakeError> {
+        let fonts = match self {
+            FontSource::SystemDir(dir) => Self::search_dir(dir)?,
+            FontSource::Embedded => Self::embedded()?,
+            FontSource::InMemory(data) => data
+                .iter()
+                .flat_map(|bytes| Font::iter(Bytes::new(bytes.clone())))
+                .collect(),
+        };
+
+        if fonts.is_empty() {
+            return Err(font_loading_error(format!("no usable fonts in {:?}", self)));
+        }
+
+        Ok((FontBook::from_fonts(&fonts), fonts))
+    }
+
+    #[cfg(feature = "fs")]
+    fn search_dir(dir: &Path) -> Result<Vec<Font>, crate::error::PapermakeError> {
+        std::fs::read_dir(dir).map_err(|e| {
+            font_loading_error(format!(
+                "cannot read font directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        let mut font_searcher = FontSearcher::new();
+        font_searcher.include_system_fonts(false);
+        #[cfg(feature = "embed-fonts")]
+        font_searcher.include_embedded_fonts(false);
+
+        let fonts = font_searcher.search_with([dir]);
+        Ok(fonts.fonts.iter().filter_map(FontSlot::get).collect())
+    }
+
+    #[cfg(not(feature = "fs"))]
+    fn search_dir(dir: &Path) -> Result<Vec<Font>, crate::error::PapermakeError> {
+        Err(font_loading_error(format!(
+            "font directory {} requires the `fs` feature",
+            dir.display()
+        )))
+    }
+
+    #[cfg(feature = "embed-fonts")]
+    fn embedded() -> Result<Vec<Font>, crate::error::PapermakeError> {
+        let fonts = FontSearcher::new()
+            .include_system_fonts(false)
+            .include_embedded_fonts(true)
+            .search();
+        Ok(fonts.fonts.iter().filter_map(FontSlot::get).collect())
+    }
+
+    #[cfg(not(feature = "embed-fonts"))]
+    fn embedded() -> Result<Vec<Font>, crate::error::PapermakeError> {
+        Err(font_loading_error(
+            "embedded fonts require the `embed-fonts` feature",
+        ))
+    }
+}
+
+fn font_loading_error(reason: impl Into<String>) -> crate::error::PapermakeError {
+    crate::error::ConfigError::FontLoading {
+        reason: reason.into(),
+    }
+    .into()
+}
+
+/// Default `sys.inputs` key the render data is injected under
+pub const DEFAULT_DATA_KEY: &str = "data";
+
+/// Virtual path under which [`PapermakeWorld::with_data_file`] exposes the render data
+pub const DATA_FILE_PATH: &str = "/.papermake/data.json";
+
 /// File system abstraction for Typst rendering
 ///
 /// This trait provides file access to TypstWorld during rendering,
@@ -45,6 +157,10 @@ pub trait RenderFileSystem: Send + Sync {
 }
 
 /// Main interface that determines the environment for Typst.
+///
+/// Clones share the cache of loaded files, so a clone of a world that has
+/// already compiled a template does not fetch its imports and assets again.
+#[derive(Clone)]
 pub struct PapermakeWorld {
     /// The content of a source.
     source: Source,
@@ -52,24 +168,46 @@ pub struct PapermakeWorld {
     /// The standard library.
     library: LazyHash<Library>,
 
-    /// Metadata about all known fonts.
-    book: LazyHash<FontBook>,
-
-    /// Metadata about all known fonts.
-    fonts: Vec<Font>,
+    /// Fonts from a font source; `None` uses the cached default fonts,
+    /// which are only searched once a world without a font source needs them
+    fonts: Option<Fonts>,
 
     /// Map of all known files.
     files: Arc<Mutex<HashMap<FileId, FileEntry>>>,
 
-    /// Cache directory (e.g. where packages are downloaded to).
-    #[allow(dead_code)]
-    cache_directory: PathBuf,
-
     /// Datetime.
     time: time::OffsetDateTime,
 
     /// File system abstraction for loading template files/assets
     file_system: Option<Arc<dyn RenderFileSystem>>,
+
+    /// Raw JSON data served at `DATA_FILE_PATH`, if data is passed as a file
+    data_file: Option<Bytes>,
+
+    /// `sys.inputs` key holding the serialized data
+    data_key: String,
+
+    /// All `sys.inputs` values, kept to rebuild the library when one changes
+    inputs: Dict,
+
+    /// Length in bytes of the code papermake prepends to the main template
+    prelude_len: usize,
+
+    /// Families tried after Typst's default font, in order
+    font_fallbacks: Vec<String>,
+}
+
+/// Standard library with `inputs` and a default font list ending in `font_fallbacks`
+fn build_library(inputs: &Dict, font_fallbacks: &[String]) -> Library {
+    let mut library = Library::builder().with_inputs(inputs.clone()).build();
+    if !font_fallbacks.is_empty() {
+        let families = std::iter::once(DEFAULT_FONT_FAMILY)
+            .chain(font_fallbacks.iter().map(String::as_str))
+            .map(FontFamily::new)
+            .collect();
+        library.styles.set(TextElem::set_font(FontList(families)));
+    }
+    library
 }
 
 impl std::fmt::Debug for PapermakeWorld {
@@ -77,15 +215,15 @@ impl std::fmt::Debug for PapermakeWorld {
         f.debug_struct("TypstWorld")
             .field("source", &self.source)
             .field("library", &self.library)
-            .field("book", &self.book)
-            .field("fonts_count", &self.fonts.len())
+            .field("book", &self.fonts().0)
+            .field("fonts_count", &self.fonts().1.len())
             .field(
                 "files_count",
                 &self.files.lock().map(|f| f.len()).unwrap_or(0),
             )
-            .field("cache_directory", &self.cache_directory)
             .field("time", &self.time)
             .field("has_file_system", &self.file_system.is_some())
+            .field("has_data_file", &self.data_file.is_some())
             .finish()
     }
 }
@@ -93,30 +231,82 @@ impl std::fmt::Debug for PapermakeWorld {
 impl PapermakeWorld {
     /// Create a new TypstWorld with the given template content and data
     pub fn new(template_content: String, data: String) -> Self {
-        // Use the cached fonts directly
-        let (book, fonts) = CACHED_FONTS.clone();
+        Self::with_data_key(template_content, data, DEFAULT_DATA_KEY)
+    }
+
+    /// Create TypstWorld injecting the data under `sys.inputs.<data_key>`
+    ///
+    /// The prelude still binds the decoded value to `data`, so templates can
+    /// use either `data` or decode `sys.inputs.<data_key>` themselves.
+    pub fn with_data_key(template_content: String, data: String, data_key: &str) -> Self {
+        Self::with_inputs(
+            template_content,
+            BTreeMap::from([(data_key.to_string(), data)]),
+            data_key,
+        )
+    }
+
+    /// Create TypstWorld injecting each serialized JSON value in `inputs`
+    /// under its own `sys.inputs` key
+    ///
+    /// The entry under `data_key` is bound to `data` as with
+    /// [`PapermakeWorld::with_data_key`]; if there is none, `data` is `none`.
+    /// Every other entry is only reachable through `sys.inputs`, e.g.
+    /// `json(bytes(sys.inputs.branding))`.
+    pub fn with_inputs(
+        template_content: String,
+        inputs: BTreeMap<String, String>,
+        data_key: &str,
+    ) -> Self {
+        let data_binding = if inputs.contains_key(data_key) {
+            format!("json(bytes(sys.inputs.at(\"{}\")))", data_key)
+        } else {
+            "none".to_string()
+        };
+        let prelude = format!("#let data = {}\n", data_binding);
 
         let mut inputs_dict = Dict::new();
-        inputs_dict.insert("data".into(), data.as_str().into_value());
+        for (key, value) in inputs {
+            inputs_dict.insert(key.into(), value.into_value());
+        }
 
-        let library = Library::builder().with_inputs(inputs_dict).build();
+        let mut world = Self::from_parts(prelude, template_content, inputs_dict);
+        world.data_key = data_key.to_string();
+        world
+    }
 
-        let source_text = format!(
-            "#let data = json.decode(sys.inputs.data)\n{}",
-            template_content
-        );
+    /// Create TypstWorld that reads the data from a virtual JSON file
+    ///
+    /// Unlike [`PapermakeWorld::new`], the raw JSON bytes are not copied into a
+    /// `sys.inputs` string. They are served as-is at [`DATA_FILE_PATH`] and the
+    /// template prelude loads them with `json(..)`, so the serialized data only
+    /// exists once in memory. `sys.inputs.data` is not set in this mode.
+    pub fn with_data_file(
+        template_content: String,
+        data: Vec<u8>,
+        file_system: Arc<dyn RenderFileSystem>,
+    ) -> Self {
+        let prelude = format!("#let data = json(\"{}\")\n", DATA_FILE_PATH);
+
+        let mut world = Self::from_parts(prelude, template_content, Dict::new());
+        world.file_system = Some(file_system);
+        world.data_file = Some(Bytes::new(data));
+        world
+    }
 
+    fn from_parts(prelude: String, template_content: String, inputs: Dict) -> Self {
         Self {
-            library: LazyHash::new(library),
-            book: LazyHash::new(book),
-            fonts,
-            source: Source::detached(source_text),
+            library: LazyHash::new(build_library(&inputs, &[])),
+            fonts: None,
+            source: Source::detached(format!("{}{}", prelude, template_content)),
             time: time::OffsetDateTime::now_utc(),
-            cache_directory: std::env::var_os("CACHE_DIRECTORY")
-                .map(|os_path| os_path.into())
-                .unwrap_or(std::env::temp_dir()),
             files: Arc::new(Mutex::new(HashMap::new())),
             file_system: None,
+            data_file: None,
+            data_key: DEFAULT_DATA_KEY.to_string(),
+            inputs,
+            prelude_len: prelude.len(),
+            font_fallbacks: Vec::new(),
         }
     }
 
@@ -131,16 +321,120 @@ impl PapermakeWorld {
         world
     }
 
-    /// Update the data available to the template
-    pub fn update_data(&mut self, data: String) -> Result<(), crate::error::PapermakeError> {
-        // Update the data in the inputs dictionary
-        let mut inputs_dict = Dict::new();
-        inputs_dict.insert("data".into(), data.as_str().into_value());
+    /// Create TypstWorld with the given template, data and fonts
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::FontLoading` if the fonts cannot be loaded.
+    pub fn with_font_source(
+        template_content: String,
+        data: String,
+        font_source: &FontSource,
+    ) -> Result<Self, crate::error::PapermakeError> {
+        let mut world = Self::new(template_content, data);
+        world.set_font_source(font_source)?;
+        Ok(world)
+    }
+
+    /// Replace the world's fonts with those from `font_source`
+    pub fn set_font_source(
+        &mut self,
+        font_source: &FontSource,
+    ) -> Result<(), crate::error::PapermakeError> {
+        let (book, fonts) = font_source.load()?;
+        self.fonts = Some((LazyHash::new(book), fonts));
+        Ok(())
+    }
+
+    /// Fonts from the font source, or the cached default fonts
+    fn fonts(&self) -> &Fonts {
+        self.fonts.as_ref().unwrap_or(&CACHED_FONTS)
+    }
+
+    /// Attach a file system for resolving imports and assets
+    pub fn set_file_system(&mut self, file_system: Arc<dyn RenderFileSystem>) {
+        self.file_system = Some(file_system);
+    }
+
+    /// Set a string value in `sys.inputs`
+    pub fn set_input(&mut self, key: &str, value: &str) {
+        self.inputs.insert(key.into(), value.into_value());
+        self.library = LazyHash::new(build_library(&self.inputs, &self.font_fallbacks));
+    }
+
+    /// Try `families`, in order, for characters the default font lacks
+    ///
+    /// The list becomes the document's default `text(font: ..)` after
+    /// Typst's own default, Libertinus Serif. A template that sets its own
+    /// font replaces the whole list, so such templates should name their
+    /// fallbacks themselves. Typst's automatic fallback to any loaded font
+    /// that covers a character still applies after the list.
+    pub fn set_font_fallbacks(&mut self, families: Vec<String>) {
+        self.font_fallbacks = families;
+        self.library = LazyHash::new(build_library(&self.inputs, &self.font_fallbacks));
+    }
+
+    /// Override the current time, which determines `datetime.today()`
+    pub fn set_time(&mut self, time: time::OffsetDateTime) {
+        self.time = time;
+    }
+
+    /// Serve `content` at the absolute `path`, ahead of the file system
+    pub(crate) fn add_virtual_file(&mut self, path: &str, content: Vec<u8>) {
+        let id = FileId::new(None, VirtualPath::new(path));
+        if let Ok(mut files) = self.files.lock() {
+            files.insert(id, FileEntry::new(content, None));
+        }
+    }
+
+    /// Count the first `len` bytes of the template passed to the
+    /// constructor as generated prelude rather than user code
+    pub(crate) fn extend_prelude(&mut self, len: usize) {
+        self.prelude_len += len;
+    }
+
+    /// The keys set in `sys.inputs`, sorted
+    pub(crate) fn input_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.inputs.iter().map(|(key, _)| key.to_string()).collect();
+        keys.sort();
+        keys
+    }
+
+    /// The `sys.inputs` key holding the serialized data
+    pub(crate) fn data_key(&self) -> &str {
+        &self.data_key
+    }
 
-        // Create a new library with updated inputs
-        let library = Library::builder().with_inputs(inputs_dict).build();
-        self.library = LazyHash::new(library);
+    /// Location of `span` as the template author sees it
+    ///
+    /// The file is the logical path, e.g. `main.typ` or
+    /// `components/header.typ`. In the main template, lines and byte ranges
+    /// exclude the prelude papermake prepends (the `data` binding and render
+    /// option settings), so they match the template as written. Returns
+    /// `None` for detached spans and spans inside the prelude.
+    pub fn source_location(&self, span: Span) -> Option<SourceLocation> {
+        let id = span.id()?;
+        let source = self.source(id).ok()?;
+        let range = self.range(span)?;
+        let offset = if id == self.source.id() {
+            self.prelude_len
+        } else {
+            0
+        };
 
+        let start = range.start.checked_sub(offset)?;
+        let line = source.byte_to_line(range.start)? - source.byte_to_line(offset)?;
+        Some(SourceLocation {
+            file: logical_path(id),
+            line: line + 1,
+            column: source.byte_to_column(range.start)? + 1,
+            range: Some((start, range.end - offset)),
+        })
+    }
+
+    /// Update the data available to the template
+    pub fn update_data(&mut self, data: String) -> Result<(), crate::error::PapermakeError> {
+        self.set_input(&self.data_key.clone(), &data);
         Ok(())
     }
 }
@@ -178,6 +472,16 @@ impl PapermakeWorld {
     ///
     /// Requests will be either in packages or a local file.
     fn file(&self, id: FileId) -> FileResult<FileEntry> {
+        if let Some(data) = &self.data_file
+            && id.package().is_none()
+            && id.vpath() == &VirtualPath::new(DATA_FILE_PATH)
+        {
+            return Ok(FileEntry {
+                bytes: data.clone(),
+                source: None,
+            });
+        }
+
         let mut files = self.files.lock().map_err(|_| FileError::AccessDenied)?;
         if let Some(entry) = files.get(&id) {
             return Ok(entry.clone());
@@ -214,6 +518,15 @@ impl PapermakeWorld {
     }
 }
 
+/// Path of `id` relative to the template root, prefixed by its package if any
+fn logical_path(id: FileId) -> String {
+    let path = id.vpath().as_rootless_path().display();
+    match id.package() {
+        Some(package) => format!("{}/{}", package, path),
+        None => path.to_string(),
+    }
+}
+
 /// This is the interface we have to implement such that `typst` can compile it.
 impl typst::World for PapermakeWorld {
     /// Standard library.
@@ -223,7 +536,7 @@ impl typst::World for PapermakeWorld {
 
     /// Metadata about all known Books.
     fn book(&self) -> &LazyHash<FontBook> {
-        &self.book
+        &self.fonts().0
     }
 
     /// Accessing the main source file.
@@ -247,7 +560,7 @@ impl typst::World for PapermakeWorld {
 
     /// Accessing a specified font per index of font book.
     fn font(&self, id: usize) -> Option<Font> {
-        self.fonts.get(id).cloned()
+        self.fonts().1.get(id).cloned()
     }
 
     /// Get the current date.
@@ -266,6 +579,12 @@ pub struct InMemoryFileSystem {
     files: HashMap<String, Vec<u8>>,
 }
 
+impl Default for InMemoryFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InMemoryFileSystem {
     pub fn new() -> Self {
         Self {
@@ -294,6 +613,41 @@ mod tests {
     use super::*;
     use std::sync::Arc;
 
+    #[test]
+    fn test_source_location_of_syntax_error() {
+        let template = "= Title\n\nSome text\n#let total = (1, 2\n".to_string();
+        let world = PapermakeWorld::new(template, "{}".to_string());
+
+        let errors = typst::compile::<typst::layout::PagedDocument>(&world)
+            .output
+            .unwrap_err();
+        let location = world.source_location(errors[0].span).unwrap();
+
+        assert_eq!(location.file, "main.typ");
+        assert_eq!(location.line, 4);
+        assert_eq!(location.column, 14);
+    }
+
+    #[test]
+    fn test_source_location_in_imported_file() {
+        let mut fs = InMemoryFileSystem::new();
+        fs.add_file("/parts/header.typ", b"#let a = 1\n#let b = a +\n".to_vec());
+        let world = PapermakeWorld::with_file_system(
+            "#include \"parts/header.typ\"".to_string(),
+            "{}".to_string(),
+            Arc::new(fs),
+        );
+
+        let errors = typst::compile::<typst::layout::PagedDocument>(&world)
+            .output
+            .unwrap_err();
+        let diagnostic = crate::error::convert_typst_diagnostic(&world, errors[0].clone());
+        let location = diagnostic.location.unwrap();
+
+        assert_eq!(location.file, "parts/header.typ");
+        assert_eq!(location.line, 2);
+    }
+
     #[tokio::test]
     async fn test_simple_template_rendering() {
         let template = r#"
@@ -0,0 +1,119 @@
+//! JSON Schema validation of template data
+//!
+//! Templates may ship a `schema.json` describing the data they expect.
+//! Validating data against it before rendering turns a typo in a field name
+//! into an error naming the offending field, instead of a Typst error deep
+//! inside compilation.
+
+use jsonschema::error::ValidationErrorKind;
+
+use crate::error::DataError;
+
+/// Validate `data` against the JSON Schema `schema`
+///
+/// # Errors
+///
+/// Returns `DataError::SchemaValidation` for the first violation, with
+/// `path` set to the JSON pointer of the offending value. For a missing
+/// required property the pointer names the property itself, e.g.
+/// `/customer/name`. An invalid schema is reported the same way with an
+/// empty path.
+pub fn validate_data(
+    data: &serde_json::Value,
+    schema: &serde_json::Value,
+) -> Result<(), DataError> {
+    let validator = jsonschema::validator_for(schema).map_err(|e| DataError::SchemaValidation {
+        path: String::new(),
+        message: format!("invalid schema: {}", e),
+    })?;
+
+    let mut errors = validator.iter_errors(data);
+    let Some(error) = errors.next() else {
+        return Ok(());
+    };
+
+    let mut path = error.instance_path().to_string();
+    if let ValidationErrorKind::Required { property } = error.kind()
+        && let Some(property) = property.as_str()
+    {
+        path.push('/');
+        path.push_str(&escape_pointer_token(property));
+    }
+
+    let remaining = errors.count();
+    let message = if remaining == 0 {
+        error.to_string()
+    } else {
+        format!("{} (and {} more)", error, remaining)
+    };
+
+    Err(DataError::SchemaValidation { path, message })
+}
+
+/// Escape a property name for use as a JSON pointer token (RFC 6901)
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["customer"],
+            "properties": {
+                "customer": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "age": { "type": "integer" }
+                    }
+                }
+            }
+        })
+    }
+
+    fn path_of(result: Result<(), DataError>) -> String {
+        match result {
+            Err(DataError::SchemaValidation { path, .. }) => path,
+            other => panic!("expected schema validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_data() {
+        let data = json!({ "customer": { "name": "Acme", "age": 3 } });
+        assert!(validate_data(&data, &schema()).is_ok());
+    }
+
+    #[test]
+    fn test_missing_required_field_reports_path() {
+        let data = json!({ "customer": { "age": 3 } });
+        assert_eq!(path_of(validate_data(&data, &schema())), "/customer/name");
+    }
+
+    #[test]
+    fn test_type_mismatch_reports_path() {
+        let data = json!({ "customer": { "name": "Acme", "age": "three" } });
+        let result = validate_data(&data, &schema());
+
+        let message = result.as_ref().unwrap_err().to_string();
+        assert!(message.contains("\"three\" is not of type \"integer\""));
+        assert_eq!(path_of(result), "/customer/age");
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        let schema = json!({ "type": "no-such-type" });
+        assert_eq!(path_of(validate_data(&json!({}), &schema)), "");
+    }
+
+    #[test]
+    fn test_escape_pointer_token() {
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+    }
+}
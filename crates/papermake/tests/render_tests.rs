@@ -1,6 +1,17 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use papermake::{InMemoryFileSystem, render_template};
+use papermake::error::{CompilationError, ConfigError, DataError, DiagnosticSeverity};
+use papermake::{
+    AssetFallback, CsvOptions, DecorationContent, FileError, FontSource, HtmlRenderOptions,
+    InMemoryFileSystem, Locale, OutlineEntry, OutputFormat, PageDecoration, PapermakeError,
+    PapermakeWorld, PdfOptions, Preprocessor, RenderFileSystem, RenderOptions, Watermark,
+    check_template, merge_pdfs, render_html, render_markdown, render_template,
+    render_template_from_reader, render_template_output, render_template_to,
+    render_template_to_writer, render_template_with_cache, render_template_with_csv,
+    render_template_with_inputs, render_template_with_options, render_with_outline,
+    render_with_thumbnail,
+};
 use pdf::object::MaybeRef;
 use serde_json::json;
 
@@ -41,26 +52,26 @@ fn test_render_pdf() {
     let mut found_arial = false;
 
     // Check each page's resources for fonts
-    if let Ok(page) = file.get_page(0) {
-        if let Ok(resources) = page.resources() {
-            for (_, font_ref) in resources.fonts.iter() {
-                match font_ref {
-                    MaybeRef::Direct(font) => {
-                        if let Some(name) = &font.name {
-                            if name.to_string().to_lowercase().contains("arial") {
-                                found_arial = true;
-                                break;
-                            }
-                        }
+    if let Ok(page) = file.get_page(0)
+        && let Ok(resources) = page.resources()
+    {
+        for (_, font_ref) in resources.fonts.iter() {
+            match font_ref {
+                MaybeRef::Direct(font) => {
+                    if let Some(name) = &font.name
+                        && name.to_string().to_lowercase().contains("arial")
+                    {
+                        found_arial = true;
+                        break;
                     }
-                    MaybeRef::Indirect(r) => {
-                        let font = r.data();
-                        if let Some(name) = &font.name {
-                            if name.to_string().to_lowercase().contains("arial") {
-                                found_arial = true;
-                                break;
-                            }
-                        }
+                }
+                MaybeRef::Indirect(r) => {
+                    let font = r.data();
+                    if let Some(name) = &font.name
+                        && name.to_string().to_lowercase().contains("arial")
+                    {
+                        found_arial = true;
+                        break;
                     }
                 }
             }
@@ -69,3 +80,1666 @@ fn test_render_pdf() {
 
     assert!(found_arial, "PDF should contain Arial font");
 }
+
+/// Render with the given options and return the PDF catalog's `/Lang` entry
+fn render_catalog_lang(template: &str, options: &RenderOptions) -> String {
+    let result = render_template_with_options(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        options,
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+
+    let pdf = String::from_utf8_lossy(result.pdf.as_ref().unwrap()).into_owned();
+    let start = pdf
+        .find("/Lang (")
+        .expect("PDF catalog should declare a language")
+        + 7;
+    let end = start + pdf[start..].find(')').unwrap();
+    pdf[start..end].to_string()
+}
+
+#[test]
+fn test_document_lang_default_applies() {
+    // No `set text(lang: ..)`, so the catalog language comes from the options
+    let template = "Donaudampfschifffahrtsgesellschaft";
+
+    assert_eq!(
+        render_catalog_lang(template, &RenderOptions::default()),
+        "en"
+    );
+
+    let options = RenderOptions::default().with_document_lang("de-DE");
+    assert_eq!(render_catalog_lang(template, &options), "de");
+}
+
+#[test]
+fn test_document_lang_overridden_by_template() {
+    let template = "#set text(lang: \"fr\")\nBonjour";
+    let options = RenderOptions::default().with_document_lang("en-GB");
+    assert_eq!(render_catalog_lang(template, &options), "fr");
+}
+
+#[test]
+fn test_document_lang_invalid_tag() {
+    let options = RenderOptions::default().with_document_lang("english");
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+    assert!(matches!(result, Err(PapermakeError::Config(_))));
+}
+
+#[test]
+fn test_render_large_data_from_reader() {
+    // ~5 MB of JSON, produced straight into bytes without a serde_json::Value
+    let rows = 100_000;
+    let mut json = String::from("{\"rows\": [");
+    for i in 0..rows {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(&format!(
+            "{{\"id\": {}, \"label\": \"row-{}\", \"amount\": {}}}",
+            i,
+            i,
+            i % 100
+        ));
+    }
+    json.push_str("]}");
+    assert!(json.len() > 4_000_000);
+
+    let template = format!(
+        r#"#assert.eq(data.rows.len(), {rows})
+#assert.eq(data.rows.last().label, "row-{last}")
+Rows: #data.rows.len(), total: #data.rows.map(r => r.amount).sum()"#,
+        rows = rows,
+        last = rows - 1
+    );
+
+    let result = render_template_from_reader(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        std::io::Cursor::new(json.into_bytes()),
+        &RenderOptions::default(),
+    )
+    .unwrap();
+
+    assert!(result.success, "Render failed: {:?}", result.errors);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_render_from_reader_invalid_json() {
+    let result = render_template_from_reader(
+        "Hello #data.name".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &b"{not json"[..],
+        &RenderOptions::default(),
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    assert!(!result.errors.is_empty());
+}
+
+#[cfg(feature = "linearize")]
+#[test]
+fn test_render_linearized_pdf() {
+    let options = RenderOptions::default().with_linearize(true);
+    let result = render_template_with_options(
+        "#set page(height: 100pt)\n#for i in range(5) [Page #i #pagebreak()]".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+
+    let pdf = result.pdf.unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+
+    // The linearization dictionary and first-page xref must lead the file
+    let head = String::from_utf8_lossy(&pdf[..1024.min(pdf.len())]).into_owned();
+    assert!(head.contains("/Linearized"));
+    assert!(head.contains("xref") || head.contains("/XRef"));
+    assert!(qpdf::QPdf::read_from_memory(&pdf).unwrap().is_linearized());
+}
+
+#[cfg(not(feature = "linearize"))]
+#[test]
+fn test_render_linearize_requires_feature() {
+    let options = RenderOptions::default().with_linearize(true);
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+    assert!(matches!(result, Err(PapermakeError::Config(_))));
+}
+
+#[test]
+fn test_render_with_custom_data_key() {
+    let template = r#"#let payload = json.decode(sys.inputs.payload)
+#assert.eq(payload.name, "World")
+#assert.eq(data.name, "World")
+Hello #payload.name!"#;
+    let options = RenderOptions::default().with_data_key("payload");
+
+    let result = render_template_with_options(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({ "name": "World" }),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "Render failed: {:?}", result.errors);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_render_with_invalid_data_key() {
+    let options = RenderOptions::default().with_data_key("bad\"key");
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+    assert!(matches!(result, Err(PapermakeError::Config(_))));
+}
+
+#[test]
+fn test_render_with_outline() {
+    let template = r#"#set page(height: 200pt)
+= Introduction
+Some text.
+== Background on *#data.topic*
+#pagebreak()
+== Scope
+=== Details
+#heading(outlined: false)[Hidden]
+= Conclusion
+"#;
+
+    let (result, outline) = render_with_outline(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({ "topic": "PDFs" }),
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+
+    let entry = |level, title: &str, page| OutlineEntry {
+        level,
+        title: title.to_string(),
+        page,
+    };
+    assert_eq!(
+        outline,
+        vec![
+            entry(1, "Introduction", 1),
+            entry(2, "Background on PDFs", 1),
+            entry(2, "Scope", 2),
+            entry(3, "Details", 2),
+            entry(1, "Conclusion", 2),
+        ]
+    );
+}
+
+#[test]
+fn test_render_with_outline_compile_error() {
+    let (result, outline) = render_with_outline(
+        "= Title\n#undefined_function()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    assert!(outline.is_empty());
+}
+
+#[test]
+fn test_missing_data_driven_image_falls_back() {
+    let template = r#"#for country in data.countries [
+  #image("flags/" + country + ".png", width: 10pt)
+]"#;
+    let data = json!({ "countries": ["de", "xx"] });
+
+    let mut fs = InMemoryFileSystem::new();
+    fs.add_file(
+        "/flags/de.png",
+        papermake::assets::DEFAULT_PLACEHOLDER_PNG.to_vec(),
+    );
+    let fs = Arc::new(fs);
+
+    // Default behaviour: the missing flag fails the render
+    let result = render_template(template.to_string(), fs.clone(), &data).unwrap();
+    assert!(!result.success);
+
+    // With fallback enabled the render succeeds and reports the missing path
+    let options = RenderOptions::default().with_asset_fallback(AssetFallback::new("flags"));
+    let result = render_template_with_options(template.to_string(), fs, &data, &options).unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+    assert!(result.errors.is_empty());
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].message.contains("/flags/xx.png"));
+    assert!(!result.warnings[0].message.contains("/flags/de.png"));
+}
+
+#[test]
+fn test_render_with_multiple_inputs() {
+    let template = r##"#let branding = json(bytes(sys.inputs.branding))
+#assert.eq(data.customer, "Acme")
+#assert.eq(branding.color, "#336699")
+#assert.eq(json(bytes(sys.inputs.data)), data)
+#text(fill: rgb(branding.color))[Invoice for #data.customer]"##;
+    let inputs = BTreeMap::from([
+        ("data".to_string(), json!({"customer": "Acme"})),
+        ("branding".to_string(), json!({"color": "#336699"})),
+    ]);
+
+    let result = render_template_with_inputs(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &inputs,
+        &RenderOptions::default(),
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+
+    // Without an entry under the data key, `data` is none
+    let inputs = BTreeMap::from([("rows".to_string(), json!([1, 2, 3]))]);
+    let result = render_template_with_inputs(
+        "#assert.eq(data, none)
+#json(bytes(sys.inputs.rows)).len() rows"
+            .to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &inputs,
+        &RenderOptions::default(),
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+}
+
+#[test]
+fn test_render_to_writer_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let pdf_path = temp_dir.path().join("invoice.pdf");
+    let file = std::fs::File::create(&pdf_path).unwrap();
+
+    let result = render_template_to_writer(
+        "= Invoice #data.number".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"number": 42}),
+        std::io::BufWriter::new(file),
+    )
+    .unwrap();
+
+    assert!(result.success);
+    assert!(result.pdf.is_none());
+
+    let pdf_bytes = std::fs::read(&pdf_path).unwrap();
+    assert!(pdf_bytes.starts_with(b"%PDF"));
+    let document = pdf::file::FileOptions::cached().load(pdf_bytes).unwrap();
+    assert_eq!(document.num_pages(), 1);
+}
+
+#[test]
+fn test_render_to_writer_matches_buffered_render() {
+    let template = "= Report\n#for row in data.rows [- #row]".to_string();
+    let data = json!({"rows": (0..500).collect::<Vec<_>>()});
+    let mut sink = Vec::new();
+
+    let result = render_template_to_writer(
+        template.clone(),
+        Arc::new(InMemoryFileSystem::new()),
+        &data,
+        &mut sink,
+    )
+    .unwrap();
+    let buffered = render_template(template, Arc::new(InMemoryFileSystem::new()), &data).unwrap();
+
+    assert!(result.success);
+    assert_eq!(sink, buffered.pdf.unwrap());
+}
+
+#[test]
+fn test_render_to_writer_compile_error_writes_nothing() {
+    let mut sink = Vec::new();
+
+    let result = render_template_to_writer(
+        "#unknown_function()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &mut sink,
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    assert!(!result.errors.is_empty());
+    assert!(sink.is_empty());
+}
+
+#[test]
+fn test_render_with_in_memory_font() {
+    // The last bundled font is DejaVuSansMono.ttf
+    let ttf = typst_assets::fonts().last().unwrap().to_vec();
+    let template = "#set text(font: \"DejaVu Sans Mono\")\nHello #data.name".to_string();
+
+    let mut world = PapermakeWorld::with_font_source(
+        template.clone(),
+        "{}".to_string(),
+        &FontSource::InMemory(vec![ttf]),
+    )
+    .unwrap();
+
+    let result = render_template_with_cache(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        json!({"name": "World"}),
+        Some(&mut world),
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    let pdf_bytes = result.pdf.unwrap();
+    assert!(
+        pdf_bytes
+            .windows(b"DejaVuSansMono".len())
+            .any(|w| w == b"DejaVuSansMono")
+    );
+}
+
+#[test]
+fn test_render_with_unreadable_font_dir() {
+    let options = RenderOptions::default()
+        .with_font_source(FontSource::SystemDir("/nonexistent/fonts".into()));
+
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::FontLoading { .. }))
+    ));
+}
+
+#[test]
+fn test_in_memory_font_source_rejects_invalid_data() {
+    let result = FontSource::InMemory(vec![b"not a font".to_vec()]).load();
+
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::FontLoading { .. }))
+    ));
+}
+
+#[cfg(feature = "embed-fonts")]
+#[test]
+fn test_embedded_font_source_loads_bundled_fonts() {
+    let (book, fonts) = FontSource::Embedded.load().unwrap();
+    assert!(!fonts.is_empty());
+    assert!(
+        book.families()
+            .any(|(family, _)| family == "Libertinus Serif")
+    );
+}
+
+#[cfg(not(feature = "embed-fonts"))]
+#[test]
+fn test_embedded_font_source_requires_feature() {
+    assert!(matches!(
+        FontSource::Embedded.load(),
+        Err(PapermakeError::Config(ConfigError::FontLoading { .. }))
+    ));
+}
+
+#[cfg(not(feature = "fs"))]
+#[test]
+fn test_font_dir_source_requires_fs_feature() {
+    assert!(matches!(
+        FontSource::SystemDir("/usr/share/fonts".into()).load(),
+        Err(PapermakeError::Config(ConfigError::FontLoading { .. }))
+    ));
+}
+
+#[test]
+fn test_render_to_png_pages() {
+    let result = render_template_to(
+        "#set page(width: 100pt, height: 100pt)\nPage one #pagebreak() Page two".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        OutputFormat::Png { dpi: 144 },
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.pages.len(), 2);
+    for (i, page) in result.pages.iter().enumerate() {
+        assert_eq!(page.index, i);
+        assert!(page.bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    // 100pt at 144 DPI is 200px; the width is stored big-endian in the IHDR chunk
+    let width = u32::from_be_bytes(result.pages[0].bytes[16..20].try_into().unwrap());
+    assert_eq!(width, 200);
+}
+
+#[test]
+fn test_render_to_svg_pages() {
+    let result = render_template_to(
+        "Hello #data.name".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"name": "World"}),
+        OutputFormat::Svg,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.pages.len(), 1);
+    let svg = String::from_utf8(result.pages[0].bytes.clone()).unwrap();
+    assert!(svg.contains("<svg"));
+}
+
+#[test]
+fn test_render_to_pdf_matches_render_template() {
+    let result = render_template_to(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        OutputFormat::Pdf,
+    )
+    .unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.pages.len(), 1);
+    assert!(result.pages[0].bytes.starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_render_to_png_rejects_zero_dpi() {
+    let result = render_template_to(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        OutputFormat::Png { dpi: 0 },
+    );
+
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::InvalidConfig { .. }))
+    ));
+}
+
+#[test]
+fn test_pdf_options_write_document_metadata() {
+    let options = RenderOptions::default().with_pdf_options(
+        PdfOptions::default()
+            .with_title("Invoice 42")
+            .with_author("Acme Corp")
+            .with_keywords(["invoice"]),
+    );
+
+    let result = render_template_with_options(
+        "= Invoice".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    let document = pdf::file::FileOptions::cached()
+        .load(result.pdf.unwrap())
+        .unwrap();
+    let info = document.trailer.info_dict.as_ref().unwrap();
+    assert_eq!(info.title.as_ref().unwrap().to_string_lossy(), "Invoice 42");
+    assert_eq!(info.author.as_ref().unwrap().to_string_lossy(), "Acme Corp");
+}
+
+#[test]
+fn test_pdf_options_export_page_range() {
+    let options =
+        RenderOptions::default().with_pdf_options(PdfOptions::default().with_page_range(1..2));
+
+    let result = render_template_output(
+        "One #pagebreak() Two #pagebreak() Three".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.page_info.len(), 3);
+    let document = pdf::file::FileOptions::cached()
+        .load(result.pages[0].bytes.clone())
+        .unwrap();
+    assert_eq!(document.num_pages(), 1);
+}
+
+#[test]
+fn test_pdf_options_reject_empty_page_range() {
+    let options =
+        RenderOptions::default().with_pdf_options(PdfOptions::default().with_page_range(2..2));
+
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::InvalidConfig { setting, .. })) if setting == "page_range"
+    ));
+}
+
+#[test]
+fn test_pdf_options_pdf_a() {
+    let options = RenderOptions::default().with_pdf_options(
+        PdfOptions::default()
+            .with_title("Archived")
+            .with_pdf_a(true),
+    );
+
+    let result = render_template_with_options(
+        "= Archived".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    let pdf = result.pdf.unwrap();
+    let pdf = String::from_utf8_lossy(&pdf);
+    assert!(pdf.contains("pdfaid:part"));
+}
+
+#[test]
+fn test_pdf_options_deterministic_output() {
+    let template = "= Report\nGenerated on #datetime.today().display()".to_string();
+    let render = |pdf: PdfOptions| {
+        render_template_with_options(
+            template.clone(),
+            Arc::new(InMemoryFileSystem::new()),
+            &json!({"total": 42}),
+            &RenderOptions::default().with_pdf_options(pdf),
+        )
+        .unwrap()
+        .pdf
+        .unwrap()
+    };
+
+    let deterministic = PdfOptions::default().with_deterministic(true);
+    let pdf1 = render(deterministic.clone());
+    let pdf2 = render(deterministic);
+    assert_eq!(pdf1, pdf2);
+    assert!(String::from_utf8_lossy(&pdf1).contains("D:19700101"));
+
+    // Without the flag no creation date is written and the clock is not
+    // pinned, so output only varies for templates reading the current date
+    let pdf = render(PdfOptions::default());
+    assert!(!String::from_utf8_lossy(&pdf).contains("CreationDate"));
+}
+
+#[test]
+fn test_mustache_preprocessor_substitutes_placeholders() {
+    let options = RenderOptions::default().with_preprocessor(Preprocessor::Mustache);
+
+    let result = render_template_with_options(
+        "Hello {{name}}".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"name": "Bob"}),
+        &options,
+    )
+    .unwrap();
+    let expected = render_template(
+        "Hello Bob".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"name": "Bob"}),
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.pdf, expected.pdf);
+}
+
+#[test]
+fn test_mustache_preprocessor_escapes_markup() {
+    let options = RenderOptions::default().with_preprocessor(Preprocessor::Mustache);
+
+    // Unescaped, this would abort the render
+    let result = render_template_with_options(
+        "Hello {{name}}".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"name": "#panic(\"injected\")"}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+}
+
+/// Render `template` with the `pm` helpers for `locale`, failing on errors
+fn assert_renders_with_locale(locale: &str, template: &str) {
+    let options = RenderOptions::default().with_locale(Locale::from_code(locale).unwrap());
+    let result = render_template_with_options(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"total": 1234567.5, "date": "2025-03-01"}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+}
+
+#[test]
+fn test_locale_prelude_formats_numbers() {
+    assert_renders_with_locale(
+        "en-US",
+        r#"#assert.eq(pm.format-number(data.total), "1,234,567.50")
+#assert.eq(pm.format-currency(data.total), "$1,234,567.50")
+#assert.eq(pm.format-number(-999, decimals: 0), "-999")
+#assert.eq(pm.format-number(0.05), "0.05")
+#assert.eq(pm.format-date(data.date), "03/01/2025")"#,
+    );
+    assert_renders_with_locale(
+        "de-DE",
+        r#"#assert.eq(pm.locale, "de-DE")
+#assert.eq(pm.format-number(data.total), "1.234.567,50")
+#assert.eq(pm.format-currency(data.total), "1.234.567,50 €")
+#assert.eq(pm.format-date(data.date), "01.03.2025")
+#assert.eq(pm.format-date(datetime(year: 2025, month: 3, day: 1), pattern: "[year]"), "2025")"#,
+    );
+}
+
+#[test]
+fn test_locale_prelude_keeps_diagnostic_lines() {
+    let options = RenderOptions::default().with_locale(Locale::default());
+    let result = render_template_with_options(
+        "= Invoice\n#undefined_function()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    let location = result.errors[0].location.as_ref().unwrap();
+    assert_eq!(location.line, 2);
+}
+
+/// File system with `/level0.typ` importing `/level1.typ` ... down to `/level{depth-1}.typ`
+fn import_chain(depth: usize) -> InMemoryFileSystem {
+    let mut fs = InMemoryFileSystem::new();
+    for i in 0..depth {
+        let content = if i + 1 < depth {
+            format!("#import \"level{}.typ\": value\n", i + 1)
+        } else {
+            "#let value = 42\n".to_string()
+        };
+        fs.add_file(format!("/level{}.typ", i), content.into_bytes());
+    }
+    fs
+}
+
+#[test]
+fn test_import_chain_within_limit() {
+    let options = RenderOptions::default().with_max_import_depth(5);
+    let result = render_template_with_options(
+        "#import \"level0.typ\": value\nValue: #value".to_string(),
+        Arc::new(import_chain(5)),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+}
+
+#[test]
+fn test_import_chain_exceeding_limit_fails() {
+    let options = RenderOptions::default().with_max_import_depth(5);
+    let result = render_template_with_options(
+        "#import \"level0.typ\": value\nValue: #value".to_string(),
+        Arc::new(import_chain(6)),
+        &json!({}),
+        &options,
+    );
+
+    match result {
+        Err(PapermakeError::Compilation(CompilationError::ImportResolution {
+            import_path,
+            reason,
+        })) => {
+            assert_eq!(import_path, "level5.typ");
+            assert!(reason.contains("limit of 5"), "reason: {}", reason);
+            assert!(
+                reason.contains("main.typ -> level0.typ -> level1.typ"),
+                "reason: {}",
+                reason
+            );
+        }
+        other => panic!(
+            "expected import depth error, got {:?}",
+            other.map(|r| r.success)
+        ),
+    }
+}
+
+#[test]
+fn test_import_cycle_is_left_to_typst() {
+    let mut fs = InMemoryFileSystem::new();
+    fs.add_file("/a.typ", b"#import \"b.typ\"\n".to_vec());
+    fs.add_file("/b.typ", b"#import \"a.typ\"\n".to_vec());
+
+    let result = render_template(
+        "#import \"a.typ\"\nHello".to_string(),
+        Arc::new(fs),
+        &json!({}),
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    assert!(
+        result
+            .errors
+            .iter()
+            .any(|e| e.message.contains("cyclic import"))
+    );
+}
+
+#[test]
+fn test_render_result_page_info() {
+    let result = render_template(
+        "#set page(paper: \"a4\")\nFirst page #pagebreak() Second page".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+    )
+    .unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.pages.len(), 2);
+    for (i, page) in result.pages.iter().enumerate() {
+        assert_eq!(page.index, i);
+        // A4 is 210mm x 297mm
+        assert!(
+            (page.width_pt - 595.28).abs() < 0.01,
+            "width {}",
+            page.width_pt
+        );
+        assert!(
+            (page.height_pt - 841.89).abs() < 0.01,
+            "height {}",
+            page.height_pt
+        );
+    }
+}
+
+#[test]
+fn test_render_result_page_info_empty_on_error() {
+    let result = render_template(
+        "#unknown_function()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    assert!(result.pages.is_empty());
+}
+
+#[test]
+fn test_options_paper_margin_and_max_pages() {
+    let template = "Page one #pagebreak() Page two #pagebreak() Page three".to_string();
+    let options = RenderOptions::default()
+        .with_paper("us-letter")
+        .with_margin("1in");
+
+    let result = render_template_with_options(
+        template.clone(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.pages.len(), 3);
+    assert!((result.pages[0].width_pt - 612.0).abs() < 0.01);
+    assert!((result.pages[0].height_pt - 792.0).abs() < 0.01);
+
+    let result = render_template_with_options(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options.with_max_pages(2),
+    )
+    .unwrap();
+    assert!(!result.success);
+    assert!(result.pdf.is_none());
+    assert!(
+        result.errors[0]
+            .message
+            .contains("exceeding the limit of 2")
+    );
+}
+
+#[test]
+fn test_output_guards_stop_oversized_documents() {
+    // Page count driven by the data, as with an attacker-controlled array
+    let template = "#for item in data.items [Item #item #pagebreak()]".to_string();
+    let data = json!({"items": (0..200).collect::<Vec<_>>()});
+
+    let result = render_template_with_options(
+        template.clone(),
+        Arc::new(InMemoryFileSystem::new()),
+        &data,
+        &RenderOptions::default().with_max_pages(5),
+    )
+    .unwrap();
+    assert!(!result.success);
+    assert!(result.pdf.is_none());
+    assert_eq!(result.pages.len(), 201);
+    assert!(
+        result.errors[0]
+            .message
+            .contains("document has 201 pages, exceeding the limit of 5")
+    );
+
+    let result = render_template_with_options(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        &data,
+        &RenderOptions::default().with_max_output_bytes(10_000),
+    )
+    .unwrap();
+    assert!(!result.success);
+    assert!(result.pdf.is_none());
+    assert!(
+        result.errors[0]
+            .message
+            .contains("exceeding the limit of 10000 bytes"),
+        "errors: {:?}",
+        result.errors
+    );
+}
+
+#[test]
+fn test_options_today_seed_and_deterministic() {
+    let template = r#"
+#assert.eq(datetime.today(), datetime(year: 2024, month: 2, day: 29))
+#assert.eq(sys.inputs.seed, "42")
+Generated on #datetime.today().display()
+"#
+    .to_string();
+    let options = RenderOptions::default()
+        .with_today(time::Date::from_calendar_date(2024, time::Month::February, 29).unwrap())
+        .with_seed(42)
+        .with_watermark("DRAFT \"internal\"");
+
+    let result = render_template_with_options(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+
+    let template = "Generated on #datetime.today().display()".to_string();
+    let options = RenderOptions::default().with_deterministic(true);
+    let render = || {
+        render_template_with_options(
+            template.clone(),
+            Arc::new(InMemoryFileSystem::new()),
+            &json!({}),
+            &options,
+        )
+        .unwrap()
+        .pdf
+        .unwrap()
+    };
+    assert_eq!(render(), render());
+}
+
+#[test]
+fn test_render_reports_warnings_separately() {
+    let result = render_template(
+        "#let parsed = json.decode(\"[1, 2]\")\nItems: #parsed.len()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+    )
+    .unwrap();
+
+    assert!(result.success);
+    assert!(result.errors.is_empty());
+    assert_eq!(result.warnings.len(), 1);
+    let warning = &result.warnings[0];
+    assert_eq!(warning.severity, DiagnosticSeverity::Warning);
+    assert!(
+        warning.message.contains("deprecated"),
+        "{}",
+        warning.message
+    );
+    assert!(warning.location.is_some());
+}
+
+#[test]
+fn test_warning_location_excludes_prelude() {
+    let options = RenderOptions::default().with_paper("a5").with_margin("1cm");
+    let result = render_template_with_options(
+        "= Title\n\n#let parsed = json.decode(\"[]\")".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success, "Render failed: {:?}", result.errors);
+    let location = result.warnings[0].location.as_ref().unwrap();
+    assert_eq!(location.file, "main.typ");
+    assert_eq!(location.line, 3);
+    assert_eq!(location.column, 20);
+}
+
+#[test]
+fn test_options_strict_fails_on_warnings() {
+    let template = "#set text(font: \"No Such Font\")\nHello".to_string();
+
+    let result = render_template(
+        template.clone(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+    )
+    .unwrap();
+    assert!(result.success);
+
+    let result = render_template_with_options(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &RenderOptions::default().with_strict(true),
+    )
+    .unwrap();
+    assert!(!result.success);
+    assert!(result.pdf.is_none());
+    assert!(
+        result.errors[0].message.contains("unknown font family"),
+        "{:?}",
+        result.errors
+    );
+}
+
+#[test]
+fn test_options_format() {
+    let options = RenderOptions::default()
+        .with_paper("a5")
+        .with_format(OutputFormat::Svg);
+
+    let output = render_template_output(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+    assert!(output.success);
+    assert!(output.pages[0].bytes.starts_with(b"<svg"));
+
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::InvalidConfig { .. }))
+    ));
+
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &RenderOptions::default().with_margin("2 cm; #panic()"),
+    );
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::InvalidConfig { .. }))
+    ));
+}
+
+#[test]
+fn test_render_timeout() {
+    // Roughly 10^10 loop iterations without allocating a large array
+    let template = "#for i in range(100000) { for j in range(100000) {} }".to_string();
+    let options = RenderOptions::default().with_timeout(std::time::Duration::from_millis(100));
+
+    let started = std::time::Instant::now();
+    let result = render_template_with_options(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    );
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    match result {
+        Err(PapermakeError::Compilation(CompilationError::Timeout { timeout_ms })) => {
+            assert_eq!(timeout_ms, 100);
+        }
+        other => panic!("expected timeout error, got {:?}", other.map(|r| r.success)),
+    }
+
+    // A render finishing in time is unaffected
+    let result = render_template_with_options(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &RenderOptions::default().with_timeout(std::time::Duration::from_secs(30)),
+    )
+    .unwrap();
+    assert!(result.success);
+}
+
+#[test]
+fn test_warn_unused_data_keys() {
+    let options = RenderOptions::default().with_warn_unused_data(true);
+
+    let result = render_template_with_options(
+        "Hello #data.at(\"name\", default: \"stranger\")".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({ "nam": "x" }),
+        &options,
+    )
+    .unwrap();
+
+    assert!(result.success);
+    assert!(result.errors.is_empty());
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(
+        result.warnings[0].message,
+        "data key 'nam' is never read by the template"
+    );
+
+    // Off by default
+    let result = render_template(
+        "Hello #data.at(\"name\", default: \"stranger\")".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({ "nam": "x" }),
+    )
+    .unwrap();
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn test_runaway_templates_hit_typst_limits() {
+    let cases = [
+        (
+            "#let i = 0\n#while i >= 0 { i += 1 }",
+            "loop seems to be infinite",
+        ),
+        (
+            "#let f(n) = f(n + 1)\n#f(0)",
+            "maximum function call depth exceeded",
+        ),
+    ];
+
+    for (template, expected) in cases {
+        let result = render_template(
+            template.to_string(),
+            Arc::new(InMemoryFileSystem::new()),
+            &json!({}),
+        )
+        .unwrap();
+
+        assert!(!result.success);
+        assert!(
+            result.errors.iter().any(|e| e.message.contains(expected)),
+            "{}: {:?}",
+            template,
+            result.errors
+        );
+    }
+}
+
+/// File system counting how often each file is fetched
+struct CountingFileSystem {
+    inner: InMemoryFileSystem,
+    fetches: std::sync::Mutex<Vec<String>>,
+}
+
+impl RenderFileSystem for CountingFileSystem {
+    fn get_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        self.fetches.lock().unwrap().push(path.to_string());
+        self.inner.get_file(path)
+    }
+}
+
+#[test]
+fn test_render_with_thumbnail() {
+    let mut inner = InMemoryFileSystem::new();
+    inner.add_file("/title.typ", b"= Gallery item".to_vec());
+    let fs = Arc::new(CountingFileSystem {
+        inner,
+        fetches: std::sync::Mutex::new(Vec::new()),
+    });
+
+    let (result, thumbnail) = render_with_thumbnail(
+        "#set page(paper: \"a4\")\n#include \"title.typ\"\n#data.name #pagebreak() Page two"
+            .to_string(),
+        fs.clone(),
+        &json!({ "name": "Item 1" }),
+        36,
+    )
+    .unwrap();
+
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+
+    // First page only, A4 at 36 DPI is 298 x 421 pixels
+    let png = thumbnail.unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    assert_eq!((width, height), (298, 421));
+
+    // A single compilation fetches the included file once
+    assert_eq!(*fs.fetches.lock().unwrap(), vec!["/title.typ".to_string()]);
+}
+
+#[test]
+fn test_render_with_thumbnail_failure() {
+    let (result, thumbnail) = render_with_thumbnail(
+        "#unknown_function()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        72,
+    )
+    .unwrap();
+    assert!(!result.success);
+    assert!(thumbnail.is_none());
+
+    let result = render_with_thumbnail(
+        "Hello".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        0,
+    );
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::InvalidConfig { .. }))
+    ));
+}
+
+#[test]
+fn test_render_html_heading_paragraph_and_list() {
+    let html = r#"
+<h1>Quarterly Report</h1>
+<p>Revenue grew by <strong>12%</strong> to $1.2M &mdash; see <em>details</em> below.</p>
+<ul>
+  <li>North: 1. place</li>
+  <li>South: #2 // steady</li>
+</ul>
+"#;
+    let options = HtmlRenderOptions::default()
+        .with_paper("us-letter")
+        .with_margin("1in");
+
+    let result = render_html(html, options).unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.pages.len(), 1);
+    assert!((result.pages[0].width_pt - 612.0).abs() < 0.01);
+
+    let pdf = result.pdf.unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    // The heading becomes a PDF bookmark
+    assert!(String::from_utf8_lossy(&pdf).contains("/Outlines"));
+}
+
+#[test]
+fn test_render_html_images_use_file_system() {
+    let html = r#"<p><img src="missing.png" alt="Logo"></p>"#;
+
+    let result = render_html(html, HtmlRenderOptions::default()).unwrap();
+    assert!(!result.success);
+    assert!(result.errors[0].message.contains("missing.png"));
+}
+
+#[test]
+fn test_render_markdown_table_and_code_block() {
+    let markdown = r#"# Invoice INV-001
+
+Billed to **Acme Corp**.
+
+| Item   | Qty | Price |
+|--------|----:|------:|
+| Widget | 2   | $10   |
+| Gadget | 1   | $25   |
+
+```rust
+fn total(items: &[u32]) -> u32 {
+    items.iter().sum()
+}
+```
+"#;
+
+    let result = render_markdown(markdown, HtmlRenderOptions::default()).unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.pages.len(), 1);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF-"));
+}
+
+#[test]
+fn test_render_markdown_long_document_page_count() {
+    let markdown: String = (1..=10)
+        .map(|section| {
+            let paragraphs = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(8);
+            format!(
+                "## Section {}\n\n{}\n\n{}\n\n{}\n\n",
+                section, paragraphs, paragraphs, paragraphs
+            )
+        })
+        .collect();
+
+    let render = |paper: &str| {
+        let options = HtmlRenderOptions::default().with_paper(paper);
+        let result = render_markdown(&markdown, options).unwrap();
+        assert!(result.success, "errors: {:?}", result.errors);
+        result.pages.len()
+    };
+
+    let a4_pages = render("a4");
+    let a6_pages = render("a6");
+    assert!(a4_pages > 1);
+    assert!(a6_pages > a4_pages);
+}
+
+#[test]
+fn test_check_template_reports_diagnostics_without_data() {
+    let mut fs = InMemoryFileSystem::new();
+    fs.add_file("/lib.typ", b"#let greet(name) = [Hello #name]".to_vec());
+    let fs = Arc::new(fs);
+
+    let clean = r#"#import "lib.typ": greet
+#greet(data.at("name", default: "World"))"#;
+    assert!(
+        check_template(clean.to_string(), fs.clone())
+            .unwrap()
+            .is_empty()
+    );
+
+    let diagnostics = check_template(
+        "#let total = (1, 2
+"
+        .to_string(),
+        fs,
+    )
+    .unwrap();
+    assert!(!diagnostics.is_empty());
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+    assert_eq!(diagnostics[0].location.as_ref().unwrap().line, 1);
+}
+
+#[test]
+fn test_missing_glyphs_reported_for_uncovered_script() {
+    // Typst's bundled fonts are Latin only
+    let fonts = typst_assets::fonts().map(|font| font.to_vec()).collect();
+    let options = RenderOptions::default()
+        .with_font_source(FontSource::InMemory(fonts))
+        .with_font_fallbacks(["DejaVu Sans Mono"]);
+
+    let result = render_template_with_options(
+        "Invoice 发票 #data.number".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"number": "INV-001"}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert_eq!(result.missing_glyphs, vec!['发', '票']);
+
+    let result = render_template_with_options(
+        "Invoice #data.number".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"number": "INV-001"}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+    assert!(result.missing_glyphs.is_empty());
+}
+
+#[test]
+fn test_font_fallbacks_are_tried_in_order() {
+    // Everything but Libertinus Serif, Typst's default font
+    let fonts: Vec<Vec<u8>> = typst_assets::fonts()
+        .map(|font| font.to_vec())
+        .filter(|font| !font.windows(10).any(|w| w == b"Libertinus"))
+        .collect();
+    let options = RenderOptions::default()
+        .with_font_source(FontSource::InMemory(fonts))
+        .with_font_fallbacks(["Missing Family", "DejaVu Sans Mono"]);
+
+    let result = render_template_with_options(
+        "Hello World".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+    let pdf = result.pdf.unwrap();
+    assert!(pdf.windows(14).any(|w| w == b"DejaVuSansMono"));
+    assert!(!pdf.windows(5).any(|w| w == b"NewCM"));
+}
+
+#[test]
+fn test_render_csv_rows_as_table() {
+    let template = r#"#let rows = json(bytes(sys.inputs.rows))
+#assert.eq(rows.map(row => row.item), ("Pen", "Ink, blue", "Pad"))
+#assert.eq(rows.map(row => int(row.qty)).sum(), 6)
+#table(
+  columns: 3,
+  ..rows.map(row => (row.item, row.qty, row.price)).flatten()
+)"#;
+    let csv = "item,qty,price\nPen,2,1.50\n\"Ink, blue\",1,4.00\nPad,3,2.25\n";
+
+    let result = render_template_with_csv(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        csv,
+        &CsvOptions::default(),
+        &RenderOptions::default(),
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_render_csv_ragged_row() {
+    let result = render_template_with_csv(
+        "#json(bytes(sys.inputs.rows)).len()".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        "item;qty;price\nPen;2;1.50\nInk;1\n",
+        &CsvOptions::default().with_delimiter(b';'),
+        &RenderOptions::default(),
+    );
+
+    match result {
+        Err(PapermakeError::Data(DataError::InvalidFormat { expected, actual })) => {
+            assert_eq!(expected, "rows with 3 fields");
+            assert_eq!(actual, "2 fields on line 3");
+        }
+        other => panic!("expected InvalidFormat, got {:?}", other.map(|r| r.success)),
+    }
+}
+
+#[test]
+fn test_render_with_runtime_image() {
+    let template = r#"#image("runtime/signature.png", width: 2cm)
+Signed by #data.name"#;
+    let options = RenderOptions::default().with_runtime_file(
+        "runtime/signature.png",
+        papermake::assets::DEFAULT_PLACEHOLDER_PNG.to_vec(),
+    );
+
+    let result = render_template_with_options(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"name": "Jane Doe"}),
+        &options,
+    )
+    .unwrap();
+    assert!(result.success, "Render failed: {:?}", result.errors);
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+
+    // Without the runtime file the image is missing
+    let result = render_template_with_options(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"name": "Jane Doe"}),
+        &RenderOptions::default(),
+    )
+    .unwrap();
+    assert!(!result.success);
+}
+
+/// Render `chart` alone on a page fitted to it, returning the page size in points
+fn render_chart(chart: &str) -> (f64, f64) {
+    let template = format!(
+        "#set page(width: auto, height: auto, margin: 0pt)\n{}",
+        chart
+    );
+    let result = render_template_with_options(
+        template,
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({"values": [10, 20, 30]}),
+        &RenderOptions::default().with_locale(Locale::default()),
+    )
+    .unwrap();
+    assert!(result.success, "errors: {:?}", result.errors);
+
+    let page = result.pages[0];
+    (page.width_pt, page.height_pt)
+}
+
+#[test]
+fn test_chart_helpers_draw_within_bounds() {
+    let cm = 72.0 / 2.54;
+    let close = |a: f64, b: f64| (a - b).abs() < 0.01;
+
+    let (width, height) = render_chart("#pm.bar-chart(data.values)");
+    assert!(
+        close(width, 8.0 * cm) && close(height, 5.0 * cm),
+        "{width}x{height}"
+    );
+
+    // Labels are set below the plot area
+    let (width, height) = render_chart(
+        r#"#pm.bar-chart(data.values, labels: ("Q1", "Q2", "Q3"), width: 6cm, height: 4cm)"#,
+    );
+    assert!(close(width, 6.0 * cm), "{width}");
+    assert!(height > 4.0 * cm && height < 5.0 * cm, "{height}");
+
+    let (width, height) = render_chart(
+        r#"#pm.line-chart((data.values, (30, -5, 15)), labels: ("Jan", "Feb", "Mar"))"#,
+    );
+    assert!(close(width, 8.0 * cm), "{width}");
+    assert!(height > 5.0 * cm && height < 6.0 * cm, "{height}");
+}
+
+#[test]
+fn test_watermark_overlays_without_changing_layout() {
+    let template = r#"= Quarterly report
+#table(columns: 2, fill: luma(90%), [Revenue], [1200], [Costs], [800])
+#pagebreak()
+= Appendix
+#lorem(400)"#;
+    let render = |options: &RenderOptions| {
+        let result = render_template_with_options(
+            template.to_string(),
+            Arc::new(InMemoryFileSystem::new()),
+            &json!({}),
+            options,
+        )
+        .unwrap();
+        assert!(result.success, "errors: {:?}", result.errors);
+        result.pages
+    };
+
+    let plain = render(&RenderOptions::default());
+    let watermark = Watermark::new("CONFIDENTIAL")
+        .with_opacity(0.3)
+        .with_angle(-30.0)
+        .with_color("#c00000");
+    let watermarked = render(&RenderOptions::default().with_watermark(watermark));
+    assert_eq!(watermarked, plain);
+    assert!(plain.len() >= 2);
+
+    let invalid =
+        RenderOptions::default().with_watermark(Watermark::new("DRAFT").with_color("red"));
+    let result = render_template_with_options(
+        template.to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+        &invalid,
+    );
+    assert!(matches!(
+        result,
+        Err(PapermakeError::Config(ConfigError::InvalidConfig { setting, .. })) if setting == "watermark.color"
+    ));
+}
+
+/// Whether the left and right half of a PNG page's bottom margin hold any ink
+fn footer_ink(png_bytes: &[u8]) -> (bool, bool) {
+    let mut reader = png::Decoder::new(png_bytes).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    let channels = info.color_type.samples();
+    let (width, height) = (info.width as usize, info.height as usize);
+
+    let mut ink = (false, false);
+    for y in height * 92 / 100..height {
+        for x in 0..width {
+            let offset = (y * width + x) * channels;
+            if pixels[offset] < 128 {
+                if x < width / 2 {
+                    ink.0 = true;
+                } else {
+                    ink.1 = true;
+                }
+            }
+        }
+    }
+    ink
+}
+
+#[test]
+fn test_page_decoration_footer_on_every_page() {
+    let render = |template: &str, options: &RenderOptions| {
+        let output = render_template_output(
+            template.to_string(),
+            Arc::new(InMemoryFileSystem::new()),
+            &json!({}),
+            &options.clone().with_format(OutputFormat::Png { dpi: 36 }),
+        )
+        .unwrap();
+        assert!(output.success, "errors: {:?}", output.errors);
+        output
+            .pages
+            .iter()
+            .map(|page| footer_ink(&page.bytes))
+            .collect::<Vec<_>>()
+    };
+    let decoration = PageDecoration::new().with_footer(DecorationContent::markup(
+        "#h(1fr) Acme Corp, Page #context counter(page).display()",
+    ));
+    let options = RenderOptions::default().with_page_decoration(decoration.clone());
+
+    let template = "One #pagebreak() Two #pagebreak() Three";
+    assert_eq!(
+        render(template, &RenderOptions::default()),
+        vec![(false, false); 3]
+    );
+    assert_eq!(render(template, &options), vec![(false, true); 3]);
+
+    // The template's own footer replaces the decoration unless forced
+    let own_footer = "#set page(footer: [Own footer])\nOne #pagebreak() Two";
+    assert_eq!(render(own_footer, &options), vec![(true, false); 2]);
+    let forced = RenderOptions::default().with_page_decoration(decoration.with_force(true));
+    assert_eq!(render(own_footer, &forced), vec![(false, true); 2]);
+}
+
+#[test]
+fn test_merge_pdfs() {
+    let render = |number: u32| {
+        let result = render_template(
+            "= Invoice #data.number\nThank you for your order.".to_string(),
+            Arc::new(InMemoryFileSystem::new()),
+            &json!({ "number": number }),
+        )
+        .unwrap();
+        assert!(result.success, "errors: {:?}", result.errors);
+        result.pdf.unwrap()
+    };
+    let first = render(1);
+    let second = render(2);
+
+    let merged = merge_pdfs(&[first.clone(), second]).unwrap();
+    let document = pdf::file::FileOptions::cached().load(merged).unwrap();
+    assert_eq!(document.num_pages(), 2);
+
+    // Merging a PDF with itself stores its fonts only once
+    let doubled = merge_pdfs(&[first.clone(), first.clone()]).unwrap();
+    assert_eq!(
+        pdf::file::FileOptions::cached()
+            .load(doubled.clone())
+            .unwrap()
+            .num_pages(),
+        2
+    );
+    assert!(doubled.len() < first.len() * 3 / 2);
+
+    assert!(matches!(
+        merge_pdfs(&[first, b"not a pdf".to_vec()]),
+        Err(PapermakeError::Data(DataError::InvalidFieldValue { field, .. })) if field == "pdfs[1]"
+    ));
+    assert!(merge_pdfs(&[]).is_err());
+}
+
+#[test]
+fn test_undefined_input_hints_available_keys() {
+    let result = render_template(
+        "#let rows = json(bytes(sys.inputs.rows))\n= Report for #data.name".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({ "name": "Acme" }),
+    )
+    .unwrap();
+
+    assert!(!result.success);
+    assert_eq!(result.used_inputs, ["data", "rows"]);
+    let error = &result.errors[0];
+    assert!(error.message.contains("rows"), "{}", error.message);
+    assert!(
+        error
+            .hints
+            .contains(&"available `sys.inputs` keys: data".to_string()),
+        "hints: {:?}",
+        error.hints
+    );
+
+    // Errors unrelated to `sys.inputs` get no such hint
+    let result = render_template(
+        "#let x = (a: 1)\n#x.b".to_string(),
+        Arc::new(InMemoryFileSystem::new()),
+        &json!({}),
+    )
+    .unwrap();
+    assert!(!result.success);
+    assert!(
+        result.errors[0]
+            .hints
+            .iter()
+            .all(|hint| !hint.contains("sys.inputs"))
+    );
+    assert!(result.used_inputs.is_empty());
+}